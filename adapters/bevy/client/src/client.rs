@@ -6,7 +6,9 @@ use bevy::ecs::{
     world::{Mut, World},
 };
 
-use naia_client::{Client as NaiaClient, EntityRef, ProtocolType, Replicate};
+use naia_client::{
+    BlobHash, ChannelIndex, Client as NaiaClient, EntityRef, Interpolate, ProtocolType, Replicate,
+};
 
 use naia_bevy_shared::{WorldProxy, WorldRef};
 
@@ -61,15 +63,50 @@ impl<'a, P: ProtocolType> Client<'a, P> {
         return self.client.interpolation();
     }
 
+    pub fn enable_interpolation<R: Replicate<P>>(&mut self) {
+        self.client.enable_interpolation::<R>();
+    }
+
+    pub fn interpolated<R: Replicate<P> + Interpolate>(&self, entity: &Entity) -> Option<R> {
+        return self.client.interpolated::<R>(entity);
+    }
+
+    // Reconciliation
+
+    pub fn set_prediction_tolerance<
+        R: Replicate<P>,
+        F: Fn(&R, &R) -> bool + Send + Sync + 'static,
+    >(
+        &mut self,
+        tolerance: F,
+    ) {
+        self.client.set_prediction_tolerance::<R, F>(tolerance);
+    }
+
+    pub fn smoothed<R: Replicate<P> + Interpolate>(&self, predicted_entity: &Entity) -> Option<R> {
+        return self
+            .client
+            .smoothed::<R, WorldRef>(&self.world.proxy(), predicted_entity);
+    }
+
     //// Messages ////
-    pub fn send_message<R: Replicate<P>>(&mut self, message_ref: &R, guaranteed_delivery: bool) {
-        return self.client.send_message(message_ref, guaranteed_delivery);
+    pub fn send_message<R: Replicate<P>, C: ChannelIndex>(&mut self, message_ref: &R, channel: C) {
+        return self.client.send_message(message_ref, channel);
     }
 
     pub fn send_command<R: Replicate<P>>(&mut self, entity: &Entity, command: R) {
         return self.client.send_command(entity, command);
     }
 
+    //// Blobs ////
+    pub fn cache_blob(&mut self, bytes: Box<[u8]>) -> BlobHash {
+        return self.client.cache_blob(bytes);
+    }
+
+    pub fn cached_blob(&self, hash: &BlobHash) -> Option<Box<[u8]>> {
+        return self.client.cached_blob(hash);
+    }
+
     //// Entities ////
 
     pub fn entity(&self, entity: &Entity) -> EntityRef<P, Entity, WorldRef> {
@@ -80,6 +117,10 @@ impl<'a, P: ProtocolType> Client<'a, P> {
         return self.client.entities(&self.world.proxy());
     }
 
+    pub fn has_authority<R: Replicate<P>>(&self, entity: &Entity) -> bool {
+        return self.client.has_authority::<R>(entity);
+    }
+
     //// Ticks ////
 
     pub fn client_tick(&self) -> Option<u16> {
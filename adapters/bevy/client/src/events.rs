@@ -12,6 +12,12 @@ pub struct RewindEntityEvent(pub OwnedEntity);
 pub struct InsertComponentEvent<P: ProtocolType>(pub Entity, pub P::Kind);
 pub struct UpdateComponentEvent<P: ProtocolType>(pub Entity, pub P::Kind);
 pub struct RemoveComponentEvent<P: ProtocolType>(pub Entity, pub P);
+pub struct GrantAuthorityEvent<P: ProtocolType>(pub Entity, pub P::Kind);
+pub struct RevokeAuthorityEvent<P: ProtocolType>(pub Entity, pub P::Kind);
+pub struct RejectEntityEvent(pub Entity);
+pub struct TakeOverEntityEvent(pub Entity);
 pub struct MessageEvent<P: ProtocolType>(pub P);
 pub struct NewCommandEvent<P: ProtocolType>(pub OwnedEntity, pub P);
 pub struct ReplayCommandEvent<P: ProtocolType>(pub OwnedEntity, pub P);
+pub struct BlobProgressEvent(pub String, pub u32, pub u32);
+pub struct BlobEvent(pub String, pub Box<[u8]>);
@@ -12,9 +12,10 @@ use naia_bevy_shared::WorldData;
 
 use super::{
     events::{
-        DespawnEntityEvent, DisownEntityEvent, InsertComponentEvent, MessageEvent, NewCommandEvent,
-        OwnEntityEvent, RemoveComponentEvent, ReplayCommandEvent, RewindEntityEvent,
-        SpawnEntityEvent, UpdateComponentEvent,
+        BlobEvent, BlobProgressEvent, DespawnEntityEvent, DisownEntityEvent, GrantAuthorityEvent,
+        InsertComponentEvent, MessageEvent, NewCommandEvent, OwnEntityEvent, RejectEntityEvent,
+        RemoveComponentEvent, ReplayCommandEvent, RevokeAuthorityEvent, RewindEntityEvent,
+        SpawnEntityEvent, TakeOverEntityEvent, UpdateComponentEvent,
     },
     resource::ClientResource,
     stage::{PrivateStage, Stage},
@@ -88,9 +89,15 @@ impl<P: ProtocolType, R: Replicate<P>> PluginType for Plugin<P, R> {
             .add_event::<InsertComponentEvent<P>>()
             .add_event::<UpdateComponentEvent<P>>()
             .add_event::<RemoveComponentEvent<P>>()
+            .add_event::<GrantAuthorityEvent<P>>()
+            .add_event::<RevokeAuthorityEvent<P>>()
+            .add_event::<RejectEntityEvent>()
+            .add_event::<TakeOverEntityEvent>()
             .add_event::<MessageEvent<P>>()
             .add_event::<NewCommandEvent<P>>()
             .add_event::<ReplayCommandEvent<P>>()
+            .add_event::<BlobProgressEvent>()
+            .add_event::<BlobEvent>()
         // STAGES //
             // events //
             .add_stage_before(CoreStage::PreUpdate,
@@ -17,9 +17,10 @@ use super::{
     resource::ClientResource,
 };
 use crate::events::{
-    DespawnEntityEvent, DisownEntityEvent, InsertComponentEvent, MessageEvent, NewCommandEvent,
-    OwnEntityEvent, RemoveComponentEvent, ReplayCommandEvent, RewindEntityEvent, SpawnEntityEvent,
-    UpdateComponentEvent,
+    BlobEvent, BlobProgressEvent, DespawnEntityEvent, DisownEntityEvent, GrantAuthorityEvent,
+    InsertComponentEvent, MessageEvent, NewCommandEvent, OwnEntityEvent, RejectEntityEvent,
+    RemoveComponentEvent, ReplayCommandEvent, RevokeAuthorityEvent, RewindEntityEvent,
+    SpawnEntityEvent, TakeOverEntityEvent, UpdateComponentEvent,
 };
 
 pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
@@ -55,9 +56,27 @@ pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
                 let mut remove_component_event_writer = world
                     .get_resource_unchecked_mut::<Events<RemoveComponentEvent<P>>>()
                     .unwrap();
+                let mut grant_authority_event_writer = world
+                    .get_resource_unchecked_mut::<Events<GrantAuthorityEvent<P>>>()
+                    .unwrap();
+                let mut revoke_authority_event_writer = world
+                    .get_resource_unchecked_mut::<Events<RevokeAuthorityEvent<P>>>()
+                    .unwrap();
+                let mut reject_entity_event_writer = world
+                    .get_resource_unchecked_mut::<Events<RejectEntityEvent>>()
+                    .unwrap();
+                let mut take_over_entity_event_writer = world
+                    .get_resource_unchecked_mut::<Events<TakeOverEntityEvent>>()
+                    .unwrap();
                 let mut message_event_writer = world
                     .get_resource_unchecked_mut::<Events<MessageEvent<P>>>()
                     .unwrap();
+                let mut blob_progress_event_writer = world
+                    .get_resource_unchecked_mut::<Events<BlobProgressEvent>>()
+                    .unwrap();
+                let mut blob_event_writer = world
+                    .get_resource_unchecked_mut::<Events<BlobEvent>>()
+                    .unwrap();
                 let mut new_command_event_writer = world
                     .get_resource_unchecked_mut::<Events<NewCommandEvent<P>>>()
                     .unwrap();
@@ -110,6 +129,20 @@ pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
                             update_component_event_writer
                                 .send(UpdateComponentEvent(entity, component));
                         }
+                        Ok(Event::GrantAuthority(entity, component)) => {
+                            grant_authority_event_writer
+                                .send(GrantAuthorityEvent(entity, component));
+                        }
+                        Ok(Event::RevokeAuthority(entity, component)) => {
+                            revoke_authority_event_writer
+                                .send(RevokeAuthorityEvent(entity, component));
+                        }
+                        Ok(Event::RejectEntity(entity)) => {
+                            reject_entity_event_writer.send(RejectEntityEvent(entity));
+                        }
+                        Ok(Event::TakeOverEntity(entity)) => {
+                            take_over_entity_event_writer.send(TakeOverEntityEvent(entity));
+                        }
                         Ok(Event::Message(message)) => {
                             message_event_writer.send(MessageEvent(message));
                         }
@@ -119,6 +152,16 @@ pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
                         Ok(Event::ReplayCommand(entity, command)) => {
                             replay_command_event_writer.send(ReplayCommandEvent(entity, command));
                         }
+                        Ok(Event::BlobProgress(name, received_bytes, total_bytes)) => {
+                            blob_progress_event_writer.send(BlobProgressEvent(
+                                name,
+                                received_bytes,
+                                total_bytes,
+                            ));
+                        }
+                        Ok(Event::Blob(name, bytes)) => {
+                            blob_event_writer.send(BlobEvent(name, bytes));
+                        }
                         Err(_) => {}
                     }
                 }
@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Duration};
 
 use bevy::ecs::entity::Entity;
 
@@ -109,3 +109,239 @@ impl<P: ProtocolType> Command<P> for OwnEntity {
             .set_owner(&self.user_key);
     }
 }
+
+//// Set Priority ////
+
+#[derive(Debug)]
+pub(crate) struct SetPriority {
+    entity: Entity,
+    priority: f32,
+}
+
+impl SetPriority {
+    pub fn new(entity: &Entity, priority: f32) -> Self {
+        return SetPriority {
+            entity: *entity,
+            priority,
+        };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for SetPriority {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .set_priority(self.priority);
+    }
+}
+
+//// Set Component Priority ////
+
+#[derive(Debug)]
+pub(crate) struct SetComponentPriority<P: ProtocolType, R: Replicate<P>> {
+    entity: Entity,
+    priority: f32,
+    phantom_p: PhantomData<P>,
+    phantom_r: PhantomData<R>,
+}
+
+impl<P: ProtocolType, R: Replicate<P>> SetComponentPriority<P, R> {
+    pub fn new(entity: &Entity, priority: f32) -> Self {
+        return SetComponentPriority {
+            entity: *entity,
+            priority,
+            phantom_p: PhantomData,
+            phantom_r: PhantomData,
+        };
+    }
+}
+
+impl<P: ProtocolType, R: Replicate<P>> Command<P> for SetComponentPriority<P, R> {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .set_component_priority::<R>(self.priority);
+    }
+}
+
+//// Set Update Interval ////
+
+#[derive(Debug)]
+pub(crate) struct SetUpdateInterval {
+    entity: Entity,
+    interval: Duration,
+}
+
+impl SetUpdateInterval {
+    pub fn new(entity: &Entity, interval: Duration) -> Self {
+        return SetUpdateInterval {
+            entity: *entity,
+            interval,
+        };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for SetUpdateInterval {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .set_update_interval(self.interval);
+    }
+}
+
+//// Set Interest Position ////
+
+#[derive(Debug)]
+pub(crate) struct SetInterestPosition {
+    entity: Entity,
+    x: f32,
+    y: f32,
+}
+
+impl SetInterestPosition {
+    pub fn new(entity: &Entity, x: f32, y: f32) -> Self {
+        return SetInterestPosition {
+            entity: *entity,
+            x,
+            y,
+        };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for SetInterestPosition {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .set_interest_position(self.x, self.y);
+    }
+}
+
+//// Give Authority ////
+
+#[derive(Debug)]
+pub(crate) struct GiveAuthority<P: ProtocolType, R: Replicate<P>> {
+    entity: Entity,
+    user_key: UserKey,
+    phantom_p: PhantomData<P>,
+    phantom_r: PhantomData<R>,
+}
+
+impl<P: ProtocolType, R: Replicate<P>> GiveAuthority<P, R> {
+    pub fn new(entity: &Entity, user_key: &UserKey) -> Self {
+        return GiveAuthority {
+            entity: *entity,
+            user_key: *user_key,
+            phantom_p: PhantomData,
+            phantom_r: PhantomData,
+        };
+    }
+}
+
+impl<P: ProtocolType, R: Replicate<P>> Command<P> for GiveAuthority<P, R> {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .give_authority::<R>(&self.user_key);
+    }
+}
+
+//// Revoke Authority ////
+
+#[derive(Debug)]
+pub(crate) struct RevokeAuthority<P: ProtocolType, R: Replicate<P>> {
+    entity: Entity,
+    phantom_p: PhantomData<P>,
+    phantom_r: PhantomData<R>,
+}
+
+impl<P: ProtocolType, R: Replicate<P>> RevokeAuthority<P, R> {
+    pub fn new(entity: &Entity) -> Self {
+        return RevokeAuthority {
+            entity: *entity,
+            phantom_p: PhantomData,
+            phantom_r: PhantomData,
+        };
+    }
+}
+
+impl<P: ProtocolType, R: Replicate<P>> Command<P> for RevokeAuthority<P, R> {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server
+            .entity_mut(world, &self.entity)
+            .revoke_authority::<R>();
+    }
+}
+
+//// Apply Authority Updates ////
+
+pub(crate) struct ApplyAuthorityUpdates<P: ProtocolType> {
+    validator: Box<dyn FnMut(&UserKey, &Entity, &P) -> bool + Send + Sync>,
+}
+
+impl<P: ProtocolType> ApplyAuthorityUpdates<P> {
+    pub fn new<F: FnMut(&UserKey, &Entity, &P) -> bool + Send + Sync + 'static>(
+        validator: F,
+    ) -> Self {
+        return ApplyAuthorityUpdates {
+            validator: Box::new(validator),
+        };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for ApplyAuthorityUpdates<P> {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        let mut validator = self.validator;
+        server.apply_authority_updates(world, |user_key, entity, component| {
+            validator(user_key, entity, component)
+        });
+    }
+}
+
+//// Apply Client Entity Actions ////
+
+#[derive(Debug)]
+pub(crate) struct ApplyClientEntityActions;
+
+impl<P: ProtocolType> Command<P> for ApplyClientEntityActions {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server.apply_client_entity_actions(world);
+    }
+}
+
+//// Reject Client Entity ////
+
+#[derive(Debug)]
+pub(crate) struct RejectClientEntity {
+    entity: Entity,
+}
+
+impl RejectClientEntity {
+    pub fn new(entity: &Entity) -> Self {
+        return RejectClientEntity { entity: *entity };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for RejectClientEntity {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server.reject_client_entity(world, &self.entity);
+    }
+}
+
+//// Take Over Client Entity ////
+
+#[derive(Debug)]
+pub(crate) struct TakeOverClientEntity {
+    entity: Entity,
+}
+
+impl TakeOverClientEntity {
+    pub fn new(entity: &Entity) -> Self {
+        return TakeOverClientEntity { entity: *entity };
+    }
+}
+
+impl<P: ProtocolType> Command<P> for TakeOverClientEntity {
+    fn write(self: Box<Self>, server: &mut Server<P, Entity>, world: WorldMut) {
+        server.take_over_client_entity(world, &self.entity);
+    }
+}
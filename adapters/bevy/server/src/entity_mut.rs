@@ -1,9 +1,14 @@
+use std::time::Duration;
+
 use bevy::ecs::entity::Entity;
 
 use naia_server::{ProtocolType, Replicate, RoomKey, UserKey};
 
 use super::{
-    commands::{DespawnEntity, InsertComponent, OwnEntity, RemoveComponent},
+    commands::{
+        DespawnEntity, GiveAuthority, InsertComponent, OwnEntity, RemoveComponent, RevokeAuthority,
+        SetComponentPriority, SetInterestPosition, SetPriority, SetUpdateInterval,
+    },
     server::Server,
 };
 
@@ -43,6 +48,48 @@ impl<'a, 'b, P: ProtocolType> EntityMut<'a, 'b, P> {
         self
     }
 
+    // Priority
+
+    pub fn set_priority(&mut self, priority: f32) -> &mut Self {
+        self.server.add(SetPriority::new(&self.entity, priority));
+        self
+    }
+
+    pub fn set_component_priority<R: Replicate<P>>(&mut self, priority: f32) -> &mut Self {
+        self.server
+            .add(SetComponentPriority::<P, R>::new(&self.entity, priority));
+        self
+    }
+
+    // Update Rate
+
+    pub fn set_update_interval(&mut self, interval: Duration) -> &mut Self {
+        self.server
+            .add(SetUpdateInterval::new(&self.entity, interval));
+        self
+    }
+
+    // Interest
+
+    pub fn set_interest_position(&mut self, x: f32, y: f32) -> &mut Self {
+        self.server
+            .add(SetInterestPosition::new(&self.entity, x, y));
+        self
+    }
+
+    // Authority
+
+    pub fn give_authority<R: Replicate<P>>(&mut self, user_key: &UserKey) -> &mut Self {
+        self.server
+            .add(GiveAuthority::<P, R>::new(&self.entity, user_key));
+        self
+    }
+
+    pub fn revoke_authority<R: Replicate<P>>(&mut self) -> &mut Self {
+        self.server.add(RevokeAuthority::<P, R>::new(&self.entity));
+        self
+    }
+
     // Users
 
     pub fn set_owner(&mut self, user_key: &UserKey) -> &mut Self {
@@ -7,3 +7,5 @@ pub struct ConnectionEvent(pub UserKey);
 pub struct DisconnectionEvent(pub UserKey, pub User);
 pub struct MessageEvent<P: ProtocolType>(pub UserKey, pub P);
 pub struct CommandEvent<P: ProtocolType>(pub UserKey, pub Entity, pub P);
+pub struct BlobDeliveredEvent(pub UserKey, pub String);
+pub struct ClientSpawnedEntityEvent(pub UserKey, pub Entity);
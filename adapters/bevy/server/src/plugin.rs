@@ -11,7 +11,10 @@ use naia_server::{ProtocolType, Server, ServerAddrs, ServerConfig, SharedConfig}
 use naia_bevy_shared::WorldData;
 
 use super::{
-    events::{AuthorizationEvent, CommandEvent, ConnectionEvent, DisconnectionEvent, MessageEvent},
+    events::{
+        AuthorizationEvent, BlobDeliveredEvent, ClientSpawnedEntityEvent, CommandEvent,
+        ConnectionEvent, DisconnectionEvent, MessageEvent,
+    },
     resource::ServerResource,
     stage::{PrivateStage, Stage},
     systems::{before_receive_events, finish_tick, should_tick},
@@ -71,6 +74,8 @@ impl<P: ProtocolType> PluginType for Plugin<P> {
             .add_event::<DisconnectionEvent>()
             .add_event::<MessageEvent<P>>()
             .add_event::<CommandEvent<P>>()
+            .add_event::<BlobDeliveredEvent>()
+            .add_event::<ClientSpawnedEntityEvent>()
         // STAGES //
             .add_stage_before(CoreStage::PreUpdate,
                               PrivateStage::BeforeReceiveEvents,
@@ -1,4 +1,4 @@
-use std::{collections::VecDeque, marker::PhantomData};
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc, time::Duration};
 
 use bevy::ecs::{
     entity::Entity,
@@ -7,13 +7,20 @@ use bevy::ecs::{
 };
 
 use naia_server::{
-    EntityRef, Event, NaiaServerError, ProtocolType, Replicate, RoomKey, RoomMut, RoomRef,
-    Server as NaiaServer, UserKey, UserMut, UserRef, UserScopeMut,
+    ChannelIndex, EntityRef, Event, NaiaServerError, ProtocolType, Replicate, RoomKey, RoomMut,
+    RoomRef, Server as NaiaServer, UserKey, UserMut, UserRef, UserScopeMut,
 };
 
 use naia_bevy_shared::{WorldProxy, WorldRef};
 
-use super::{commands::Command, entity_mut::EntityMut, state::State};
+use super::{
+    commands::{
+        ApplyAuthorityUpdates, ApplyClientEntityActions, Command, RejectClientEntity,
+        TakeOverClientEntity,
+    },
+    entity_mut::EntityMut,
+    state::State,
+};
 
 // Server
 
@@ -57,15 +64,18 @@ impl<'a, P: ProtocolType> Server<'a, P> {
     }
 
     //// Messages ////
-    pub fn send_message<R: Replicate<P>>(
+    pub fn send_message<R: Replicate<P>, C: ChannelIndex>(
         &mut self,
         user_key: &UserKey,
         message_ref: &R,
-        guaranteed_delivery: bool,
+        channel: C,
     ) {
-        return self
-            .server
-            .send_message(user_key, message_ref, guaranteed_delivery);
+        return self.server.send_message(user_key, message_ref, channel);
+    }
+
+    //// Blobs ////
+    pub fn send_blob<B: Into<Arc<[u8]>>>(&mut self, user_key: &UserKey, name: &str, bytes: B) {
+        return self.server.send_blob(user_key, name, bytes);
     }
 
     //// Updates ////
@@ -74,10 +84,73 @@ impl<'a, P: ProtocolType> Server<'a, P> {
         return self.server.scope_checks();
     }
 
+    pub fn dirty_scope_checks(&mut self) -> Vec<(RoomKey, UserKey, Entity)> {
+        return self.server.dirty_scope_checks();
+    }
+
+    pub fn mark_entity_scope_dirty(&mut self, entity: &Entity) {
+        return self.server.mark_entity_scope_dirty(entity);
+    }
+
+    pub fn mark_user_scope_dirty(&mut self, user_key: &UserKey) {
+        return self.server.mark_user_scope_dirty(user_key);
+    }
+
     pub fn send_all_updates(&mut self) {
         return self.server.send_all_updates(self.world.proxy());
     }
 
+    pub fn send_all_updates_with_priority<F: FnMut(&World, &UserKey, &Entity) -> f32>(
+        &mut self,
+        mut priority_evaluator: F,
+    ) {
+        let world = self.world;
+        return self
+            .server
+            .send_all_updates_with_priority(world.proxy(), |_, user_key, entity| {
+                priority_evaluator(world, user_key, entity)
+            });
+    }
+
+    pub fn set_component_update_interval<R: Replicate<P>>(&mut self, interval: Duration) {
+        return self.server.set_component_update_interval::<R>(interval);
+    }
+
+    pub fn apply_authority_updates<
+        F: FnMut(&UserKey, &Entity, &P) -> bool + Send + Sync + 'static,
+    >(
+        &mut self,
+        validator: F,
+    ) {
+        self.add(ApplyAuthorityUpdates::new(validator));
+    }
+
+    //// Client Entities ////
+
+    pub fn apply_client_entity_actions(&mut self) {
+        self.add(ApplyClientEntityActions);
+    }
+
+    pub fn accept_client_entity(&mut self, entity: &Entity) {
+        self.server.accept_client_entity(entity);
+    }
+
+    pub fn reject_client_entity(&mut self, entity: &Entity) {
+        self.add(RejectClientEntity::new(entity));
+    }
+
+    pub fn take_over_client_entity(&mut self, entity: &Entity) {
+        self.add(TakeOverClientEntity::new(entity));
+    }
+
+    pub fn client_entity_owner(&self, entity: &Entity) -> Option<UserKey> {
+        return self.server.client_entity_owner(entity);
+    }
+
+    pub fn client_entity_is_accepted(&self, entity: &Entity) -> bool {
+        return self.server.client_entity_is_accepted(entity);
+    }
+
     //// Entities ////
 
     pub fn spawn(&mut self) -> EntityMut<'a, '_, P> {
@@ -154,6 +227,14 @@ impl<'a, P: ProtocolType> Server<'a, P> {
         return self.server.rooms_count();
     }
 
+    pub fn entity_rooms(&self, entity: &Entity) -> Vec<RoomKey> {
+        return self.server.entity_rooms(entity);
+    }
+
+    pub fn user_rooms(&self, user_key: &UserKey) -> Vec<RoomKey> {
+        return self.server.user_rooms(user_key);
+    }
+
     //// Ticks ////
 
     pub fn client_tick(&self, user_key: &UserKey) -> Option<u16> {
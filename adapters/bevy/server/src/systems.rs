@@ -11,7 +11,10 @@ use bevy::{
 use naia_server::{Event, ProtocolType, Server};
 
 use super::{
-    events::{AuthorizationEvent, CommandEvent, ConnectionEvent, DisconnectionEvent, MessageEvent},
+    events::{
+        AuthorizationEvent, BlobDeliveredEvent, ClientSpawnedEntityEvent, CommandEvent,
+        ConnectionEvent, DisconnectionEvent, MessageEvent,
+    },
     resource::ServerResource,
 };
 
@@ -36,6 +39,12 @@ pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
                 let mut command_event_writer = world
                     .get_resource_unchecked_mut::<Events<CommandEvent<P>>>()
                     .unwrap();
+                let mut blob_delivered_event_writer = world
+                    .get_resource_unchecked_mut::<Events<BlobDeliveredEvent>>()
+                    .unwrap();
+                let mut client_spawned_entity_event_writer = world
+                    .get_resource_unchecked_mut::<Events<ClientSpawnedEntityEvent>>()
+                    .unwrap();
 
                 for event_result in event_results {
                     match event_result {
@@ -58,6 +67,13 @@ pub fn before_receive_events<P: ProtocolType>(world: &mut World) {
                         Ok(Event::Command(user_key, entity, command)) => {
                             command_event_writer.send(CommandEvent(user_key, entity, command));
                         }
+                        Ok(Event::BlobDelivered(user_key, name)) => {
+                            blob_delivered_event_writer.send(BlobDeliveredEvent(user_key, name));
+                        }
+                        Ok(Event::ClientSpawnedEntity(user_key, entity)) => {
+                            client_spawned_entity_event_writer
+                                .send(ClientSpawnedEntityEvent(user_key, entity));
+                        }
                         Err(_) => {}
                     }
                 }
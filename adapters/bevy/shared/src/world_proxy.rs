@@ -4,7 +4,7 @@ use bevy::ecs::{
 };
 
 use naia_shared::{
-    DiffMask, PacketReader, ProtocolInserter, ProtocolKindType, ProtocolType, ReplicaDynRefWrapper,
+    BitReader, ProtocolInserter, ProtocolKindType, ProtocolType, ReplicaDynRefWrapper,
     ReplicaMutWrapper, ReplicaRefWrapper, ReplicateSafe, WorldMutType, WorldRefType,
 };
 
@@ -144,15 +144,26 @@ impl<'w, P: 'static + ProtocolType> WorldMutType<P, Entity> for WorldMut<'w> {
         &mut self,
         entity: &Entity,
         component_kind: &P::Kind,
-        diff_mask: &DiffMask,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         packet_index: u16,
     ) {
         self.world
             .resource_scope(|world: &mut World, data: Mut<WorldData<P>>| {
                 if let Some(accessor) = data.get_component_access(component_kind) {
                     if let Some(mut component) = accessor.get_component_mut(world, entity) {
-                        component.read_partial(diff_mask, reader, packet_index);
+                        component.read_partial(reader, packet_index);
+                    }
+                }
+            });
+    }
+
+    fn mirror_component(&mut self, entity: &Entity, component: &P) {
+        let component_kind = component.dyn_ref().get_kind();
+        self.world
+            .resource_scope(|world: &mut World, data: Mut<WorldData<P>>| {
+                if let Some(accessor) = data.get_component_access(&component_kind) {
+                    if let Some(mut component_mut) = accessor.get_component_mut(world, entity) {
+                        component_mut.mirror(component);
                     }
                 }
             });
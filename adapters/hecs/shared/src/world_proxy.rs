@@ -1,8 +1,8 @@
 use hecs::{Entity, World};
 
 use naia_shared::{
-    DiffMask, PacketReader, ProtocolInserter, ProtocolType, ReplicaDynRefWrapper,
-    ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType, WorldRefType,
+    BitReader, ProtocolInserter, ProtocolType, ReplicaDynRefWrapper, ReplicaMutWrapper,
+    ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType, WorldRefType,
 };
 
 use super::{
@@ -149,13 +149,21 @@ impl<'w, 'd, P: ProtocolType> WorldMutType<P, Entity> for WorldMut<'w, 'd, P> {
         &mut self,
         entity: &Entity,
         component_kind: &P::Kind,
-        diff_mask: &DiffMask,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         packet_index: u16,
     ) {
         if let Some(access) = self.world_data.get_component_access(component_kind) {
             if let Some(mut component) = access.get_component_mut(self.world, entity) {
-                component.read_partial(diff_mask, reader, packet_index);
+                component.read_partial(reader, packet_index);
+            }
+        }
+    }
+
+    fn mirror_component(&mut self, entity: &Entity, component: &P) {
+        let component_kind = component.dyn_ref().get_kind();
+        if let Some(access) = self.world_data.get_component_access(&component_kind) {
+            if let Some(mut component_mut) = access.get_component_mut(self.world, entity) {
+                component_mut.mirror(component);
             }
         }
     }
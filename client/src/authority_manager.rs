@@ -0,0 +1,163 @@
+use std::{collections::HashMap, hash::Hash};
+
+use naia_shared::{
+    sequence_greater_than, BitWriter, LocalComponentKey, PacketNotifiable, ProtocolType,
+    WorldRefType,
+};
+
+use super::packet_writer::PacketWriter;
+
+struct AuthorityRecord<E: Copy, K: Copy> {
+    entity: E,
+    kind: K,
+    // the last collected state of the Component, as bytes to compare against
+    // & as bits to write into an outgoing packet
+    state: Option<(Box<[u8]>, BitWriter)>,
+    pending: bool,
+}
+
+/// Keeps track of the Components the Server has given this Client authority
+/// over, and sends their full state to the Server whenever they change
+pub struct AuthorityManager<P: ProtocolType, E: Copy + Eq + Hash> {
+    records: HashMap<LocalComponentKey, AuthorityRecord<E, P::Kind>>,
+    last_authority_changes: HashMap<LocalComponentKey, u16>,
+    sent_updates: HashMap<u16, Vec<LocalComponentKey>>,
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> AuthorityManager<P, E> {
+    pub fn new() -> Self {
+        AuthorityManager {
+            records: HashMap::new(),
+            last_authority_changes: HashMap::new(),
+            sent_updates: HashMap::new(),
+        }
+    }
+
+    /// Records that authority over a Component has been granted, in the
+    /// packet with the given index. Returns true if authority was not already
+    /// held, and has not since been revoked by a newer packet
+    pub fn grant(
+        &mut self,
+        packet_index: u16,
+        component_key: &LocalComponentKey,
+        entity: &E,
+        component_kind: &P::Kind,
+    ) -> bool {
+        if !self.is_newest_change(packet_index, component_key)
+            || self.records.contains_key(component_key)
+        {
+            return false;
+        }
+        self.records.insert(
+            *component_key,
+            AuthorityRecord {
+                entity: *entity,
+                kind: *component_kind,
+                state: None,
+                pending: false,
+            },
+        );
+        return true;
+    }
+
+    /// Records that authority over a Component has been revoked, in the packet
+    /// with the given index. Returns the Entity & kind of the Component if
+    /// authority was held
+    pub fn revoke(
+        &mut self,
+        packet_index: u16,
+        component_key: &LocalComponentKey,
+    ) -> Option<(E, P::Kind)> {
+        if !self.is_newest_change(packet_index, component_key) {
+            return None;
+        }
+        let record = self.records.remove(component_key)?;
+        return Some((record.entity, record.kind));
+    }
+
+    /// Forgets about a Component which has been removed
+    pub fn remove_component(&mut self, component_key: &LocalComponentKey) {
+        self.records.remove(component_key);
+        self.last_authority_changes.remove(component_key);
+    }
+
+    pub fn has_authority(&self, entity: &E, component_kind: &P::Kind) -> bool {
+        return self
+            .records
+            .values()
+            .any(|record| record.entity == *entity && record.kind == *component_kind);
+    }
+
+    /// Compares the state of each Component held authority over with the
+    /// state last collected, queueing an update for those which have changed
+    pub fn collect_updates<W: WorldRefType<P, E>>(&mut self, world: &W) {
+        for record in self.records.values_mut() {
+            if let Some(component) = world.get_component_of_kind(&record.entity, &record.kind) {
+                let mut bits = BitWriter::new();
+                component.write(&mut bits);
+                let bytes = bits.clone().to_bytes();
+
+                if let Some((last_bytes, last_bits)) = &record.state {
+                    if *last_bytes == bytes && last_bits.bits_number() == bits.bits_number() {
+                        continue;
+                    }
+                    record.pending = true;
+                }
+                // the first state collected is the one received from the Server
+                record.state = Some((bytes, bits));
+            }
+        }
+    }
+
+    pub fn has_outgoing_updates(&self) -> bool {
+        return self.records.values().any(|record| record.pending);
+    }
+
+    /// Writes queued updates into the outgoing packet with the given index,
+    /// until it is full
+    pub fn write_updates(&mut self, writer: &mut PacketWriter, packet_index: u16) {
+        for (component_key, record) in self.records.iter_mut() {
+            if !record.pending {
+                continue;
+            }
+            if let Some((_, bits)) = &record.state {
+                if !writer.write_component_update::<P>(component_key, &record.kind, bits) {
+                    break;
+                }
+                record.pending = false;
+                self.sent_updates
+                    .entry(packet_index)
+                    .or_insert_with(Vec::new)
+                    .push(*component_key);
+            }
+        }
+    }
+
+    fn is_newest_change(&mut self, packet_index: u16, component_key: &LocalComponentKey) -> bool {
+        if let Some(last_index) = self.last_authority_changes.get(component_key) {
+            if !sequence_greater_than(packet_index, *last_index) {
+                return false;
+            }
+        }
+        self.last_authority_changes
+            .insert(*component_key, packet_index);
+        return true;
+    }
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for AuthorityManager<P, E> {
+    fn notify_packet_delivered(&mut self, packet_index: u16) {
+        self.sent_updates.remove(&packet_index);
+    }
+
+    fn notify_packet_dropped(&mut self, packet_index: u16) {
+        if let Some(component_keys) = self.sent_updates.remove(&packet_index) {
+            // the latest state is resent, rather than the one which was dropped
+            for component_key in component_keys {
+                if let Some(record) = self.records.get_mut(&component_key) {
+                    record.pending = true;
+                }
+            }
+        }
+    }
+}
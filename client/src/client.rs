@@ -1,24 +1,35 @@
-use std::{collections::VecDeque, hash::Hash, marker::PhantomData, net::SocketAddr};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use naia_client_socket::{Packet, Socket};
+use naia_shared::Clock;
 
 pub use naia_shared::{
-    ConnectionConfig, ManagerType, Manifest, PacketReader, PacketType, ProtocolKindType,
-    ProtocolType, ReplicateSafe, SequenceIterator, SharedConfig, StandardHeader, Timer, Timestamp,
-    WorldMutType, WorldRefType,
+    BitWriter, BlobEvent, BlobHash, BlobStore, ChannelConfig, ChannelIndex, ConnectionConfig,
+    Interpolate, ManagerType, Manifest, PacketReader, PacketType, ProtocolKindType, ProtocolType,
+    Replicate, ReplicateSafe, SendMessageError, SequenceIterator, SharedConfig, StandardHeader,
+    Timer, Timestamp, WorldMutType, WorldRefType,
 };
 
 use super::{
     client_config::ClientConfig,
     connection::Connection,
     entity_action::EntityAction,
-    entity_ref::EntityRef,
+    entity_ref::{EntityMut, EntityRef},
     error::NaiaClientError,
     event::Event,
     handshake_manager::{HandshakeManager, HandshakeResult},
     io::Io,
     owned_entity::OwnedEntity,
+    reconciler::Tolerance,
     tick_manager::TickManager,
+    transport::{ClientTransport, SocketTransport},
 };
 
 /// Client can send/receive messages to/from a server, and has a pool of
@@ -26,6 +37,10 @@ use super::{
 pub struct Client<P: ProtocolType, E: Copy + Eq + Hash> {
     // Manifest
     manifest: Manifest<P>,
+    delta_compression: bool,
+    channel_configs: Vec<ChannelConfig>,
+    // Blobs
+    blob_store: Arc<RwLock<BlobStore>>,
     // Connection
     connection_config: ConnectionConfig,
     socket: Socket,
@@ -38,6 +53,14 @@ pub struct Client<P: ProtocolType, E: Copy + Eq + Hash> {
     outstanding_errors: VecDeque<NaiaClientError>,
     // Ticks
     tick_manager: Option<TickManager>,
+    // Interpolation
+    interpolation_buffer_size: usize,
+    interpolated_kinds: HashSet<P::Kind>,
+    // Reconciliation
+    prediction_tolerances: HashMap<P::Kind, Tolerance<P>>,
+    prediction_smoothing_duration: Duration,
+    // Time
+    clock: Arc<dyn Clock>,
     // Phantom
     phantom_k: PhantomData<E>,
 }
@@ -57,13 +80,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
 
         let socket = Socket::new(client_config.socket_config);
 
-        let handshake_manager = HandshakeManager::new(client_config.send_handshake_interval);
+        let handshake_manager = HandshakeManager::new(
+            client_config.send_handshake_interval,
+            &shared_config.channels,
+            &client_config.clock,
+        );
 
         let tick_manager = {
             if let Some(duration) = shared_config.tick_interval {
                 Some(TickManager::new(
                     duration,
                     client_config.minimum_command_latency,
+                    &client_config.clock,
                 ))
             } else {
                 None
@@ -73,6 +101,10 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
         Client {
             // Manifest
             manifest: shared_config.manifest,
+            delta_compression: shared_config.delta_compression,
+            channel_configs: shared_config.channels,
+            // Blobs
+            blob_store: Arc::new(RwLock::new(BlobStore::new())),
             // Connection
             io: Io::new(),
             socket,
@@ -85,6 +117,14 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
             outstanding_errors: VecDeque::new(),
             // Ticks
             tick_manager,
+            // Interpolation
+            interpolation_buffer_size: client_config.interpolation_buffer_size,
+            interpolated_kinds: HashSet::new(),
+            // Reconciliation
+            prediction_tolerances: HashMap::new(),
+            prediction_smoothing_duration: client_config.prediction_smoothing_duration,
+            // Time
+            clock: client_config.clock,
             // Phantom
             phantom_k: PhantomData,
         }
@@ -94,10 +134,21 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
     pub fn connect(&mut self, server_address: SocketAddr) {
         self.address = Some(server_address);
         self.socket.connect(server_address);
-        self.io.load(
+        self.io.load(Box::new(SocketTransport::new(
             self.socket.get_packet_sender(),
             self.socket.get_packet_receiver(),
-        );
+        )));
+    }
+
+    /// Connect to the given server address through the given transport rather
+    /// than the Client's socket, for example a MemoryClientTransport in tests
+    pub fn connect_with_transport<T: ClientTransport + 'static>(
+        &mut self,
+        server_address: SocketAddr,
+        transport: T,
+    ) {
+        self.address = Some(server_address);
+        self.io.load(Box::new(transport));
     }
 
     /// Set the auth object to use when setting up a connection with the Server
@@ -108,13 +159,43 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
 
     // Messages
 
-    /// Queues up an Message to be sent to the Server
-    pub fn send_message<R: ReplicateSafe<P>>(&mut self, message: &R, guaranteed_delivery: bool) {
+    /// Queues up an Message to be sent to the Server, through the given Channel
+    pub fn send_message<R: ReplicateSafe<P>, C: ChannelIndex>(&mut self, message: &R, channel: C) {
         if let Some(connection) = &mut self.server_connection {
-            connection.send_message(message, guaranteed_delivery);
+            match connection.send_message(message, channel) {
+                Ok(()) => {}
+                Err(SendMessageError::QueueOverflow) => {
+                    self.outstanding_errors
+                        .push_back(NaiaClientError::QueueOverflow);
+                }
+                Err(SendMessageError::ChannelNotFound(channel_index)) => {
+                    self.outstanding_errors
+                        .push_back(NaiaClientError::ChannelNotFound(channel_index));
+                }
+                Err(SendMessageError::MessageTooLarge) => {
+                    self.outstanding_errors
+                        .push_back(NaiaClientError::MessageTooLarge);
+                }
+            }
+        } else {
+            self.outstanding_errors
+                .push_back(NaiaClientError::NotConnected);
         }
     }
 
+    // Blobs
+
+    /// Caches a Blob, so that the Server need not send it, returning the
+    /// Blob's content hash. Useful for Blobs persisted between sessions
+    pub fn cache_blob(&mut self, bytes: Box<[u8]>) -> BlobHash {
+        return self.blob_store.write().unwrap().insert(bytes);
+    }
+
+    /// Gets a cached Blob, given its content hash
+    pub fn cached_blob(&self, hash: &BlobHash) -> Option<Box<[u8]>> {
+        return self.blob_store.read().unwrap().get(hash).map(Box::from);
+    }
+
     /// Queues up a Command for an assigned Entity to be sent to the Server
     pub fn send_command<R: ReplicateSafe<P>>(&mut self, predicted_entity: &E, command: R) {
         if let Some(connection) = self.server_connection.as_mut() {
@@ -123,6 +204,9 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                     OwnedEntity::new(&confirmed_entity, &predicted_entity);
                 connection.send_command(entity_pair, command);
             }
+        } else {
+            self.outstanding_errors
+                .push_back(NaiaClientError::NotConnected);
         }
     }
 
@@ -139,11 +223,49 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
         return EntityRef::new(self, world, &entity);
     }
 
+    /// Spawns a new Entity on the Client, which is replicated to the Server
+    /// along with the Components inserted into it, and returns an EntityMut
+    /// which can be used for further operations on the Entity. Entities
+    /// spawned while not connected are not replicated
+    pub fn spawn_entity<'s, W: WorldMutType<P, E>>(
+        &'s mut self,
+        mut world: W,
+    ) -> EntityMut<'s, P, E, W> {
+        let entity = world.spawn_entity();
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.spawn_client_entity(&entity);
+        }
+        return EntityMut::new(self, world, &entity);
+    }
+
+    /// Retrieves an EntityMut that exposes read and write operations for an
+    /// Entity spawned by the Client.
+    /// Panics if the Entity does not exist.
+    pub fn entity_mut<'s, W: WorldMutType<P, E>>(
+        &'s mut self,
+        world: W,
+        entity: &E,
+    ) -> EntityMut<'s, P, E, W> {
+        if !world.has_entity(entity) {
+            panic!("No Entity exists for given Key!");
+        }
+        return EntityMut::new(self, world, &entity);
+    }
+
     /// Return a list of all Entities
     pub fn entities<W: WorldRefType<P, E>>(&self, world: &W) -> Vec<E> {
         return world.entities();
     }
 
+    /// Returns whether the Server has given the Client authority over the
+    /// Component of the given type on an Entity
+    pub fn has_authority<R: ReplicateSafe<P>>(&self, entity: &E) -> bool {
+        if let Some(connection) = &self.server_connection {
+            return connection.has_authority(entity, &P::kind_of::<R>());
+        }
+        return false;
+    }
+
     // Connection
 
     /// Get the address currently associated with the Server
@@ -199,6 +321,88 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
         }
     }
 
+    /// Keeps the most recent states received from the Server for every
+    /// Component of the given type, so that they can be retrieved with
+    /// `interpolated`
+    pub fn enable_interpolation<R: ReplicateSafe<P>>(&mut self) {
+        let component_kind = P::kind_of::<R>();
+        self.interpolated_kinds.insert(component_kind);
+        if let Some(connection) = &mut self.server_connection {
+            connection.enable_interpolation(&component_kind);
+        }
+    }
+
+    /// Gets the state of a Component on an Entity for the current frame,
+    /// interpolated between the states received from the Server. The
+    /// Component's type must first be passed to `enable_interpolation`.
+    /// Returns None if no state of the Component has been received
+    pub fn interpolated<R: ReplicateSafe<P> + Interpolate>(&self, entity: &E) -> Option<R> {
+        let connection = self.server_connection.as_ref()?;
+        // interpolate between the states of the previous & current tick
+        let render_time = self.tick_manager.as_ref().map(|tick_manager| {
+            (
+                tick_manager.get_server_tick().wrapping_sub(1),
+                tick_manager.fraction,
+            )
+        });
+        let (previous, next, fraction) =
+            connection.interpolation_states(entity, &P::kind_of::<R>(), render_time)?;
+        let previous = previous.cast_ref::<R>()?;
+        let next = next.cast_ref::<R>()?;
+        return Some(previous.interpolate(next, fraction));
+    }
+
+    // Reconciliation
+
+    /// Enables reconciliation of predicted Entities, given a tolerance which
+    /// returns whether the predicted & authoritative states of a Component of
+    /// the given type are close enough. Once enabled, an owned Entity is only
+    /// rewound & its Commands replayed if the state received from the Server
+    /// is not within tolerance of the state predicted for that tick.
+    /// Components without a tolerance are not compared
+    pub fn set_prediction_tolerance<
+        R: ReplicateSafe<P>,
+        F: Fn(&R, &R) -> bool + Send + Sync + 'static,
+    >(
+        &mut self,
+        tolerance: F,
+    ) {
+        let component_kind = P::kind_of::<R>();
+        let tolerance: Tolerance<P> = Arc::new(move |predicted: &P, authoritative: &P| {
+            match (predicted.cast_ref::<R>(), authoritative.cast_ref::<R>()) {
+                (Some(predicted), Some(authoritative)) => tolerance(predicted, authoritative),
+                _ => false,
+            }
+        });
+        if let Some(connection) = &mut self.server_connection {
+            connection.set_prediction_tolerance(&component_kind, tolerance.clone());
+        }
+        self.prediction_tolerances.insert(component_kind, tolerance);
+    }
+
+    /// Gets the state of a Component on a predicted Entity, smoothed from the
+    /// mispredicted state to the current one after a correction, so that
+    /// the correction is not visible as a snap.
+    /// Returns None if the Component does not exist
+    pub fn smoothed<R: ReplicateSafe<P> + Interpolate, W: WorldRefType<P, E>>(
+        &self,
+        world: &W,
+        predicted_entity: &E,
+    ) -> Option<R> {
+        let component = world.get_component::<R>(predicted_entity)?;
+        let current: &R = &component;
+        if let Some(connection) = &self.server_connection {
+            if let Some((mispredicted, fraction)) =
+                connection.prediction_correction(predicted_entity, &P::kind_of::<R>())
+            {
+                if let Some(mispredicted) = mispredicted.cast_ref::<R>() {
+                    return Some(mispredicted.interpolate(current, fraction));
+                }
+            }
+        }
+        return Some(current.interpolate(current, 1.0));
+    }
+
     // Receive Data from Server! Very important!
 
     /// Must call this regularly (preferably at the beginning of every draw
@@ -233,15 +437,39 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                 if connection.should_drop() {
                     self.server_connection = None;
                     self.handshake_manager.disconnect();
+                    events.push_back(Err(NaiaClientError::Timeout));
                     events.push_back(Ok(Event::Disconnection));
                     return events; // exit early, we're disconnected, who cares?
                 }
-                // process replays
+                // record predictions, then process replays
+                if let Some(client_tick) = client_tick_opt {
+                    connection.record_predictions(&world, client_tick);
+                }
                 connection.process_replays(&mut world);
                 // receive messages
                 while let Some(message) = connection.get_incoming_message() {
                     events.push_back(Ok(Event::Message(message)));
                 }
+                // receive blobs
+                while let Some(blob_event) = connection.get_blob_event() {
+                    match blob_event {
+                        BlobEvent::Progress {
+                            name,
+                            received_bytes,
+                            total_bytes,
+                        } => {
+                            events.push_back(Ok(Event::BlobProgress(
+                                name,
+                                received_bytes,
+                                total_bytes,
+                            )));
+                        }
+                        BlobEvent::Received { name, bytes } => {
+                            events.push_back(Ok(Event::Blob(name, bytes)));
+                        }
+                        BlobEvent::Delivered { .. } => {}
+                    }
+                }
                 // receive entity actions
                 while let Some(action) = connection.get_incoming_entity_action() {
                     let event: Event<P, E> = {
@@ -262,6 +490,14 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                             EntityAction::RemoveComponent(entity, component) => {
                                 Event::RemoveComponent(entity, component.clone())
                             }
+                            EntityAction::GrantAuthority(entity, component_kind) => {
+                                Event::GrantAuthority(entity, component_kind)
+                            }
+                            EntityAction::RevokeAuthority(entity, component_kind) => {
+                                Event::RevokeAuthority(entity, component_kind)
+                            }
+                            EntityAction::RejectEntity(entity) => Event::RejectEntity(entity),
+                            EntityAction::TakeOverEntity(entity) => Event::TakeOverEntity(entity),
                         }
                     };
                     events.push_back(Ok(event));
@@ -296,6 +532,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                     );
                 }
                 // send packets
+                connection.collect_authority_updates(&world);
                 while let Some(payload) = connection.get_outgoing_packet(client_tick_opt) {
                     self.io.send_packet(Packet::new_raw(payload));
                     connection.mark_sent();
@@ -308,8 +545,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                 } else {
                     connection.tickless_read_incoming(&mut world, &self.manifest);
                 }
+                // malformed data packets
+                while let Some(error) = connection.pop_decode_error() {
+                    events.push_back(Err(NaiaClientError::from_decode(error)));
+                }
             }
             None => {
+                // new errors
+                while let Some(err) = self.outstanding_errors.pop_front() {
+                    events.push_back(Err(err));
+                }
                 self.handshake_manager.send_packet(&mut self.io);
             }
         }
@@ -330,6 +575,53 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
         return false;
     }
 
+    //// Client Entities
+
+    /// Despawns an Entity spawned by the Client, along with its Components
+    pub(crate) fn client_entity_despawn<W: WorldMutType<P, E>>(
+        &mut self,
+        world: &mut W,
+        entity: &E,
+    ) {
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.despawn_client_entity(entity);
+        }
+        world.despawn_entity(entity);
+    }
+
+    /// Adds a Component to an Entity spawned by the Client
+    pub(crate) fn client_entity_insert_component<R: ReplicateSafe<P>, W: WorldMutType<P, E>>(
+        &mut self,
+        world: &mut W,
+        entity: &E,
+        component_ref: R,
+    ) {
+        if let Some(connection) = self.server_connection.as_mut() {
+            if connection.has_client_entity(entity) {
+                let mut component_bits = BitWriter::new();
+                component_ref.write(&mut component_bits);
+                connection.client_entity_insert_component(
+                    entity,
+                    &component_ref.get_kind(),
+                    &component_bits,
+                );
+            }
+        }
+        world.insert_component(entity, component_ref);
+    }
+
+    /// Removes a Component from an Entity spawned by the Client
+    pub(crate) fn client_entity_remove_component<R: Replicate<P>, W: WorldMutType<P, E>>(
+        &mut self,
+        world: &mut W,
+        entity: &E,
+    ) -> Option<R> {
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.client_entity_remove_component(entity, &P::kind_of::<R>());
+        }
+        return world.remove_component::<R>(entity);
+    }
+
     // internal functions
 
     fn maintain_socket(&mut self) {
@@ -338,12 +630,20 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
             match self.io.receive_packet() {
                 Ok(event) => {
                     if let Some(packet) = event {
+                        let client_tick_opt = self.client_tick();
                         let server_connection_wrapper = self.server_connection.as_mut();
 
                         if let Some(server_connection) = server_connection_wrapper {
+                            let (header, payload) = match StandardHeader::read(packet.payload()) {
+                                Ok(header_and_payload) => header_and_payload,
+                                Err(error) => {
+                                    self.outstanding_errors
+                                        .push_back(NaiaClientError::from_decode(error));
+                                    continue;
+                                }
+                            };
                             server_connection.mark_heard();
 
-                            let (header, payload) = StandardHeader::read(packet.payload());
                             let tick_manager: Option<&mut TickManager> = {
                                 if let Some(tick_manager) = &mut self.tick_manager {
                                     Some(tick_manager)
@@ -362,22 +662,62 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                                     );
                                 }
                                 PacketType::Heartbeat => {}
+                                PacketType::Ping => {
+                                    match server_connection.process_ping(&payload) {
+                                        Ok(pong_payload) => {
+                                            internal_send_with_connection::<P, E>(
+                                                client_tick_opt,
+                                                &mut self.io,
+                                                server_connection,
+                                                PacketType::Pong,
+                                                pong_payload,
+                                            );
+                                        }
+                                        Err(error) => {
+                                            self.outstanding_errors
+                                                .push_back(NaiaClientError::from_decode(error));
+                                        }
+                                    }
+                                }
                                 PacketType::Pong => {
-                                    server_connection.process_pong(&payload);
+                                    if let Err(error) = server_connection.process_pong(&payload) {
+                                        self.outstanding_errors
+                                            .push_back(NaiaClientError::from_decode(error));
+                                    }
                                 }
                                 _ => {} // TODO: explicitly cover these cases
                             }
                         } else {
-                            if self
+                            let handshake_result = match self
                                 .handshake_manager
                                 .receive_packet(&mut self.tick_manager, packet)
-                                == HandshakeResult::Connected
                             {
-                                let server_connection =
-                                    Connection::new(self.server_address(), &self.connection_config);
+                                Ok(handshake_result) => handshake_result,
+                                Err(error) => {
+                                    self.outstanding_errors
+                                        .push_back(NaiaClientError::from_decode(error));
+                                    continue;
+                                }
+                            };
+                            if handshake_result == HandshakeResult::Connected {
+                                let server_connection = Connection::new(
+                                    self.server_address(),
+                                    &self.connection_config,
+                                    self.delta_compression,
+                                    &self.channel_configs,
+                                    &self.blob_store,
+                                    self.interpolation_buffer_size,
+                                    &self.interpolated_kinds,
+                                    &self.prediction_tolerances,
+                                    self.prediction_smoothing_duration,
+                                    &self.clock,
+                                );
 
                                 self.server_connection = Some(server_connection);
                                 self.outstanding_connect = true;
+                            } else if handshake_result == HandshakeResult::Rejected {
+                                self.outstanding_errors
+                                    .push_back(NaiaClientError::HandshakeRejected);
                             }
                         }
                     } else {
@@ -386,7 +726,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Client<P, E> {
                 }
                 Err(error) => {
                     self.outstanding_errors
-                        .push_back(NaiaClientError::Wrapped(Box::new(error)));
+                        .push_back(NaiaClientError::Socket(Box::new(error)));
                 }
             }
         }
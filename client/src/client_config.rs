@@ -1,6 +1,6 @@
-use std::{default::Default, time::Duration};
+use std::{default::Default, sync::Arc, time::Duration};
 
-use naia_shared::SocketConfig;
+use naia_shared::{Clock, SocketConfig, SystemClock};
 
 /// Contains Config properties which will be used by a Server or Client
 #[derive(Clone)]
@@ -32,6 +32,16 @@ pub struct ClientConfig {
     /// helpful early on in the connection, when estimates of latency are
     /// less accurate.
     pub minimum_command_latency: Option<Duration>,
+    /// The number of states received from the Server to keep for every
+    /// Component which is interpolated. At least 2 states are always kept
+    pub interpolation_buffer_size: usize,
+    /// The duration over which the correction of a mispredicted Entity is
+    /// smoothed, when reconciling predictions. A duration of 0 disables
+    /// smoothing
+    pub prediction_smoothing_duration: Duration,
+    /// The source of time for the Client's ticks, timers & timeouts. Tests &
+    /// replays can use a ManualClock to drive time explicitly
+    pub clock: Arc<dyn Clock>,
 }
 
 impl Default for ClientConfig {
@@ -44,6 +54,9 @@ impl Default for ClientConfig {
             ping_interval: Duration::from_secs(1),
             rtt_sample_size: 20,
             minimum_command_latency: None,
+            interpolation_buffer_size: 2,
+            prediction_smoothing_duration: Duration::from_millis(100),
+            clock: Arc::new(SystemClock::new()),
         }
     }
 }
@@ -0,0 +1,207 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use naia_shared::{
+    BitWriter, EntityActionType, LocalEntity, NaiaKey, PacketNotifiable, ProtocolKindType,
+    ProtocolType, CLIENT_ENTITY_ACTION_WINDOW,
+};
+
+use super::packet_writer::PacketWriter;
+
+/// Keeps track of the Entities spawned by the Client, and sends the changes
+/// made to them to the Server. Actions are numbered, so that the Server can
+/// apply them in the order they were made, and are resent until delivered
+pub struct ClientEntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
+    world_to_local_entity: HashMap<E, LocalEntity>,
+    local_to_world_entity: HashMap<LocalEntity, E>,
+    // keys are not recycled, so that a response from the Server about an
+    // Entity which has since been despawned can't affect a newer one
+    next_local_entity: u16,
+    next_action_index: u16,
+    queued_actions: VecDeque<(u16, BitWriter)>,
+    sent_actions: HashMap<u16, Vec<(u16, BitWriter)>>,
+    undelivered_actions: HashSet<u16>,
+    oldest_undelivered_action: u16,
+    phantom_p: PhantomData<P>,
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> ClientEntityManager<P, E> {
+    pub fn new() -> Self {
+        ClientEntityManager {
+            world_to_local_entity: HashMap::new(),
+            local_to_world_entity: HashMap::new(),
+            next_local_entity: 0,
+            next_action_index: 0,
+            queued_actions: VecDeque::new(),
+            sent_actions: HashMap::new(),
+            undelivered_actions: HashSet::new(),
+            oldest_undelivered_action: 0,
+            phantom_p: PhantomData,
+        }
+    }
+
+    pub fn has_entity(&self, entity: &E) -> bool {
+        return self.world_to_local_entity.contains_key(entity);
+    }
+
+    pub fn spawn_entity(&mut self, entity: &E) {
+        let local_entity = LocalEntity::from_u16(self.next_local_entity);
+        self.next_local_entity = self.next_local_entity.wrapping_add(1);
+        self.world_to_local_entity.insert(*entity, local_entity);
+        self.local_to_world_entity.insert(local_entity, *entity);
+
+        let mut action_bits = BitWriter::new();
+        EntityActionType::SpawnEntity.write(&mut action_bits);
+        action_bits.write_u16(local_entity.to_u16()); // write local entity
+        self.queue_action(action_bits);
+    }
+
+    pub fn despawn_entity(&mut self, entity: &E) {
+        if let Some(local_entity) = self.world_to_local_entity.remove(entity) {
+            self.local_to_world_entity.remove(&local_entity);
+
+            let mut action_bits = BitWriter::new();
+            EntityActionType::DespawnEntity.write(&mut action_bits);
+            action_bits.write_u16(local_entity.to_u16()); // write local entity
+            self.queue_action(action_bits);
+        }
+    }
+
+    /// Queues the insertion of a Component, given its full state
+    pub fn insert_component(
+        &mut self,
+        entity: &E,
+        component_kind: &P::Kind,
+        component_bits: &BitWriter,
+    ) {
+        if let Some(local_entity) = self.world_to_local_entity.get(entity) {
+            let mut action_bits = BitWriter::new();
+            EntityActionType::InsertComponent.write(&mut action_bits);
+            action_bits.write_u16(local_entity.to_u16()); // write local entity
+            component_kind.write(&mut action_bits); // write component kind
+            action_bits.append(component_bits); // write component payload
+            self.queue_action(action_bits);
+        }
+    }
+
+    pub fn remove_component(&mut self, entity: &E, component_kind: &P::Kind) {
+        if let Some(local_entity) = self.world_to_local_entity.get(entity) {
+            let mut action_bits = BitWriter::new();
+            EntityActionType::RemoveComponent.write(&mut action_bits);
+            action_bits.write_u16(local_entity.to_u16()); // write local entity
+            component_kind.write(&mut action_bits); // write component kind
+            self.queue_action(action_bits);
+        }
+    }
+
+    /// Stops tracking an Entity the Server has rejected or taken over,
+    /// returning it
+    pub fn release_entity(&mut self, local_entity: &LocalEntity) -> Option<E> {
+        let entity = self.local_to_world_entity.remove(local_entity)?;
+        self.world_to_local_entity.remove(&entity);
+        return Some(entity);
+    }
+
+    pub fn has_outgoing_actions(&self) -> bool {
+        return !self.queued_actions.is_empty();
+    }
+
+    /// Writes queued actions into the outgoing packet with the given index,
+    /// until it is full, or the Server has no room for more
+    pub fn write_actions(&mut self, writer: &mut PacketWriter, packet_index: u16) {
+        while let Some((action_index, action_bits)) = self.queued_actions.pop_front() {
+            if action_index.wrapping_sub(self.oldest_undelivered_action)
+                >= CLIENT_ENTITY_ACTION_WINDOW
+                || !writer.write_client_entity_action(action_index, &action_bits)
+            {
+                self.queued_actions.push_front((action_index, action_bits));
+                break;
+            }
+            self.sent_actions
+                .entry(packet_index)
+                .or_insert_with(Vec::new)
+                .push((action_index, action_bits));
+        }
+    }
+
+    fn queue_action(&mut self, action_bits: BitWriter) {
+        self.queued_actions
+            .push_back((self.next_action_index, action_bits));
+        self.undelivered_actions.insert(self.next_action_index);
+        self.next_action_index = self.next_action_index.wrapping_add(1);
+    }
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for ClientEntityManager<P, E> {
+    fn notify_packet_delivered(&mut self, packet_index: u16) {
+        if let Some(delivered_actions) = self.sent_actions.remove(&packet_index) {
+            for (action_index, _) in delivered_actions {
+                self.undelivered_actions.remove(&action_index);
+            }
+            while self.oldest_undelivered_action != self.next_action_index
+                && !self
+                    .undelivered_actions
+                    .contains(&self.oldest_undelivered_action)
+            {
+                self.oldest_undelivered_action = self.oldest_undelivered_action.wrapping_add(1);
+            }
+        }
+    }
+
+    fn notify_packet_dropped(&mut self, packet_index: u16) {
+        if let Some(dropped_actions) = self.sent_actions.remove(&packet_index) {
+            // resent ahead of newer actions, the Server re-orders them anyway
+            for dropped_action in dropped_actions.into_iter().rev() {
+                self.queued_actions.push_front(dropped_action);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod client_entity_manager_tests {
+    use naia_shared::{PacketNotifiable, CLIENT_ENTITY_ACTION_WINDOW};
+
+    use super::ClientEntityManager;
+    use crate::{packet_writer::PacketWriter, test_protocol::Protocol};
+
+    // writes queued actions into packets until none are written, returning
+    // the index of the next packet
+    fn write_packets(manager: &mut ClientEntityManager<Protocol, u32>, packet_index: u16) -> u16 {
+        let mut packet_index = packet_index;
+        loop {
+            manager.write_actions(&mut PacketWriter::new(), packet_index);
+            if !manager.sent_actions.contains_key(&packet_index) {
+                return packet_index;
+            }
+            packet_index += 1;
+        }
+    }
+
+    #[test]
+    fn action_window() {
+        let mut manager = ClientEntityManager::<Protocol, u32>::new();
+        for entity in 0..=u32::from(CLIENT_ENTITY_ACTION_WINDOW) {
+            manager.spawn_entity(&entity);
+        }
+
+        // nothing is sent past the window of the Server while the first
+        // action is undelivered
+        let packet_count = write_packets(&mut manager, 0);
+        let sent_count: usize = manager.sent_actions.values().map(Vec::len).sum();
+        assert_eq!(sent_count, usize::from(CLIENT_ENTITY_ACTION_WINDOW));
+        assert!(manager.has_outgoing_actions());
+
+        for packet_index in 1..packet_count {
+            manager.notify_packet_delivered(packet_index);
+        }
+        assert_eq!(write_packets(&mut manager, packet_count), packet_count);
+
+        manager.notify_packet_delivered(0);
+        assert_eq!(write_packets(&mut manager, packet_count), packet_count + 1);
+        assert!(!manager.has_outgoing_actions());
+    }
+}
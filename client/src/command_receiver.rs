@@ -5,7 +5,7 @@ use std::{
 
 use naia_shared::{wrapping_diff, ProtocolType, SequenceBuffer, SequenceIterator, WorldMutType};
 
-use super::{entity_manager::EntityManager, owned_entity::OwnedEntity};
+use super::{entity_manager::EntityManager, owned_entity::OwnedEntity, reconciler::Reconciler};
 
 const COMMAND_HISTORY_SIZE: u16 = 64;
 
@@ -39,14 +39,28 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> CommandReceiver<P, E> {
         self.queued_command_replays.pop_front()
     }
 
-    /// Process any necessary replayed Command
+    /// Process any necessary replayed Command. When reconciling, a rewind only
+    /// happens if the authoritative state differs from the predicted one
     pub fn process_command_replay<W: WorldMutType<P, E>>(
         &mut self,
         world: &mut W,
         entity_manager: &mut EntityManager<P, E>,
+        reconciler: &mut Reconciler<P, E>,
     ) {
         for (world_entity, history_tick) in self.replay_trigger.iter() {
             if let Some(predicted_entity) = entity_manager.get_predicted_entity(world_entity) {
+                if reconciler.is_enabled() {
+                    if !reconciler.is_mispredicted(world, *history_tick, world_entity) {
+                        continue;
+                    }
+                    reconciler.record_correction(
+                        world,
+                        *history_tick,
+                        world_entity,
+                        &predicted_entity,
+                    );
+                }
+
                 // set prediction to server authoritative entity
                 entity_manager.prediction_reset_entity(world, world_entity);
 
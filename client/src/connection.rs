@@ -1,44 +1,100 @@
-use std::{collections::VecDeque, hash::Hash, net::SocketAddr};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use naia_client_socket::Packet;
 
 use naia_shared::{
-    BaseConnection, ConnectionConfig, ManagerType, Manifest, PacketReader, PacketType,
-    ProtocolType, ReplicateSafe, SequenceNumber, StandardHeader, WorldMutType,
+    BaseConnection, BitReader, BitWriter, BlobEvent, BlobStore, ChannelConfig, ChannelIndex, Clock,
+    ConnectionConfig, DecodeError, ManagerType, Manifest, PacketNotifiable, PacketType,
+    ProtocolType, ReplicateSafe, SendMessageError, SequenceNumber, StandardHeader, WorldMutType,
+    WorldRefType,
 };
 
 use super::{
-    command_receiver::CommandReceiver, entity_action::EntityAction, entity_manager::EntityManager,
-    owned_entity::OwnedEntity, packet_writer::PacketWriter, ping_manager::PingManager,
-    tick_manager::TickManager, tick_queue::TickQueue,
+    authority_manager::AuthorityManager,
+    client_entity_manager::ClientEntityManager,
+    command_receiver::CommandReceiver,
+    entity_action::EntityAction,
+    entity_manager::EntityManager,
+    owned_entity::OwnedEntity,
+    packet_writer::PacketWriter,
+    ping_manager::PingManager,
+    reconciler::{Reconciler, Tolerance},
+    tick_manager::TickManager,
+    tick_queue::TickQueue,
 };
 
 pub struct Connection<P: ProtocolType, E: Copy + Eq + Hash> {
     base_connection: BaseConnection<P>,
     entity_manager: EntityManager<P, E>,
+    authority_manager: AuthorityManager<P, E>,
+    client_entity_manager: ClientEntityManager<P, E>,
     ping_manager: PingManager,
     command_sender: VecDeque<(OwnedEntity<E>, P)>,
     command_receiver: CommandReceiver<P, E>,
+    reconciler: Reconciler<P, E>,
     jitter_buffer: TickQueue<(u16, Box<[u8]>)>,
+    decode_errors: VecDeque<DecodeError>,
 }
 
 impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
-    pub fn new(address: SocketAddr, connection_config: &ConnectionConfig) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        connection_config: &ConnectionConfig,
+        delta_compression: bool,
+        channel_configs: &[ChannelConfig],
+        blob_store: &Arc<RwLock<BlobStore>>,
+        interpolation_buffer_size: usize,
+        interpolated_kinds: &HashSet<P::Kind>,
+        prediction_tolerances: &HashMap<P::Kind, Tolerance<P>>,
+        prediction_smoothing_duration: Duration,
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
         return Connection {
-            base_connection: BaseConnection::new(address, connection_config),
-            entity_manager: EntityManager::new(),
+            base_connection: BaseConnection::new(
+                address,
+                connection_config,
+                channel_configs,
+                Some(blob_store.clone()),
+                clock,
+            ),
+            entity_manager: EntityManager::new(
+                delta_compression,
+                interpolation_buffer_size,
+                interpolated_kinds,
+            ),
+            authority_manager: AuthorityManager::new(),
+            client_entity_manager: ClientEntityManager::new(),
             ping_manager: PingManager::new(
                 connection_config.ping_interval,
                 connection_config.ping_sample_size,
+                clock,
             ),
             command_sender: VecDeque::new(),
             command_receiver: CommandReceiver::new(),
+            reconciler: Reconciler::new(
+                prediction_tolerances,
+                prediction_smoothing_duration,
+                clock,
+            ),
             jitter_buffer: TickQueue::new(),
+            decode_errors: VecDeque::new(),
         };
     }
 
     pub fn get_outgoing_packet(&mut self, host_tick_opt: Option<u16>) -> Option<Box<[u8]>> {
-        if self.base_connection.has_outgoing_messages() || !self.command_sender.is_empty() {
+        if self.base_connection.has_outgoing_messages()
+            || !self.command_sender.is_empty()
+            || self.base_connection.has_outgoing_blob_records()
+            || self.authority_manager.has_outgoing_updates()
+            || self.entity_manager.has_outgoing_state_requests()
+            || self.client_entity_manager.has_outgoing_actions()
+        {
             let mut writer = PacketWriter::new();
 
             // Commands
@@ -60,8 +116,21 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                 }
             }
 
-            // Messages
             let next_packet_index: u16 = self.get_next_packet_index();
+
+            // Client Entities
+            self.client_entity_manager
+                .write_actions(&mut writer, next_packet_index);
+
+            // Authority Updates
+            self.authority_manager
+                .write_updates(&mut writer, next_packet_index);
+
+            // Requests for the whole state of Components
+            self.entity_manager
+                .write_state_requests(&mut writer, next_packet_index);
+
+            // Messages
             while let Some(popped_message) =
                 self.base_connection.pop_outgoing_message(next_packet_index)
             {
@@ -72,6 +141,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                 }
             }
 
+            // Blobs
+            while let Some(popped_record) = self
+                .base_connection
+                .pop_outgoing_blob_record(next_packet_index)
+            {
+                if !writer.write_blob_record(&popped_record) {
+                    self.base_connection
+                        .unpop_outgoing_blob_record(next_packet_index, popped_record);
+                    break;
+                }
+            }
+
             // Add header
             if writer.has_bytes() {
                 // Get bytes from writer
@@ -98,28 +179,41 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         packet_index: u16,
         manifest: &Manifest<P>,
         data: &[u8],
-    ) {
-        let mut reader = PacketReader::new(data);
-        while reader.has_more() {
-            let manager_type: ManagerType = reader.read_u8().into();
+    ) -> Result<(), DecodeError> {
+        let mut reader = BitReader::new(data);
+        // each manager section is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let manager_type = ManagerType::read(&mut reader);
             match manager_type {
                 ManagerType::Message => {
-                    self.base_connection
-                        .process_message_data(&mut reader, manifest, packet_index);
+                    self.base_connection.process_message_data(
+                        &mut reader,
+                        manifest,
+                        packet_index,
+                    )?;
+                }
+                ManagerType::Blob => {
+                    self.base_connection.process_blob_data(&mut reader)?;
                 }
                 ManagerType::Entity => {
                     self.entity_manager.process_data(
                         world,
                         manifest,
                         &mut self.command_receiver,
+                        &mut self.authority_manager,
+                        &mut self.client_entity_manager,
                         packet_tick,
                         packet_index,
                         &mut reader,
-                    );
+                    )?;
+                }
+                _ => {
+                    // unable to read the remainder of the packet
+                    return Err(DecodeError::UnknownManagerType);
                 }
-                _ => {}
             }
         }
+        return reader.check();
     }
 
     pub fn buffer_data_packet(
@@ -143,6 +237,58 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         return self.entity_manager.entity_is_owned(entity);
     }
 
+    // Pass-through methods to underlying Authority Manager
+    pub fn collect_authority_updates<W: WorldRefType<P, E>>(&mut self, world: &W) {
+        self.authority_manager.collect_updates(world);
+    }
+
+    pub fn has_authority(&self, entity: &E, component_kind: &P::Kind) -> bool {
+        return self.authority_manager.has_authority(entity, component_kind);
+    }
+
+    pub fn enable_interpolation(&mut self, component_kind: &P::Kind) {
+        self.entity_manager.enable_interpolation(component_kind);
+    }
+
+    pub fn interpolation_states(
+        &self,
+        entity: &E,
+        component_kind: &P::Kind,
+        render_time: Option<(u16, f32)>,
+    ) -> Option<(&P, &P, f32)> {
+        return self
+            .entity_manager
+            .interpolation_states(entity, component_kind, render_time);
+    }
+
+    // Pass-through methods to underlying Client Entity Manager
+    pub fn has_client_entity(&self, entity: &E) -> bool {
+        return self.client_entity_manager.has_entity(entity);
+    }
+
+    pub fn spawn_client_entity(&mut self, entity: &E) {
+        self.client_entity_manager.spawn_entity(entity);
+    }
+
+    pub fn despawn_client_entity(&mut self, entity: &E) {
+        self.client_entity_manager.despawn_entity(entity);
+    }
+
+    pub fn client_entity_insert_component(
+        &mut self,
+        entity: &E,
+        component_kind: &P::Kind,
+        component_bits: &BitWriter,
+    ) {
+        self.client_entity_manager
+            .insert_component(entity, component_kind, component_bits);
+    }
+
+    pub fn client_entity_remove_component(&mut self, entity: &E, component_kind: &P::Kind) {
+        self.client_entity_manager
+            .remove_component(entity, component_kind);
+    }
+
     /// Reads buffered incoming data on the appropriate tick boundary
     pub fn frame_begin<W: WorldMutType<P, E>>(
         &mut self,
@@ -177,10 +323,20 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         while let Some((tick, packet_index, data_packet)) =
             self.get_buffered_data_packet(target_tick)
         {
-            self.process_incoming_data(world, tick, packet_index, manifest, &data_packet);
+            // the remainder of a malformed packet is skipped
+            if let Err(error) =
+                self.process_incoming_data(world, tick, packet_index, manifest, &data_packet)
+            {
+                self.decode_errors.push_back(error);
+            }
         }
     }
 
+    /// Gets the next error found while reading an incoming data packet
+    pub fn pop_decode_error(&mut self) -> Option<DecodeError> {
+        return self.decode_errors.pop_front();
+    }
+
     // Pass-through methods to underlying Connection
 
     pub fn mark_sent(&mut self) {
@@ -211,8 +367,13 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                 self.ping_manager.get_jitter(),
             );
         }
+        let mut notifier = OutgoingNotifier {
+            entity_manager: &mut self.entity_manager,
+            authority_manager: &mut self.authority_manager,
+            client_entity_manager: &mut self.client_entity_manager,
+        };
         self.base_connection
-            .process_incoming_header(header, &mut None);
+            .process_incoming_header(header, &mut Some(&mut notifier));
     }
 
     pub fn process_outgoing_header(
@@ -234,16 +395,22 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         return self.base_connection.get_next_packet_index();
     }
 
-    pub fn send_message<R: ReplicateSafe<P>>(&mut self, message: &R, guaranteed_delivery: bool) {
-        return self
-            .base_connection
-            .send_message(message, guaranteed_delivery);
+    pub fn send_message<R: ReplicateSafe<P>, C: ChannelIndex>(
+        &mut self,
+        message: &R,
+        channel: C,
+    ) -> Result<(), SendMessageError> {
+        return self.base_connection.send_message(message, channel);
     }
 
     pub fn get_incoming_message(&mut self) -> Option<P> {
         return self.base_connection.get_incoming_message();
     }
 
+    pub fn get_blob_event(&mut self) -> Option<BlobEvent> {
+        return self.base_connection.get_blob_event();
+    }
+
     pub fn get_last_received_tick(&self) -> u16 {
         self.base_connection.get_last_received_tick()
     }
@@ -255,8 +422,42 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
     }
 
     pub fn process_replays<W: WorldMutType<P, E>>(&mut self, world: &mut W) {
-        self.command_receiver
-            .process_command_replay(world, &mut self.entity_manager);
+        self.command_receiver.process_command_replay(
+            world,
+            &mut self.entity_manager,
+            &mut self.reconciler,
+        );
+    }
+
+    // Reconciliation
+
+    pub fn set_prediction_tolerance(&mut self, component_kind: &P::Kind, tolerance: Tolerance<P>) {
+        self.reconciler.set_tolerance(component_kind, tolerance);
+    }
+
+    /// Records the predicted state of every owned Entity for the given tick
+    pub fn record_predictions<W: WorldRefType<P, E>>(&mut self, world: &W, tick: u16) {
+        if !self.reconciler.is_enabled() {
+            return;
+        }
+        let owned_entities = self.entity_manager.owned_entities();
+        for (confirmed_entity, predicted_entity) in &owned_entities {
+            self.reconciler
+                .record_prediction(world, tick, confirmed_entity, predicted_entity);
+        }
+        let confirmed_entities: Vec<E> = owned_entities
+            .iter()
+            .map(|(confirmed_entity, _)| *confirmed_entity)
+            .collect();
+        self.reconciler.cleanup(&confirmed_entities);
+    }
+
+    pub fn prediction_correction(
+        &self,
+        predicted_entity: &E,
+        component_kind: &P::Kind,
+    ) -> Option<(&P, f32)> {
+        return self.reconciler.correction(predicted_entity, component_kind);
     }
 
     pub fn get_incoming_replay(&mut self) -> Option<(OwnedEntity<E>, P)> {
@@ -288,8 +489,13 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         return Packet::new_raw(payload);
     }
 
-    pub fn process_pong(&mut self, pong_payload: &[u8]) {
-        self.ping_manager.process_pong(pong_payload);
+    pub fn process_ping(&self, ping_payload: &[u8]) -> Result<Packet, DecodeError> {
+        let payload = self.ping_manager.process_ping(ping_payload)?;
+        return Ok(Packet::new_raw(payload));
+    }
+
+    pub fn process_pong(&mut self, pong_payload: &[u8]) -> Result<(), DecodeError> {
+        return self.ping_manager.process_pong(pong_payload);
     }
 
     pub fn get_rtt(&self) -> f32 {
@@ -307,3 +513,27 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         return None;
     }
 }
+
+// Notifies each of the managers which write into outgoing packets of their
+// delivery
+struct OutgoingNotifier<'a, P: ProtocolType, E: Copy + Eq + Hash> {
+    entity_manager: &'a mut EntityManager<P, E>,
+    authority_manager: &'a mut AuthorityManager<P, E>,
+    client_entity_manager: &'a mut ClientEntityManager<P, E>,
+}
+
+impl<'a, P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for OutgoingNotifier<'a, P, E> {
+    fn notify_packet_delivered(&mut self, packet_index: u16) {
+        self.entity_manager.notify_packet_delivered(packet_index);
+        self.authority_manager.notify_packet_delivered(packet_index);
+        self.client_entity_manager
+            .notify_packet_delivered(packet_index);
+    }
+
+    fn notify_packet_dropped(&mut self, packet_index: u16) {
+        self.entity_manager.notify_packet_dropped(packet_index);
+        self.authority_manager.notify_packet_dropped(packet_index);
+        self.client_entity_manager
+            .notify_packet_dropped(packet_index);
+    }
+}
@@ -3,4 +3,5 @@ pub enum ConnectionState {
     AwaitingChallengeResponse,
     AwaitingConnectResponse,
     Connected,
+    Rejected,
 }
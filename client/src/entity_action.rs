@@ -12,4 +12,8 @@ pub enum EntityAction<P: ProtocolType, E: Copy> {
     InsertComponent(E, P::Kind),
     UpdateComponent(E, P::Kind),
     RemoveComponent(E, P),
+    GrantAuthority(E, P::Kind),
+    RevokeAuthority(E, P::Kind),
+    RejectEntity(E),
+    TakeOverEntity(E),
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
     ops::Deref,
 };
@@ -7,13 +7,15 @@ use std::{
 use log::warn;
 
 use naia_shared::{
-    DiffMask, EntityActionType, LocalComponentKey, LocalEntity, Manifest, NaiaKey, PacketReader,
-    ProtocolKindType, ProtocolType, WorldMutType,
+    BitReader, DecodeError, EntityActionType, LocalComponentKey, LocalEntity, Manifest, NaiaKey,
+    PacketNotifiable, ProtocolKindType, ProtocolType, WorldMutType,
 };
 
 use super::{
+    authority_manager::AuthorityManager, client_entity_manager::ClientEntityManager,
     command_receiver::CommandReceiver, entity_action::EntityAction, entity_record::EntityRecord,
-    owned_entity::OwnedEntity,
+    interpolation_buffer::InterpolationBuffer, owned_entity::OwnedEntity,
+    packet_writer::PacketWriter, snapshot_history::SnapshotHistory,
 };
 
 pub struct EntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
@@ -22,16 +24,35 @@ pub struct EntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
     component_to_entity_map: HashMap<LocalComponentKey, E>,
     predicted_to_confirmed_entity: HashMap<E, E>,
     queued_incoming_messages: VecDeque<EntityAction<P, E>>,
+    delta_compression: bool,
+    component_snapshots: HashMap<LocalComponentKey, SnapshotHistory<P>>,
+    // Components missing a delta baseline, & whether a request for their
+    // whole state is waiting to be sent
+    state_requests: HashMap<LocalComponentKey, bool>,
+    sent_state_requests: HashMap<u16, Vec<LocalComponentKey>>,
+    interpolation_buffer: InterpolationBuffer<P, E>,
 }
 
 impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
-    pub fn new() -> Self {
+    pub fn new(
+        delta_compression: bool,
+        interpolation_buffer_size: usize,
+        interpolated_kinds: &HashSet<P::Kind>,
+    ) -> Self {
         EntityManager {
             local_to_world_entity: HashMap::new(),
             entity_records: HashMap::new(),
             component_to_entity_map: HashMap::new(),
             predicted_to_confirmed_entity: HashMap::new(),
             queued_incoming_messages: VecDeque::new(),
+            delta_compression,
+            component_snapshots: HashMap::new(),
+            state_requests: HashMap::new(),
+            sent_state_requests: HashMap::new(),
+            interpolation_buffer: InterpolationBuffer::new(
+                interpolation_buffer_size,
+                interpolated_kinds,
+            ),
         }
     }
 
@@ -40,30 +61,54 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         world: &mut W,
         manifest: &Manifest<P>,
         command_receiver: &mut CommandReceiver<P, E>,
+        authority_manager: &mut AuthorityManager<P, E>,
+        client_entity_manager: &mut ClientEntityManager<P, E>,
         packet_tick: u16,
         packet_index: u16,
-        reader: &mut PacketReader,
-    ) {
-        let entity_action_count = reader.read_u8();
-
-        for _ in 0..entity_action_count {
-            let message_type = EntityActionType::from_u8(reader.read_u8());
+        reader: &mut BitReader,
+    ) -> Result<(), DecodeError> {
+        // each action is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let message_type = EntityActionType::read(reader);
 
             match message_type {
                 EntityActionType::SpawnEntity => {
                     // Entity Creation
                     let local_entity = LocalEntity::from_u16(reader.read_u16());
-                    let components_num = reader.read_u8();
+                    // read every Component before touching the World, so that a malformed
+                    // packet doesn't leave a partially spawned Entity behind
+                    let mut new_components: Vec<(LocalComponentKey, P)> = Vec::new();
+                    while reader.read_bit() {
+                        let component_kind = P::Kind::read(reader);
+                        let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                        let new_component =
+                            manifest.create_replica(component_kind, reader, packet_index)?;
+                        new_components.push((component_key, new_component));
+                    }
+                    reader.check()?;
+
                     if self.local_to_world_entity.contains_key(&local_entity) {
                         // its possible we received a very late duplicate message
                         warn!("attempted to insert duplicate entity");
-                        // continue reading, just don't do anything with the data
-                        for _ in 0..components_num {
-                            let component_kind = P::Kind::from_u16(reader.read_u16());
-                            let _component_key = reader.read_u16();
-                            manifest.create_replica(component_kind, reader, packet_index);
+                        // just don't do anything with the data
+                        for (component_key, component) in new_components {
+                            self.record_duplicate_snapshot(&component_key, packet_index, component);
                         }
                     } else {
+                        let mut component_keys = HashSet::new();
+                        let mut component_kinds = HashSet::new();
+                        for (component_key, new_component) in &new_components {
+                            if self.component_to_entity_map.contains_key(component_key)
+                                || !component_keys.insert(*component_key)
+                            {
+                                return Err(DecodeError::InvalidReference);
+                            }
+                            // an Entity has at most one Component of each kind
+                            if !component_kinds.insert(new_component.dyn_ref().get_kind()) {
+                                return Err(DecodeError::InvalidValue);
+                            }
+                        }
+
                         // set up entity
                         let world_entity = world.spawn_entity();
                         self.local_to_world_entity
@@ -73,30 +118,34 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                         let entity_record = self.entity_records.get_mut(&world_entity).unwrap();
 
                         let mut component_list: Vec<P::Kind> = Vec::new();
-                        for _ in 0..components_num {
+                        for (component_key, new_component) in new_components {
                             // Component Creation //
-                            let component_kind = P::Kind::from_u16(reader.read_u16());
-                            let component_key = LocalComponentKey::from_u16(reader.read_u16());
-
-                            let new_component =
-                                manifest.create_replica(component_kind, reader, packet_index);
-                            if self.component_to_entity_map.contains_key(&component_key) {
-                                panic!("attempted to insert duplicate component");
-                            } else {
-                                {
-                                    let new_component_kind = new_component.dyn_ref().get_kind();
-                                    entity_record
-                                        .insert_component(&component_key, &new_component_kind);
-                                    component_list.push(new_component_kind);
-                                }
+                            {
+                                let new_component_kind = new_component.dyn_ref().get_kind();
+                                entity_record.insert_component(&component_key, &new_component_kind);
+                                component_list.push(new_component_kind);
+                            }
 
-                                self.component_to_entity_map
-                                    .insert(component_key, world_entity);
-                                new_component.extract_and_insert(&world_entity, world);
+                            self.component_to_entity_map
+                                .insert(component_key, world_entity);
+                            new_component.extract_and_insert(&world_entity, world);
+                            if self.delta_compression {
+                                let mut history = SnapshotHistory::new();
+                                history.insert(packet_index, new_component);
+                                self.component_snapshots.insert(component_key, history);
                             }
                             ////////////////////////
                         }
 
+                        for component_kind in &component_list {
+                            self.interpolation_buffer.record(
+                                world,
+                                &world_entity,
+                                component_kind,
+                                packet_tick,
+                            );
+                        }
+
                         self.queued_incoming_messages
                             .push_back(EntityAction::SpawnEntity(world_entity, component_list));
                         continue;
@@ -105,6 +154,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 EntityActionType::DespawnEntity => {
                     // Entity Deletion
                     let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    reader.check()?;
                     if let Some(world_entity) = self.local_to_world_entity.remove(&local_entity) {
                         if let Some(entity_record) = self.entity_records.remove(&world_entity) {
                             if entity_record.is_owned() {
@@ -128,9 +178,12 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
 
                             for component_key in entity_record.get_component_keys() {
                                 self.component_to_entity_map.remove(&component_key);
+                                self.component_snapshots.remove(&component_key);
+                                authority_manager.remove_component(&component_key);
                             }
 
                             world.despawn_entity(&world_entity);
+                            self.interpolation_buffer.remove_entity(&world_entity);
 
                             self.queued_incoming_messages
                                 .push_back(EntityAction::DespawnEntity(world_entity));
@@ -142,6 +195,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 EntityActionType::OwnEntity => {
                     // Assign Prediction Entity
                     let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    reader.check()?;
                     if let Some(world_entity) = self.local_to_world_entity.remove(&local_entity) {
                         if let Some(entity_record) = self.entity_records.get_mut(&world_entity) {
                             let prediction_entity = world.spawn_entity();
@@ -178,6 +232,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 EntityActionType::DisownEntity => {
                     // Unassign Prediction Entity
                     let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    reader.check()?;
                     if let Some(world_entity) = self.local_to_world_entity.get(&local_entity) {
                         if let Some(entity_record) = self.entity_records.get_mut(&world_entity) {
                             if entity_record.is_owned() {
@@ -202,11 +257,12 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 EntityActionType::InsertComponent => {
                     // Add Component to Entity
                     let local_entity = LocalEntity::from_u16(reader.read_u16());
-                    let component_kind = P::Kind::from_u16(reader.read_u16());
+                    let component_kind = P::Kind::read(reader);
                     let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
 
                     let new_component =
-                        manifest.create_replica(component_kind, reader, packet_index);
+                        manifest.create_replica(component_kind, reader, packet_index)?;
                     if self.component_to_entity_map.contains_key(&component_key) {
                         // its possible we received a very late duplicate message
                         warn!(
@@ -214,6 +270,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                             component_key.to_u16(),
                             local_entity.to_u16()
                         );
+                        self.record_duplicate_snapshot(&component_key, packet_index, new_component);
                     } else {
                         if !self.local_to_world_entity.contains_key(&local_entity) {
                             // its possible we received a very late duplicate message
@@ -225,6 +282,9 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                         } else {
                             let world_entity =
                                 self.local_to_world_entity.get(&local_entity).unwrap();
+                            if world.has_component_of_kind(world_entity, &component_kind) {
+                                return Err(DecodeError::InvalidValue);
+                            }
                             self.component_to_entity_map
                                 .insert(component_key, *world_entity);
 
@@ -233,6 +293,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                             entity_record.insert_component(&component_key, &component_kind);
 
                             new_component.extract_and_insert(world_entity, world);
+                            if self.delta_compression {
+                                let mut history = SnapshotHistory::new();
+                                history.insert(packet_index, new_component);
+                                self.component_snapshots.insert(component_key, history);
+                            }
+
+                            self.interpolation_buffer.record(
+                                world,
+                                world_entity,
+                                &component_kind,
+                                packet_tick,
+                            );
 
                             //TODO: handle inserting Component into an Entity that has a
                             // Prediction... !!!
@@ -248,6 +320,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 EntityActionType::UpdateComponent => {
                     // Component Update
                     let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
 
                     if let Some(world_entity) = self.component_to_entity_map.get_mut(&component_key)
                     {
@@ -255,15 +328,68 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                             let component_kind =
                                 entity_record.get_kind_from_key(&component_key).unwrap();
 
-                            let diff_mask: DiffMask = DiffMask::read(reader);
+                            if self.delta_compression {
+                                // read incoming state, relative to a previously received state
+                                let history = self
+                                    .component_snapshots
+                                    .entry(component_key)
+                                    .or_insert_with(SnapshotHistory::new);
+                                let snapshot = if reader.read_bit() {
+                                    let baseline_index = reader.read_u16();
+                                    if let Some(baseline) = history.get(baseline_index) {
+                                        let mut snapshot = ProtocolType::clone(baseline);
+                                        snapshot.dyn_mut().read_delta(reader, packet_index);
+                                        reader.check()?;
+                                        snapshot
+                                    } else {
+                                        // the packet holding the baseline was acknowledged, but
+                                        // never applied, so the whole Component is requested
+                                        warn!(
+                                            "received update for component: {}, relative to unknown state",
+                                            component_key.to_u16()
+                                        );
+                                        self.state_requests.insert(component_key, true);
+
+                                        // the layout of a delta doesn't depend on its baseline, so
+                                        // it is read past against the last state received. An
+                                        // encoding which rejects that state ends the packet
+                                        let mut skipped = match history.last() {
+                                            Some(last) => ProtocolType::clone(last),
+                                            None => return Err(DecodeError::InvalidReference),
+                                        };
+                                        skipped.dyn_mut().read_delta(reader, packet_index);
+                                        reader.check()?;
+                                        continue;
+                                    }
+                                } else {
+                                    self.state_requests.remove(&component_key);
+                                    manifest.create_replica(
+                                        *component_kind,
+                                        reader,
+                                        packet_index,
+                                    )?
+                                };
+                                let is_newest =
+                                    history.insert(packet_index, ProtocolType::clone(&snapshot));
+                                if is_newest {
+                                    world.mirror_component(world_entity, &snapshot);
+                                }
+                            } else {
+                                // read incoming delta
+                                world.component_read_partial(
+                                    world_entity,
+                                    component_kind,
+                                    reader,
+                                    packet_index,
+                                );
+                                reader.check()?;
+                            }
 
-                            // read incoming delta
-                            world.component_read_partial(
+                            self.interpolation_buffer.record(
+                                world,
                                 world_entity,
                                 component_kind,
-                                &diff_mask,
-                                reader,
-                                packet_index,
+                                packet_tick,
                             );
 
                             // check if Entity is Owned
@@ -281,12 +407,21 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                                     *world_entity,
                                     *component_kind,
                                 ));
+                            continue;
                         }
                     }
+
+                    // without the Component, the rest of the packet can't be read
+                    warn!(
+                        "received update for nonexistent component: {}",
+                        component_key.to_u16()
+                    );
+                    return Err(DecodeError::InvalidReference);
                 }
                 EntityActionType::RemoveComponent => {
                     // Component Removal
                     let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
 
                     if !self.component_to_entity_map.contains_key(&component_key) {
                         // This could happen due to a duplicated unreliable message
@@ -300,30 +435,162 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                     } else {
                         let world_entity =
                             self.component_to_entity_map.remove(&component_key).unwrap();
+                        self.component_snapshots.remove(&component_key);
+                        self.state_requests.remove(&component_key);
+                        authority_manager.remove_component(&component_key);
 
                         // Get entity record, remove component
-                        let component_kind = self
-                            .entity_records
-                            .get_mut(&world_entity)
-                            .expect("entity not instantiated properly? no such entity")
-                            .remove_component(&component_key)
-                            .expect("entity not instantiated properly? no type");
-
-                        // Get component for last change
-                        let component = world
-                            .remove_component_of_kind(&world_entity, &component_kind)
-                            .expect("Component already removed?");
-
-                        // Generate event
+                        let component_kind =
+                            match self.entity_records.get_mut(&world_entity).and_then(
+                                |entity_record| entity_record.remove_component(&component_key),
+                            ) {
+                                Some(component_kind) => component_kind,
+                                None => return Err(DecodeError::InvalidReference),
+                            };
+                        self.interpolation_buffer
+                            .remove_component(&world_entity, &component_kind);
+
+                        // Get component for last change, & generate event
+                        if let Some(component) =
+                            world.remove_component_of_kind(&world_entity, &component_kind)
+                        {
+                            self.queued_incoming_messages
+                                .push_back(EntityAction::RemoveComponent(world_entity, component));
+                        }
+                    }
+                }
+                EntityActionType::GrantAuthority => {
+                    // Give Client authority over Component
+                    let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
+                    if let Some((world_entity, component_kind)) =
+                        self.get_component_entity_and_kind(&component_key)
+                    {
+                        if authority_manager.grant(
+                            packet_index,
+                            &component_key,
+                            &world_entity,
+                            &component_kind,
+                        ) {
+                            self.queued_incoming_messages
+                                .push_back(EntityAction::GrantAuthority(
+                                    world_entity,
+                                    component_kind,
+                                ));
+                        }
+                    }
+                }
+                EntityActionType::RevokeAuthority => {
+                    // Take away Client authority over Component
+                    let component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
+                    if let Some((world_entity, component_kind)) =
+                        authority_manager.revoke(packet_index, &component_key)
+                    {
                         self.queued_incoming_messages
-                            .push_back(EntityAction::RemoveComponent(world_entity, component));
+                            .push_back(EntityAction::RevokeAuthority(world_entity, component_kind));
                     }
                 }
-                EntityActionType::Unknown => {
-                    panic!("received unknown type of entity action");
+                EntityActionType::RejectEntity => {
+                    // Client-spawned Entity rejected by the Server
+                    let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    reader.check()?;
+                    if let Some(world_entity) = client_entity_manager.release_entity(&local_entity)
+                    {
+                        if world.has_entity(&world_entity) {
+                            world.despawn_entity(&world_entity);
+                        }
+                        self.queued_incoming_messages
+                            .push_back(EntityAction::RejectEntity(world_entity));
+                    }
+                }
+                EntityActionType::TakeOverEntity => {
+                    // Client-spawned Entity taken over by the Server, which replicates it
+                    // from now on like any other Entity
+                    let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    reader.check()?;
+                    if let Some(world_entity) = client_entity_manager.release_entity(&local_entity)
+                    {
+                        if world.has_entity(&world_entity) {
+                            world.despawn_entity(&world_entity);
+                        }
+                        self.queued_incoming_messages
+                            .push_back(EntityAction::TakeOverEntity(world_entity));
+                    }
+                }
+                EntityActionType::RequestComponentState | EntityActionType::Unknown => {
+                    return Err(DecodeError::UnknownEntityAction);
                 }
             }
         }
+        return reader.check();
+    }
+
+    // Interpolation
+
+    pub fn enable_interpolation(&mut self, component_kind: &P::Kind) {
+        self.interpolation_buffer.enable_kind(component_kind);
+    }
+
+    pub fn interpolation_states(
+        &self,
+        entity: &E,
+        component_kind: &P::Kind,
+        render_time: Option<(u16, f32)>,
+    ) -> Option<(&P, &P, f32)> {
+        return self
+            .interpolation_buffer
+            .get(entity, component_kind, render_time);
+    }
+
+    // Delta Compression
+
+    /// Returns whether the state of any Component needs to be requested from
+    /// the Server
+    pub fn has_outgoing_state_requests(&self) -> bool {
+        return self.state_requests.values().any(|pending| *pending);
+    }
+
+    /// Writes requests for the whole state of Components missing a delta
+    /// baseline into the outgoing packet with the given index, until it is
+    /// full
+    pub fn write_state_requests(&mut self, writer: &mut PacketWriter, packet_index: u16) {
+        for (component_key, pending) in self.state_requests.iter_mut() {
+            if !*pending {
+                continue;
+            }
+            if !writer.write_state_request(component_key) {
+                break;
+            }
+            *pending = false;
+            self.sent_state_requests
+                .entry(packet_index)
+                .or_insert_with(Vec::new)
+                .push(*component_key);
+        }
+    }
+
+    // records the state of a Component received in a late duplicate message, as
+    // the Server may still encode later updates relative to it
+    fn record_duplicate_snapshot(
+        &mut self,
+        component_key: &LocalComponentKey,
+        packet_index: u16,
+        component: P,
+    ) {
+        if let Some(history) = self.component_snapshots.get_mut(component_key) {
+            history.insert(packet_index, component);
+        }
+    }
+
+    fn get_component_entity_and_kind(
+        &self,
+        component_key: &LocalComponentKey,
+    ) -> Option<(E, P::Kind)> {
+        let world_entity = self.component_to_entity_map.get(component_key)?;
+        let entity_record = self.entity_records.get(world_entity)?;
+        let component_kind = entity_record.get_kind_from_key(component_key)?;
+        return Some((*world_entity, *component_kind));
     }
 
     pub fn world_to_local_entity(&self, world_entity: &E) -> Option<LocalEntity> {
@@ -340,6 +607,15 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         return None;
     }
 
+    /// Gets every owned Entity, along with its prediction
+    pub fn owned_entities(&self) -> Vec<(E, E)> {
+        let mut output = Vec::new();
+        for (predicted_entity, confirmed_entity) in &self.predicted_to_confirmed_entity {
+            output.push((*confirmed_entity, *predicted_entity));
+        }
+        return output;
+    }
+
     pub fn get_confirmed_entity(&self, predicted_entity: &E) -> Option<&E> {
         return self.predicted_to_confirmed_entity.get(predicted_entity);
     }
@@ -374,3 +650,180 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         }
     }
 }
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for EntityManager<P, E> {
+    fn notify_packet_delivered(&mut self, packet_index: u16) {
+        self.sent_state_requests.remove(&packet_index);
+    }
+
+    fn notify_packet_dropped(&mut self, packet_index: u16) {
+        if let Some(component_keys) = self.sent_state_requests.remove(&packet_index) {
+            // requests are resent until the whole state has been received
+            for component_key in component_keys {
+                if let Some(pending) = self.state_requests.get_mut(&component_key) {
+                    *pending = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod entity_manager_tests {
+    use std::collections::HashSet;
+
+    use naia_demo_world::{Entity, World, WorldRefType};
+    use naia_shared::{
+        BitReader, BitWriter, DecodeError, EntityActionType, LocalEntity, NaiaKey,
+        ProtocolKindType, ProtocolType, ReplicateSafe,
+    };
+
+    use super::EntityManager;
+    use crate::{
+        authority_manager::AuthorityManager,
+        client_entity_manager::ClientEntityManager,
+        command_receiver::CommandReceiver,
+        test_protocol::{Position, Protocol},
+    };
+
+    fn process(
+        manager: &mut EntityManager<Protocol, Entity>,
+        world: &mut World<Protocol>,
+        packet_index: u16,
+        writer: BitWriter,
+    ) -> Result<(), DecodeError> {
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        return manager.process_data(
+            &mut world.proxy_mut(),
+            &Protocol::load(),
+            &mut CommandReceiver::new(),
+            &mut AuthorityManager::new(),
+            &mut ClientEntityManager::new(),
+            0,
+            packet_index,
+            &mut reader,
+        );
+    }
+
+    fn write_position(writer: &mut BitWriter, component_key: u16) {
+        Protocol::kind_of::<Position>().write(writer);
+        writer.write_u16(component_key);
+        Position::new_complete(1, 2).write(writer);
+    }
+
+    // writes a spawn of the given Entity, with a Position at (1, 2)
+    fn write_spawn(writer: &mut BitWriter, local_entity: u16, component_key: u16) {
+        writer.write_bit(true);
+        EntityActionType::SpawnEntity.write(writer);
+        writer.write_u16(local_entity);
+        writer.write_bit(true);
+        write_position(writer, component_key);
+        writer.write_bit(false);
+    }
+
+    fn x_at(manager: &EntityManager<Protocol, Entity>, world: &World<Protocol>, local: u16) -> u8 {
+        let entity = manager.local_to_world_entity[&LocalEntity::from_u16(local)];
+        return *world
+            .proxy()
+            .get_component::<Position>(&entity)
+            .unwrap()
+            .x
+            .get();
+    }
+
+    #[test]
+    fn spawn_with_duplicate_kind() {
+        let mut manager = EntityManager::new(false, 2, &HashSet::new());
+        let mut world = World::new();
+
+        // the Entity section the fuzz target crashed the demo World with: a
+        // spawn carrying two Components of the same kind
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::SpawnEntity.write(&mut writer);
+        writer.write_u16(0);
+        for component_key in 0..2 {
+            writer.write_bit(true);
+            write_position(&mut writer, component_key);
+        }
+        writer.write_bit(false);
+        writer.write_bit(false);
+
+        assert_eq!(
+            process(&mut manager, &mut world, 0, writer),
+            Err(DecodeError::InvalidValue)
+        );
+        assert!(world.proxy().entities().is_empty());
+    }
+
+    #[test]
+    fn insert_duplicate_kind() {
+        let mut manager = EntityManager::new(false, 2, &HashSet::new());
+        let mut world = World::new();
+
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::SpawnEntity.write(&mut writer);
+        writer.write_u16(0);
+        writer.write_bit(true);
+        write_position(&mut writer, 0);
+        writer.write_bit(false);
+        writer.write_bit(false);
+        process(&mut manager, &mut world, 0, writer).unwrap();
+
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::InsertComponent.write(&mut writer);
+        writer.write_u16(0);
+        write_position(&mut writer, 1);
+        writer.write_bit(false);
+        assert_eq!(
+            process(&mut manager, &mut world, 0, writer),
+            Err(DecodeError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn missing_baseline_requests_state() {
+        let mut manager = EntityManager::new(true, 2, &HashSet::new());
+        let mut world = World::new();
+
+        let mut writer = BitWriter::new();
+        write_spawn(&mut writer, 0, 0);
+        writer.write_bit(false);
+        process(&mut manager, &mut world, 0, writer).unwrap();
+
+        // an update relative to packet 7, which was acknowledged but never
+        // applied, followed by another action in the same packet
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::UpdateComponent.write(&mut writer);
+        writer.write_u16(0);
+        writer.write_bit(true);
+        writer.write_u16(7);
+        let baseline = Protocol::Position(Position::new_complete(3, 3));
+        Position::new_complete(5, 6).write_delta(&baseline, &mut writer);
+        write_spawn(&mut writer, 1, 1);
+        writer.write_bit(false);
+        process(&mut manager, &mut world, 8, writer).unwrap();
+
+        assert_eq!(x_at(&manager, &world, 0), 1);
+        assert_eq!(x_at(&manager, &world, 1), 1);
+        assert!(manager.has_outgoing_state_requests());
+
+        // the whole state answers the request
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::UpdateComponent.write(&mut writer);
+        writer.write_u16(0);
+        writer.write_bit(false);
+        Position::new_complete(5, 6).write(&mut writer);
+        writer.write_bit(false);
+        process(&mut manager, &mut world, 9, writer).unwrap();
+
+        assert_eq!(x_at(&manager, &world, 0), 5);
+        assert!(!manager.has_outgoing_state_requests());
+        assert!(manager.state_requests.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 use std::{hash::Hash, marker::PhantomData};
 
-use naia_shared::{ProtocolType, ReplicaRefWrapper, ReplicateSafe, WorldRefType};
+use naia_shared::{
+    ProtocolType, ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType,
+    WorldRefType,
+};
 
 use super::client::Client;
 
@@ -44,6 +47,54 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash, W: WorldRefType<P, E>> EntityRef<
     }
 }
 
+// EntityMut
+pub struct EntityMut<'s, P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>> {
+    client: &'s mut Client<P, E>,
+    world: W,
+    id: E,
+}
+
+impl<'s, P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>> EntityMut<'s, P, E, W> {
+    pub fn new(client: &'s mut Client<P, E>, world: W, key: &E) -> Self {
+        EntityMut {
+            client,
+            world,
+            id: *key,
+        }
+    }
+
+    pub fn id(&self) -> E {
+        self.id
+    }
+
+    pub fn despawn(&mut self) {
+        self.client.client_entity_despawn(&mut self.world, &self.id);
+    }
+
+    // Components
+
+    pub fn has_component<R: ReplicateSafe<P>>(&self) -> bool {
+        return self.world.has_component::<R>(&self.id);
+    }
+
+    pub fn component<R: ReplicateSafe<P>>(&mut self) -> Option<ReplicaMutWrapper<P, R>> {
+        return self.world.get_component_mut::<R>(&self.id);
+    }
+
+    pub fn insert_component<R: ReplicateSafe<P>>(&mut self, component_ref: R) -> &mut Self {
+        self.client
+            .client_entity_insert_component(&mut self.world, &self.id, component_ref);
+
+        self
+    }
+
+    pub fn remove_component<R: Replicate<P>>(&mut self) -> Option<R> {
+        return self
+            .client
+            .client_entity_remove_component::<R, W>(&mut self.world, &self.id);
+    }
+}
+
 // PredictedEntityRef
 pub struct PredictedEntityRef<P: ProtocolType, E: Copy, W: WorldRefType<P, E>> {
     world: W,
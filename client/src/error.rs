@@ -1,9 +1,39 @@
 use std::{error::Error, fmt};
 
+use naia_shared::DecodeError;
+
 #[derive(Debug)]
 pub enum NaiaClientError {
     Message(String),
-    Wrapped(Box<dyn Error + Send>),
+    /// Any other error. Must be `Send + Sync`, so that the Client's errors
+    /// can be sent between threads
+    Wrapped(Box<dyn Error + Send + Sync>),
+    /// The underlying socket failed to send or receive a packet
+    Socket(Box<dyn Error + Send + Sync>),
+    /// A malformed packet was received from the Server, & skipped
+    Decode(DecodeError),
+    /// A packet received from the Server refers to a Message or Component
+    /// kind unknown to the Client's Protocol, & was skipped
+    ProtocolMismatch(u16),
+    /// The Server hasn't been heard from in too long, & the Client has
+    /// disconnected
+    Timeout,
+    /// A Message to the Server was dropped, as the queue of its Channel was
+    /// full
+    QueueOverflow,
+    /// A Message was sent through a Channel index which isn't configured in
+    /// the SharedConfig, & was dropped
+    ChannelNotFound(u8),
+    /// A Message to the Server was dropped, as it is larger than
+    /// `MAX_MESSAGE_SIZE_BYTES`
+    MessageTooLarge,
+    /// The Server rejected the Client's connection request, as it could not
+    /// be validated, or didn't match the Server's authentication setting or
+    /// Channels, or its auth message was rejected
+    HandshakeRejected,
+    /// A Message or Command was sent before a connection to the Server was
+    /// established, & was dropped
+    NotConnected,
 }
 
 impl fmt::Display for NaiaClientError {
@@ -11,10 +41,71 @@ impl fmt::Display for NaiaClientError {
         match self {
             NaiaClientError::Message(msg) => write!(f, "Naia Client Error: {}", msg),
             NaiaClientError::Wrapped(boxed_err) => fmt::Display::fmt(boxed_err.as_ref(), f),
+            NaiaClientError::Socket(boxed_err) => {
+                write!(f, "Naia Client Error: socket error: {}", boxed_err)
+            }
+            NaiaClientError::Decode(error) => {
+                write!(f, "Naia Client Error: malformed packet: {}", error)
+            }
+            NaiaClientError::ProtocolMismatch(kind) => write!(
+                f,
+                "Naia Client Error: packet refers to unknown protocol kind: {}",
+                kind
+            ),
+            NaiaClientError::Timeout => write!(f, "Naia Client Error: connection timed out"),
+            NaiaClientError::QueueOverflow => write!(
+                f,
+                "Naia Client Error: message dropped, as its channel queue is full"
+            ),
+            NaiaClientError::ChannelNotFound(channel_index) => write!(
+                f,
+                "Naia Client Error: message dropped, as channel {} does not exist",
+                channel_index
+            ),
+            NaiaClientError::MessageTooLarge => {
+                write!(f, "Naia Client Error: message dropped, as it is too large")
+            }
+            NaiaClientError::HandshakeRejected => {
+                write!(f, "Naia Client Error: connection request rejected")
+            }
+            NaiaClientError::NotConnected => write!(
+                f,
+                "Naia Client Error: message or command dropped, as the client is not connected"
+            ),
+        }
+    }
+}
+
+impl NaiaClientError {
+    /// Classifies an error encountered while decoding a packet from the Server
+    pub(crate) fn from_decode(error: DecodeError) -> Self {
+        match error {
+            DecodeError::UnknownKind(kind) => NaiaClientError::ProtocolMismatch(kind),
+            error => NaiaClientError::Decode(error),
         }
     }
 }
 
 impl Error for NaiaClientError {}
-unsafe impl Send for NaiaClientError {}
-unsafe impl Sync for NaiaClientError {}
+
+#[cfg(test)]
+mod error_tests {
+    use std::io;
+
+    use super::NaiaClientError;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn socket_error() {
+        let error = NaiaClientError::Socket(Box::new(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "connection reset",
+        )));
+        assert_send_sync(&error);
+        assert_eq!(
+            error.to_string(),
+            "Naia Client Error: socket error: connection reset"
+        );
+    }
+}
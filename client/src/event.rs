@@ -38,6 +38,20 @@ pub enum Event<P: ProtocolType, E: Copy> {
     UpdateComponent(E, P::Kind),
     /// Occurs when a Component should be removed from the given Entity
     RemoveComponent(E, P),
+    /// Occurs when the Server has given the Client authority over a
+    /// Component, meaning changes made to it by the Client are sent to the
+    /// Server
+    GrantAuthority(E, P::Kind),
+    /// Occurs when the Server has taken away the Client's authority over a
+    /// Component, after which its state is again received from the Server
+    RevokeAuthority(E, P::Kind),
+    /// Occurs when the Server has rejected an Entity spawned by the Client.
+    /// The Entity has been despawned
+    RejectEntity(E),
+    /// Occurs when the Server has taken over an Entity spawned by the Client.
+    /// The Client's copy has been despawned, and the Entity is replicated
+    /// from the Server from now on, like any other, while it is in scope
+    TakeOverEntity(E),
     /// An Message emitted to the Client from the Server
     Message(P),
     /// A new Command received immediately to an assigned Entity, used to
@@ -47,4 +61,10 @@ pub enum Event<P: ProtocolType, E: Copy> {
     /// but which must be replayed after a "RewindEntity" event in order
     /// to extrapolate back to the "present"
     ReplayCommand(OwnedEntity<E>, P),
+    /// Occurs as part of a Blob streamed from the Server is received, with the
+    /// Blob's name, the number of bytes received, and its total size
+    BlobProgress(String, u32, u32),
+    /// A Blob streamed from the Server has been fully received, or was
+    /// already cached in the Client's BlobStore
+    Blob(String, Box<[u8]>),
 }
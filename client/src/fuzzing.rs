@@ -0,0 +1,83 @@
+//! Entry points into the Client's packet decoding, used by the fuzz targets
+//! in the `fuzz` directory. Not part of the public API
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use naia_shared::{
+    BitReader, BlobStore, ChannelConfig, Clock, ConnectionConfig, Manifest, ProtocolType,
+    StandardHeader, SystemClock, WorldMutType,
+};
+
+use super::{
+    authority_manager::AuthorityManager, client_entity_manager::ClientEntityManager,
+    command_receiver::CommandReceiver, connection::Connection, entity_manager::EntityManager,
+};
+
+/// Feeds arbitrary bytes to a new Connection as a whole packet, handled as
+/// the Client handles a Data packet from the Server
+pub fn incoming_packet<P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>>(
+    world: &mut W,
+    manifest: &Manifest<P>,
+    channel_configs: &[ChannelConfig],
+    data: &[u8],
+) {
+    let (header, payload) = match StandardHeader::read(data) {
+        Ok(header_and_payload) => header_and_payload,
+        Err(_) => return,
+    };
+
+    let address: SocketAddr = "127.0.0.1:14191"
+        .parse()
+        .expect("could not parse socket address from string");
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock::new());
+    let mut connection = Connection::<P, E>::new(
+        address,
+        &ConnectionConfig::default(),
+        true,
+        channel_configs,
+        &Arc::new(RwLock::new(BlobStore::new())),
+        2,
+        &HashSet::new(),
+        &HashMap::new(),
+        Duration::ZERO,
+        &clock,
+    );
+    connection.process_incoming_header(&header, None);
+    let _ = connection.process_incoming_data(
+        world,
+        header.host_tick(),
+        header.local_packet_index(),
+        manifest,
+        &payload,
+    );
+}
+
+/// Feeds arbitrary bytes to a new EntityManager as the Entity section of a
+/// Data packet
+pub fn entity_data<P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>>(
+    world: &mut W,
+    manifest: &Manifest<P>,
+    data: &[u8],
+) {
+    let mut entity_manager = EntityManager::<P, E>::new(true, 2, &HashSet::new());
+    let mut command_receiver = CommandReceiver::new();
+    let mut authority_manager = AuthorityManager::new();
+    let mut client_entity_manager = ClientEntityManager::new();
+    let mut reader = BitReader::new(data);
+    let _ = entity_manager.process_data(
+        world,
+        manifest,
+        &mut command_receiver,
+        &mut authority_manager,
+        &mut client_entity_manager,
+        0,
+        0,
+        &mut reader,
+    );
+}
@@ -1,13 +1,13 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{BigEndian, ReadBytesExt};
 
 use naia_client_socket::Packet;
 
 pub use naia_shared::{
-    ConnectionConfig, ManagerType, Manifest, PacketReader, PacketType, ProtocolKindType,
-    ProtocolType, ReplicateSafe, SequenceIterator, SharedConfig, StandardHeader, Timer, Timestamp,
-    WorldMutType, WorldRefType,
+    BitWriter, ChannelConfig, ChannelMode, Clock, ClockTimer, ConnectionConfig, DecodeError,
+    ManagerType, Manifest, PacketReader, PacketType, ProtocolKindType, ProtocolType, ReplicateSafe,
+    SequenceIterator, Serde, SharedConfig, StandardHeader, Timestamp, WorldMutType, WorldRefType,
 };
 
 use super::{
@@ -16,23 +16,33 @@ use super::{
     tick_manager::TickManager,
 };
 
+// Number of bytes in a challenge response: the Server tick, the Timestamp
+// sent by the Client, & its digest
+const CHALLENGE_RESPONSE_BYTES: usize = 2 + 8 + 32;
+
 #[derive(PartialEq)]
 pub enum HandshakeResult {
     None,
     Connected,
+    Rejected,
 }
 
 pub struct HandshakeManager<P: ProtocolType> {
-    handshake_timer: Timer,
+    handshake_timer: ClockTimer,
     pre_connection_timestamp: Option<Timestamp>,
     pre_connection_digest: Option<Box<[u8]>>,
     connection_state: ConnectionState,
     auth_message: Option<P>,
+    channel_modes: Vec<ChannelMode>,
 }
 
 impl<P: ProtocolType> HandshakeManager<P> {
-    pub fn new(send_interval: Duration) -> Self {
-        let mut handshake_timer = Timer::new(send_interval);
+    pub fn new(
+        send_interval: Duration,
+        channel_configs: &[ChannelConfig],
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
+        let mut handshake_timer = ClockTimer::new(clock, send_interval);
         handshake_timer.ring_manual();
 
         Self {
@@ -41,6 +51,7 @@ impl<P: ProtocolType> HandshakeManager<P> {
             pre_connection_digest: None,
             connection_state: AwaitingChallengeResponse,
             auth_message: None,
+            channel_modes: channel_configs.iter().map(|config| config.mode).collect(),
         }
     }
 
@@ -52,7 +63,7 @@ impl<P: ProtocolType> HandshakeManager<P> {
         self.handshake_timer.reset();
 
         match self.connection_state {
-            ConnectionState::Connected => {
+            ConnectionState::Connected | ConnectionState::Rejected => {
                 // do nothing, not necessary
             }
             ConnectionState::AwaitingChallengeResponse => {
@@ -81,22 +92,24 @@ impl<P: ProtocolType> HandshakeManager<P> {
                 for digest_byte in self.pre_connection_digest.as_ref().unwrap().as_ref() {
                     payload_bytes.push(*digest_byte);
                 }
+                // write Channels, which the Server must have been configured with too
+                let mut auth_writer = BitWriter::new();
+                self.channel_modes.ser(&mut auth_writer);
                 // write auth message if there is one
                 if let Some(auth_message) = &mut self.auth_message {
                     let auth_dyn = auth_message.dyn_ref();
                     let auth_kind = auth_dyn.get_kind();
                     // write that we have auth
-                    payload_bytes.write_u8(1).unwrap();
+                    auth_writer.write_bit(true);
                     // write auth kind
-                    payload_bytes
-                        .write_u16::<BigEndian>(auth_kind.to_u16())
-                        .unwrap();
+                    auth_kind.write(&mut auth_writer);
                     // write payload
-                    auth_dyn.write(&mut payload_bytes);
+                    auth_dyn.write(&mut auth_writer);
                 } else {
                     // write that we do not have auth
-                    payload_bytes.write_u8(0).unwrap();
+                    auth_writer.write_bit(false);
                 }
+                payload_bytes.extend_from_slice(&auth_writer.to_bytes());
                 internal_send_connectionless(
                     io,
                     PacketType::ClientConnectRequest,
@@ -120,10 +133,13 @@ impl<P: ProtocolType> HandshakeManager<P> {
         &mut self,
         tick_manager: &mut Option<TickManager>,
         packet: Packet,
-    ) -> HandshakeResult {
-        let (header, payload) = StandardHeader::read(packet.payload());
+    ) -> Result<HandshakeResult, DecodeError> {
+        let (header, payload) = StandardHeader::read(packet.payload())?;
         match header.packet_type() {
             PacketType::ServerChallengeResponse => {
+                if payload.len() < CHALLENGE_RESPONSE_BYTES {
+                    return Err(DecodeError::UnexpectedEnd);
+                }
                 if self.connection_state == ConnectionState::AwaitingChallengeResponse {
                     if let Some(my_timestamp) = self.pre_connection_timestamp {
                         let mut reader = PacketReader::new(&payload);
@@ -148,12 +164,18 @@ impl<P: ProtocolType> HandshakeManager<P> {
             }
             PacketType::ServerConnectResponse => {
                 self.connection_state = ConnectionState::Connected;
-                return HandshakeResult::Connected;
+                return Ok(HandshakeResult::Connected);
+            }
+            PacketType::ServerRejectResponse
+                if self.connection_state == ConnectionState::AwaitingConnectResponse =>
+            {
+                self.connection_state = ConnectionState::Rejected;
+                return Ok(HandshakeResult::Rejected);
             }
             _ => {}
         }
 
-        return HandshakeResult::None;
+        return Ok(HandshakeResult::None);
     }
 }
 
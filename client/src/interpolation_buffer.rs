@@ -0,0 +1,199 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use naia_shared::{sequence_greater_than, wrapping_diff, ProtocolType, WorldRefType};
+
+/// Keeps the most recent states of the Components chosen for interpolation,
+/// as received from the Server, tagged with the Server tick they were sent on
+pub struct InterpolationBuffer<P: ProtocolType, E: Copy + Eq + Hash> {
+    buffer_size: usize,
+    kinds: HashSet<P::Kind>,
+    states: HashMap<(E, P::Kind), VecDeque<(u16, P)>>,
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> InterpolationBuffer<P, E> {
+    pub fn new(buffer_size: usize, kinds: &HashSet<P::Kind>) -> Self {
+        InterpolationBuffer {
+            // at least 2 states are needed to interpolate between
+            buffer_size: buffer_size.max(2),
+            kinds: kinds.clone(),
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn enable_kind(&mut self, component_kind: &P::Kind) {
+        self.kinds.insert(*component_kind);
+    }
+
+    /// Records the current state of a Component in the World, if its type
+    /// has been chosen for interpolation
+    pub fn record<W: WorldRefType<P, E>>(
+        &mut self,
+        world: &W,
+        entity: &E,
+        component_kind: &P::Kind,
+        tick: u16,
+    ) {
+        if !self.kinds.contains(component_kind) {
+            return;
+        }
+        let state = match world.get_component_of_kind(entity, component_kind) {
+            Some(component) => component.protocol_copy(),
+            None => return,
+        };
+
+        let states = self
+            .states
+            .entry((*entity, *component_kind))
+            .or_insert_with(VecDeque::new);
+        if let Some((last_tick, last_state)) = states.back_mut() {
+            if *last_tick == tick {
+                // several changes made on the same tick
+                *last_state = state;
+                return;
+            }
+            if !sequence_greater_than(tick, *last_tick) {
+                return;
+            }
+        }
+        states.push_back((tick, state));
+        while states.len() > self.buffer_size {
+            states.pop_front();
+        }
+    }
+
+    pub fn remove_component(&mut self, entity: &E, component_kind: &P::Kind) {
+        self.states.remove(&(*entity, *component_kind));
+    }
+
+    pub fn remove_entity(&mut self, entity: &E) {
+        self.states
+            .retain(|(state_entity, _), _| state_entity != entity);
+    }
+
+    /// Gets the two recorded states surrounding the given point in time,
+    /// along with the fraction of the way between them it lies. Without a
+    /// point in time, or outside of the recorded states, the nearest state is
+    /// returned twice
+    pub fn get(
+        &self,
+        entity: &E,
+        component_kind: &P::Kind,
+        render_time: Option<(u16, f32)>,
+    ) -> Option<(&P, &P, f32)> {
+        let states = self.states.get(&(*entity, *component_kind))?;
+        let (newest_tick, newest_state) = states.back()?;
+        let (render_tick, render_fraction) = match render_time {
+            Some(render_time) => render_time,
+            None => return Some((newest_state, newest_state, 0.0)),
+        };
+
+        // offsets in ticks, relative to the newest state
+        let render_offset = f32::from(wrapping_diff(*newest_tick, render_tick)) + render_fraction;
+        let mut previous: Option<(f32, &P)> = None;
+        for (tick, state) in states.iter() {
+            let offset = f32::from(wrapping_diff(*newest_tick, *tick));
+            if render_offset < offset {
+                return match previous {
+                    Some((previous_offset, previous_state)) => Some((
+                        previous_state,
+                        state,
+                        (render_offset - previous_offset) / (offset - previous_offset),
+                    )),
+                    None => Some((state, state, 0.0)),
+                };
+            }
+            previous = Some((offset, state));
+        }
+        return Some((newest_state, newest_state, 0.0));
+    }
+}
+
+#[cfg(test)]
+mod interpolation_buffer_tests {
+    use std::collections::HashSet;
+
+    use naia_demo_world::{Entity, World, WorldMutType};
+    use naia_shared::ProtocolType;
+
+    use super::InterpolationBuffer;
+    use crate::test_protocol::{set_x, x_of, Position, Protocol};
+
+    struct Fixture {
+        buffer: InterpolationBuffer<Protocol, Entity>,
+        world: World<Protocol>,
+        entity: Entity,
+    }
+
+    impl Fixture {
+        fn new(buffer_size: usize) -> Self {
+            let mut kinds = HashSet::new();
+            kinds.insert(Protocol::kind_of::<Position>());
+            let mut world = World::new();
+            let entity = world.proxy_mut().spawn_entity();
+            world
+                .proxy_mut()
+                .insert_component(&entity, Position::new_complete(0, 0));
+            return Fixture {
+                buffer: InterpolationBuffer::new(buffer_size, &kinds),
+                world,
+                entity,
+            };
+        }
+
+        // records the Entity's Position, after setting it to the given x
+        fn record(&mut self, tick: u16, x: u8) {
+            set_x(&mut self.world.proxy_mut(), &self.entity, x);
+            self.buffer.record(
+                &self.world.proxy(),
+                &self.entity,
+                &Protocol::kind_of::<Position>(),
+                tick,
+            );
+        }
+
+        // gets the x of the states surrounding the given point in time
+        fn get(&self, render_time: Option<(u16, f32)>) -> Option<(u8, u8, f32)> {
+            let (previous, next, fraction) =
+                self.buffer
+                    .get(&self.entity, &Protocol::kind_of::<Position>(), render_time)?;
+            return Some((x_of(previous), x_of(next), fraction));
+        }
+    }
+
+    #[test]
+    fn interpolates_between_ticks() {
+        let mut fixture = Fixture::new(8);
+        assert_eq!(fixture.get(None), None);
+        fixture.record(1, 10);
+        fixture.record(2, 20);
+        fixture.record(3, 40);
+
+        assert_eq!(fixture.get(None), Some((40, 40, 0.0)));
+        assert_eq!(fixture.get(Some((1, 0.5))), Some((10, 20, 0.5)));
+        assert_eq!(fixture.get(Some((2, 0.25))), Some((20, 40, 0.25)));
+        // outside of the recorded states, the nearest state is returned
+        assert_eq!(fixture.get(Some((0, 0.5))), Some((10, 10, 0.0)));
+        assert_eq!(fixture.get(Some((3, 0.5))), Some((40, 40, 0.0)));
+    }
+
+    #[test]
+    fn keeps_newest_states() {
+        let mut fixture = Fixture::new(2);
+        fixture.record(1, 10);
+        fixture.record(2, 20);
+        fixture.record(3, 30);
+        // changes made on the same tick replace the state, older ones are
+        // ignored
+        fixture.record(3, 40);
+        fixture.record(2, 50);
+
+        assert_eq!(fixture.get(Some((1, 0.0))), Some((20, 20, 0.0)));
+        assert_eq!(fixture.get(Some((2, 0.5))), Some((20, 40, 0.5)));
+
+        fixture.buffer.remove_entity(&fixture.entity);
+        assert_eq!(fixture.get(None), None);
+    }
+}
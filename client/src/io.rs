@@ -1,4 +1,4 @@
-use naia_client_socket::{NaiaClientSocketError, Packet, PacketReceiver, PacketSender};
+use naia_client_socket::{NaiaClientSocketError, Packet};
 
 pub use naia_shared::{
     ConnectionConfig, ManagerType, Manifest, PacketReader, PacketType, ProtocolKindType,
@@ -6,30 +6,27 @@ pub use naia_shared::{
     WorldMutType, WorldRefType,
 };
 
+use super::transport::ClientTransport;
+
 pub struct Io {
-    packet_sender: Option<PacketSender>,
-    packet_receiver: Option<PacketReceiver>,
+    transport: Option<Box<dyn ClientTransport>>,
 }
 
 impl Io {
     pub fn new() -> Self {
-        Io {
-            packet_sender: None,
-            packet_receiver: None,
-        }
+        Io { transport: None }
     }
 
-    pub fn load(&mut self, packet_sender: PacketSender, packet_receiver: PacketReceiver) {
-        if self.packet_sender.is_some() {
+    pub fn load(&mut self, transport: Box<dyn ClientTransport>) {
+        if self.transport.is_some() {
             panic!("Packet sender/receiver already loaded! Cannot do this twice!");
         }
 
-        self.packet_sender = Some(packet_sender);
-        self.packet_receiver = Some(packet_receiver);
+        self.transport = Some(transport);
     }
 
     pub fn send_packet(&mut self, packet: Packet) {
-        self.packet_sender
+        self.transport
             .as_mut()
             .expect("Cannot call Client.send_packet() until you call Client.connect()!")
             .send(packet);
@@ -37,7 +34,7 @@ impl Io {
 
     pub fn receive_packet(&mut self) -> Result<Option<Packet>, NaiaClientSocketError> {
         return self
-            .packet_receiver
+            .transport
             .as_mut()
             .expect("Cannot call Client.receive_packet() until you call Client.connect()!")
             .receive();
@@ -10,8 +10,10 @@
     unused_import_braces
 )]
 
+mod authority_manager;
 mod client;
 mod client_config;
+mod client_entity_manager;
 mod command_receiver;
 mod connection;
 mod connection_state;
@@ -22,21 +24,35 @@ mod entity_ref;
 mod error;
 mod event;
 mod handshake_manager;
+mod interpolation_buffer;
 mod io;
 mod owned_entity;
 mod packet_writer;
 mod ping_manager;
+mod reconciler;
+mod snapshot_history;
 mod tick_manager;
 mod tick_queue;
+mod transport;
+
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+
+#[cfg(test)]
+mod test_protocol;
 
 pub use naia_shared::{
-    wrapping_diff, Instant, LinkConditionerConfig, ProtocolType, Random, Replicate, SharedConfig,
+    blob_hash, wrapping_diff, BlobHash, ChannelConfig, ChannelIndex, ChannelMode, Channels,
+    DefaultChannels, Instant, Interpolate, LinkConditionerConfig, MemoryNetwork, ProtocolType,
+    Random, Replicate, SharedConfig,
 };
 
 pub use client::Client;
 pub use client_config::ClientConfig;
-pub use entity_ref::EntityRef;
+pub use entity_ref::{EntityMut, EntityRef};
 pub use error::NaiaClientError;
 pub use event::Event;
-pub use naia_client_socket::Packet;
+pub use naia_client_socket::{NaiaClientSocketError, Packet};
 pub use owned_entity::OwnedEntity;
+pub use transport::{ClientTransport, MemoryClientTransport};
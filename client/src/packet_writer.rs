@@ -1,10 +1,9 @@
 use std::hash::Hash;
 
-use byteorder::{BigEndian, WriteBytesExt};
-
 use naia_shared::{
-    wrapping_diff, ManagerType, MessagePacketWriter, NaiaKey, ProtocolKindType, ProtocolType,
-    MTU_SIZE,
+    wrapping_diff, BitWriter, BlobPacketWriter, BlobRecord, EntityActionType, LocalComponentKey,
+    ManagerType, MessagePacketWriter, NaiaKey, OutgoingMessage, ProtocolKindType, ProtocolType,
+    MTU_SIZE_BITS, SECTION_OVERHEAD_BITS,
 };
 
 use super::{
@@ -12,12 +11,18 @@ use super::{
 };
 
 const MAX_PAST_COMMANDS: u8 = 3;
+const PAST_COMMANDS_NUMBER_BITS: u8 = 2;
 
 /// Handles writing of Message/Command data into an outgoing packet
 pub struct PacketWriter {
-    command_working_bytes: Vec<u8>,
-    command_count: u8,
+    command_working_bits: BitWriter,
+    command_count: u16,
+    authority_working_bits: BitWriter,
+    authority_update_count: u16,
+    client_entity_working_bits: BitWriter,
+    client_entity_action_count: u16,
     message_writer: MessagePacketWriter,
+    blob_writer: BlobPacketWriter,
 }
 
 impl PacketWriter {
@@ -25,38 +30,85 @@ impl PacketWriter {
     /// used to read information from.
     pub fn new() -> PacketWriter {
         PacketWriter {
-            command_working_bytes: Vec::<u8>::new(),
+            command_working_bits: BitWriter::new(),
             command_count: 0,
+            authority_working_bits: BitWriter::new(),
+            authority_update_count: 0,
+            client_entity_working_bits: BitWriter::new(),
+            client_entity_action_count: 0,
             message_writer: MessagePacketWriter::new(),
+            blob_writer: BlobPacketWriter::new(),
         }
     }
 
     /// Returns whether the writer has bytes to write into the outgoing packet
     pub fn has_bytes(&self) -> bool {
-        return self.command_count != 0 || self.message_writer.has_bytes();
+        return self.command_count != 0
+            || self.authority_update_count != 0
+            || self.client_entity_action_count != 0
+            || self.message_writer.has_bytes()
+            || self.blob_writer.has_bytes();
     }
 
     /// Gets the bytes to write into an outgoing packet
     pub fn get_bytes(&mut self) -> Box<[u8]> {
-        let mut out_bytes = Vec::<u8>::new();
+        let mut writer = BitWriter::new();
 
-        //Write manager "header" (manager type & command count)
+        //Write manager "header" (manager type)
         if self.command_count != 0 {
-            out_bytes.write_u8(ManagerType::Command as u8).unwrap(); // write manager type
-            out_bytes.write_u8(self.command_count).unwrap(); // write number of commands in the following message
-            out_bytes.append(&mut self.command_working_bytes); // write command payload
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::Command.write(&mut writer); // write manager type
+            writer.append(&self.command_working_bits); // write command payload
+            writer.write_bit(false); // write that there are no more commands
+
+            self.command_working_bits = BitWriter::new();
             self.command_count = 0;
         }
 
-        self.message_writer.get_bytes(&mut out_bytes);
+        if self.authority_update_count != 0 {
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::Entity.write(&mut writer); // write manager type
+            writer.append(&self.authority_working_bits); // write authority update payload
+            writer.write_bit(false); // write that there are no more authority updates
+
+            self.authority_working_bits = BitWriter::new();
+            self.authority_update_count = 0;
+        }
+
+        if self.client_entity_action_count != 0 {
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::ClientEntity.write(&mut writer); // write manager type
+            writer.append(&self.client_entity_working_bits); // write client entity payload
+            writer.write_bit(false); // write that there are no more client entity actions
+
+            self.client_entity_working_bits = BitWriter::new();
+            self.client_entity_action_count = 0;
+        }
+
+        self.message_writer.get_bytes(&mut writer);
+
+        self.blob_writer.get_bytes(&mut writer);
+
+        writer.write_bit(false); // write that there are no more manager sections
 
-        out_bytes.into_boxed_slice()
+        writer.to_bytes()
     }
 
-    /// Get the number of bytes which is ready to be written into an outgoing
+    /// Get the number of bits which is ready to be written into an outgoing
     /// packet
-    pub fn bytes_number(&self) -> usize {
-        return self.command_working_bytes.len() + self.message_writer.bytes_number();
+    pub fn bits_number(&self) -> usize {
+        let mut output = self.message_writer.bits_number();
+        if self.command_count != 0 {
+            output += self.command_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
+        }
+        if self.authority_update_count != 0 {
+            output += self.authority_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
+        }
+        if self.client_entity_action_count != 0 {
+            output += self.client_entity_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
+        }
+        output += self.blob_writer.bits_number();
+        return output;
     }
 
     /// Writes a Command into the Writer's internal buffer, which will
@@ -72,9 +124,9 @@ impl PacketWriter {
         let world_entity = owned_entity.confirmed;
         if let Some(local_entity) = entity_manager.world_to_local_entity(&world_entity) {
             //Write command payload
-            let mut command_payload_bytes = Vec::<u8>::new();
+            let mut command_payload_bits = BitWriter::new();
 
-            command.dyn_ref().write(&mut command_payload_bytes);
+            command.dyn_ref().write(&mut command_payload_bits);
 
             // write past commands
             let past_commands_number = command_receiver
@@ -89,9 +141,9 @@ impl PacketWriter {
                         let diff_i8: i16 = wrapping_diff(past_tick, host_tick);
                         if diff_i8 > 0 && diff_i8 <= 255 {
                             // write the tick diff
-                            command_payload_bytes.write_u8(diff_i8 as u8).unwrap();
+                            command_payload_bits.write_u8(diff_i8 as u8);
                             // write the command payload
-                            past_command.dyn_ref().write(&mut command_payload_bytes);
+                            past_command.dyn_ref().write(&mut command_payload_bits);
 
                             past_command_index += 1;
                         }
@@ -102,27 +154,24 @@ impl PacketWriter {
             }
 
             //Write command "header"
-            let mut command_total_bytes = Vec::<u8>::new();
+            let mut command_total_bits = BitWriter::new();
 
-            command_total_bytes
-                .write_u16::<BigEndian>(local_entity.to_u16())
-                .unwrap(); // write local entity
+            command_total_bits.write_bit(true); // write that there is another command
+            command_total_bits.write_u16(local_entity.to_u16()); // write local entity
 
             let command_kind = command.dyn_ref().get_kind();
-            command_total_bytes
-                .write_u16::<BigEndian>(command_kind.to_u16())
-                .unwrap(); // write command kind
-            command_total_bytes.write_u8(past_command_index).unwrap(); // write past command number
-            command_total_bytes.append(&mut command_payload_bytes); // write payload
+            command_kind.write(&mut command_total_bits); // write command kind
+            command_total_bits.write_bits(u64::from(past_command_index), PAST_COMMANDS_NUMBER_BITS); // write past command number
+            command_total_bits.append(&command_payload_bits); // write payload
 
             let mut hypothetical_next_payload_size =
-                self.bytes_number() + command_total_bytes.len();
+                self.bits_number() + command_total_bits.bits_number();
             if self.command_count == 0 {
-                hypothetical_next_payload_size += 2;
+                hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
             }
-            if hypothetical_next_payload_size < MTU_SIZE {
+            if hypothetical_next_payload_size < MTU_SIZE_BITS {
                 self.command_count += 1;
-                self.command_working_bytes.append(&mut command_total_bytes);
+                self.command_working_bits.append(&command_total_bits);
                 return true;
             } else {
                 return false;
@@ -131,9 +180,98 @@ impl PacketWriter {
         return true;
     }
 
+    /// Writes the full state of a Component the Client has authority over
+    /// into the Writer's internal buffer, which will eventually be put into
+    /// the outgoing packet
+    pub fn write_component_update<P: ProtocolType>(
+        &mut self,
+        component_key: &LocalComponentKey,
+        component_kind: &P::Kind,
+        component_bits: &BitWriter,
+    ) -> bool {
+        let mut update_total_bits = BitWriter::new();
+
+        update_total_bits.write_bit(true); // write that there is another authority update
+        EntityActionType::UpdateComponent.write(&mut update_total_bits); // write action type
+        update_total_bits.write_u16(component_key.to_u16()); // write local component key
+        component_kind.write(&mut update_total_bits); // write component kind
+        update_total_bits.append(component_bits); // write payload
+
+        let mut hypothetical_next_payload_size =
+            self.bits_number() + update_total_bits.bits_number();
+        if self.authority_update_count == 0 {
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
+        }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
+            self.authority_update_count += 1;
+            self.authority_working_bits.append(&update_total_bits);
+            return true;
+        } else {
+            return false;
+        }
+    }
+
+    /// Writes a request for the whole state of a Component, which is missing
+    /// the baseline of a delta-compressed update, into the Writer's internal
+    /// buffer, which will eventually be put into the outgoing packet
+    pub fn write_state_request(&mut self, component_key: &LocalComponentKey) -> bool {
+        let mut request_total_bits = BitWriter::new();
+
+        request_total_bits.write_bit(true); // write that there is another entry
+        EntityActionType::RequestComponentState.write(&mut request_total_bits); // write action type
+        request_total_bits.write_u16(component_key.to_u16()); // write local component key
+
+        let mut hypothetical_next_payload_size =
+            self.bits_number() + request_total_bits.bits_number();
+        if self.authority_update_count == 0 {
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
+        }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
+            self.authority_update_count += 1;
+            self.authority_working_bits.append(&request_total_bits);
+            return true;
+        } else {
+            return false;
+        }
+    }
+
+    /// Writes an action on an Entity spawned by the Client into the Writer's
+    /// internal buffer, which will eventually be put into the outgoing packet
+    pub fn write_client_entity_action(
+        &mut self,
+        action_index: u16,
+        action_bits: &BitWriter,
+    ) -> bool {
+        let mut action_total_bits = BitWriter::new();
+
+        action_total_bits.write_bit(true); // write that there is another client entity action
+        action_total_bits.write_u16(action_index); // write action index
+        action_total_bits.append(action_bits); // write action payload
+
+        let mut hypothetical_next_payload_size =
+            self.bits_number() + action_total_bits.bits_number();
+        if self.client_entity_action_count == 0 {
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
+        }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
+            self.client_entity_action_count += 1;
+            self.client_entity_working_bits.append(&action_total_bits);
+            return true;
+        } else {
+            return false;
+        }
+    }
+
     /// Writes a Message into the Writer's internal buffer, which will
     /// eventually be put into the outgoing packet
-    pub fn write_message<P: ProtocolType>(&mut self, message: &P) -> bool {
+    pub fn write_message<P: ProtocolType>(&mut self, message: &OutgoingMessage<P>) -> bool {
         return self.message_writer.write_message(message);
     }
+
+    /// Writes a Blob record into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet
+    pub fn write_blob_record(&mut self, record: &BlobRecord) -> bool {
+        let packet_bits_number = self.bits_number() - self.blob_writer.bits_number();
+        return self.blob_writer.write_record(record, packet_bits_number);
+    }
 }
@@ -1,16 +1,17 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
-use naia_shared::{Instant, PacketReader, SequenceBuffer, SequenceNumber, Timer};
+use naia_shared::{Clock, ClockTimer, DecodeError, PacketReader, SequenceBuffer, SequenceNumber};
 
 #[derive(Clone)]
 struct SentPing {
-    time_sent: Instant,
+    time_sent: Duration,
 }
 
 pub struct PingManager {
-    ping_timer: Timer,
+    clock: Arc<dyn Clock>,
+    ping_timer: ClockTimer,
     sent_pings: SequenceBuffer<SentPing>,
     ping_index: SequenceNumber,
     samples: f32,
@@ -21,10 +22,11 @@ pub struct PingManager {
 }
 
 impl PingManager {
-    pub fn new(ping_interval: Duration, ping_sample_size: u16) -> Self {
+    pub fn new(ping_interval: Duration, ping_sample_size: u16, clock: &Arc<dyn Clock>) -> Self {
         PingManager {
+            clock: clock.clone(),
             ping_index: 0,
-            ping_timer: Timer::new(ping_interval),
+            ping_timer: ClockTimer::new(clock, ping_interval),
             sent_pings: SequenceBuffer::with_capacity(ping_sample_size),
             samples: 0.0,
             max_samples: f32::from(ping_sample_size),
@@ -46,7 +48,7 @@ impl PingManager {
         self.sent_pings.insert(
             self.ping_index,
             SentPing {
-                time_sent: Instant::now(),
+                time_sent: self.clock.now(),
             },
         );
 
@@ -59,19 +61,43 @@ impl PingManager {
         out_bytes.into_boxed_slice()
     }
 
+    /// Process an incoming ping payload
+    pub fn process_ping(&self, ping_payload: &[u8]) -> Result<Box<[u8]>, DecodeError> {
+        // read incoming ping index
+        let mut reader = PacketReader::new(&ping_payload);
+        let ping_index = reader
+            .get_cursor()
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+
+        // write pong payload
+        let mut out_bytes = Vec::<u8>::new();
+        out_bytes.write_u16::<BigEndian>(ping_index).unwrap(); // write index
+        Ok(out_bytes.into_boxed_slice())
+    }
+
     /// Process an incoming pong payload
-    pub fn process_pong(&mut self, pong_payload: &[u8]) {
+    pub fn process_pong(&mut self, pong_payload: &[u8]) -> Result<(), DecodeError> {
         let mut reader = PacketReader::new(&pong_payload);
-        let ping_index = reader.get_cursor().read_u16::<BigEndian>().unwrap();
+        let ping_index = reader
+            .get_cursor()
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
 
         match self.sent_pings.remove(ping_index) {
             None => {}
             Some(ping) => {
-                let rtt_millis = &ping.time_sent.elapsed().as_secs_f32() * 1000.0;
+                let rtt_millis = self
+                    .clock
+                    .now()
+                    .saturating_sub(ping.time_sent)
+                    .as_secs_f32()
+                    * 1000.0;
                 let ping_millis = rtt_millis / 2.0;
                 self.process_new_ping(ping_millis);
             }
         }
+        Ok(())
     }
 
     fn process_new_ping(&mut self, ping_millis: f32) {
@@ -0,0 +1,289 @@
+use std::{collections::HashMap, hash::Hash, sync::Arc, time::Duration};
+
+use naia_shared::{
+    sequence_less_than, Clock, ProtocolType, SequenceBuffer, WorldMutType, WorldRefType,
+};
+
+const PREDICTION_HISTORY_SIZE: u16 = 64;
+
+/// Returns whether a predicted state of a Component is close enough to the
+/// authoritative state received from the Server
+pub type Tolerance<P> = Arc<dyn Fn(&P, &P) -> bool + Send + Sync>;
+
+/// Keeps the predicted state of owned Entities for every tick, so that it can
+/// be compared with the authoritative state received from the Server, & a
+/// rewind only happens on a real misprediction. Also keeps the mispredicted
+/// state of corrected Entities, so that corrections can be smoothed over
+pub struct Reconciler<P: ProtocolType, E: Copy + Eq + Hash> {
+    tolerances: HashMap<P::Kind, Tolerance<P>>,
+    predicted_states: HashMap<E, SequenceBuffer<Vec<P>>>,
+    smoothing_duration: Duration,
+    clock: Arc<dyn Clock>,
+    corrections: HashMap<(E, P::Kind), (Duration, P)>,
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> Reconciler<P, E> {
+    pub fn new(
+        tolerances: &HashMap<P::Kind, Tolerance<P>>,
+        smoothing_duration: Duration,
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
+        Reconciler {
+            tolerances: tolerances.clone(),
+            predicted_states: HashMap::new(),
+            smoothing_duration,
+            clock: clock.clone(),
+            corrections: HashMap::new(),
+        }
+    }
+
+    pub fn set_tolerance(&mut self, component_kind: &P::Kind, tolerance: Tolerance<P>) {
+        self.tolerances.insert(*component_kind, tolerance);
+    }
+
+    /// Reconciliation is only performed once a tolerance has been set for
+    /// some type of Component, otherwise every update rewinds
+    pub fn is_enabled(&self) -> bool {
+        return !self.tolerances.is_empty();
+    }
+
+    /// Records the predicted state of an owned Entity for the given tick,
+    /// before any Command for that tick has been applied
+    pub fn record_prediction<W: WorldRefType<P, E>>(
+        &mut self,
+        world: &W,
+        tick: u16,
+        confirmed_entity: &E,
+        predicted_entity: &E,
+    ) {
+        let history = self
+            .predicted_states
+            .entry(*confirmed_entity)
+            .or_insert_with(|| SequenceBuffer::with_capacity(PREDICTION_HISTORY_SIZE));
+        if history.exists(tick) {
+            return;
+        }
+
+        let mut states = Vec::new();
+        for component_kind in self.tolerances.keys() {
+            if let Some(component) = world.get_component_of_kind(predicted_entity, component_kind) {
+                states.push(component.protocol_copy());
+            }
+        }
+        history.insert(tick, states);
+    }
+
+    /// Forgets about owned Entities which no longer have a prediction, and
+    /// about corrections which have been fully smoothed over
+    pub fn cleanup(&mut self, owned_entities: &[E]) {
+        self.predicted_states
+            .retain(|confirmed_entity, _| owned_entities.contains(confirmed_entity));
+        let now = self.clock.now();
+        let smoothing_duration = self.smoothing_duration;
+        self.corrections
+            .retain(|_, (time, _)| now.saturating_sub(*time) < smoothing_duration);
+    }
+
+    /// Compares the authoritative state of an owned Entity with the state
+    /// predicted for the given tick, then forgets about the states predicted
+    /// before it
+    pub fn is_mispredicted<W: WorldRefType<P, E>>(
+        &mut self,
+        world: &W,
+        tick: u16,
+        confirmed_entity: &E,
+    ) -> bool {
+        let history = match self.predicted_states.get_mut(confirmed_entity) {
+            Some(history) => history,
+            None => return true,
+        };
+        let mut mispredicted = false;
+        // without a recorded state, the prediction has just been corrected
+        if let Some(predicted_states) = history.get(tick) {
+            for predicted_state in predicted_states {
+                let component_kind = predicted_state.dyn_ref().get_kind();
+                let tolerance = match self.tolerances.get(&component_kind) {
+                    Some(tolerance) => tolerance,
+                    None => continue,
+                };
+                match world.get_component_of_kind(confirmed_entity, &component_kind) {
+                    Some(component) => {
+                        let authoritative_state = component.protocol_copy();
+                        if !tolerance(predicted_state, &authoritative_state) {
+                            mispredicted = true;
+                            break;
+                        }
+                    }
+                    None => {
+                        mispredicted = true;
+                        break;
+                    }
+                }
+            }
+        }
+        history.remove_until(tick);
+        return mispredicted;
+    }
+
+    /// Records the mispredicted state of an owned Entity before it is reset
+    /// to the authoritative state, & forgets about the states predicted after
+    /// the given tick, as they will be predicted again
+    pub fn record_correction<W: WorldMutType<P, E>>(
+        &mut self,
+        world: &mut W,
+        tick: u16,
+        confirmed_entity: &E,
+        predicted_entity: &E,
+    ) {
+        if let Some(history) = self.predicted_states.get_mut(confirmed_entity) {
+            let mut later_tick = tick.wrapping_add(1);
+            while sequence_less_than(later_tick, history.sequence_num()) {
+                history.remove(later_tick);
+                later_tick = later_tick.wrapping_add(1);
+            }
+        }
+
+        if self.smoothing_duration == Duration::ZERO {
+            return;
+        }
+        let now = self.clock.now();
+        for component_kind in world.get_component_kinds(predicted_entity) {
+            if let Some(component) = world.get_component_of_kind(predicted_entity, &component_kind)
+            {
+                let mispredicted_state = component.protocol_copy();
+                self.corrections.insert(
+                    (*predicted_entity, component_kind),
+                    (now, mispredicted_state),
+                );
+            }
+        }
+    }
+
+    /// Gets the mispredicted state of a corrected Component, along with how
+    /// far the correction has been smoothed over, from 0.0 to 1.0
+    pub fn correction(&self, predicted_entity: &E, component_kind: &P::Kind) -> Option<(&P, f32)> {
+        let (time, mispredicted_state) = self
+            .corrections
+            .get(&(*predicted_entity, *component_kind))?;
+        let elapsed = self.clock.now().saturating_sub(*time).as_secs_f32();
+        let fraction = elapsed / self.smoothing_duration.as_secs_f32();
+        if fraction >= 1.0 {
+            return None;
+        }
+        return Some((mispredicted_state, fraction));
+    }
+}
+
+#[cfg(test)]
+mod reconciler_tests {
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    use naia_demo_world::{Entity, World, WorldMutType};
+    use naia_shared::{Clock, ManualClock, ProtocolType};
+
+    use super::{Reconciler, Tolerance};
+    use crate::test_protocol::{set_x, x_of, Position, Protocol};
+
+    struct Fixture {
+        reconciler: Reconciler<Protocol, Entity>,
+        clock: ManualClock,
+        world: World<Protocol>,
+        confirmed_entity: Entity,
+        predicted_entity: Entity,
+    }
+
+    impl Fixture {
+        fn new(smoothing_duration: Duration) -> Self {
+            // predictions within 1 of the authoritative x are correct
+            let tolerance: Tolerance<Protocol> = Arc::new(|predicted, authoritative| {
+                return (i16::from(x_of(predicted)) - i16::from(x_of(authoritative))).abs() <= 1;
+            });
+            let mut tolerances = HashMap::new();
+            tolerances.insert(Protocol::kind_of::<Position>(), tolerance);
+            let clock = ManualClock::new();
+            let shared_clock: Arc<dyn Clock> = Arc::new(clock.clone());
+
+            let mut world = World::new();
+            let confirmed_entity = world.proxy_mut().spawn_entity();
+            let predicted_entity = world.proxy_mut().spawn_entity();
+            for entity in [confirmed_entity, predicted_entity].iter() {
+                world
+                    .proxy_mut()
+                    .insert_component(entity, Position::new_complete(0, 0));
+            }
+            return Fixture {
+                reconciler: Reconciler::new(&tolerances, smoothing_duration, &shared_clock),
+                clock,
+                world,
+                confirmed_entity,
+                predicted_entity,
+            };
+        }
+
+        // predicts the given x for the given tick
+        fn predict(&mut self, tick: u16, x: u8) {
+            set_x(&mut self.world.proxy_mut(), &self.predicted_entity, x);
+            self.reconciler.record_prediction(
+                &self.world.proxy(),
+                tick,
+                &self.confirmed_entity,
+                &self.predicted_entity,
+            );
+        }
+
+        // receives the given authoritative x for the given tick
+        fn is_mispredicted(&mut self, tick: u16, x: u8) -> bool {
+            set_x(&mut self.world.proxy_mut(), &self.confirmed_entity, x);
+            return self.reconciler.is_mispredicted(
+                &self.world.proxy(),
+                tick,
+                &self.confirmed_entity,
+            );
+        }
+
+        fn correction(&self) -> Option<(u8, f32)> {
+            let (state, fraction) = self
+                .reconciler
+                .correction(&self.predicted_entity, &Protocol::kind_of::<Position>())?;
+            return Some((x_of(state), fraction));
+        }
+    }
+
+    #[test]
+    fn mispredicted_beyond_tolerance() {
+        let mut fixture = Fixture::new(Duration::ZERO);
+        fixture.predict(5, 10);
+        fixture.predict(6, 12);
+        fixture.predict(7, 14);
+
+        assert!(!fixture.is_mispredicted(5, 11));
+        assert!(fixture.is_mispredicted(6, 20));
+        // recording a prediction again for the same tick keeps the first
+        fixture.predict(7, 30);
+        assert!(!fixture.is_mispredicted(7, 14));
+
+        // an Entity without predictions always rewinds
+        fixture.reconciler.cleanup(&[]);
+        assert!(fixture.is_mispredicted(8, 14));
+    }
+
+    #[test]
+    fn corrections_smoothed_over() {
+        let mut fixture = Fixture::new(Duration::from_millis(100));
+        fixture.predict(5, 10);
+        fixture.predict(6, 12);
+        assert!(fixture.is_mispredicted(5, 20));
+
+        fixture.reconciler.record_correction(
+            &mut fixture.world.proxy_mut(),
+            5,
+            &fixture.confirmed_entity,
+            &fixture.predicted_entity,
+        );
+        assert_eq!(fixture.correction(), Some((12, 0.0)));
+        fixture.clock.advance(Duration::from_millis(50));
+        assert_eq!(fixture.correction(), Some((12, 0.5)));
+        fixture.clock.advance(Duration::from_millis(50));
+        assert_eq!(fixture.correction(), None);
+    }
+}
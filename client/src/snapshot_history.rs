@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use naia_shared::{sequence_greater_than, ProtocolType, DELTA_BASELINE_WINDOW};
+
+/// Keeps a limited history of the states of a Component, as received from the
+/// Server, so that delta-compressed updates can be decoded
+pub struct SnapshotHistory<P: ProtocolType> {
+    snapshots: VecDeque<(u16, P)>,
+}
+
+impl<P: ProtocolType> SnapshotHistory<P> {
+    pub fn new() -> Self {
+        SnapshotHistory {
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Stores the state of the Component received in the given packet.
+    /// Returns whether this is the most recent state received
+    pub fn insert(&mut self, packet_index: u16, snapshot: P) -> bool {
+        let mut is_newest = true;
+        for (index, _) in self.snapshots.iter() {
+            if *index == packet_index {
+                // duplicate packet
+                return false;
+            }
+            if sequence_greater_than(*index, packet_index) {
+                is_newest = false;
+            }
+        }
+
+        self.snapshots.push_back((packet_index, snapshot));
+
+        // the Server never encodes updates relative to states older than this
+        while self.snapshots.len() > usize::from(DELTA_BASELINE_WINDOW) * 2 {
+            self.snapshots.pop_front();
+        }
+
+        return is_newest;
+    }
+
+    /// Gets the state of the Component received in the given packet
+    pub fn get(&self, packet_index: u16) -> Option<&P> {
+        for (index, snapshot) in self.snapshots.iter() {
+            if *index == packet_index {
+                return Some(snapshot);
+            }
+        }
+        return None;
+    }
+
+    /// Gets the state of the Component received last
+    pub fn last(&self) -> Option<&P> {
+        return self.snapshots.back().map(|(_, snapshot)| snapshot);
+    }
+}
@@ -0,0 +1,32 @@
+use naia_derive::ProtocolType;
+use naia_shared::WorldMutType;
+
+pub use position::Position;
+
+mod position {
+    use naia_derive::Replicate;
+    use naia_shared::Property;
+
+    #[derive(Replicate)]
+    #[protocol_path = "crate::test_protocol::Protocol"]
+    pub struct Position {
+        pub x: Property<u8>,
+        pub y: Property<u8>,
+    }
+}
+
+#[derive(ProtocolType)]
+pub enum Protocol {
+    Position(Position),
+}
+
+/// Gets the x of a Position
+pub fn x_of(state: &Protocol) -> u8 {
+    return *state.cast_ref::<Position>().unwrap().x.get();
+}
+
+/// Replaces an Entity's Position with one at the given x
+pub fn set_x<E, W: WorldMutType<Protocol, E>>(world: &mut W, entity: &E, x: u8) {
+    world.remove_component::<Position>(entity);
+    world.insert_component(entity, Position::new_complete(x, 0));
+}
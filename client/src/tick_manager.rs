@@ -1,6 +1,6 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use naia_shared::{wrapping_diff, Instant};
+use naia_shared::{wrapping_diff, Clock};
 
 /// Manages the current tick for the host
 pub struct TickManager {
@@ -10,7 +10,8 @@ pub struct TickManager {
     client_tick_adjust: u16,
     server_tick_adjust: u16,
     server_tick_running_diff: i16,
-    last_tick_instant: Instant,
+    clock: Arc<dyn Clock>,
+    last_tick_time: Duration,
     pub fraction: f32,
     accumulator: f32,
     minimum_latency: f32,
@@ -18,7 +19,11 @@ pub struct TickManager {
 
 impl TickManager {
     /// Create a new TickManager with a given tick interval duration
-    pub fn new(tick_interval: Duration, minimum_latency_duration: Option<Duration>) -> Self {
+    pub fn new(
+        tick_interval: Duration,
+        minimum_latency_duration: Option<Duration>,
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
         let minimum_latency = {
             if let Some(min_latency) = minimum_latency_duration {
                 min_latency.as_millis() as f32
@@ -36,7 +41,8 @@ impl TickManager {
             client_tick_adjust: 0,
             server_tick_adjust: 0,
             server_tick_running_diff: 0,
-            last_tick_instant: Instant::now(),
+            clock: clock.clone(),
+            last_tick_time: clock.now(),
             accumulator: 0.0,
             fraction: 0.0,
             minimum_latency,
@@ -45,12 +51,14 @@ impl TickManager {
 
     pub fn mark_frame(&mut self) -> bool {
         let mut ticked = false;
-        let mut frame_time = self.last_tick_instant.elapsed().as_nanos() as f32 / 1000000000.0;
+        let now = self.clock.now();
+        let mut frame_time =
+            now.saturating_sub(self.last_tick_time).as_nanos() as f32 / 1000000000.0;
         if frame_time > 0.25 {
             frame_time = 0.25;
         }
         self.accumulator += frame_time;
-        self.last_tick_instant = Instant::now();
+        self.last_tick_time = now;
         if self.accumulator >= self.tick_interval_seconds {
             while self.accumulator >= self.tick_interval_seconds {
                 self.accumulator -= self.tick_interval_seconds;
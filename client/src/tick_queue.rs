@@ -7,6 +7,7 @@ use naia_shared::sequence_greater_than;
 #[derive(Debug)]
 pub struct TickQueue<T: Eq + PartialEq> {
     queue: BinaryHeap<ItemContainer<T>>,
+    next_order: u64,
 }
 
 impl<T: Eq + PartialEq> TickQueue<T> {
@@ -14,12 +15,15 @@ impl<T: Eq + PartialEq> TickQueue<T> {
     pub fn new() -> Self {
         TickQueue {
             queue: BinaryHeap::new(),
+            next_order: 0,
         }
     }
 
     /// Adds an item to the queue marked by tick
     pub fn add_item(&mut self, tick: u16, item: T) {
-        self.queue.push(ItemContainer { tick, item });
+        let order = self.next_order;
+        self.next_order = self.next_order.wrapping_add(1);
+        self.queue.push(ItemContainer { tick, order, item });
     }
 
     /// Returns whether or not there is an item that is ready to be returned
@@ -47,13 +51,18 @@ impl<T: Eq + PartialEq> TickQueue<T> {
 #[derive(Debug, Eq, PartialEq)]
 pub struct ItemContainer<T: Eq + PartialEq> {
     pub tick: u16,
+    // items marked with the same tick are popped in the order they were added.
+    // With delta compression, an update is read relative to a state received
+    // in an earlier packet, possibly one marked with the same tick, so that
+    // packet must be read first, or the baseline will be unknown
+    pub order: u64,
     pub item: T,
 }
 
 impl<T: Eq + PartialEq> Ord for ItemContainer<T> {
     fn cmp(&self, other: &ItemContainer<T>) -> Ordering {
         if self.tick == other.tick {
-            return Ordering::Equal;
+            return other.order.cmp(&self.order);
         }
         if sequence_greater_than(other.tick, self.tick) {
             return Ordering::Greater;
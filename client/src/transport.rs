@@ -0,0 +1,78 @@
+use std::net::SocketAddr;
+
+use naia_client_socket::{NaiaClientSocketError, Packet, PacketReceiver, PacketSender};
+use naia_shared::MemoryNetwork;
+
+/// Sends & receives the packets of a Client. Implemented for the Client's
+/// own socket, & for a MemoryNetwork, so that tests can run a Server & its
+/// Clients in a single thread
+pub trait ClientTransport: Send + Sync {
+    /// Sends a packet to the Server
+    fn send(&mut self, packet: Packet);
+    /// Receives the next packet sent by the Server, if there is one
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaClientSocketError>;
+}
+
+/// A ClientTransport over the Client's socket
+pub struct SocketTransport {
+    packet_sender: PacketSender,
+    packet_receiver: PacketReceiver,
+}
+
+impl SocketTransport {
+    pub fn new(packet_sender: PacketSender, packet_receiver: PacketReceiver) -> Self {
+        SocketTransport {
+            packet_sender,
+            packet_receiver,
+        }
+    }
+}
+
+impl ClientTransport for SocketTransport {
+    fn send(&mut self, packet: Packet) {
+        self.packet_sender.send(packet);
+    }
+
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaClientSocketError> {
+        return self.packet_receiver.receive();
+    }
+}
+
+/// A ClientTransport over a MemoryNetwork, sending from the given address to
+/// the given Server address
+pub struct MemoryClientTransport {
+    network: MemoryNetwork,
+    address: SocketAddr,
+    server_address: SocketAddr,
+}
+
+impl MemoryClientTransport {
+    pub fn new(network: &MemoryNetwork, address: SocketAddr, server_address: SocketAddr) -> Self {
+        MemoryClientTransport {
+            network: network.clone(),
+            address,
+            server_address,
+        }
+    }
+}
+
+impl ClientTransport for MemoryClientTransport {
+    fn send(&mut self, packet: Packet) {
+        self.network.send(
+            &self.address,
+            &self.server_address,
+            Box::from(packet.payload()),
+        );
+    }
+
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaClientSocketError> {
+        // packets from anywhere other than the Server are ignored, as a
+        // socket connected to the Server would
+        while let Some((address, payload)) = self.network.receive(&self.address) {
+            if address == self.server_address {
+                return Ok(Some(Packet::new_raw(payload)));
+            }
+        }
+        return Ok(None);
+    }
+}
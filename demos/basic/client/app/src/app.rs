@@ -6,7 +6,7 @@ cfg_if! {
     }
 }
 
-use naia_client::{Client as NaiaClient, ClientConfig, Event, ProtocolType};
+use naia_client::{Client as NaiaClient, ClientConfig, DefaultChannels, Event, ProtocolType};
 
 use naia_demo_world::{Entity, World as DemoWorld};
 
@@ -59,7 +59,8 @@ impl App {
                     info!("Client send -> {}", new_message_contents);
 
                     let string_message = StringMessage::new(new_message_contents);
-                    self.client.send_message(&string_message, true);
+                    self.client
+                        .send_message(&string_message, DefaultChannels::UnorderedReliable);
                     self.message_count += 1;
                 }
                 Ok(Event::SpawnEntity(entity, _)) => {
@@ -1,5 +1,6 @@
 use naia_server::{
-    Event, ProtocolType, RoomKey, Server as NaiaServer, ServerAddrs, ServerConfig, WorldRefType,
+    DefaultChannels, Event, ProtocolType, RoomKey, Server as NaiaServer, ServerAddrs, ServerConfig,
+    WorldRefType,
 };
 
 use naia_demo_world::{Entity, World as DemoWorld};
@@ -124,7 +125,11 @@ impl App {
                         );
 
                         let new_message = StringMessage::new(new_message_contents);
-                        self.server.send_message(&user_key, &new_message, true);
+                        self.server.send_message(
+                            &user_key,
+                            &new_message,
+                            DefaultChannels::UnorderedReliable,
+                        );
                     }
 
                     // Iterate through Characters, marching them from (0,0) to (20, N)
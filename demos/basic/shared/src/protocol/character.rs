@@ -1,10 +1,8 @@
-use nanoserde::{DeBin, SerBin};
-
-use naia_derive::Replicate;
+use naia_derive::{Replicate, Serde};
 use naia_shared::Property;
 
 // Here's an example of a Custom Property
-#[derive(Default, PartialEq, Clone, DeBin, SerBin)]
+#[derive(Default, PartialEq, Clone, Serde)]
 pub struct FullName {
     pub first: String,
     pub last: String,
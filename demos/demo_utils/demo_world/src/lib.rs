@@ -8,4 +8,4 @@ mod entity;
 mod world;
 
 pub use entity::Entity;
-pub use world::World;
+pub use world::{World, WorldMut, WorldRef};
@@ -3,9 +3,8 @@ use std::collections::HashMap;
 use slotmap::DenseSlotMap;
 
 use naia_shared::{
-    DiffMask, PacketReader, ProtocolInserter, ProtocolType, ReplicaDynMutWrapper,
-    ReplicaDynRefWrapper, ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe,
-    WorldMutType, WorldRefType,
+    BitReader, ProtocolInserter, ProtocolType, ReplicaDynMutWrapper, ReplicaDynRefWrapper,
+    ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType, WorldRefType,
 };
 
 use super::{
@@ -156,12 +155,20 @@ impl<'w, P: ProtocolType> WorldMutType<P, Entity> for WorldMut<'w, P> {
         &mut self,
         entity: &Entity,
         component_kind: &P::Kind,
-        diff_mask: &DiffMask,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         packet_index: u16,
     ) {
         if let Some(mut component) = get_component_mut_of_kind(self.world, entity, component_kind) {
-            component.read_partial(diff_mask, reader, packet_index);
+            component.read_partial(reader, packet_index);
+        }
+    }
+
+    fn mirror_component(&mut self, entity: &Entity, component: &P) {
+        let component_kind = component.dyn_ref().get_kind();
+        if let Some(mut component_mut) =
+            get_component_mut_of_kind(self.world, entity, &component_kind)
+        {
+            component_mut.mirror(component);
         }
     }
 
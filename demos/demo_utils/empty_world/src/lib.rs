@@ -4,8 +4,8 @@ mod inner {
     use std::marker::PhantomData;
 
     use naia_shared::{
-        DiffMask, PacketReader, ProtocolInserter, ProtocolType, ReplicaDynRefWrapper,
-        ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType, WorldRefType,
+        BitReader, ProtocolInserter, ProtocolType, ReplicaDynRefWrapper, ReplicaMutWrapper,
+        ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType, WorldRefType,
     };
 
     pub type EmptyEntity = u8;
@@ -118,13 +118,16 @@ mod inner {
             &mut self,
             _: &EmptyEntity,
             _: &P::Kind,
-            _: &DiffMask,
-            _: &mut PacketReader,
+            _: &mut BitReader,
             _: u16,
         ) {
             unimplemented!()
         }
 
+        fn mirror_component(&mut self, _: &EmptyEntity, _: &P) {
+            unimplemented!()
+        }
+
         fn mirror_components(&mut self, _: &EmptyEntity, _: &EmptyEntity, _: &P::Kind) {
             unimplemented!()
         }
@@ -1,9 +1,7 @@
-use nanoserde::{DeBin, SerBin};
-
-use naia_derive::Replicate;
+use naia_derive::{Replicate, Serde};
 use naia_shared::Property;
 
-#[derive(Clone, PartialEq, DeBin, SerBin)]
+#[derive(Clone, PartialEq, Serde)]
 pub enum Color {
     Red,
     Blue,
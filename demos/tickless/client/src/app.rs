@@ -1,4 +1,4 @@
-use naia_client::{Client as NaiaClient, ClientConfig, Event};
+use naia_client::{Client as NaiaClient, ClientConfig, DefaultChannels, Event};
 
 use naia_tickless_demo_shared::{get_server_address, get_shared_config, Protocol, Text};
 
@@ -58,7 +58,8 @@ impl App {
         info!("Client send -> {}", message_contents);
 
         let message = Text::new(&message_contents);
-        self.client.send_message(&message, true);
+        self.client
+            .send_message(&message, DefaultChannels::UnorderedReliable);
         self.message_count = self.message_count.wrapping_add(1);
     }
 }
@@ -1,6 +1,6 @@
 use std::{thread, time::Duration};
 
-use naia_server::{Event, Server as NaiaServer, ServerAddrs, ServerConfig};
+use naia_server::{DefaultChannels, Event, Server as NaiaServer, ServerAddrs, ServerConfig};
 
 use naia_tickless_demo_shared::{get_server_address, get_shared_config, Protocol, Text};
 
@@ -57,7 +57,11 @@ impl App {
                     info!("Server echo -> {}", new_message_contents);
 
                     let message = Text::new(&new_message_contents);
-                    self.server.send_message(&user_key, &message, true);
+                    self.server.send_message(
+                        &user_key,
+                        &message,
+                        DefaultChannels::UnorderedReliable,
+                    );
 
                     // Sleep the thread to keep the demo from being unintelligibly fast
                     let sleep_time = Duration::from_millis(500);
@@ -0,0 +1,167 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Ident, Lit, Meta, NestedMeta, Variant};
+
+struct Channel {
+    name: Ident,
+    mode: Ident,
+    priority: u8,
+    max_messages_per_packet: Option<u16>,
+    max_queued_messages: Option<usize>,
+}
+
+pub fn channels_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let enum_name = input.ident;
+
+    let channels = get_channels(&input.data);
+    if channels.len() > 256 {
+        panic!("Cannot derive 'Channels' for an enum with more than 256 variants");
+    }
+
+    let index_method = get_index_method(&enum_name, &channels);
+    let configs_method = get_configs_method(&channels);
+
+    let gen = quote! {
+        impl naia_shared::ChannelIndex for #enum_name {
+            #index_method
+        }
+        impl naia_shared::Channels for #enum_name {
+            #configs_method
+        }
+    };
+
+    proc_macro::TokenStream::from(gen)
+}
+
+fn get_channels(data: &Data) -> Vec<Channel> {
+    let mut channels = Vec::new();
+    if let Data::Enum(ref data) = *data {
+        for variant in data.variants.iter() {
+            channels.push(get_channel(variant));
+        }
+    } else {
+        panic!("Can only derive 'Channels' for an enum");
+    }
+    return channels;
+}
+
+fn get_channel(variant: &Variant) -> Channel {
+    let mut mode = None;
+    let mut priority = 0;
+    let mut max_messages_per_packet = None;
+    let mut max_queued_messages = None;
+
+    for attr in variant.attrs.iter() {
+        if !attr.path.is_ident("channel") {
+            continue;
+        }
+        if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
+            for nested in meta_list.nested.iter() {
+                if let NestedMeta::Meta(Meta::NameValue(name_value)) = nested {
+                    let path = &name_value.path;
+                    match &name_value.lit {
+                        Lit::Str(lit_str) if path.is_ident("mode") => {
+                            mode = Some(Ident::new(&lit_str.value(), Span::call_site()));
+                        }
+                        Lit::Int(lit_int) if path.is_ident("priority") => {
+                            priority = lit_int
+                                .base10_parse::<u8>()
+                                .expect("Channel priority must fit in a u8");
+                        }
+                        Lit::Int(lit_int) if path.is_ident("max_messages_per_packet") => {
+                            max_messages_per_packet = Some(
+                                lit_int
+                                    .base10_parse::<u16>()
+                                    .expect("Channel max_messages_per_packet must fit in a u16"),
+                            );
+                        }
+                        Lit::Int(lit_int) if path.is_ident("max_queued_messages") => {
+                            max_queued_messages = Some(
+                                lit_int
+                                    .base10_parse::<usize>()
+                                    .expect("Channel max_queued_messages must fit in a usize"),
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    match mode {
+        Some(mode) => {
+            return Channel {
+                name: variant.ident.clone(),
+                mode,
+                priority,
+                max_messages_per_packet,
+                max_queued_messages,
+            };
+        }
+        None => {
+            panic!("When deriving 'Channels' you MUST specify the mode of each Channel. IE: '#[channel(mode = \"OrderedReliable\")]'");
+        }
+    }
+}
+
+fn get_index_method(enum_name: &Ident, channels: &Vec<Channel>) -> TokenStream {
+    let mut variants = quote! {};
+
+    for (index, channel) in channels.iter().enumerate() {
+        let name = &channel.name;
+        let index = index as u8;
+        let new_output_right = quote! {
+            #enum_name::#name => #index,
+        };
+        variants = quote! {
+            #variants
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn index(&self) -> u8 {
+            match self {
+                #variants
+            }
+        }
+    };
+}
+
+fn get_configs_method(channels: &Vec<Channel>) -> TokenStream {
+    let mut configs = quote! {};
+
+    for channel in channels.iter() {
+        let mode = &channel.mode;
+        let priority = channel.priority;
+        let max_messages_per_packet = match channel.max_messages_per_packet {
+            Some(max_messages) => quote! { Some(#max_messages) },
+            None => quote! { None },
+        };
+        let max_queued_messages = match channel.max_queued_messages {
+            Some(max_messages) => quote! { Some(#max_messages) },
+            None => quote! { None },
+        };
+        let new_output_right = quote! {
+            naia_shared::ChannelConfig {
+                mode: naia_shared::ChannelMode::#mode,
+                priority: #priority,
+                max_messages_per_packet: #max_messages_per_packet,
+                max_queued_messages: #max_queued_messages,
+            },
+        };
+        configs = quote! {
+            #configs
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn configs() -> Vec<naia_shared::ChannelConfig> {
+            return vec![#configs];
+        }
+    };
+}
@@ -1,14 +1,20 @@
 //! # Naia Derive
-//! Procedural macros to simplify implementation of Naia ReplicateSafe &
-//! ProtocolType traits
+//! Procedural macros to simplify implementation of Naia ReplicateSafe,
+//! ProtocolType, Serde, NestedProperty & Channels traits
 
 #![deny(trivial_casts, trivial_numeric_casts, unstable_features)]
 
+mod channels;
+mod nested_property;
 mod protocol_type;
 mod replicate;
+mod serde;
 
+use channels::channels_impl;
+use nested_property::nested_property_impl;
 use protocol_type::protocol_type_impl;
 use replicate::replicate_impl;
+use serde::serde_impl;
 
 /// Derives the ProtocolType trait for a given enum
 #[proc_macro_derive(ProtocolType)]
@@ -16,8 +22,30 @@ pub fn protocol_type_derive(input: proc_macro::TokenStream) -> proc_macro::Token
     protocol_type_impl(input)
 }
 
-/// Derives the Replicate trait for a given struct
-#[proc_macro_derive(Replicate, attributes(protocol_path))]
+/// Derives the Replicate trait for a given struct. A `Property<T>` field may be
+/// written through a compact encoding, IE: '#[encoding = "QuantizedFloat<0,
+/// 100, 12>"]'
+#[proc_macro_derive(Replicate, attributes(protocol_path, encoding))]
 pub fn replicate_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     replicate_impl(input)
 }
+
+/// Derives the Serde trait for a given struct or enum
+#[proc_macro_derive(Serde)]
+pub fn serde_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    serde_impl(input)
+}
+
+/// Derives the NestedProperty trait for a given struct, so that it can be used
+/// as a Property of a Replica which syncs only its changed fields
+#[proc_macro_derive(NestedProperty, attributes(encoding))]
+pub fn nested_property_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    nested_property_impl(input)
+}
+
+/// Derives the Channels trait for a given enum, where each variant declares a
+/// Channel, IE: '#[channel(mode = "OrderedReliable", priority = 1)]'
+#[proc_macro_derive(Channels, attributes(channel))]
+pub fn channels_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    channels_impl(input)
+}
@@ -0,0 +1,369 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, Ident};
+
+use super::replicate::{
+    get_clone_args, get_new_complete_method, get_new_read_properties, get_properties,
+    get_property_enum, get_property_method, get_property_names, get_variant_name, Property,
+};
+
+pub fn nested_property_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    // Helper Properties
+    let properties = get_properties(&input);
+
+    // Names
+    let struct_name = input.ident;
+    let enum_name = format_ident!("{}Property", struct_name);
+
+    // Definitions
+    let property_enum_definition = get_property_enum(&enum_name, &properties);
+
+    // Methods
+    let new_complete_method = get_new_complete_method(&struct_name, &enum_name, &properties);
+    let clone_args = get_clone_args(&properties);
+    let new_read_method = get_new_read_method(&struct_name, &enum_name, &properties);
+    let read_method = get_read_method(&properties);
+    let read_partial_method = get_read_partial_method(&properties);
+    let read_delta_method = get_read_delta_method(&properties);
+    let write_method = get_write_method(&properties);
+    let write_partial_method = get_write_partial_method(&enum_name, &properties);
+    let write_delta_method = get_write_delta_method(&properties);
+    let equals_method = get_equals_method(&properties);
+    let mirror_method = get_mirror_method(&properties);
+    let set_mutator_method = get_set_mutator_method(&enum_name, &properties);
+
+    let gen = quote! {
+        #property_enum_definition
+        impl #struct_name {
+            #new_complete_method
+        }
+        impl Clone for #struct_name {
+            fn clone(&self) -> #struct_name {
+                return #struct_name::new_complete(#clone_args);
+            }
+        }
+        impl naia_shared::NestedProperty for #struct_name {
+            #new_read_method
+            #read_method
+            #read_partial_method
+            #read_delta_method
+            #write_method
+            #write_partial_method
+            #write_delta_method
+            #equals_method
+            #mirror_method
+            #set_mutator_method
+        }
+    };
+
+    proc_macro::TokenStream::from(gen)
+}
+
+fn get_new_read_method(
+    struct_name: &Ident,
+    enum_name: &Ident,
+    properties: &Vec<Property>,
+) -> TokenStream {
+    let prop_names = get_property_names(properties);
+    let prop_reads = get_new_read_properties(enum_name, properties);
+
+    return quote! {
+        fn new_read(reader: &mut naia_shared::BitReader, packet_index: u16) -> Self {
+            #prop_reads
+
+            return #struct_name {
+                #prop_names
+            };
+        }
+    };
+}
+
+fn get_read_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "read");
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::Nested#property_method(&mut self.#field_name, reader, packet_index);
+            }
+        } else {
+            quote! {
+                #property_method(&mut self.#field_name, reader, packet_index);
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn read(&mut self, reader: &mut naia_shared::BitReader, packet_index: u16) {
+            #output
+        }
+    };
+}
+
+fn get_read_partial_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "read");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if reader.read_bit() {
+                    naia_shared::NestedProperty::read_masked(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        } else {
+            quote! {
+                if reader.read_bit() {
+                    #property_method(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn read_partial(&mut self, reader: &mut naia_shared::BitReader, packet_index: u16) {
+            #output
+        }
+    };
+}
+
+fn get_read_delta_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "read_delta");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if reader.read_bit() {
+                    naia_shared::Nested#property_method(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        } else {
+            quote! {
+                if reader.read_bit() {
+                    #property_method(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn read_delta(&mut self, reader: &mut naia_shared::BitReader, packet_index: u16) {
+            #output
+        }
+    };
+}
+
+fn get_write_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write");
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::Nested#property_method(&self.#field_name, writer);
+            }
+        } else {
+            quote! {
+                #property_method(&self.#field_name, writer);
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn write(&self, writer: &mut naia_shared::BitWriter) {
+            #output
+        }
+    };
+}
+
+fn get_write_partial_method(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write");
+        let uppercase_variant_name = get_variant_name(property);
+        let new_output_right = if property.is_nested {
+            quote! {
+                let index = u16::from(#enum_name::#uppercase_variant_name as u8);
+                if diff_mask.contains(index) {
+                    writer.write_bit(true);
+                    naia_shared::NestedProperty::write_masked(&self.#field_name, diff_mask.get(index), writer);
+                } else {
+                    writer.write_bit(false);
+                }
+            }
+        } else {
+            quote! {
+                if diff_mask.contains(u16::from(#enum_name::#uppercase_variant_name as u8)) {
+                    writer.write_bit(true);
+                    #property_method(&self.#field_name, writer);
+                } else {
+                    writer.write_bit(false);
+                }
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn write_partial(&self, diff_mask: &naia_shared::SubDiffMask, writer: &mut naia_shared::BitWriter) {
+            #output
+        }
+    };
+}
+
+fn get_write_delta_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write_delta");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if naia_shared::NestedProperty::equals(&self.#field_name, &baseline.#field_name) {
+                    writer.write_bit(false);
+                } else {
+                    writer.write_bit(true);
+                    naia_shared::Nested#property_method(&self.#field_name, &baseline.#field_name, writer);
+                }
+            }
+        } else {
+            quote! {
+                if naia_shared::Property::equals(&self.#field_name, &baseline.#field_name) {
+                    writer.write_bit(false);
+                } else {
+                    writer.write_bit(true);
+                    #property_method(&self.#field_name, &baseline.#field_name, writer);
+                }
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn write_delta(&self, baseline: &Self, writer: &mut naia_shared::BitWriter) {
+            #output
+        }
+    };
+}
+
+fn get_equals_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let new_output_right = if property.is_nested {
+            quote! {
+                if !naia_shared::NestedProperty::equals(&self.#field_name, &other.#field_name) {
+                    return false;
+                }
+            }
+        } else {
+            quote! {
+                if !naia_shared::Property::equals(&self.#field_name, &other.#field_name) {
+                    return false;
+                }
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn equals(&self, other: &Self) -> bool {
+            #output
+            return true;
+        }
+    };
+}
+
+fn get_mirror_method(properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::NestedProperty::mirror(&mut self.#field_name, &other.#field_name);
+            }
+        } else {
+            quote! {
+                naia_shared::Property::mirror(&mut self.#field_name, &other.#field_name);
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn mirror(&mut self, other: &Self) {
+            #output
+        }
+    };
+}
+
+fn get_set_mutator_method(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let uppercase_variant_name = get_variant_name(property);
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::NestedProperty::set_mutator(&mut self.#field_name, &nested_mutator, #enum_name::#uppercase_variant_name as u8);
+            }
+        } else {
+            quote! {
+                self.#field_name.set_mutator(&nested_mutator);
+            }
+        };
+        output = quote! {
+            #output
+            #new_output_right
+        };
+    }
+
+    return quote! {
+        fn set_mutator(&mut self, mutator: &naia_shared::PropertyMutator, property_index: u8) {
+            // changes to the Properties of this struct are reported as changes
+            // to nested elements of the Property which contains it
+            let nested_mutator = naia_shared::PropertyMutator::new(naia_shared::NestedMutator::new(mutator, property_index));
+            #output
+        }
+    };
+}
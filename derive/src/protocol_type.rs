@@ -25,7 +25,7 @@ pub fn protocol_type_impl(input: proc_macro::TokenStream) -> proc_macro::TokenSt
     let gen = quote! {
         use std::{any::{Any, TypeId}, ops::{Deref, DerefMut}};
         use naia_shared::{ProtocolType, ProtocolInserter, ProtocolKindType, ReplicateSafe,
-            DiffMask, PacketReader, ReplicaDynRef, ReplicaDynMut, Replicate, Manifest};
+            DiffMask, BitReader, BitWriter, ReplicaDynRef, ReplicaDynMut, Replicate, Manifest};
 
         #kind_enum_def
 
@@ -119,6 +119,12 @@ pub fn get_kind_enum(enum_name: &Ident, properties: &Vec<Ident>) -> TokenStream
         }
     }
 
+    // minimum number of bits needed to represent every kind
+    let mut kind_bits: u8 = 1;
+    while (1u32 << kind_bits) < u32::from(variant_index) {
+        kind_bits += 1;
+    }
+
     return quote! {
         #hashtag[repr(u16)]
         #hashtag[derive(Hash, Eq, PartialEq, Copy, Clone)]
@@ -142,6 +148,12 @@ pub fn get_kind_enum(enum_name: &Ident, properties: &Vec<Ident>) -> TokenStream
                     #variant_types
                 }
             }
+            fn write(&self, writer: &mut BitWriter) {
+                writer.write_bits(u64::from(self.to_u16()), #kind_bits);
+            }
+            fn read(reader: &mut BitReader) -> Self {
+                return Self::from_u16(reader.read_bits(#kind_bits) as u16);
+            }
         }
     };
 }
@@ -155,55 +167,29 @@ fn get_kind_of_method() -> TokenStream {
 }
 
 fn get_type_to_kind_method(enum_name: &Ident, properties: &Vec<Ident>) -> TokenStream {
-    let mut const_list = quote! {};
-
-    {
-        for variant in properties {
-            let variant_name_string = variant.to_string();
-            let variant_name_ident = Ident::new(&variant_name_string, Span::call_site());
-
-            let id_const_name =
-                format_ident!("{}_TYPE_ID", variant_name_string.to_uppercase().as_str());
-            let id_const_ident = Ident::new(&id_const_name.to_string(), Span::call_site());
-            let new_output_right = quote! {
-                const #id_const_ident: TypeId = TypeId::of::<#variant_name_ident>();
-            };
-            let new_output_result = quote! {
-                #const_list
-                #new_output_right
-            };
-            const_list = new_output_result;
-        }
-    }
-
-    let mut match_branches = quote! {};
+    let mut type_branches = quote! {};
 
     {
         for variant in properties {
-            let variant_name_string = variant.to_string();
-            let variant_name_ident = Ident::new(&variant_name_string, Span::call_site());
+            let variant_name_ident = Ident::new(&variant.to_string(), Span::call_site());
 
-            let id_const_name =
-                format_ident!("{}_TYPE_ID", variant_name_string.to_uppercase().as_str());
-            let id_const_ident = Ident::new(&id_const_name.to_string(), Span::call_site());
             let new_output_right = quote! {
-                #id_const_ident => #enum_name::#variant_name_ident,
+                if type_id == TypeId::of::<#variant_name_ident>() {
+                    return #enum_name::#variant_name_ident;
+                }
             };
             let new_output_result = quote! {
-                #match_branches
+                #type_branches
                 #new_output_right
             };
-            match_branches = new_output_result;
+            type_branches = new_output_result;
         }
     }
 
     return quote! {
         fn type_to_kind(type_id: TypeId) -> Self::Kind {
-            #const_list
-            match type_id {
-                #match_branches
-                _ => #enum_name::UNKNOWN,
-            }
+            #type_branches
+            return #enum_name::UNKNOWN;
         }
     };
 }
@@ -1,8 +1,8 @@
 use proc_macro2::{Punct, Spacing, Span, TokenStream};
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, Ident, Lit, Meta, Path,
-    PathArguments, Result, Type,
+    parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, Ident, Lit, Meta,
+    Path, PathArguments, Result, Type,
 };
 
 pub fn replicate_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -39,14 +39,16 @@ pub fn replicate_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let protocol_copy_method = get_protocol_copy_method(&protocol_name, &replica_name);
     let clone_method = get_clone_method(&replica_name, &properties);
     let mirror_method = get_mirror_method(&protocol_name, &replica_name, &properties);
-    let set_mutator_method = get_set_mutator_method(&properties);
-    let read_partial_method = get_read_partial_method(&enum_name, &properties);
+    let set_mutator_method = get_set_mutator_method(&enum_name, &properties);
+    let read_partial_method = get_read_partial_method(&properties);
     let write_method = get_write_method(&properties);
     let write_partial_method = get_write_partial_method(&enum_name, &properties);
+    let read_delta_method = get_read_delta_method(&properties);
+    let write_delta_method = get_write_delta_method(&protocol_name, &replica_name, &properties);
 
     let gen = quote! {
         use std::{rc::Rc, cell::RefCell, io::Cursor};
-        use naia_shared::{DiffMask, ReplicaBuilder, PropertyMutate, PacketReader, Replicate, ReplicateSafe, PropertyMutator, ProtocolType, ReplicaDynRef, ReplicaDynMut};
+        use naia_shared::{DiffMask, ReplicaBuilder, PropertyMutate, BitReader, BitWriter, Replicate, ReplicateSafe, PropertyMutator, ProtocolType, ReplicaDynRef, ReplicaDynMut};
         use #protocol_path::{#protocol_name, #protocol_kind_name};
         #property_enum_definition
         pub struct #replica_builder_name {
@@ -56,7 +58,7 @@ pub fn replicate_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             fn get_kind(&self) -> #protocol_kind_name {
                 return self.kind;
             }
-            fn build(&self, reader: &mut PacketReader, packet_index: u16) -> #protocol_name {
+            fn build(&self, reader: &mut BitReader, packet_index: u16) -> #protocol_name {
                 return #replica_name::read_to_type(reader, packet_index);
             }
         }
@@ -83,6 +85,8 @@ pub fn replicate_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             #read_partial_method
             #write_method
             #write_partial_method
+            #read_delta_method
+            #write_delta_method
         }
         impl Replicate<#protocol_name> for #replica_name {
             #clone_method
@@ -92,23 +96,37 @@ pub fn replicate_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     proc_macro::TokenStream::from(gen)
 }
 
-fn get_properties(input: &DeriveInput) -> Vec<(Ident, Type)> {
+/// A field of a Replica. Fields of type `Property<T>` hold the type `T`, & may
+/// be written through an encoding given with `#[encoding = "Type"]`. Any other
+/// field must implement `NestedProperty`
+pub struct Property {
+    pub name: Ident,
+    pub ty: Type,
+    pub is_nested: bool,
+    pub encoding: Option<Type>,
+}
+
+pub fn get_properties(input: &DeriveInput) -> Vec<Property> {
     let mut fields = Vec::new();
 
     if let Data::Struct(data_struct) = &input.data {
         if let Fields::Named(fields_named) = &data_struct.fields {
             for field in fields_named.named.iter() {
                 if let Some(property_name) = &field.ident {
-                    if let Type::Path(type_path) = &field.ty {
-                        if let PathArguments::AngleBracketed(angle_args) =
-                            &type_path.path.segments.first().unwrap().arguments
-                        {
-                            if let Some(GenericArgument::Type(property_type)) =
-                                angle_args.args.first()
-                            {
-                                fields.push((property_name.clone(), property_type.clone()));
-                            }
-                        }
+                    if let Some(property_type) = get_property_type(&field.ty) {
+                        fields.push(Property {
+                            name: property_name.clone(),
+                            ty: property_type,
+                            is_nested: false,
+                            encoding: get_property_encoding(&field.attrs),
+                        });
+                    } else {
+                        fields.push(Property {
+                            name: property_name.clone(),
+                            ty: field.ty.clone(),
+                            is_nested: true,
+                            encoding: None,
+                        });
                     }
                 }
             }
@@ -118,6 +136,62 @@ fn get_properties(input: &DeriveInput) -> Vec<(Ident, Type)> {
     fields
 }
 
+// Gets the type `T` of a field of type `Property<T>`
+fn get_property_type(field_type: &Type) -> Option<Type> {
+    if let Type::Path(type_path) = field_type {
+        let segment = type_path.path.segments.last()?;
+        if segment.ident != "Property" {
+            return None;
+        }
+        if let PathArguments::AngleBracketed(angle_args) = &segment.arguments {
+            if let Some(GenericArgument::Type(property_type)) = angle_args.args.first() {
+                return Some(property_type.clone());
+            }
+        }
+    }
+    return None;
+}
+
+// Gets the type `S` of a field's `#[encoding = "S"]` attribute
+fn get_property_encoding(attrs: &[Attribute]) -> Option<Type> {
+    for attr in attrs {
+        if let Ok(Meta::NameValue(meta_name_value)) = attr.parse_meta() {
+            if meta_name_value.path.is_ident("encoding") {
+                if let Lit::Str(lit_str) = meta_name_value.lit {
+                    return Some(
+                        lit_str
+                            .parse()
+                            .expect("'encoding' attribute must name a type"),
+                    );
+                }
+            }
+        }
+    }
+    return None;
+}
+
+/// Gets the path of the given Property method, or of its encoded counterpart
+/// if the Property was given an encoding
+pub fn get_property_method(property: &Property, method: &str) -> TokenStream {
+    if let Some(encoding) = &property.encoding {
+        let method = format_ident!("{}_encoded", method);
+        return quote! {
+            naia_shared::Property::#method::<#encoding>
+        };
+    }
+    let method = format_ident!("{}", method);
+    return quote! {
+        naia_shared::Property::#method
+    };
+}
+
+pub fn get_variant_name(property: &Property) -> Ident {
+    return Ident::new(
+        property.name.to_string().to_uppercase().as_str(),
+        Span::call_site(),
+    );
+}
+
 fn get_protocol_path(input: &DeriveInput) -> (Path, Ident) {
     let mut path_result: Option<Result<Path>> = None;
 
@@ -154,16 +228,13 @@ fn get_protocol_path(input: &DeriveInput) -> (Path, Ident) {
     panic!("When deriving 'Replicate' you MUST specify the path of the accompanying protocol. IE: '#[protocol_path = \"crate::MyProtocol\"]'");
 }
 
-fn get_property_enum(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
+pub fn get_property_enum(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
     let hashtag = Punct::new('#', Spacing::Alone);
 
     let mut variant_index: u8 = 0;
     let mut variant_list = quote! {};
-    for (variant, _) in properties {
-        let uppercase_variant_name = Ident::new(
-            variant.to_string().to_uppercase().as_str(),
-            Span::call_site(),
-        );
+    for property in properties {
+        let uppercase_variant_name = get_variant_name(property);
 
         let new_output_right = quote! {
             #uppercase_variant_name = #variant_index,
@@ -217,12 +288,29 @@ pub fn get_dyn_mut_method(protocol_name: &Ident) -> TokenStream {
     };
 }
 
-fn get_clone_method(replica_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
+fn get_clone_method(replica_name: &Ident, properties: &Vec<Property>) -> TokenStream {
+    let output = get_clone_args(properties);
+
+    return quote! {
+        fn clone(&self) -> #replica_name {
+            return #replica_name::new_complete(#output);
+        }
+    };
+}
+
+pub fn get_clone_args(properties: &Vec<Property>) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let new_output_right = quote! {
-            self.#field_name.get().clone(),
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let new_output_right = if property.is_nested {
+            quote! {
+                self.#field_name.clone(),
+            }
+        } else {
+            quote! {
+                self.#field_name.get().clone(),
+            }
         };
         let new_output_result = quote! {
             #output
@@ -231,23 +319,26 @@ fn get_clone_method(replica_name: &Ident, properties: &Vec<(Ident, Type)>) -> To
         output = new_output_result;
     }
 
-    return quote! {
-        fn clone(&self) -> #replica_name {
-            return #replica_name::new_complete(#output);
-        }
-    };
+    return output;
 }
 
 fn get_mirror_method(
     protocol_name: &Ident,
     replica_name: &Ident,
-    properties: &Vec<(Ident, Type)>,
+    properties: &Vec<Property>,
 ) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let new_output_right = quote! {
-            self.#field_name.mirror(&replica.#field_name);
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::NestedProperty::mirror(&mut self.#field_name, &replica.#field_name);
+            }
+        } else {
+            quote! {
+                self.#field_name.mirror(&replica.#field_name);
+            }
         };
         let new_output_result = quote! {
             #output
@@ -265,12 +356,20 @@ fn get_mirror_method(
     };
 }
 
-fn get_write_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
+fn get_write_method(properties: &Vec<Property>) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let new_output_right = quote! {
-            Property::write(&self.#field_name, buffer);
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write");
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::Nested#property_method(&self.#field_name, writer);
+            }
+        } else {
+            quote! {
+                #property_method(&self.#field_name, writer);
+            }
         };
         let new_output_result = quote! {
             #output
@@ -280,24 +379,29 @@ fn get_write_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
     }
 
     return quote! {
-        fn write(&self, buffer: &mut Vec<u8>) {
+        fn write(&self, writer: &mut BitWriter) {
             #output
         }
     };
 }
 
-fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
+fn get_read_partial_method(properties: &Vec<Property>) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let uppercase_variant_name = Ident::new(
-            field_name.to_string().to_uppercase().as_str(),
-            Span::call_site(),
-        );
-
-        let new_output_right = quote! {
-            if let Some(true) = diff_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
-                Property::read(&mut self.#field_name, reader, packet_index);
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "read");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if reader.read_bit() {
+                    naia_shared::NestedProperty::read_masked(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        } else {
+            quote! {
+                if reader.read_bit() {
+                    #property_method(&mut self.#field_name, reader, packet_index);
+                }
             }
         };
         let new_output_result = quote! {
@@ -308,18 +412,30 @@ fn get_read_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -
     }
 
     return quote! {
-        fn read_partial(&mut self, diff_mask: &DiffMask, reader: &mut PacketReader, packet_index: u16) {
+        fn read_partial(&mut self, reader: &mut BitReader, packet_index: u16) {
             #output
         }
     };
 }
 
-fn get_set_mutator_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
+fn get_read_delta_method(properties: &Vec<Property>) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let new_output_right = quote! {
-            self.#field_name.set_mutator(mutator);
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "read_delta");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if reader.read_bit() {
+                    naia_shared::Nested#property_method(&mut self.#field_name, reader, packet_index);
+                }
+            }
+        } else {
+            quote! {
+                if reader.read_bit() {
+                    #property_method(&mut self.#field_name, reader, packet_index);
+                }
+            }
         };
         let new_output_result = quote! {
             #output
@@ -329,24 +445,70 @@ fn get_set_mutator_method(properties: &Vec<(Ident, Type)>) -> TokenStream {
     }
 
     return quote! {
-        fn set_mutator(&mut self, mutator: &PropertyMutator) {
+        fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16) {
             #output
         }
     };
 }
 
-fn get_write_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>) -> TokenStream {
+fn get_write_delta_method(
+    protocol_name: &Ident,
+    replica_name: &Ident,
+    properties: &Vec<Property>,
+) -> TokenStream {
     let mut output = quote! {};
 
-    for (field_name, _) in properties.iter() {
-        let uppercase_variant_name = Ident::new(
-            field_name.to_string().to_uppercase().as_str(),
-            Span::call_site(),
-        );
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write_delta");
+        let new_output_right = if property.is_nested {
+            quote! {
+                if naia_shared::NestedProperty::equals(&self.#field_name, &baseline.#field_name) {
+                    writer.write_bit(false);
+                } else {
+                    writer.write_bit(true);
+                    naia_shared::Nested#property_method(&self.#field_name, &baseline.#field_name, writer);
+                }
+            }
+        } else {
+            quote! {
+                if Property::equals(&self.#field_name, &baseline.#field_name) {
+                    writer.write_bit(false);
+                } else {
+                    writer.write_bit(true);
+                    #property_method(&self.#field_name, &baseline.#field_name, writer);
+                }
+            }
+        };
+        let new_output_result = quote! {
+            #output
+            #new_output_right
+        };
+        output = new_output_result;
+    }
+
+    return quote! {
+        fn write_delta(&self, baseline: &#protocol_name, writer: &mut BitWriter) {
+            if let #protocol_name::#replica_name(baseline) = baseline {
+                #output
+            }
+        }
+    };
+}
 
-        let new_output_right = quote! {
-            if let Some(true) = diff_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
-                Property::write(&self.#field_name, buffer);
+fn get_set_mutator_method(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let uppercase_variant_name = get_variant_name(property);
+        let new_output_right = if property.is_nested {
+            quote! {
+                naia_shared::NestedProperty::set_mutator(&mut self.#field_name, mutator, #enum_name::#uppercase_variant_name as u8);
+            }
+        } else {
+            quote! {
+                self.#field_name.set_mutator(mutator);
             }
         };
         let new_output_result = quote! {
@@ -357,8 +519,48 @@ fn get_write_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>)
     }
 
     return quote! {
-        fn write_partial(&self, diff_mask: &DiffMask, buffer: &mut Vec<u8>) {
+        fn set_mutator(&mut self, mutator: &PropertyMutator) {
+            #output
+        }
+    };
+}
+
+fn get_write_partial_method(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
+    let mut output = quote! {};
+
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let property_method = get_property_method(property, "write");
+        let uppercase_variant_name = get_variant_name(property);
+
+        let new_output_right = if property.is_nested {
+            quote! {
+                if let Some(true) = diff_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
+                    writer.write_bit(true);
+                    naia_shared::NestedProperty::write_masked(&self.#field_name, diff_mask.get_nested(#enum_name::#uppercase_variant_name as u8), writer);
+                } else {
+                    writer.write_bit(false);
+                }
+            }
+        } else {
+            quote! {
+                if let Some(true) = diff_mask.get_bit(#enum_name::#uppercase_variant_name as u8) {
+                    writer.write_bit(true);
+                    #property_method(&self.#field_name, writer);
+                } else {
+                    writer.write_bit(false);
+                }
+            }
+        };
+        let new_output_result = quote! {
+            #output
+            #new_output_right
+        };
+        output = new_output_result;
+    }
 
+    return quote! {
+        fn write_partial(&self, diff_mask: &DiffMask, writer: &mut BitWriter) {
             #output
         }
     };
@@ -367,10 +569,12 @@ fn get_write_partial_method(enum_name: &Ident, properties: &Vec<(Ident, Type)>)
 pub fn get_new_complete_method(
     replica_name: &Ident,
     enum_name: &Ident,
-    properties: &Vec<(Ident, Type)>,
+    properties: &Vec<Property>,
 ) -> TokenStream {
     let mut args = quote! {};
-    for (field_name, field_type) in properties.iter() {
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let field_type = &property.ty;
         let new_output_right = quote! {
             #field_name: #field_type
         };
@@ -381,14 +585,19 @@ pub fn get_new_complete_method(
     }
 
     let mut fields = quote! {};
-    for (field_name, field_type) in properties.iter() {
-        let uppercase_variant_name = Ident::new(
-            field_name.to_string().to_uppercase().as_str(),
-            Span::call_site(),
-        );
-
-        let new_output_right = quote! {
-            #field_name: Property::<#field_type>::new(#field_name, #enum_name::#uppercase_variant_name as u8, 0)
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let field_type = &property.ty;
+        let uppercase_variant_name = get_variant_name(property);
+
+        let new_output_right = if property.is_nested {
+            quote! {
+                #field_name
+            }
+        } else {
+            quote! {
+                #field_name: Property::<#field_type>::new(#field_name, #enum_name::#uppercase_variant_name as u8, 0)
+            }
         };
         let new_output_result = quote! {
             #fields
@@ -410,10 +619,26 @@ pub fn get_read_to_type_method(
     protocol_name: &Ident,
     replica_name: &Ident,
     enum_name: &Ident,
-    properties: &Vec<(Ident, Type)>,
+    properties: &Vec<Property>,
 ) -> TokenStream {
+    let prop_names = get_property_names(properties);
+    let prop_reads = get_new_read_properties(enum_name, properties);
+
+    return quote! {
+        fn read_to_type(reader: &mut BitReader, packet_index: u16) -> #protocol_name {
+            #prop_reads
+
+            return #protocol_name::#replica_name(#replica_name {
+                #prop_names
+            });
+        }
+    };
+}
+
+pub fn get_property_names(properties: &Vec<Property>) -> TokenStream {
     let mut prop_names = quote! {};
-    for (field_name, _) in properties.iter() {
+    for property in properties.iter() {
+        let field_name = &property.name;
         let new_output_right = quote! {
             #field_name
         };
@@ -424,15 +649,28 @@ pub fn get_read_to_type_method(
         prop_names = new_output_result;
     }
 
+    return prop_names;
+}
+
+pub fn get_new_read_properties(enum_name: &Ident, properties: &Vec<Property>) -> TokenStream {
     let mut prop_reads = quote! {};
-    for (field_name, field_type) in properties.iter() {
-        let uppercase_variant_name = Ident::new(
-            field_name.to_string().to_uppercase().as_str(),
-            Span::call_site(),
-        );
+    for property in properties.iter() {
+        let field_name = &property.name;
+        let field_type = &property.ty;
+        let uppercase_variant_name = get_variant_name(property);
+        let new_read_method = match &property.encoding {
+            Some(encoding) => quote! { new_read_encoded::<#encoding> },
+            None => quote! { new_read },
+        };
 
-        let new_output_right = quote! {
-            let #field_name = Property::<#field_type>::new_read(reader, #enum_name::#uppercase_variant_name as u8, packet_index);
+        let new_output_right = if property.is_nested {
+            quote! {
+                let #field_name = <#field_type as naia_shared::NestedProperty>::new_read(reader, packet_index);
+            }
+        } else {
+            quote! {
+                let #field_name = naia_shared::Property::<#field_type>::#new_read_method(reader, #enum_name::#uppercase_variant_name as u8, packet_index);
+            }
         };
         let new_output_result = quote! {
             #prop_reads
@@ -441,13 +679,5 @@ pub fn get_read_to_type_method(
         prop_reads = new_output_result;
     }
 
-    return quote! {
-        fn read_to_type(reader: &mut PacketReader, packet_index: u16) -> #protocol_name {
-            #prop_reads
-
-            return #protocol_name::#replica_name(#replica_name {
-                #prop_names
-            });
-        }
-    };
+    return prop_reads;
 }
@@ -0,0 +1,181 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, Index};
+
+pub fn serde_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let type_name = input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let (ser_method, de_method) = match &input.data {
+        Data::Struct(data) => (
+            get_struct_ser_method(&data.fields),
+            get_struct_de_method(&data.fields),
+        ),
+        Data::Enum(data) => {
+            let variants: Vec<(Ident, Fields)> = data
+                .variants
+                .iter()
+                .map(|variant| (variant.ident.clone(), variant.fields.clone()))
+                .collect();
+            (
+                get_enum_ser_method(&variants),
+                get_enum_de_method(&type_name, &variants),
+            )
+        }
+        Data::Union(_) => panic!("Serde can't be derived for a union"),
+    };
+
+    let gen = quote! {
+        impl #impl_generics naia_shared::Serde for #type_name #type_generics #where_clause {
+            #ser_method
+            #de_method
+        }
+    };
+
+    proc_macro::TokenStream::from(gen)
+}
+
+fn get_struct_ser_method(fields: &Fields) -> TokenStream {
+    let mut field_writes = quote! {};
+
+    for (index, field) in fields.iter().enumerate() {
+        let field_access = match &field.ident {
+            Some(field_name) => quote! { self.#field_name },
+            None => {
+                let field_index = Index::from(index);
+                quote! { self.#field_index }
+            }
+        };
+        field_writes = quote! {
+            #field_writes
+            naia_shared::Serde::ser(&#field_access, writer);
+        };
+    }
+
+    return quote! {
+        fn ser(&self, writer: &mut naia_shared::BitWriter) {
+            #field_writes
+        }
+    };
+}
+
+fn get_struct_de_method(fields: &Fields) -> TokenStream {
+    let constructor = get_fields_constructor(&quote! { Self }, fields);
+
+    return quote! {
+        fn de(reader: &mut naia_shared::BitReader) -> Self {
+            return #constructor;
+        }
+    };
+}
+
+fn get_enum_ser_method(variants: &Vec<(Ident, Fields)>) -> TokenStream {
+    let variant_bits = get_variant_bits(variants.len());
+    let mut variant_arms = quote! {};
+
+    for (variant_index, (variant_name, fields)) in variants.iter().enumerate() {
+        let variant_index = variant_index as u64;
+
+        let mut field_names = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            field_names.push(match &field.ident {
+                Some(field_name) => field_name.clone(),
+                None => format_ident!("field_{}", index),
+            });
+        }
+
+        let pattern = match fields {
+            Fields::Named(_) => quote! { Self::#variant_name { #(#field_names),* } },
+            Fields::Unnamed(_) => quote! { Self::#variant_name ( #(#field_names),* ) },
+            Fields::Unit => quote! { Self::#variant_name },
+        };
+
+        variant_arms = quote! {
+            #variant_arms
+            #pattern => {
+                writer.write_bits(#variant_index, #variant_bits);
+                #(naia_shared::Serde::ser(#field_names, writer);)*
+            }
+        };
+    }
+
+    return quote! {
+        fn ser(&self, writer: &mut naia_shared::BitWriter) {
+            match self {
+                #variant_arms
+            }
+        }
+    };
+}
+
+fn get_enum_de_method(type_name: &Ident, variants: &Vec<(Ident, Fields)>) -> TokenStream {
+    let variant_bits = get_variant_bits(variants.len());
+    let mut variant_arms = quote! {};
+
+    for (variant_index, (variant_name, fields)) in variants.iter().enumerate() {
+        let variant_index = variant_index as u64;
+        let constructor = get_fields_constructor(&quote! { Self::#variant_name }, fields);
+
+        variant_arms = quote! {
+            #variant_arms
+            #variant_index => {
+                return #constructor;
+            }
+        };
+    }
+
+    // an unknown variant is recorded as an error, & the first variant is
+    // returned in its place
+    let unknown_arm = match variants.first() {
+        Some((variant_name, fields)) => {
+            let constructor = get_fields_constructor(&quote! { Self::#variant_name }, fields);
+            quote! {
+                _ => {
+                    reader.set_error(naia_shared::DecodeError::InvalidValue);
+                    return #constructor;
+                }
+            }
+        }
+        None => {
+            let type_name_string = type_name.to_string();
+            quote! {
+                _ => panic!("error deserializing {}: enum has no variants", #type_name_string),
+            }
+        }
+    };
+
+    return quote! {
+        fn de(reader: &mut naia_shared::BitReader) -> Self {
+            match reader.read_bits(#variant_bits) {
+                #variant_arms
+                #unknown_arm
+            }
+        }
+    };
+}
+
+fn get_fields_constructor(path: &TokenStream, fields: &Fields) -> TokenStream {
+    let field_reads = fields
+        .iter()
+        .map(|_| quote! { naia_shared::Serde::de(reader) });
+
+    return match fields {
+        Fields::Named(_) => {
+            let field_names = fields.iter().map(|field| &field.ident);
+            quote! { #path { #(#field_names: #field_reads),* } }
+        }
+        Fields::Unnamed(_) => quote! { #path ( #(#field_reads),* ) },
+        Fields::Unit => quote! { #path },
+    };
+}
+
+// the minimum number of bits needed to represent the index of each variant
+fn get_variant_bits(variant_count: usize) -> u8 {
+    let mut variant_bits: u8 = 0;
+    while (1usize << variant_bits) < variant_count {
+        variant_bits += 1;
+    }
+    return variant_bits;
+}
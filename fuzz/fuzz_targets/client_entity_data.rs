@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_basic_demo_shared::protocol::Protocol;
+use naia_client::fuzzing;
+use naia_demo_world::World;
+
+fuzz_target!(|data: &[u8]| {
+    let manifest = Protocol::load();
+    let mut world = World::<Protocol>::new();
+    fuzzing::entity_data(&mut world.proxy_mut(), &manifest, data);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_basic_demo_shared::protocol::Protocol;
+use naia_client::{fuzzing, Channels, DefaultChannels};
+use naia_demo_world::World;
+
+fuzz_target!(|data: &[u8]| {
+    let manifest = Protocol::load();
+    let mut world = World::<Protocol>::new();
+    fuzzing::incoming_packet(
+        &mut world.proxy_mut(),
+        &manifest,
+        &DefaultChannels::configs(),
+        data,
+    );
+});
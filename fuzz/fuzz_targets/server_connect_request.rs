@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_basic_demo_shared::protocol::Protocol;
+use naia_server::fuzzing;
+
+fuzz_target!(|data: &[u8]| {
+    let manifest = Protocol::load();
+    fuzzing::connect_request(&manifest, true, data);
+    fuzzing::connect_request(&manifest, false, data);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_basic_demo_shared::protocol::Protocol;
+use naia_server::{fuzzing, Channels, DefaultChannels};
+
+fuzz_target!(|data: &[u8]| {
+    let manifest = Protocol::load();
+    fuzzing::incoming_packet(&manifest, &DefaultChannels::configs(), data);
+});
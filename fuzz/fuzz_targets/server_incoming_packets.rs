@@ -0,0 +1,25 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_basic_demo_shared::protocol::{Character, Protocol};
+use naia_demo_world::{World, WorldMutType};
+use naia_server::{fuzzing, Channels, DefaultChannels};
+
+fuzz_target!(|data: &[u8]| {
+    let manifest = Protocol::load();
+    let mut world = World::<Protocol>::new();
+    {
+        let mut world_mut = world.proxy_mut();
+        for x in 0..4 {
+            let entity = world_mut.spawn_entity();
+            world_mut.insert_component(&entity, Character::new(x, 0, "first", "last"));
+        }
+    }
+    fuzzing::incoming_packets(
+        &mut world.proxy_mut(),
+        &manifest,
+        &DefaultChannels::configs(),
+        data,
+    );
+});
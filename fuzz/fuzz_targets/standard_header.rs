@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use naia_shared::StandardHeader;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = StandardHeader::read(data);
+});
@@ -0,0 +1,9 @@
+use naia_shared::{LocalEntity, ProtocolType};
+
+/// An action performed by a Client on an Entity it has spawned
+pub enum ClientEntityAction<P: ProtocolType> {
+    SpawnEntity(LocalEntity),
+    DespawnEntity(LocalEntity),
+    InsertComponent(LocalEntity, P),
+    RemoveComponent(LocalEntity, P::Kind),
+}
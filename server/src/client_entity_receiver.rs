@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+use naia_shared::{
+    BitReader, DecodeError, EntityActionType, LocalEntity, Manifest, NaiaKey, ProtocolKindType,
+    ProtocolType, CLIENT_ENTITY_ACTION_WINDOW,
+};
+
+use super::client_entity_action::ClientEntityAction;
+
+/// Handles incoming actions on the Entities a Client has spawned, buffering
+/// them so that they are applied in the order the Client performed them, and
+/// maps the Client's keys for those Entities to Entities in the Server's world
+pub struct ClientEntityReceiver<P: ProtocolType, E: Copy + Eq + Hash> {
+    next_action_index: u16,
+    buffered_actions: HashMap<u16, ClientEntityAction<P>>,
+    local_to_world_entity: HashMap<LocalEntity, E>,
+    max_entities: usize,
+    rejected_spawns: VecDeque<LocalEntity>,
+}
+
+impl<P: ProtocolType, E: Copy + Eq + Hash> ClientEntityReceiver<P, E> {
+    pub fn new(max_entities: usize) -> Self {
+        ClientEntityReceiver {
+            next_action_index: 0,
+            buffered_actions: HashMap::new(),
+            local_to_world_entity: HashMap::new(),
+            max_entities,
+            rejected_spawns: VecDeque::new(),
+        }
+    }
+
+    /// Given incoming packet data, read transmitted actions and buffer them
+    /// until all the actions before them have been received
+    pub fn process_incoming_actions(
+        &mut self,
+        manifest: &Manifest<P>,
+        packet_index: u16,
+        reader: &mut BitReader,
+    ) -> Result<(), DecodeError> {
+        // each action is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let action_index = reader.read_u16();
+            let action = match EntityActionType::read(reader) {
+                EntityActionType::SpawnEntity => {
+                    ClientEntityAction::SpawnEntity(LocalEntity::from_u16(reader.read_u16()))
+                }
+                EntityActionType::DespawnEntity => {
+                    ClientEntityAction::DespawnEntity(LocalEntity::from_u16(reader.read_u16()))
+                }
+                EntityActionType::InsertComponent => {
+                    let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    let component_kind = P::Kind::read(reader);
+                    let component =
+                        manifest.create_replica(component_kind, reader, packet_index)?;
+                    ClientEntityAction::InsertComponent(local_entity, component)
+                }
+                EntityActionType::RemoveComponent => {
+                    let local_entity = LocalEntity::from_u16(reader.read_u16());
+                    let component_kind = P::Kind::read(reader);
+                    ClientEntityAction::RemoveComponent(local_entity, component_kind)
+                }
+                _ => {
+                    // unable to read the remainder of the section
+                    return Err(DecodeError::UnknownEntityAction);
+                }
+            };
+            reader.check()?;
+
+            // actions which have already been applied may be received again, and
+            // actions beyond the window would be buffered indefinitely
+            if action_index.wrapping_sub(self.next_action_index) >= CLIENT_ENTITY_ACTION_WINDOW {
+                continue;
+            }
+            self.buffered_actions.insert(action_index, action);
+        }
+        return Ok(());
+    }
+
+    /// Pops the next action, if all the actions before it have been popped.
+    /// Spawns of an Entity which is already mapped are dropped, and spawns
+    /// beyond the maximum number of Entities are rejected
+    pub fn pop_action(&mut self) -> Option<ClientEntityAction<P>> {
+        loop {
+            let action = self.buffered_actions.remove(&self.next_action_index)?;
+            self.next_action_index = self.next_action_index.wrapping_add(1);
+            if let ClientEntityAction::SpawnEntity(local_entity) = &action {
+                if self.local_to_world_entity.contains_key(local_entity) {
+                    warn!("client spawned an entity which already exists");
+                    continue;
+                }
+                if self.local_to_world_entity.len() >= self.max_entities {
+                    self.rejected_spawns.push_back(*local_entity);
+                    continue;
+                }
+            }
+            return Some(action);
+        }
+    }
+
+    /// Pops the next spawn rejected for exceeding the maximum number of
+    /// Entities, which the Client must be told about
+    pub fn pop_rejected_spawn(&mut self) -> Option<LocalEntity> {
+        return self.rejected_spawns.pop_front();
+    }
+
+    // Entities
+
+    pub fn insert_entity(&mut self, local_entity: &LocalEntity, entity: &E) {
+        self.local_to_world_entity.insert(*local_entity, *entity);
+    }
+
+    pub fn remove_entity(&mut self, local_entity: &LocalEntity) -> Option<E> {
+        return self.local_to_world_entity.remove(local_entity);
+    }
+
+    pub fn get_entity(&self, local_entity: &LocalEntity) -> Option<E> {
+        return self.local_to_world_entity.get(local_entity).copied();
+    }
+}
+
+#[cfg(test)]
+mod client_entity_receiver_tests {
+    use naia_shared::{
+        BitReader, BitWriter, EntityActionType, LocalEntity, NaiaKey, ProtocolType,
+        CLIENT_ENTITY_ACTION_WINDOW,
+    };
+
+    use super::ClientEntityReceiver;
+    use crate::{client_entity_action::ClientEntityAction, test_protocol::Protocol};
+
+    // receives the Client's spawns of the given Entities, starting at the
+    // given action index
+    fn receive_spawns(
+        receiver: &mut ClientEntityReceiver<Protocol, u32>,
+        first_action_index: u16,
+        local_entities: &[u16],
+    ) {
+        let mut writer = BitWriter::new();
+        for (offset, local_entity) in local_entities.iter().enumerate() {
+            writer.write_bit(true);
+            writer.write_u16(first_action_index + offset as u16);
+            EntityActionType::SpawnEntity.write(&mut writer);
+            writer.write_u16(*local_entity);
+        }
+        writer.write_bit(false);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        receiver
+            .process_incoming_actions(&Protocol::load(), 0, &mut reader)
+            .unwrap();
+    }
+
+    // pops the next spawn, and maps it to the given Entity
+    fn pop_spawn(receiver: &mut ClientEntityReceiver<Protocol, u32>, entity: u32) -> Option<u16> {
+        match receiver.pop_action()? {
+            ClientEntityAction::SpawnEntity(local_entity) => {
+                receiver.insert_entity(&local_entity, &entity);
+                return Some(local_entity.to_u16());
+            }
+            _ => panic!("expected a spawn"),
+        }
+    }
+
+    #[test]
+    fn duplicate_spawn() {
+        let mut receiver = ClientEntityReceiver::new(8);
+        receive_spawns(&mut receiver, 0, &[3]);
+        assert_eq!(pop_spawn(&mut receiver, 30), Some(3));
+
+        // spawning an Entity which is still mapped does not remap it
+        receive_spawns(&mut receiver, 1, &[3, 4]);
+        assert_eq!(pop_spawn(&mut receiver, 31), Some(4));
+        assert_eq!(pop_spawn(&mut receiver, 32), None);
+        assert_eq!(receiver.get_entity(&LocalEntity::from_u16(3)), Some(30));
+        assert!(receiver.pop_rejected_spawn().is_none());
+    }
+
+    #[test]
+    fn spawn_quota() {
+        let mut receiver = ClientEntityReceiver::new(2);
+        receive_spawns(&mut receiver, 0, &[0, 1, 2]);
+        assert_eq!(pop_spawn(&mut receiver, 10), Some(0));
+        assert_eq!(pop_spawn(&mut receiver, 11), Some(1));
+        assert_eq!(pop_spawn(&mut receiver, 12), None);
+        assert_eq!(
+            receiver.pop_rejected_spawn(),
+            Some(LocalEntity::from_u16(2))
+        );
+        assert!(receiver.get_entity(&LocalEntity::from_u16(2)).is_none());
+
+        // despawning an Entity makes room for another
+        receiver.remove_entity(&LocalEntity::from_u16(0));
+        receive_spawns(&mut receiver, 3, &[2]);
+        assert_eq!(pop_spawn(&mut receiver, 12), Some(2));
+        assert!(receiver.pop_rejected_spawn().is_none());
+    }
+
+    #[test]
+    fn action_window() {
+        let mut receiver = ClientEntityReceiver::new(8);
+        receive_spawns(&mut receiver, CLIENT_ENTITY_ACTION_WINDOW, &[1]);
+        receive_spawns(&mut receiver, u16::MAX, &[2]);
+        assert!(receiver.buffered_actions.is_empty());
+
+        receive_spawns(&mut receiver, CLIENT_ENTITY_ACTION_WINDOW - 1, &[1]);
+        receive_spawns(&mut receiver, 0, &[0]);
+        assert_eq!(receiver.buffered_actions.len(), 2);
+        assert_eq!(pop_spawn(&mut receiver, 10), Some(0));
+    }
+}
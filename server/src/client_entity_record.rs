@@ -0,0 +1,19 @@
+use naia_shared::LocalEntity;
+
+use super::user::user_key::UserKey;
+
+pub struct ClientEntityRecord {
+    pub user_key: UserKey,
+    pub local_entity: LocalEntity,
+    pub accepted: bool,
+}
+
+impl ClientEntityRecord {
+    pub fn new(user_key: &UserKey, local_entity: &LocalEntity) -> Self {
+        Self {
+            user_key: *user_key,
+            local_entity: *local_entity,
+            accepted: false,
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
 use naia_shared::{
-    sequence_greater_than, LocalEntity, Manifest, NaiaKey, PacketReader, ProtocolKindType,
-    ProtocolType, SequenceBuffer,
+    sequence_greater_than, BitReader, DecodeError, LocalEntity, Manifest, NaiaKey,
+    ProtocolKindType, ProtocolType, SequenceBuffer,
 };
 
 const COMMAND_BUFFER_MAX_SIZE: u16 = 64;
+const PAST_COMMANDS_NUMBER_BITS: u8 = 2;
 
 /// Handles incoming commands, buffering them to be received on the correct tick
 #[derive(Debug)]
@@ -43,14 +44,14 @@ impl<P: ProtocolType> CommandReceiver<P> {
         &mut self,
         server_tick_opt: Option<u16>,
         client_tick: u16,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         manifest: &Manifest<P>,
-    ) {
-        let command_count = reader.read_u8();
-        for _x in 0..command_count {
+    ) -> Result<(), DecodeError> {
+        // each Command is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
             let owned_entity = LocalEntity::from_u16(reader.read_u16());
-            let replica_kind: P::Kind = P::Kind::from_u16(reader.read_u16());
-            let past_commands_number: u8 = reader.read_u8();
+            let replica_kind: P::Kind = P::Kind::read(reader);
+            let past_commands_number = reader.read_bits(PAST_COMMANDS_NUMBER_BITS);
 
             // process most recent sent command
             self.process_command(
@@ -60,7 +61,7 @@ impl<P: ProtocolType> CommandReceiver<P> {
                 manifest,
                 owned_entity,
                 replica_kind,
-            );
+            )?;
 
             // process past commands (for redundancy)
             for _y in 0..past_commands_number {
@@ -73,21 +74,22 @@ impl<P: ProtocolType> CommandReceiver<P> {
                     manifest,
                     owned_entity,
                     replica_kind,
-                );
+                )?;
             }
         }
+        return Ok(());
     }
 
     fn process_command(
         &mut self,
         server_tick_opt: Option<u16>,
         client_tick: u16,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         manifest: &Manifest<P>,
         owned_entity: LocalEntity,
         replica_kind: P::Kind,
-    ) -> bool {
-        let new_command = manifest.create_replica(replica_kind, reader, 0);
+    ) -> Result<bool, DecodeError> {
+        let new_command = manifest.create_replica(replica_kind, reader, 0)?;
 
         if let Some(server_tick) = server_tick_opt {
             if sequence_greater_than(client_tick, server_tick) {
@@ -101,12 +103,12 @@ impl<P: ProtocolType> CommandReceiver<P> {
                     }
                 }
 
-                return true;
+                return Ok(true);
             } else {
-                return false;
+                return Ok(false);
             }
         }
 
-        return false;
+        return Ok(false);
     }
 }
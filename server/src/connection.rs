@@ -6,11 +6,14 @@ use std::{
 };
 
 use naia_shared::{
-    BaseConnection, ConnectionConfig, ManagerType, Manifest, PacketReader, PacketType,
-    ProtocolType, ReplicateSafe, SequenceNumber, StandardHeader, WorldRefType,
+    BaseConnection, BitReader, BlobEvent, ChannelConfig, ChannelIndex, Clock, CongestionConfig,
+    CongestionController, ConnectionConfig, DecodeError, LocalEntity, ManagerType, Manifest,
+    PacketType, ProtocolType, ReplicateSafe, SendMessageError, SequenceNumber, StandardHeader,
+    WorldRefType,
 };
 
 use super::{
+    client_entity_action::ClientEntityAction, client_entity_receiver::ClientEntityReceiver,
     command_receiver::CommandReceiver, entity_manager::EntityManager,
     global_diff_handler::GlobalDiffHandler, keys::ComponentKey, packet_writer::PacketWriter,
     ping_manager::PingManager, user::user_key::UserKey, world_record::WorldRecord,
@@ -22,7 +25,9 @@ pub struct Connection<P: ProtocolType, E: Copy + Eq + Hash> {
     base_connection: BaseConnection<P>,
     entity_manager: EntityManager<P, E>,
     ping_manager: PingManager,
+    congestion_controller: CongestionController,
     command_receiver: CommandReceiver<P>,
+    client_entity_receiver: ClientEntityReceiver<P, E>,
 }
 
 impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
@@ -31,14 +36,36 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         user_address: SocketAddr,
         user_key: &UserKey,
         diff_handler: &Arc<RwLock<GlobalDiffHandler>>,
+        delta_compression: bool,
+        channel_configs: &[ChannelConfig],
+        congestion_config: &CongestionConfig,
+        max_client_entities: usize,
+        clock: &Arc<dyn Clock>,
     ) -> Self {
         Connection {
             user_key: *user_key,
             owned_entities: HashSet::new(),
-            base_connection: BaseConnection::new(user_address, connection_config),
-            entity_manager: EntityManager::new(user_address, diff_handler),
-            ping_manager: PingManager::new(),
+            base_connection: BaseConnection::new(
+                user_address,
+                connection_config,
+                channel_configs,
+                None,
+                clock,
+            ),
+            entity_manager: EntityManager::new(
+                user_address,
+                diff_handler,
+                delta_compression,
+                clock,
+            ),
+            ping_manager: PingManager::new(
+                connection_config.ping_interval,
+                connection_config.ping_sample_size,
+                clock,
+            ),
+            congestion_controller: CongestionController::new(congestion_config, clock),
             command_receiver: CommandReceiver::new(),
+            client_entity_receiver: ClientEntityReceiver::new(max_client_entities),
         }
     }
 
@@ -50,6 +77,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
     ) -> Option<Box<[u8]>> {
         if self.base_connection.has_outgoing_messages()
             || self.entity_manager.has_outgoing_actions()
+            || self.base_connection.has_outgoing_blob_records()
         {
             let mut writer = PacketWriter::new();
 
@@ -70,6 +98,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                 if !self.entity_manager.write_entity_action(
                     world,
                     &mut writer,
+                    next_packet_index,
                     &popped_entity_action,
                 ) {
                     self.entity_manager
@@ -77,6 +106,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                     break;
                 }
             }
+            while let Some(popped_record) = self
+                .base_connection
+                .pop_outgoing_blob_record(next_packet_index)
+            {
+                if !writer.write_blob_record(&popped_record) {
+                    self.base_connection
+                        .unpop_outgoing_blob_record(next_packet_index, popped_record);
+                    break;
+                }
+            }
 
             if writer.has_bytes() {
                 // Get bytes from writer
@@ -98,14 +137,17 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
 
     pub fn process_incoming_data(
         &mut self,
+        world_record: &WorldRecord<E, P::Kind>,
         server_tick: Option<u16>,
         client_tick: u16,
+        packet_index: u16,
         manifest: &Manifest<P>,
         data: &[u8],
-    ) {
-        let mut reader = PacketReader::new(data);
-        while reader.has_more() {
-            let manager_type: ManagerType = reader.read_u8().into();
+    ) -> Result<(), DecodeError> {
+        let mut reader = BitReader::new(data);
+        // each manager section is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let manager_type = ManagerType::read(&mut reader);
             match manager_type {
                 ManagerType::Command => {
                     self.command_receiver.process_incoming_commands(
@@ -113,23 +155,90 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
                         client_tick,
                         &mut reader,
                         manifest,
-                    );
+                    )?;
                 }
                 ManagerType::Message => {
                     // packet index shouldn't matter here because the server's impl of Property
                     // doesn't use it
                     self.base_connection
-                        .process_message_data(&mut reader, manifest, 0);
+                        .process_message_data(&mut reader, manifest, 0)?;
+                }
+                ManagerType::Blob => {
+                    self.base_connection.process_blob_data(&mut reader)?;
+                }
+                ManagerType::Entity => {
+                    self.entity_manager.process_authority_data(
+                        world_record,
+                        manifest,
+                        packet_index,
+                        &mut reader,
+                    )?;
+                }
+                ManagerType::ClientEntity => {
+                    self.client_entity_receiver.process_incoming_actions(
+                        manifest,
+                        packet_index,
+                        &mut reader,
+                    )?;
+                }
+                ManagerType::Unknown => {
+                    // unable to read the remainder of the packet
+                    return Err(DecodeError::UnknownManagerType);
                 }
-                _ => {}
             }
         }
+        return reader.check();
     }
 
     pub fn collect_component_updates(&mut self, world_record: &WorldRecord<E, P::Kind>) {
         self.entity_manager.collect_component_updates(world_record);
     }
 
+    pub fn give_authority(&mut self, component_key: &ComponentKey) {
+        self.entity_manager.give_authority(component_key);
+    }
+
+    pub fn revoke_authority(&mut self, component_key: &ComponentKey) {
+        self.entity_manager.revoke_authority(component_key);
+    }
+
+    pub fn pop_authority_update(&mut self) -> Option<(ComponentKey, P)> {
+        return self.entity_manager.pop_authority_update();
+    }
+
+    // Client Entities
+
+    pub fn pop_client_entity_action(&mut self) -> Option<ClientEntityAction<P>> {
+        let action = self.client_entity_receiver.pop_action();
+        while let Some(local_entity) = self.client_entity_receiver.pop_rejected_spawn() {
+            self.entity_manager.reject_client_entity(&local_entity);
+        }
+        return action;
+    }
+
+    pub fn insert_client_entity(&mut self, local_entity: &LocalEntity, entity: &E) {
+        self.client_entity_receiver
+            .insert_entity(local_entity, entity);
+    }
+
+    pub fn remove_client_entity(&mut self, local_entity: &LocalEntity) -> Option<E> {
+        return self.client_entity_receiver.remove_entity(local_entity);
+    }
+
+    pub fn client_entity(&self, local_entity: &LocalEntity) -> Option<E> {
+        return self.client_entity_receiver.get_entity(local_entity);
+    }
+
+    pub fn reject_client_entity(&mut self, local_entity: &LocalEntity) {
+        self.client_entity_receiver.remove_entity(local_entity);
+        self.entity_manager.reject_client_entity(local_entity);
+    }
+
+    pub fn take_over_client_entity(&mut self, local_entity: &LocalEntity) {
+        self.client_entity_receiver.remove_entity(local_entity);
+        self.entity_manager.take_over_client_entity(local_entity);
+    }
+
     pub fn get_incoming_command(&mut self, server_tick: u16) -> Option<(E, P)> {
         if let Some((local_entity, command)) =
             self.command_receiver.pop_incoming_command(server_tick)
@@ -148,16 +257,59 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         return None;
     }
 
-    pub fn process_ping(&self, ping_payload: &[u8]) -> Box<[u8]> {
+    pub fn process_ping(&self, ping_payload: &[u8]) -> Result<Box<[u8]>, DecodeError> {
         return self.ping_manager.process_ping(ping_payload);
     }
 
+    pub fn should_send_ping(&self) -> bool {
+        return self.ping_manager.should_send_ping();
+    }
+
+    pub fn get_ping_payload(&mut self) -> Box<[u8]> {
+        return self.ping_manager.get_ping_payload();
+    }
+
+    pub fn process_pong(&mut self, pong_payload: &[u8]) -> Result<(), DecodeError> {
+        return self.ping_manager.process_pong(pong_payload);
+    }
+
+    pub fn rtt(&self) -> f32 {
+        return self.ping_manager.get_rtt();
+    }
+
+    // Congestion control
+
+    /// Feeds the latest RTT & packet loss into the CongestionController, and
+    /// adds the budget accumulated since the last update
+    pub fn update_congestion_control(&mut self) {
+        let (delivered, dropped) = self.base_connection.take_packet_loss();
+        self.congestion_controller
+            .record_packet_loss(delivered, dropped);
+        self.congestion_controller
+            .record_rtt(self.ping_manager.get_rtt());
+        self.congestion_controller.update();
+    }
+
+    /// Returns whether the bandwidth budget allows for another packet to be
+    /// sent
+    pub fn can_send_packet(&self) -> bool {
+        return self.congestion_controller.can_send();
+    }
+
+    pub fn bandwidth(&self) -> u32 {
+        return self.congestion_controller.bandwidth();
+    }
+
     // Entity management
 
     pub fn has_entity(&self, entity: &E) -> bool {
         return self.entity_manager.has_entity(entity);
     }
 
+    pub fn entities(&self) -> Vec<E> {
+        return self.entity_manager.entities();
+    }
+
     pub fn spawn_entity(&mut self, world_record: &WorldRecord<E, P::Kind>, entity: &E) {
         self.entity_manager.spawn_entity(world_record, entity);
     }
@@ -166,6 +318,10 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         self.entity_manager.despawn_entity(world_record, entity);
     }
 
+    pub fn set_entity_priority(&mut self, entity: &E, priority: f32) {
+        self.entity_manager.set_entity_priority(entity, priority);
+    }
+
     pub fn has_prediction_entity(&self, entity: &E) -> bool {
         return self.entity_manager.has_entity_prediction(entity);
     }
@@ -226,28 +382,40 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Connection<P, E> {
         packet_type: PacketType,
         payload: &[u8],
     ) -> Box<[u8]> {
-        return self.base_connection.process_outgoing_header(
+        let packet = self.base_connection.process_outgoing_header(
             host_tick,
             last_received_tick,
             packet_type,
             payload,
         );
+        self.congestion_controller.mark_sent(packet.len());
+        return packet;
     }
 
     pub fn get_next_packet_index(&self) -> SequenceNumber {
         return self.base_connection.get_next_packet_index();
     }
 
-    pub fn send_message<R: ReplicateSafe<P>>(&mut self, message: &R, guaranteed_delivery: bool) {
-        return self
-            .base_connection
-            .send_message(message, guaranteed_delivery);
+    pub fn send_message<R: ReplicateSafe<P>, C: ChannelIndex>(
+        &mut self,
+        message: &R,
+        channel: C,
+    ) -> Result<(), SendMessageError> {
+        return self.base_connection.send_message(message, channel);
     }
 
     pub fn get_incoming_message(&mut self) -> Option<P> {
         return self.base_connection.get_incoming_message();
     }
 
+    pub fn send_blob(&mut self, name: &str, bytes: Arc<[u8]>) {
+        return self.base_connection.send_blob(name, bytes);
+    }
+
+    pub fn get_blob_event(&mut self) -> Option<BlobEvent> {
+        return self.base_connection.get_blob_event();
+    }
+
     pub fn address(&self) -> SocketAddr {
         return self.base_connection.get_address();
     }
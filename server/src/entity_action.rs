@@ -1,4 +1,4 @@
-use naia_shared::{DiffMask, EntityActionType, ProtocolType};
+use naia_shared::{DiffMask, EntityActionType, LocalEntity, ProtocolType};
 
 use super::keys::ComponentKey;
 
@@ -11,6 +11,10 @@ pub enum EntityAction<P: ProtocolType, E: Copy> {
     InsertComponent(E, ComponentKey, P::Kind),
     UpdateComponent(E, ComponentKey, DiffMask, P::Kind),
     RemoveComponent(ComponentKey),
+    GrantAuthority(ComponentKey),
+    RevokeAuthority(ComponentKey),
+    RejectClientEntity(LocalEntity),
+    TakeOverClientEntity(LocalEntity),
 }
 
 impl<P: ProtocolType, E: Copy> EntityAction<P, E> {
@@ -23,6 +27,10 @@ impl<P: ProtocolType, E: Copy> EntityAction<P, E> {
             EntityAction::InsertComponent(_, _, _) => EntityActionType::InsertComponent,
             EntityAction::UpdateComponent(_, _, _, _) => EntityActionType::UpdateComponent,
             EntityAction::RemoveComponent(_) => EntityActionType::RemoveComponent,
+            EntityAction::GrantAuthority(_) => EntityActionType::GrantAuthority,
+            EntityAction::RevokeAuthority(_) => EntityActionType::RevokeAuthority,
+            EntityAction::RejectClientEntity(_) => EntityActionType::RejectEntity,
+            EntityAction::TakeOverClientEntity(_) => EntityActionType::TakeOverEntity,
         }
     }
 }
@@ -39,6 +47,10 @@ impl<P: ProtocolType, E: Copy> Clone for EntityAction<P, E> {
                 EntityAction::UpdateComponent(*a, *b, c.clone(), *d)
             }
             EntityAction::RemoveComponent(a) => EntityAction::RemoveComponent(*a),
+            EntityAction::GrantAuthority(a) => EntityAction::GrantAuthority(*a),
+            EntityAction::RevokeAuthority(a) => EntityAction::RevokeAuthority(*a),
+            EntityAction::RejectClientEntity(a) => EntityAction::RejectClientEntity(*a),
+            EntityAction::TakeOverClientEntity(a) => EntityAction::TakeOverClientEntity(*a),
         }
     }
 }
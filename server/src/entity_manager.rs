@@ -1,17 +1,19 @@
 use std::{
     borrow::Borrow,
     clone::Clone,
+    cmp::Ordering,
     collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
     net::SocketAddr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use byteorder::{BigEndian, WriteBytesExt};
-
 use naia_shared::{
-    DiffMask, KeyGenerator, LocalComponentKey, LocalEntity, NaiaKey, PacketNotifiable,
-    ProtocolKindType, ProtocolType, WorldRefType, MTU_SIZE,
+    sequence_greater_than, BitReader, BitWriter, Clock, DecodeError, DiffMask, EntityActionType,
+    KeyGenerator, LocalComponentKey, LocalEntity, Manifest, NaiaKey, PacketNotifiable,
+    ProtocolKindType, ProtocolType, WorldRefType, DELTA_BASELINE_WINDOW, MTU_SIZE_BITS,
+    SECTION_OVERHEAD_BITS,
 };
 
 use super::{
@@ -25,6 +27,7 @@ use super::{
 /// sync on the Client
 pub struct EntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
     address: SocketAddr,
+    clock: Arc<dyn Clock>,
     // Entities
     entity_generator: KeyGenerator<LocalEntity>,
     entity_records: HashMap<E, LocalEntityRecord>,
@@ -38,6 +41,11 @@ pub struct EntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
     delayed_component_deletions: HashSet<ComponentKey>,
     // Actions / updates / ect
     queued_actions: VecDeque<EntityAction<P, E>>,
+    update_priorities: HashMap<ComponentKey, f32>,
+    update_queue: Vec<(ComponentKey, f32)>,
+    entity_priorities: HashMap<E, f32>,
+    last_update_times: HashMap<ComponentKey, Duration>,
+    last_popped_priority: Option<(ComponentKey, f32)>,
     sent_actions: HashMap<u16, Vec<EntityAction<P, E>>>,
     sent_updates: HashMap<u16, HashMap<ComponentKey, DiffMask>>,
     last_update_packet_index: u16,
@@ -45,13 +53,27 @@ pub struct EntityManager<P: ProtocolType, E: Copy + Eq + Hash> {
     last_popped_diff_mask: Option<DiffMask>,
     last_popped_diff_mask_list: Option<Vec<(ComponentKey, DiffMask)>>,
     delivered_packets: VecDeque<u16>,
+    // Delta compression
+    delta_compression: bool,
+    sent_snapshots: HashMap<u16, Vec<(ComponentKey, P)>>,
+    component_baselines: HashMap<ComponentKey, (u16, P)>,
+    // Authority
+    authoritative_components: HashSet<ComponentKey>,
+    authority_update_indices: HashMap<ComponentKey, u16>,
+    incoming_authority_updates: VecDeque<(ComponentKey, P)>,
 }
 
 impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
     /// Create a new EntityManager, given the client's address
-    pub fn new(address: SocketAddr, diff_handler: &Arc<RwLock<GlobalDiffHandler>>) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        diff_handler: &Arc<RwLock<GlobalDiffHandler>>,
+        delta_compression: bool,
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
         EntityManager {
             address,
+            clock: clock.clone(),
             // Entities
             entity_generator: KeyGenerator::new(),
             entity_records: HashMap::new(),
@@ -65,6 +87,11 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
             delayed_component_deletions: HashSet::new(),
             // Actions / updates / ect
             queued_actions: VecDeque::new(),
+            update_priorities: HashMap::new(),
+            update_queue: Vec::new(),
+            entity_priorities: HashMap::new(),
+            last_update_times: HashMap::new(),
+            last_popped_priority: None,
             sent_actions: HashMap::new(),
             sent_updates: HashMap::<u16, HashMap<ComponentKey, DiffMask>>::new(),
             last_update_packet_index: 0,
@@ -72,11 +99,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
             last_popped_diff_mask: None,
             last_popped_diff_mask_list: None,
             delivered_packets: VecDeque::new(),
+            // Delta compression
+            delta_compression,
+            sent_snapshots: HashMap::new(),
+            component_baselines: HashMap::new(),
+            // Authority
+            authoritative_components: HashSet::new(),
+            authority_update_indices: HashMap::new(),
+            incoming_authority_updates: VecDeque::new(),
         }
     }
 
     pub fn has_outgoing_actions(&self) -> bool {
-        return self.queued_actions.len() != 0;
+        return self.queued_actions.len() != 0 || self.update_priorities.len() != 0;
     }
 
     pub fn pop_outgoing_action<W: WorldRefType<P, E>>(
@@ -84,7 +119,12 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         world_record: &WorldRecord<E, P::Kind>,
         packet_index: u16,
     ) -> Option<EntityAction<P, E>> {
-        let queued_action_opt = self.queued_actions.pop_front();
+        // Component updates are only sent once all other actions have been, highest
+        // accumulated priority first
+        let queued_action_opt = self
+            .queued_actions
+            .pop_front()
+            .or_else(|| self.pop_component_update(world_record));
         if queued_action_opt.is_none() {
             return None;
         }
@@ -186,19 +226,20 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                 self.queued_actions.push_front(action);
                 return;
             }
-            EntityAction::UpdateComponent(
-                global_entity,
-                global_component_key,
-                _,
-                component_kind,
-            ) => {
-                let cloned_action = self.unpop_update_component_diff_mask(
-                    packet_index,
-                    global_entity,
-                    &global_component_key,
-                    component_kind,
-                );
-                self.queued_actions.push_front(cloned_action);
+            EntityAction::RejectClientEntity(_) | EntityAction::TakeOverClientEntity(_) => {
+                self.queued_actions.push_front(action);
+                return;
+            }
+            EntityAction::UpdateComponent(_, global_component_key, _, _) => {
+                self.undo_component_update(&packet_index, &global_component_key);
+                // the update keeps its accumulated priority
+                if let Some((popped_component_key, priority)) = self.last_popped_priority.take() {
+                    if popped_component_key == global_component_key {
+                        self.update_priorities
+                            .insert(global_component_key, priority);
+                        self.update_queue.push((global_component_key, priority));
+                    }
+                }
                 return;
             }
             _ => {}
@@ -233,6 +274,8 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
     }
 
     pub fn despawn_entity(&mut self, world_record: &WorldRecord<E, P::Kind>, global_entity: &E) {
+        self.entity_priorities.remove(global_entity);
+
         if self.has_entity_prediction(global_entity) {
             self.remove_prediction_entity(global_entity);
         }
@@ -268,6 +311,11 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         return self.entity_records.contains_key(entity);
     }
 
+    /// Gets every Entity in scope for this connection's User
+    pub fn entities(&self) -> Vec<E> {
+        return self.entity_records.keys().copied().collect();
+    }
+
     // Prediction Entities
 
     pub fn add_prediction_entity(&mut self, entity: &E) {
@@ -373,57 +421,71 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         return self.local_to_global_entity_map.get(&local_entity);
     }
 
+    /// Sets the priority of an Entity's updates for this connection's User,
+    /// which scales the base priority of the Entity's Components
+    pub fn set_entity_priority(&mut self, entity: &E, priority: f32) {
+        if self.entity_records.contains_key(entity) {
+            self.entity_priorities.insert(*entity, priority);
+        }
+    }
+
+    /// Raises the priority of every pending Component update by the
+    /// Component's base priority, scaled by the priority of its Entity for
+    /// this connection's User. Called once per tick, so that updates which
+    /// have waited longer are sent sooner. Components with an update interval
+    /// are left out until the interval has elapsed since their last update,
+    /// with any changes made in the meantime merged into that next update.
+    /// Components this connection's User has authority over are left out
+    /// entirely, as the User is the source of their changes
     pub fn collect_component_updates(&mut self, world_record: &WorldRecord<E, P::Kind>) {
         for (component_key, record) in self.component_records.iter() {
             if record.status == LocalityStatus::Created
-                && self.diff_handler.has_diff_mask(component_key)
+                && !self.authoritative_components.contains(component_key)
+                && self.has_pending_update(component_key)
+                && self.update_interval_elapsed(world_record, component_key)
             {
-                let (entity, component_kind) = world_record
-                    .get_component_record(component_key)
-                    .expect("component does not exist!");
-
-                let new_diff_mask = self
-                    .diff_handler
-                    .get_diff_mask(component_key)
-                    .expect("DiffHandler does not have registered Component!")
-                    .clone();
-                self.queued_actions.push_back(EntityAction::UpdateComponent(
-                    entity,
-                    *component_key,
-                    new_diff_mask,
-                    component_kind,
-                ));
+                let mut increase = world_record.get_priority(component_key);
+                if let Some((entity, _)) = world_record.get_component_record(component_key) {
+                    if let Some(entity_priority) = self.entity_priorities.get(&entity) {
+                        increase *= *entity_priority;
+                    }
+                }
+                let priority = self.update_priorities.entry(*component_key).or_insert(0.0);
+                *priority += increase;
             }
         }
+
+        // sorted once per tick, so that updates are popped from the end of the
+        // queue in order of accumulated priority
+        self.update_queue = self
+            .update_priorities
+            .iter()
+            .map(|(component_key, priority)| (*component_key, *priority))
+            .collect();
+        self.update_queue
+            .sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
     }
 
     pub fn write_entity_action<W: WorldRefType<P, E>>(
-        &self,
+        &mut self,
         world: &W,
         packet_writer: &mut PacketWriter,
+        packet_index: u16,
         action: &EntityAction<P, E>,
     ) -> bool {
-        let mut action_total_bytes = Vec::<u8>::new();
+        let mut action_total_bits = BitWriter::new();
+
+        // write that there is another action
+        action_total_bits.write_bit(true);
 
         //Write EntityAction type
-        action_total_bytes
-            .write_u8(action.as_type().to_u8())
-            .unwrap();
+        action.as_type().write(&mut action_total_bits);
 
         match action {
             EntityAction::SpawnEntity(global_entity, component_list) => {
                 let local_entity = self.entity_records.get(global_entity).unwrap().local_key;
 
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_entity.to_u16())
-                    .unwrap(); //write local entity
-
-                // get list of components
-                let components_num = component_list.len();
-                if components_num > 255 {
-                    panic!("no entity should have so many components... fix this");
-                }
-                action_total_bytes.write_u8(components_num as u8).unwrap(); //write number of components
+                action_total_bits.write_u16(local_entity.to_u16()); //write local entity
 
                 for (global_component_key, component_kind) in component_list {
                     let local_component_key = self
@@ -432,41 +494,31 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                         .unwrap()
                         .local_key;
 
+                    //Write component "header"
+                    action_total_bits.write_bit(true); // write that there is another component
+                    component_kind.write(&mut action_total_bits); // write component kind
+                    action_total_bits.write_u16(local_component_key.to_u16()); //write local component key
+
                     //write component payload
                     let component_ref = world
                         .get_component_of_kind(global_entity, component_kind)
                         .expect("Component does not exist in World");
-                    let mut component_payload_bytes = Vec::<u8>::new();
-                    component_ref.write(&mut component_payload_bytes);
-
-                    //Write component "header"
-                    action_total_bytes
-                        .write_u16::<BigEndian>(component_kind.to_u16())
-                        .unwrap(); // write naia id
-                    action_total_bytes
-                        .write_u16::<BigEndian>(local_component_key.to_u16())
-                        .unwrap(); //write local component key
-                    action_total_bytes.append(&mut component_payload_bytes);
-                    // write payload
+                    component_ref.write(&mut action_total_bits);
                 }
+
+                action_total_bits.write_bit(false); // write that there are no more components
             }
             EntityAction::DespawnEntity(global_entity) => {
                 let local_entity = self.entity_records.get(global_entity).unwrap().local_key;
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_entity.to_u16())
-                    .unwrap(); //write local entity
+                action_total_bits.write_u16(local_entity.to_u16()); //write local entity
             }
             EntityAction::OwnEntity(global_entity) => {
                 let local_entity = self.entity_records.get(global_entity).unwrap().local_key;
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_entity.to_u16())
-                    .unwrap(); //write local entity
+                action_total_bits.write_u16(local_entity.to_u16()); //write local entity
             }
             EntityAction::DisownEntity(global_entity) => {
                 let local_entity = self.entity_records.get(global_entity).unwrap().local_key;
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_entity.to_u16())
-                    .unwrap(); //write local entity
+                action_total_bits.write_u16(local_entity.to_u16()); //write local entity
             }
             EntityAction::InsertComponent(global_entity, global_component_key, component_kind) => {
                 let local_entity = self.entity_records.get(global_entity).unwrap().local_key;
@@ -476,25 +528,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                     .unwrap()
                     .local_key;
 
+                //Write component "header"
+                action_total_bits.write_u16(local_entity.to_u16()); //write local entity
+                component_kind.write(&mut action_total_bits); // write component kind
+                action_total_bits.write_u16(local_component_key.to_u16()); //write local component key
+
                 //write component payload
                 let component_ref = world
                     .get_component_of_kind(global_entity, component_kind)
                     .expect("Component does not exist in World");
-
-                let mut component_payload_bytes = Vec::<u8>::new();
-                component_ref.write(&mut component_payload_bytes);
-
-                //Write component "header"
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_entity.to_u16())
-                    .unwrap(); //write local entity
-                action_total_bytes
-                    .write_u16::<BigEndian>(component_kind.to_u16())
-                    .unwrap(); // write component kind
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_component_key.to_u16())
-                    .unwrap(); //write local component key
-                action_total_bytes.append(&mut component_payload_bytes); // write payload
+                component_ref.write(&mut action_total_bits);
             }
             EntityAction::UpdateComponent(
                 global_entity,
@@ -508,56 +551,295 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                     .unwrap()
                     .local_key;
 
-                //write component payload
+                //Write component "header"
+                action_total_bits.write_u16(local_component_key.to_u16()); //write local component key
+
                 let component_ref = world
                     .get_component_of_kind(global_entity, component_kind)
                     .expect("Component does not exist in World");
 
-                let mut component_payload_bytes = Vec::<u8>::new();
-                component_ref.write_partial(diff_mask, &mut component_payload_bytes);
-
-                //Write component "header"
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_component_key.to_u16())
-                    .unwrap(); //write local component key
-                diff_mask.write(&mut action_total_bytes); // write diff mask
-                action_total_bytes.append(&mut component_payload_bytes); // write
-                                                                         // payload
-            }
-            EntityAction::RemoveComponent(global_component_key) => {
+                if !self.delta_compression {
+                    //write component payload, with a bit for each Property in the diff mask
+                    component_ref.write_partial(diff_mask, &mut action_total_bits);
+                } else if let Some((baseline_index, baseline)) =
+                    self.get_baseline(global_component_key, packet_index)
+                {
+                    action_total_bits.write_bit(true); // write that the update has a baseline
+                    action_total_bits.write_u16(baseline_index); // write baseline packet index
+
+                    //write component payload, relative to the baseline
+                    component_ref.write_delta(baseline, &mut action_total_bits);
+                } else {
+                    action_total_bits.write_bit(false); // write that the update has no baseline
+
+                    //write full component payload
+                    component_ref.write(&mut action_total_bits);
+                }
+            }
+            EntityAction::RemoveComponent(global_component_key)
+            | EntityAction::GrantAuthority(global_component_key)
+            | EntityAction::RevokeAuthority(global_component_key) => {
                 let local_component_key = self
                     .component_records
                     .get(global_component_key)
                     .unwrap()
                     .local_key;
 
-                action_total_bytes
-                    .write_u16::<BigEndian>(local_component_key.to_u16())
-                    .unwrap(); //write local key
+                action_total_bits.write_u16(local_component_key.to_u16()); //write local key
+            }
+            EntityAction::RejectClientEntity(local_entity)
+            | EntityAction::TakeOverClientEntity(local_entity) => {
+                action_total_bits.write_u16(local_entity.to_u16()); //write client's local entity
             }
         }
 
         let mut hypothetical_next_payload_size =
-            packet_writer.bytes_number() + action_total_bytes.len();
+            packet_writer.bits_number() + action_total_bits.bits_number();
         if packet_writer.entity_action_count == 0 {
-            hypothetical_next_payload_size += 2;
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
         }
-        if hypothetical_next_payload_size < MTU_SIZE {
-            if packet_writer.entity_action_count == 255 {
-                return false;
-            }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
             packet_writer.entity_action_count = packet_writer.entity_action_count.wrapping_add(1);
-            packet_writer
-                .entity_working_bytes
-                .append(&mut action_total_bytes);
+            packet_writer.entity_working_bits.append(&action_total_bits);
+            if self.delta_compression {
+                self.record_snapshots(world, packet_index, action);
+            }
+            if let EntityAction::UpdateComponent(_, global_component_key, _, _) = action {
+                // the update interval counts from when an update is written, as a
+                // popped update may not fit in the packet
+                self.last_update_times
+                    .insert(*global_component_key, self.clock.now());
+            }
             return true;
         } else {
             return false;
         }
     }
 
+    // Authority
+
+    /// Gives this connection's User authority over a Component, letting the
+    /// Client send its changes to the Component back to the Server
+    pub fn give_authority(&mut self, component_key: &ComponentKey) {
+        if !self.authoritative_components.insert(*component_key) {
+            return;
+        }
+        // Components which have not been created on the Client yet are granted
+        // authority once they are
+        if self.component_is_created(component_key) {
+            self.queued_actions
+                .push_back(EntityAction::GrantAuthority(*component_key));
+        }
+    }
+
+    /// Revokes this connection's User's authority over a Component. The whole
+    /// Component is then sent to the Client, replacing any changes the Server
+    /// did not accept
+    pub fn revoke_authority(&mut self, component_key: &ComponentKey) {
+        if !self.authoritative_components.remove(component_key) {
+            return;
+        }
+        self.authority_update_indices.remove(component_key);
+        if self.component_is_created(component_key) {
+            self.queued_actions
+                .push_back(EntityAction::RevokeAuthority(*component_key));
+            self.diff_handler.set_all_diff_mask(component_key);
+        }
+    }
+
+    // Client Entities
+
+    /// Tells the Client that an Entity it spawned has been rejected
+    pub fn reject_client_entity(&mut self, local_entity: &LocalEntity) {
+        self.queued_actions
+            .push_back(EntityAction::RejectClientEntity(*local_entity));
+    }
+
+    /// Tells the Client that an Entity it spawned has been taken over by the
+    /// Server
+    pub fn take_over_client_entity(&mut self, local_entity: &LocalEntity) {
+        self.queued_actions
+            .push_back(EntityAction::TakeOverClientEntity(*local_entity));
+    }
+
+    /// Reads the Component updates sent by the Client for Components it has
+    /// authority over
+    pub fn process_authority_data(
+        &mut self,
+        world_record: &WorldRecord<E, P::Kind>,
+        manifest: &Manifest<P>,
+        packet_index: u16,
+        reader: &mut BitReader,
+    ) -> Result<(), DecodeError> {
+        // each update is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            match EntityActionType::read(reader) {
+                EntityActionType::UpdateComponent => {
+                    let local_component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    let component_kind = P::Kind::read(reader);
+                    let component =
+                        manifest.create_replica(component_kind, reader, packet_index)?;
+
+                    let global_component_key = match self
+                        .local_to_global_component_key_map
+                        .get(&local_component_key)
+                    {
+                        Some(global_component_key) => *global_component_key,
+                        None => continue,
+                    };
+                    // the update may claim to be of a different kind of Component
+                    match world_record.get_component_record(&global_component_key) {
+                        Some((_, kind)) if kind == component_kind => {}
+                        _ => continue,
+                    }
+                    if !self
+                        .authoritative_components
+                        .contains(&global_component_key)
+                    {
+                        // authority may have been revoked since the update was sent
+                        continue;
+                    }
+                    if let Some(last_index) =
+                        self.authority_update_indices.get(&global_component_key)
+                    {
+                        if !sequence_greater_than(packet_index, *last_index) {
+                            // a newer update has already been received
+                            continue;
+                        }
+                    }
+                    self.authority_update_indices
+                        .insert(global_component_key, packet_index);
+                    self.incoming_authority_updates
+                        .push_back((global_component_key, component));
+                }
+                EntityActionType::RequestComponentState => {
+                    let local_component_key = LocalComponentKey::from_u16(reader.read_u16());
+                    reader.check()?;
+                    if let Some(global_component_key) = self
+                        .local_to_global_component_key_map
+                        .get(&local_component_key)
+                    {
+                        let global_component_key = *global_component_key;
+                        self.resend_component_state(&global_component_key);
+                    }
+                }
+                _ => {
+                    // unable to read the remainder of the section
+                    return Err(DecodeError::UnknownEntityAction);
+                }
+            }
+        }
+        return reader.check();
+    }
+
+    pub fn pop_authority_update(&mut self) -> Option<(ComponentKey, P)> {
+        return self.incoming_authority_updates.pop_front();
+    }
+
     // Private methods
 
+    fn component_is_created(&self, component_key: &ComponentKey) -> bool {
+        if let Some(record) = self.component_records.get(component_key) {
+            return record.status == LocalityStatus::Created;
+        }
+        return false;
+    }
+
+    // Grants authority over a newly created Component, if it has been given
+    fn grant_pending_authority(&mut self, component_key: &ComponentKey) {
+        if self.authoritative_components.contains(component_key) {
+            self.queued_actions
+                .push_back(EntityAction::GrantAuthority(*component_key));
+        }
+    }
+
+    fn get_baseline(&self, component_key: &ComponentKey, packet_index: u16) -> Option<(u16, &P)> {
+        if let Some((baseline_index, baseline)) = self.component_baselines.get(component_key) {
+            // the Client only keeps a limited history of Component states
+            if packet_index.wrapping_sub(*baseline_index) < DELTA_BASELINE_WINDOW {
+                return Some((*baseline_index, baseline));
+            }
+        }
+        return None;
+    }
+
+    fn record_snapshots<W: WorldRefType<P, E>>(
+        &mut self,
+        world: &W,
+        packet_index: u16,
+        action: &EntityAction<P, E>,
+    ) {
+        let mut components: Vec<(E, ComponentKey, P::Kind)> = Vec::new();
+        match action {
+            EntityAction::SpawnEntity(global_entity, component_list) => {
+                for (global_component_key, component_kind) in component_list {
+                    components.push((*global_entity, *global_component_key, *component_kind));
+                }
+            }
+            EntityAction::InsertComponent(global_entity, global_component_key, component_kind)
+            | EntityAction::UpdateComponent(
+                global_entity,
+                global_component_key,
+                _,
+                component_kind,
+            ) => {
+                components.push((*global_entity, *global_component_key, *component_kind));
+            }
+            _ => {}
+        }
+
+        if components.is_empty() {
+            return;
+        }
+
+        let snapshots = self
+            .sent_snapshots
+            .entry(packet_index)
+            .or_insert_with(Vec::new);
+        for (global_entity, global_component_key, component_kind) in components {
+            if let Some(component_ref) =
+                world.get_component_of_kind(&global_entity, &component_kind)
+            {
+                snapshots.push((global_component_key, component_ref.protocol_copy()));
+            }
+        }
+    }
+
+    // the Client is missing the baseline of a delta-compressed update, so the
+    // whole Component is sent again, without one
+    fn resend_component_state(&mut self, component_key: &ComponentKey) {
+        if !self.component_is_created(component_key)
+            || self.authoritative_components.contains(component_key)
+        {
+            return;
+        }
+        self.component_baselines.remove(component_key);
+        for snapshots in self.sent_snapshots.values_mut() {
+            snapshots.retain(|(snapshot_key, _)| snapshot_key != component_key);
+        }
+        self.diff_handler.set_all_diff_mask(component_key);
+    }
+
+    fn promote_snapshots(&mut self, packet_index: u16) {
+        if let Some(snapshots) = self.sent_snapshots.remove(&packet_index) {
+            for (global_component_key, snapshot) in snapshots {
+                if !self.component_records.contains_key(&global_component_key) {
+                    continue;
+                }
+                // only replace the baseline with a newer state
+                if let Some((baseline_index, _)) =
+                    self.component_baselines.get(&global_component_key)
+                {
+                    if !sequence_greater_than(packet_index, *baseline_index) {
+                        continue;
+                    }
+                }
+                self.component_baselines
+                    .insert(global_component_key, (packet_index, snapshot));
+            }
+        }
+    }
+
     fn component_init(
         &mut self,
         component_key: &ComponentKey,
@@ -586,6 +868,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         if let Some(component_record) = self.component_records.remove(global_component_key) {
             // actually delete the component from local records
             self.diff_handler.deregister_component(global_component_key);
+            self.update_priorities.remove(global_component_key);
+            self.last_update_times.remove(global_component_key);
+            self.component_baselines.remove(global_component_key);
+            self.authoritative_components.remove(global_component_key);
+            self.authority_update_indices.remove(global_component_key);
+            self.queued_actions.retain(|action| match action {
+                EntityAction::GrantAuthority(component_key)
+                | EntityAction::RevokeAuthority(component_key) => {
+                    component_key != global_component_key
+                }
+                _ => true,
+            });
 
             let local_component_key = component_record.local_key;
             self.local_to_global_component_key_map
@@ -600,6 +894,67 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         }
     }
 
+    fn update_interval_elapsed(
+        &self,
+        world_record: &WorldRecord<E, P::Kind>,
+        component_key: &ComponentKey,
+    ) -> bool {
+        if let Some(interval) = world_record.get_update_interval(component_key) {
+            if let Some(last_update_time) = self.last_update_times.get(component_key) {
+                return self.clock.now().saturating_sub(*last_update_time) >= interval;
+            }
+        }
+        return true;
+    }
+
+    fn has_pending_update(&self, component_key: &ComponentKey) -> bool {
+        if let Some(diff_mask) = self.diff_handler.get_diff_mask(component_key) {
+            return !diff_mask.is_clear();
+        }
+        return false;
+    }
+
+    // Pops the Component update with the highest accumulated priority
+    fn pop_component_update(
+        &mut self,
+        world_record: &WorldRecord<E, P::Kind>,
+    ) -> Option<EntityAction<P, E>> {
+        loop {
+            let (component_key, _) = self.update_queue.pop()?;
+            // the Component may have been cleaned up since the queue was sorted
+            let priority = match self.update_priorities.remove(&component_key) {
+                Some(priority) => priority,
+                None => continue,
+            };
+
+            // the update may have been sent as part of another action in the meantime
+            let is_created = match self.component_records.get(&component_key) {
+                Some(record) => record.status == LocalityStatus::Created,
+                None => false,
+            };
+            if !is_created || !self.has_pending_update(&component_key) {
+                continue;
+            }
+
+            let (entity, component_kind) = world_record
+                .get_component_record(&component_key)
+                .expect("component does not exist!");
+            let diff_mask = self
+                .diff_handler
+                .get_diff_mask(&component_key)
+                .expect("DiffHandler does not have registered Component!")
+                .clone();
+
+            self.last_popped_priority = Some((component_key, priority));
+            return Some(EntityAction::UpdateComponent(
+                entity,
+                component_key,
+                diff_mask,
+                component_kind,
+            ));
+        }
+    }
+
     fn pop_insert_component_diff_mask(&mut self, global_component_key: &ComponentKey) {
         let new_diff_mask = self
             .diff_handler
@@ -636,23 +991,6 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         );
     }
 
-    fn unpop_update_component_diff_mask(
-        &mut self,
-        packet_index: u16,
-        global_entity: E,
-        global_component_key: &ComponentKey,
-        component_kind: P::Kind,
-    ) -> EntityAction<P, E> {
-        let original_diff_mask = self.undo_component_update(&packet_index, &global_component_key);
-
-        return EntityAction::UpdateComponent(
-            global_entity,
-            *global_component_key,
-            original_diff_mask,
-            component_kind,
-        );
-    }
-
     fn process_component_update(
         &mut self,
         packet_index: u16,
@@ -711,6 +1049,9 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
         while let Some(packet_index) = self.delivered_packets.pop_front() {
             let mut deleted_components: Vec<ComponentKey> = Vec::new();
 
+            // Component states sent in the packet can now be used as baselines
+            self.promote_snapshots(packet_index);
+
             if let Some(delivered_actions_list) = self.sent_actions.remove(&packet_index) {
                 for delivered_action in delivered_actions_list.into_iter() {
                     match delivered_action {
@@ -742,6 +1083,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                                         .get_mut(&global_component_key)
                                         .expect("component not created correctly?");
                                     component_record.status = LocalityStatus::Created;
+                                    self.grant_pending_authority(&global_component_key);
                                 }
 
                                 // for any components on this entity that have not yet been created
@@ -789,6 +1131,10 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                         }
                         EntityAction::OwnEntity(_) => {}
                         EntityAction::DisownEntity(_) => {}
+                        EntityAction::GrantAuthority(_) => {}
+                        EntityAction::RevokeAuthority(_) => {}
+                        EntityAction::RejectClientEntity(_) => {}
+                        EntityAction::TakeOverClientEntity(_) => {}
                         EntityAction::InsertComponent(_, global_component_key, _) => {
                             let component_record = self
                                 .component_records
@@ -809,6 +1155,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> EntityManager<P, E> {
                             } else {
                                 // we do not need to delete just yet
                                 component_record.status = LocalityStatus::Created;
+                                self.grant_pending_authority(&global_component_key);
                             }
                         }
                     }
@@ -837,9 +1184,29 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for EntityManager<P,
                     | EntityAction::OwnEntity(_)
                     | EntityAction::DisownEntity(_)
                     | EntityAction::InsertComponent(_, _, _)
-                    | EntityAction::RemoveComponent(_) => {
+                    | EntityAction::RemoveComponent(_)
+                    | EntityAction::RejectClientEntity(_)
+                    | EntityAction::TakeOverClientEntity(_) => {
                         self.queued_actions.push_back(dropped_action);
                     }
+                    // only resent if authority has not changed again since
+                    EntityAction::GrantAuthority(global_component_key) => {
+                        if self
+                            .authoritative_components
+                            .contains(&global_component_key)
+                        {
+                            self.queued_actions.push_back(dropped_action);
+                        }
+                    }
+                    EntityAction::RevokeAuthority(global_component_key) => {
+                        if !self
+                            .authoritative_components
+                            .contains(&global_component_key)
+                            && self.component_records.contains_key(&global_component_key)
+                        {
+                            self.queued_actions.push_back(dropped_action);
+                        }
+                    }
                     // non-guaranteed delivery actions
                     EntityAction::UpdateComponent(_, global_component_key, _, _) => {
                         if let Some(diff_mask_map) = self.sent_updates.get(&dropped_packet_index) {
@@ -875,6 +1242,8 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> PacketNotifiable for EntityManager<P,
             self.sent_updates.remove(&dropped_packet_index);
             self.sent_actions.remove(&dropped_packet_index);
         }
+
+        self.sent_snapshots.remove(&dropped_packet_index);
     }
 }
 
@@ -897,3 +1266,401 @@ fn entity_delete<P: ProtocolType, E: Copy>(
 
     queued_actions.push_back(EntityAction::DespawnEntity(*entity));
 }
+
+#[cfg(test)]
+mod entity_manager_tests {
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{Arc, RwLock},
+    };
+
+    use std::time::Duration;
+
+    use naia_demo_world::{Entity, World, WorldMutType, WorldRef};
+    use naia_shared::{
+        BitReader, BitWriter, Clock, DiffMask, EntityActionType, ManualClock, NaiaKey,
+        PacketNotifiable, PropertyMutate, ProtocolKindType, ProtocolType, ReplicateSafe,
+    };
+
+    use super::EntityManager;
+    use crate::{
+        entity_action::EntityAction,
+        global_diff_handler::GlobalDiffHandler,
+        keys::ComponentKey,
+        mut_channel::MutSender,
+        packet_writer::PacketWriter,
+        test_protocol::{Health, Position, Protocol},
+        world_record::WorldRecord,
+    };
+
+    // a single connection's EntityManager, along with the Server state it
+    // reads from
+    struct Fixture {
+        manager: EntityManager<Protocol, Entity>,
+        world: World<Protocol>,
+        world_record: WorldRecord<Entity, <Protocol as ProtocolType>::Kind>,
+        diff_handler: Arc<RwLock<GlobalDiffHandler>>,
+        senders: HashMap<ComponentKey, MutSender>,
+        clock: ManualClock,
+        packet_index: u16,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            return Fixture::with_delta_compression(false);
+        }
+
+        fn with_delta_compression(delta_compression: bool) -> Self {
+            let address: SocketAddr = "127.0.0.1:14191".parse().unwrap();
+            let diff_handler = Arc::new(RwLock::new(GlobalDiffHandler::new()));
+            let clock = ManualClock::new();
+            let shared_clock: Arc<dyn Clock> = Arc::new(clock.clone());
+            return Fixture {
+                manager: EntityManager::new(
+                    address,
+                    &diff_handler,
+                    delta_compression,
+                    &shared_clock,
+                ),
+                world: World::new(),
+                world_record: WorldRecord::new(),
+                diff_handler,
+                senders: HashMap::new(),
+                clock,
+                packet_index: 0,
+            };
+        }
+
+        // spawns an Entity with a Position, which the remote host has received
+        fn spawn(&mut self, priority: f32) -> ComponentKey {
+            let entity = self.world.proxy_mut().spawn_entity();
+            self.world
+                .proxy_mut()
+                .insert_component(&entity, Position::new_complete(0, 0));
+            self.world_record.spawn_entity(&entity);
+            let component_key = self
+                .world_record
+                .add_component(&entity, &Protocol::kind_of::<Position>());
+            self.world_record
+                .set_component_priority(&component_key, priority);
+            let sender = self
+                .diff_handler
+                .write()
+                .unwrap()
+                .register_component(&component_key, 2);
+            self.senders.insert(component_key, sender);
+
+            self.manager.spawn_entity(&self.world_record, &entity);
+            assert!(matches!(
+                self.pop_action(),
+                Some(EntityAction::SpawnEntity(_, _))
+            ));
+            self.manager.notify_packet_delivered(self.packet_index);
+            self.manager.process_delivered_packets(&self.world_record);
+            return component_key;
+        }
+
+        fn mutate(&mut self, component_key: &ComponentKey, property_index: u8) {
+            self.senders
+                .get_mut(component_key)
+                .unwrap()
+                .mutate(property_index);
+        }
+
+        fn pop_action(&mut self) -> Option<EntityAction<Protocol, Entity>> {
+            self.packet_index = self.packet_index.wrapping_add(1);
+            return self
+                .manager
+                .pop_outgoing_action::<WorldRef<Protocol>>(&self.world_record, self.packet_index);
+        }
+
+        // receives an update from the Client, claiming to be to the given
+        // Component
+        fn receive_authority_update<R: ReplicateSafe<Protocol>>(
+            &mut self,
+            component_key: &ComponentKey,
+            component: R,
+        ) {
+            let local_key = self.manager.component_records[component_key].local_key;
+            let mut writer = BitWriter::new();
+            writer.write_bit(true);
+            EntityActionType::UpdateComponent.write(&mut writer);
+            writer.write_u16(local_key.to_u16());
+            Protocol::kind_of::<R>().write(&mut writer);
+            component.write(&mut writer);
+            writer.write_bit(false);
+            let bytes = writer.to_bytes();
+
+            self.packet_index = self.packet_index.wrapping_add(1);
+            let mut reader = BitReader::new(&bytes);
+            self.manager
+                .process_authority_data(
+                    &self.world_record,
+                    &Protocol::load(),
+                    self.packet_index,
+                    &mut reader,
+                )
+                .unwrap();
+        }
+
+        // pops the next Component update, if any
+        fn pop_update(&mut self) -> Option<ComponentKey> {
+            match self.pop_action()? {
+                EntityAction::UpdateComponent(_, component_key, _, _) => {
+                    return Some(component_key);
+                }
+                _ => panic!("expected a Component update"),
+            }
+        }
+
+        // pops the next Component update, and writes it into a packet
+        fn send_update(&mut self) -> Option<(ComponentKey, DiffMask)> {
+            let action = self.pop_action()?;
+            let mut writer = PacketWriter::new();
+            assert!(self.manager.write_entity_action(
+                &self.world.proxy(),
+                &mut writer,
+                self.packet_index,
+                &action
+            ));
+            match action {
+                EntityAction::UpdateComponent(_, component_key, diff_mask, _) => {
+                    return Some((component_key, diff_mask));
+                }
+                _ => panic!("expected a Component update"),
+            }
+        }
+    }
+
+    #[test]
+    fn updates_in_priority_order() {
+        let mut fixture = Fixture::new();
+        let low = fixture.spawn(1.0);
+        let high = fixture.spawn(3.0);
+        let middle = fixture.spawn(2.0);
+        for component_key in [low, high, middle].iter() {
+            fixture.mutate(component_key, 0);
+        }
+
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert_eq!(fixture.pop_update(), Some(high));
+        assert_eq!(fixture.pop_update(), Some(middle));
+        assert_eq!(fixture.pop_update(), Some(low));
+        assert_eq!(fixture.pop_update(), None);
+        assert!(!fixture.manager.has_outgoing_actions());
+    }
+
+    #[test]
+    fn priority_accumulates() {
+        let mut fixture = Fixture::new();
+        let waiting = fixture.spawn(1.0);
+        let urgent = fixture.spawn(2.5);
+
+        // an update left unsent accumulates priority every tick
+        fixture.mutate(&waiting, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        fixture.mutate(&urgent, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+
+        assert_eq!(fixture.pop_update(), Some(waiting));
+        assert_eq!(fixture.pop_update(), Some(urgent));
+    }
+
+    #[test]
+    fn unpopped_update_keeps_priority() {
+        let mut fixture = Fixture::new();
+        let other = fixture.spawn(2.0);
+        let unpopped = fixture.spawn(3.0);
+        fixture.mutate(&other, 0);
+        fixture.mutate(&unpopped, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+
+        // the update did not fit in the packet
+        let action = fixture.pop_action().unwrap();
+        fixture
+            .manager
+            .unpop_outgoing_action(fixture.packet_index, action);
+
+        // 6.0 retained, against 4.0 had its priority been reset
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert_eq!(fixture.pop_update(), Some(unpopped));
+        assert_eq!(fixture.pop_update(), Some(other));
+    }
+
+    #[test]
+    fn entity_priority_scales_updates() {
+        let mut fixture = Fixture::new();
+        let distant = fixture.spawn(2.0);
+        let nearby = fixture.spawn(1.0);
+        let (distant_entity, _) = fixture.world_record.get_component_record(&distant).unwrap();
+        let (nearby_entity, _) = fixture.world_record.get_component_record(&nearby).unwrap();
+
+        // as set from the Server's priority evaluator
+        fixture.manager.set_entity_priority(&distant_entity, 0.25);
+        fixture.manager.set_entity_priority(&nearby_entity, 4.0);
+        fixture.mutate(&distant, 0);
+        fixture.mutate(&nearby, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert_eq!(fixture.pop_update(), Some(nearby));
+        assert_eq!(fixture.pop_update(), Some(distant));
+
+        // the priority of an Entity is forgotten once it leaves scope
+        fixture
+            .manager
+            .despawn_entity(&fixture.world_record, &nearby_entity);
+        assert!(!fixture
+            .manager
+            .entity_priorities
+            .contains_key(&nearby_entity));
+    }
+
+    #[test]
+    fn update_interval() {
+        let mut fixture = Fixture::new();
+        let component_key = fixture.spawn(1.0);
+        let (entity, _) = fixture
+            .world_record
+            .get_component_record(&component_key)
+            .unwrap();
+        fixture
+            .world_record
+            .set_entity_update_interval(&entity, Duration::from_millis(100));
+
+        fixture.mutate(&component_key, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert!(fixture.send_update().is_some());
+
+        // changes within the interval are held back, and merged into one update
+        fixture.clock.advance(Duration::from_millis(40));
+        fixture.mutate(&component_key, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert!(fixture.send_update().is_none());
+        fixture.clock.advance(Duration::from_millis(40));
+        fixture.mutate(&component_key, 1);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert!(fixture.send_update().is_none());
+
+        fixture.clock.advance(Duration::from_millis(20));
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        let (_, diff_mask) = fixture.send_update().unwrap();
+        assert_eq!(diff_mask.get_bit(0), Some(true));
+        assert_eq!(diff_mask.get_bit(1), Some(true));
+    }
+
+    #[test]
+    fn unpopped_update_keeps_accumulating() {
+        let mut fixture = Fixture::new();
+        let limited = fixture.spawn(2.0);
+        let other = fixture.spawn(1.5);
+        let (entity, _) = fixture.world_record.get_component_record(&limited).unwrap();
+        fixture
+            .world_record
+            .set_entity_update_interval(&entity, Duration::from_millis(100));
+        fixture.mutate(&limited, 0);
+        fixture.mutate(&other, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+
+        // the update does not fit in the packet, so its interval has not started
+        let action = fixture.pop_action().unwrap();
+        fixture
+            .manager
+            .unpop_outgoing_action(fixture.packet_index, action);
+
+        // and it accumulates priority next tick, 4.0 against 3.0
+        fixture.clock.advance(Duration::from_millis(10));
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        assert_eq!(fixture.send_update().map(|(key, _)| key), Some(limited));
+        assert_eq!(fixture.send_update().map(|(key, _)| key), Some(other));
+    }
+
+    #[test]
+    fn authority_update_of_wrong_kind() {
+        let mut fixture = Fixture::new();
+        let component_key = fixture.spawn(1.0);
+        fixture.manager.give_authority(&component_key);
+
+        // a Health update claiming to be to the Position Component is dropped
+        fixture.receive_authority_update(&component_key, Health::new_complete(7));
+        assert!(fixture.manager.pop_authority_update().is_none());
+
+        fixture.receive_authority_update(&component_key, Position::new_complete(3, 4));
+        let (updated_key, update) = fixture.manager.pop_authority_update().unwrap();
+        assert_eq!(updated_key, component_key);
+        assert!(update.dyn_ref().get_kind() == Protocol::kind_of::<Position>());
+    }
+
+    #[test]
+    fn state_request_drops_baseline() {
+        let mut fixture = Fixture::with_delta_compression(true);
+        let component_key = fixture.spawn(1.0);
+        fixture.mutate(&component_key, 0);
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        fixture.send_update().unwrap();
+        fixture
+            .manager
+            .notify_packet_delivered(fixture.packet_index);
+        fixture
+            .manager
+            .process_delivered_packets(&fixture.world_record);
+        assert!(fixture
+            .manager
+            .component_baselines
+            .contains_key(&component_key));
+
+        // the Client never applied the packet holding the baseline
+        let local_key = fixture.manager.component_records[&component_key].local_key;
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        EntityActionType::RequestComponentState.write(&mut writer);
+        writer.write_u16(local_key.to_u16());
+        writer.write_bit(false);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        fixture
+            .manager
+            .process_authority_data(&fixture.world_record, &Protocol::load(), 0, &mut reader)
+            .unwrap();
+
+        // the whole Component is sent again, without a baseline
+        assert!(!fixture
+            .manager
+            .component_baselines
+            .contains_key(&component_key));
+        fixture
+            .manager
+            .collect_component_updates(&fixture.world_record);
+        let (_, diff_mask) = fixture.send_update().unwrap();
+        assert_eq!(diff_mask.get_bit(0), Some(true));
+        assert_eq!(diff_mask.get_bit(1), Some(true));
+    }
+}
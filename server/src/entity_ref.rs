@@ -1,4 +1,4 @@
-use std::hash::Hash;
+use std::{hash::Hash, time::Duration};
 
 use naia_shared::{
     ProtocolType, ReplicaMutWrapper, ReplicaRefWrapper, Replicate, ReplicateSafe, WorldMutType,
@@ -114,6 +114,77 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>> EntityMut<
             .remove_component::<R, W>(&mut self.world, &self.id);
     }
 
+    // Priority
+
+    /// Sets the base priority with which updates to the Entity's Components
+    /// are sent, relative to those of other Entities. Priority accumulates
+    /// every tick an update is not sent, and updates with the highest
+    /// accumulated priority are sent first. Defaults to 1.0
+    pub fn set_priority(&mut self, priority: f32) -> &mut Self {
+        self.server.entity_set_priority(&self.id, priority);
+
+        self
+    }
+
+    /// Sets the base priority with which updates to one of the Entity's
+    /// Components are sent, overriding that of the Entity
+    pub fn set_component_priority<R: ReplicateSafe<P>>(&mut self, priority: f32) -> &mut Self {
+        self.server
+            .component_set_priority(&self.id, &P::kind_of::<R>(), priority);
+
+        self
+    }
+
+    // Update Rate
+
+    /// Sets the minimum duration between updates of each of the Entity's
+    /// Components. Changes made in between are merged into the next update
+    pub fn set_update_interval(&mut self, interval: Duration) -> &mut Self {
+        self.server.entity_set_update_interval(&self.id, interval);
+
+        self
+    }
+
+    // Interest
+
+    /// Sets the Entity's position for spatial interest management. Once
+    /// given a position, the Entity is automatically scoped to the Users in
+    /// its Rooms which have a view in range of it
+    pub fn set_interest_position(&mut self, x: f32, y: f32) -> &mut Self {
+        self.server.entity_set_interest_position(&self.id, x, y);
+
+        self
+    }
+
+    // Authority
+
+    /// Gives a User authority over one of the Entity's Components, so that
+    /// the User's Client can mutate it & have its changes replicated to other
+    /// Users. Only one User holds authority over a Component at a time
+    pub fn give_authority<R: ReplicateSafe<P>>(&mut self, user_key: &UserKey) -> &mut Self {
+        self.server
+            .component_give_authority(&self.id, &P::kind_of::<R>(), user_key);
+
+        self
+    }
+
+    /// Takes away authority over one of the Entity's Components from the User
+    /// holding it, if any
+    pub fn revoke_authority<R: ReplicateSafe<P>>(&mut self) -> &mut Self {
+        self.server
+            .component_revoke_authority(&self.id, &P::kind_of::<R>());
+
+        self
+    }
+
+    /// Gets the UserKey of the User holding authority over one of the
+    /// Entity's Components, if any
+    pub fn authority<R: ReplicateSafe<P>>(&self) -> Option<UserKey> {
+        return self
+            .server
+            .component_authority(&self.id, &P::kind_of::<R>());
+    }
+
     // Users & Assignment
 
     pub fn has_owner(&self) -> bool {
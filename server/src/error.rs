@@ -1,18 +1,107 @@
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, net::SocketAddr};
+
+use naia_shared::DecodeError;
+
+use crate::{RoomKey, UserKey};
 
 #[derive(Debug)]
 pub enum NaiaServerError {
-    Wrapped(Box<dyn Error>),
+    /// Any other error. Must be `Send + Sync`, so that the Server's errors
+    /// can be sent between threads
+    Wrapped(Box<dyn Error + Send + Sync>),
+    /// The underlying socket failed to send or receive a packet
+    Socket(Box<dyn Error + Send + Sync>),
+    /// A connection request from the given address was rejected, as it could
+    /// not be validated, or didn't match the Server's authentication setting
+    /// or Channels
+    HandshakeRejected(SocketAddr),
+    /// A malformed packet was received from the given address. If it came
+    /// from a connected Client, that Client is disconnected
+    Decode(SocketAddr, DecodeError),
+    /// A packet received from the given address refers to a Message or
+    /// Component kind unknown to the Server's Protocol. If it came from a
+    /// connected Client, that Client is disconnected
+    ProtocolMismatch(SocketAddr, u16),
+    /// The Client associated with the given UserKey hasn't been heard from in
+    /// too long, & is disconnected
+    Timeout(UserKey),
+    /// A Message to the Client associated with the given UserKey was dropped,
+    /// as the queue of its Channel was full
+    QueueOverflow(UserKey),
+    /// A Message was sent through a Channel index which isn't configured in
+    /// the SharedConfig, & was dropped
+    ChannelNotFound(u8),
+    /// A Message to the Client associated with the given UserKey was dropped,
+    /// as it is larger than `MAX_MESSAGE_SIZE_BYTES`
+    MessageTooLarge(UserKey),
+    /// Attempted to access an Entity which doesn't exist
+    EntityNotFound,
+    /// Attempted to access a User which doesn't exist
+    UserNotFound(UserKey),
+    /// Attempted to access a Room which doesn't exist
+    RoomNotFound(RoomKey),
 }
 
 impl fmt::Display for NaiaServerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
             NaiaServerError::Wrapped(boxed_err) => fmt::Display::fmt(boxed_err.as_ref(), f),
+            NaiaServerError::Socket(boxed_err) => write!(f, "socket error: {}", boxed_err),
+            NaiaServerError::HandshakeRejected(address) => {
+                write!(f, "connection request from {} rejected", address)
+            }
+            NaiaServerError::Decode(address, error) => {
+                write!(f, "malformed packet received from {}: {}", address, error)
+            }
+            NaiaServerError::ProtocolMismatch(address, kind) => write!(
+                f,
+                "packet received from {} refers to unknown protocol kind: {}",
+                address, kind
+            ),
+            NaiaServerError::Timeout(user_key) => write!(f, "user {:?} timed out", user_key),
+            NaiaServerError::QueueOverflow(user_key) => write!(
+                f,
+                "message to user {:?} dropped, as its channel queue is full",
+                user_key
+            ),
+            NaiaServerError::ChannelNotFound(channel_index) => write!(
+                f,
+                "message dropped, as channel {} does not exist",
+                channel_index
+            ),
+            NaiaServerError::MessageTooLarge(user_key) => write!(
+                f,
+                "message to user {:?} dropped, as it is too large",
+                user_key
+            ),
+            NaiaServerError::EntityNotFound => write!(f, "entity does not exist"),
+            NaiaServerError::UserNotFound(user_key) => {
+                write!(f, "user {:?} does not exist", user_key)
+            }
+            NaiaServerError::RoomNotFound(room_key) => {
+                write!(f, "room {:?} does not exist", room_key)
+            }
         }
     }
 }
 
 impl Error for NaiaServerError {}
-unsafe impl Send for NaiaServerError {}
-unsafe impl Sync for NaiaServerError {}
+
+#[cfg(test)]
+mod error_tests {
+    use std::io;
+
+    use super::NaiaServerError;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn socket_error() {
+        let error = NaiaServerError::Socket(Box::new(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "connection reset",
+        )));
+        assert_send_sync(&error);
+        assert_eq!(error.to_string(), "socket error: connection reset");
+    }
+}
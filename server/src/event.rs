@@ -23,4 +23,11 @@ pub enum Event<P: ProtocolType, E> {
     /// A Command emitted to the Server from a Client, related to some
     /// user-assigned Entity
     Command(UserKey, E, P),
+    /// Occurs when a Blob sent to a Client has been fully delivered, or the
+    /// Client already had it cached
+    BlobDelivered(UserKey, String),
+    /// Occurs when a Client has spawned an Entity, which has been mapped into
+    /// the Server's world. The Entity can then be accepted, rejected, or taken
+    /// over by the Server
+    ClientSpawnedEntity(UserKey, E),
 }
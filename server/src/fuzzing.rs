@@ -0,0 +1,261 @@
+//! Entry points into the Server's packet decoding, used by the fuzz targets
+//! in the `fuzz` directory. Not part of the public API
+
+use std::{
+    hash::Hash,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
+
+use naia_shared::{
+    ChannelConfig, Channels, Clock, CongestionConfig, ConnectionConfig, DefaultChannels, Manifest,
+    PacketType, ProtocolType, StandardHeader, SystemClock, WorldMutType,
+};
+
+use super::{
+    client_entity_action::ClientEntityAction, connection::Connection,
+    global_diff_handler::GlobalDiffHandler, handshake_manager::HandshakeManager,
+    world_record::WorldRecord, ServerConfig, UserKey,
+};
+
+fn fuzz_address() -> SocketAddr {
+    return "127.0.0.1:14191"
+        .parse()
+        .expect("could not parse socket address from string");
+}
+
+/// Feeds arbitrary bytes to a new HandshakeManager as the payload of a
+/// connection request. If the first byte is odd, the rest is preceded by a
+/// validly signed Timestamp, so that the authentication payload is reached
+pub fn connect_request<P: ProtocolType>(manifest: &Manifest<P>, require_auth: bool, data: &[u8]) {
+    let mut handshake_manager =
+        HandshakeManager::<P>::new(require_auth, &DefaultChannels::configs());
+    let payload = match data.split_first() {
+        Some((selector, rest)) if selector % 2 == 1 => {
+            let mut payload = handshake_manager.signed_timestamp();
+            payload.extend_from_slice(rest);
+            payload
+        }
+        _ => data.to_vec(),
+    };
+    let _ = handshake_manager.receive_new_connect_request(
+        manifest,
+        &fuzz_address(),
+        &payload.into_boxed_slice(),
+    );
+}
+
+/// Feeds arbitrary bytes to a new Connection as a whole packet, handled as
+/// the Server handles a Data packet from a connected Client
+pub fn incoming_packet<P: ProtocolType>(
+    manifest: &Manifest<P>,
+    channel_configs: &[ChannelConfig],
+    data: &[u8],
+) {
+    let (header, payload) = match StandardHeader::read(data) {
+        Ok(header_and_payload) => header_and_payload,
+        Err(_) => return,
+    };
+
+    let diff_handler = Arc::new(RwLock::new(GlobalDiffHandler::new()));
+    let world_record = WorldRecord::<u32, P::Kind>::new();
+    let mut connection = new_connection::<P, u32>(&diff_handler, channel_configs);
+    connection.process_incoming_header(&world_record, &header);
+    let _ = connection.process_incoming_data(
+        &world_record,
+        Some(0),
+        header.host_tick(),
+        header.local_packet_index(),
+        manifest,
+        &payload,
+    );
+}
+
+/// Feeds arbitrary bytes to a Connection as a sequence of packets, each
+/// preceded by its length as two big-endian bytes. The Client has already
+/// received every Entity in the given world, and has authority over each of
+/// their Components, so that Component updates, Client Entity actions and
+/// fragmented Messages spanning several packets are all reached
+pub fn incoming_packets<P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>>(
+    world: &mut W,
+    manifest: &Manifest<P>,
+    channel_configs: &[ChannelConfig],
+    data: &[u8],
+) {
+    let diff_handler = Arc::new(RwLock::new(GlobalDiffHandler::new()));
+    let mut world_record = WorldRecord::<E, P::Kind>::new();
+    let mut connection = new_connection::<P, E>(&diff_handler, channel_configs);
+
+    let mut component_keys = Vec::new();
+    for entity in world.entities() {
+        world_record.spawn_entity(&entity);
+        for component_kind in world.get_component_kinds(&entity) {
+            let diff_mask_size = match world.get_component_of_kind(&entity, &component_kind) {
+                Some(component) => component.get_diff_mask_size(),
+                None => continue,
+            };
+            let component_key = world_record.add_component(&entity, &component_kind);
+            diff_handler
+                .write()
+                .expect("DiffHandler should be initialized")
+                .register_component(&component_key, diff_mask_size);
+            component_keys.push(component_key);
+        }
+        connection.spawn_entity(&world_record, &entity);
+        connection.add_prediction_entity(&entity);
+    }
+
+    // the Client receives everything, then is given authority over it
+    let mut remote_packet_index: u16 = 0;
+    deliver_outgoing_packets(
+        world,
+        &world_record,
+        &mut connection,
+        &mut remote_packet_index,
+    );
+    for component_key in &component_keys {
+        connection.give_authority(component_key);
+    }
+    deliver_outgoing_packets(
+        world,
+        &world_record,
+        &mut connection,
+        &mut remote_packet_index,
+    );
+
+    let mut rest = data;
+    while rest.len() >= 2 {
+        let length = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+        let packet = &rest[2..(2 + length).min(rest.len())];
+        rest = &rest[2 + packet.len()..];
+
+        let (header, payload) = match StandardHeader::read(packet) {
+            Ok(header_and_payload) => header_and_payload,
+            Err(_) => continue,
+        };
+        connection.process_incoming_header(&world_record, &header);
+        if connection
+            .process_incoming_data(
+                &world_record,
+                Some(0),
+                header.host_tick(),
+                header.local_packet_index(),
+                manifest,
+                &payload,
+            )
+            .is_err()
+        {
+            continue;
+        }
+
+        // handles what was received, as the Server does
+        while connection.get_incoming_message().is_some() {}
+        while connection.get_blob_event().is_some() {}
+        while connection.get_incoming_command(0).is_some() {}
+        while let Some((component_key, component)) = connection.pop_authority_update() {
+            if let Some((entity, _)) = world_record.get_component_record(&component_key) {
+                world.mirror_component(&entity, &component);
+                diff_handler
+                    .read()
+                    .expect("DiffHandler should be initialized")
+                    .mutate_all(&component_key);
+            }
+        }
+        while let Some(action) = connection.pop_client_entity_action() {
+            receive_client_entity_action(world, &mut connection, action);
+        }
+
+        connection.collect_component_updates(&world_record);
+        while connection
+            .get_outgoing_packet(world, &world_record, Some(0))
+            .is_some()
+        {}
+    }
+}
+
+fn new_connection<P: ProtocolType, E: Copy + Eq + Hash>(
+    diff_handler: &Arc<RwLock<GlobalDiffHandler>>,
+    channel_configs: &[ChannelConfig],
+) -> Connection<P, E> {
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock::new());
+    return Connection::<P, E>::new(
+        &ConnectionConfig::default(),
+        fuzz_address(),
+        &UserKey::default(),
+        diff_handler,
+        true,
+        channel_configs,
+        &CongestionConfig::default(),
+        ServerConfig::default().max_client_entities,
+        &clock,
+    );
+}
+
+// Sends every outgoing packet, and receives an acknowledgement of each
+fn deliver_outgoing_packets<P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>>(
+    world: &W,
+    world_record: &WorldRecord<E, P::Kind>,
+    connection: &mut Connection<P, E>,
+    remote_packet_index: &mut u16,
+) {
+    loop {
+        connection.collect_component_updates(world_record);
+        let packet = match connection.get_outgoing_packet(world, world_record, Some(0)) {
+            Some(packet) => packet,
+            None => return,
+        };
+        let (sent_header, _) =
+            StandardHeader::read(&packet).expect("outgoing packet should have a valid header");
+        let ack = StandardHeader::new(
+            PacketType::Data,
+            *remote_packet_index,
+            sent_header.local_packet_index(),
+            0,
+            0,
+            0,
+        );
+        *remote_packet_index = remote_packet_index.wrapping_add(1);
+        connection.process_incoming_header(world_record, &ack);
+    }
+}
+
+// Applies an action the Client performed on an Entity it spawned, as the
+// Server does
+fn receive_client_entity_action<P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>>(
+    world: &mut W,
+    connection: &mut Connection<P, E>,
+    action: ClientEntityAction<P>,
+) {
+    match action {
+        ClientEntityAction::SpawnEntity(local_entity) => {
+            let entity = world.spawn_entity();
+            connection.insert_client_entity(&local_entity, &entity);
+        }
+        ClientEntityAction::DespawnEntity(local_entity) => {
+            if let Some(entity) = connection.remove_client_entity(&local_entity) {
+                if world.has_entity(&entity) {
+                    world.despawn_entity(&entity);
+                }
+            }
+        }
+        ClientEntityAction::InsertComponent(local_entity, component) => {
+            if let Some(entity) = connection.client_entity(&local_entity) {
+                if !world.has_entity(&entity) {
+                    return;
+                }
+                if world.has_component_of_kind(&entity, &component.dyn_ref().get_kind()) {
+                    world.mirror_component(&entity, &component);
+                } else {
+                    component.extract_and_insert(&entity, world);
+                }
+            }
+        }
+        ClientEntityAction::RemoveComponent(local_entity, component_kind) => {
+            if let Some(entity) = connection.client_entity(&local_entity) {
+                if world.has_entity(&entity) {
+                    world.remove_component_of_kind(&entity, &component_kind);
+                }
+            }
+        }
+    }
+}
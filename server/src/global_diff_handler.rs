@@ -37,6 +37,13 @@ impl GlobalDiffHandler {
         self.mut_receiver_builders.remove(component_key);
     }
 
+    /// Marks every Property of a Component as updated, for every User
+    pub fn mutate_all(&self, component_key: &ComponentKey) {
+        if let Some(builder) = self.mut_receiver_builders.get(component_key) {
+            builder.send_all();
+        }
+    }
+
     pub fn get_receiver(
         &self,
         addr: &SocketAddr,
@@ -1,15 +1,17 @@
+use std::collections::HashSet;
+
 use super::{room::room_key::RoomKey, user::user_key::UserKey};
 
 pub struct GlobalEntityRecord {
     pub owner_key: Option<UserKey>,
-    pub room_key: Option<RoomKey>,
+    pub room_keys: HashSet<RoomKey>,
 }
 
 impl GlobalEntityRecord {
     pub fn new() -> Self {
         Self {
             owner_key: None,
-            room_key: None,
+            room_keys: HashSet::new(),
         }
     }
 }
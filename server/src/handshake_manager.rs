@@ -6,16 +6,23 @@ use ring::{hmac, rand};
 use naia_server_socket::Packet;
 
 pub use naia_shared::{
-    wrapping_diff, BaseConnection, ConnectionConfig, Instant, KeyGenerator, LocalComponentKey,
-    ManagerType, Manifest, PacketReader, PacketType, PropertyMutate, PropertyMutator,
-    ProtocolKindType, ProtocolType, Replicate, ReplicateSafe, SharedConfig, StandardHeader, Timer,
-    Timestamp, WorldMutType, WorldRefType,
+    wrapping_diff, BaseConnection, BitReader, ChannelConfig, ChannelMode, ConnectionConfig,
+    DecodeError, Instant, KeyGenerator, LocalComponentKey, ManagerType, Manifest, PacketReader,
+    PacketType, PropertyMutate, PropertyMutator, ProtocolKindType, ProtocolType, Replicate,
+    ReplicateSafe, Serde, SharedConfig, StandardHeader, Timer, Timestamp, WorldMutType,
+    WorldRefType,
 };
 
 use super::{connection::Connection, io::Io, world_record::WorldRecord};
 
+// Number of bytes in a written Timestamp
+const TIMESTAMP_BYTES: usize = 8;
+// Number of bytes in a timestamp digest
+const DIGEST_BYTES: usize = 32;
+
 pub enum HandshakeResult<P: ProtocolType> {
     None,
+    Rejected,
     DisconnectUser,
     AuthUser(P),
     ConnectUser,
@@ -24,18 +31,20 @@ pub enum HandshakeResult<P: ProtocolType> {
 pub struct HandshakeManager<P: ProtocolType> {
     connection_hash_key: hmac::Key,
     require_auth: bool,
+    channel_modes: Vec<ChannelMode>,
     address_to_timestamp_map: HashMap<SocketAddr, Timestamp>,
     phantom: PhantomData<P>,
 }
 
 impl<P: ProtocolType> HandshakeManager<P> {
-    pub fn new(require_auth: bool) -> Self {
+    pub fn new(require_auth: bool, channel_configs: &[ChannelConfig]) -> Self {
         let connection_hash_key =
             hmac::Key::generate(hmac::HMAC_SHA256, &rand::SystemRandom::new()).unwrap();
 
         Self {
             connection_hash_key,
             require_auth,
+            channel_modes: channel_configs.iter().map(|config| config.mode).collect(),
             address_to_timestamp_map: HashMap::new(),
             phantom: PhantomData,
         }
@@ -47,7 +56,10 @@ impl<P: ProtocolType> HandshakeManager<P> {
         server_tick: u16,
         address: &SocketAddr,
         incoming_bytes: &Box<[u8]>,
-    ) {
+    ) -> Result<(), DecodeError> {
+        if incoming_bytes.len() < TIMESTAMP_BYTES {
+            return Err(DecodeError::UnexpectedEnd);
+        }
         let mut reader = PacketReader::new(incoming_bytes);
         let timestamp = Timestamp::read(&mut reader);
 
@@ -76,6 +88,20 @@ impl<P: ProtocolType> HandshakeManager<P> {
         );
         io.send_packet(Packet::new_raw(outgoing_packet.address(), new_payload));
         /////////////////////////
+
+        return Ok(());
+    }
+
+    /// Writes the current Timestamp followed by its digest, as a Client would
+    /// after receiving a challenge response, so that fuzzed connection
+    /// requests get past validation
+    #[cfg(feature = "fuzzing")]
+    pub fn signed_timestamp(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        Timestamp::now().write(&mut bytes);
+        let timestamp_hash = hmac::sign(&self.connection_hash_key, &bytes);
+        bytes.extend_from_slice(timestamp_hash.as_ref());
+        return bytes;
     }
 
     pub fn receive_new_connect_request(
@@ -83,7 +109,10 @@ impl<P: ProtocolType> HandshakeManager<P> {
         manifest: &Manifest<P>,
         address: &SocketAddr,
         incoming_bytes: &Box<[u8]>,
-    ) -> HandshakeResult<P> {
+    ) -> Result<HandshakeResult<P>, DecodeError> {
+        if incoming_bytes.len() < TIMESTAMP_BYTES + DIGEST_BYTES {
+            return Err(DecodeError::UnexpectedEnd);
+        }
         let mut reader = PacketReader::new(incoming_bytes);
         let timestamp = Timestamp::read(&mut reader);
 
@@ -92,31 +121,42 @@ impl<P: ProtocolType> HandshakeManager<P> {
         let mut timestamp_bytes: Vec<u8> = Vec::new();
         timestamp.write(&mut timestamp_bytes);
         let mut digest_bytes: Vec<u8> = Vec::new();
-        for _ in 0..32 {
+        for _ in 0..DIGEST_BYTES {
             digest_bytes.push(reader.read_u8());
         }
         let validation_result =
             hmac::verify(&self.connection_hash_key, &timestamp_bytes, &digest_bytes);
         if validation_result.is_err() {
-            return HandshakeResult::None;
+            return Ok(HandshakeResult::Rejected);
         }
 
         // Timestamp hash is validated, now start configured auth process
 
-        let has_auth = reader.read_u8() == 1;
+        let auth_start = reader.get_cursor().position() as usize;
+        let mut bit_reader = BitReader::new(&incoming_bytes[auth_start..]);
 
-        if has_auth != self.require_auth {
-            return HandshakeResult::None;
+        // a Client configured with different Channels would misroute Messages
+        let channel_modes = Vec::<ChannelMode>::de(&mut bit_reader);
+        bit_reader.check()?;
+        if channel_modes != self.channel_modes {
+            return Ok(HandshakeResult::Rejected);
         }
 
-        self.address_to_timestamp_map.insert(*address, timestamp);
+        let has_auth = bit_reader.read_bit();
+        bit_reader.check()?;
+
+        if has_auth != self.require_auth {
+            return Ok(HandshakeResult::Rejected);
+        }
 
         if has_auth {
-            let auth_kind = P::Kind::from_u16(reader.read_u16());
-            let auth_message = manifest.create_replica(auth_kind, &mut reader, 0);
-            return HandshakeResult::AuthUser(auth_message);
+            let auth_kind = P::Kind::read(&mut bit_reader);
+            let auth_message = manifest.create_replica(auth_kind, &mut bit_reader, 0)?;
+            self.address_to_timestamp_map.insert(*address, timestamp);
+            return Ok(HandshakeResult::AuthUser(auth_message));
         } else {
-            return HandshakeResult::ConnectUser;
+            self.address_to_timestamp_map.insert(*address, timestamp);
+            return Ok(HandshakeResult::ConnectUser);
         }
     }
 
@@ -127,11 +167,14 @@ impl<P: ProtocolType> HandshakeManager<P> {
         connection: &mut Connection<P, E>,
         incoming_header: &StandardHeader,
         incoming_payload: &Box<[u8]>,
-    ) -> HandshakeResult<P> {
+    ) -> Result<HandshakeResult<P>, DecodeError> {
         // At this point, we have already sent the ServerConnectResponse
         // message, but we continue to send the message till the Client
         // stops sending the ClientConnectRequest
 
+        if incoming_payload.len() < TIMESTAMP_BYTES {
+            return Err(DecodeError::UnexpectedEnd);
+        }
         let mut reader = PacketReader::new(incoming_payload);
         let new_timestamp = Timestamp::read(&mut reader);
 
@@ -141,17 +184,23 @@ impl<P: ProtocolType> HandshakeManager<P> {
 
                 self.send_connect_accept_response(io, connection);
             } else {
-                return HandshakeResult::DisconnectUser;
+                return Ok(HandshakeResult::DisconnectUser);
             }
         }
 
-        return HandshakeResult::None;
+        return Ok(HandshakeResult::None);
     }
 
     pub fn delete_user(&mut self, address: &SocketAddr) {
         self.address_to_timestamp_map.remove(address);
     }
 
+    pub fn send_reject_response(&mut self, io: &mut Io, address: &SocketAddr) {
+        let payload =
+            naia_shared::utils::write_connectionless_payload(PacketType::ServerRejectResponse, &[]);
+        io.send_packet(Packet::new_raw(*address, payload));
+    }
+
     pub fn send_connect_accept_response<E: Copy + Eq + Hash>(
         &mut self,
         io: &mut Io,
@@ -0,0 +1,388 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use crate::user::user_key::UserKey;
+
+type Cell = (i32, i32);
+
+// the furthest a User's view reaches, in cells, which bounds the number of cells
+// a view covers
+const MAX_VIEW_RADIUS_CELLS: f32 = 32.0;
+
+/// Contains Config properties which determine how the Server's spatial
+/// interest management scopes Entities to Users
+#[derive(Clone, Debug)]
+pub struct InterestConfig {
+    /// The width & height of each cell of the grid Entities are sorted into.
+    /// Works best when around the size of a typical view radius. Must be
+    /// positive
+    pub cell_size: f32,
+    /// The distance beyond a User's view radius an Entity must move before it
+    /// leaves the User's scope, so that Entities moving along the edge of the
+    /// view do not repeatedly enter & leave scope
+    pub hysteresis: f32,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 64.0,
+            hysteresis: 8.0,
+        }
+    }
+}
+
+struct View {
+    x: f32,
+    y: f32,
+    radius: f32,
+    cells: Vec<Cell>,
+}
+
+/// Keeps track of the positions of Entities & the views of Users on a grid,
+/// and works out which Entities are within view of each User. Only Entities
+/// & Users which have moved since the last update are re-evaluated, against
+/// those in nearby cells
+pub struct InterestManager<E: Copy + Eq + Hash> {
+    config: InterestConfig,
+    entity_positions: HashMap<E, (f32, f32)>,
+    entity_cells: HashMap<Cell, HashSet<E>>,
+    views: HashMap<UserKey, View>,
+    view_cells: HashMap<Cell, HashSet<UserKey>>,
+    entities_in_view: HashMap<UserKey, HashSet<E>>,
+    viewers_of_entity: HashMap<E, HashSet<UserKey>>,
+    dirty_entities: HashSet<E>,
+    dirty_users: HashSet<UserKey>,
+    scope_changes: VecDeque<(UserKey, E, bool)>,
+}
+
+impl<E: Copy + Eq + Hash> InterestManager<E> {
+    pub fn new(config: &InterestConfig) -> Self {
+        if !(config.cell_size > 0.0 && config.cell_size.is_finite()) {
+            panic!("InterestConfig cell_size must be positive & finite");
+        }
+        if !(config.hysteresis >= 0.0 && config.hysteresis.is_finite()) {
+            panic!("InterestConfig hysteresis must be non-negative & finite");
+        }
+        InterestManager {
+            config: config.clone(),
+            entity_positions: HashMap::new(),
+            entity_cells: HashMap::new(),
+            views: HashMap::new(),
+            view_cells: HashMap::new(),
+            entities_in_view: HashMap::new(),
+            viewers_of_entity: HashMap::new(),
+            dirty_entities: HashSet::new(),
+            dirty_users: HashSet::new(),
+            scope_changes: VecDeque::new(),
+        }
+    }
+
+    // Entities
+
+    pub fn set_entity_position(&mut self, entity: &E, x: f32, y: f32) {
+        let new_cell = self.cell_of(x, y);
+        if let Some((old_x, old_y)) = self.entity_positions.insert(*entity, (x, y)) {
+            let old_cell = self.cell_of(old_x, old_y);
+            if old_cell == new_cell {
+                self.dirty_entities.insert(*entity);
+                return;
+            }
+            remove_from_set(&mut self.entity_cells, &old_cell, entity);
+        }
+        self.entity_cells
+            .entry(new_cell)
+            .or_insert_with(HashSet::new)
+            .insert(*entity);
+        self.dirty_entities.insert(*entity);
+    }
+
+    /// Stops tracking an Entity. No scope changes are produced, as the Entity
+    /// is expected to be leaving the scope of every User anyway
+    pub fn remove_entity(&mut self, entity: &E) {
+        if let Some((x, y)) = self.entity_positions.remove(entity) {
+            let cell = self.cell_of(x, y);
+            remove_from_set(&mut self.entity_cells, &cell, entity);
+        }
+        if let Some(viewers) = self.viewers_of_entity.remove(entity) {
+            for user_key in viewers {
+                if let Some(entities) = self.entities_in_view.get_mut(&user_key) {
+                    entities.remove(entity);
+                }
+            }
+        }
+        self.dirty_entities.remove(entity);
+    }
+
+    // Users
+
+    /// Sets a User's view. The radius is clamped so that, along with the
+    /// hysteresis, the view reaches no further than MAX_VIEW_RADIUS_CELLS cells
+    pub fn set_user_view(&mut self, user_key: &UserKey, x: f32, y: f32, radius: f32) {
+        let max_radius =
+            (self.config.cell_size * MAX_VIEW_RADIUS_CELLS - self.config.hysteresis).max(0.0);
+        if radius > max_radius {
+            warn!("User view radius {} clamped to {}", radius, max_radius);
+        }
+        // also maps a NaN radius to zero
+        let radius = radius.max(0.0).min(max_radius);
+        let cells = self.cells_within(x, y, radius + self.config.hysteresis);
+        if let Some(old_view) = self.views.remove(user_key) {
+            for cell in old_view.cells.iter() {
+                remove_from_set(&mut self.view_cells, cell, user_key);
+            }
+        }
+        for cell in cells.iter() {
+            self.view_cells
+                .entry(*cell)
+                .or_insert_with(HashSet::new)
+                .insert(*user_key);
+        }
+        self.views.insert(
+            *user_key,
+            View {
+                x,
+                y,
+                radius,
+                cells,
+            },
+        );
+        self.dirty_users.insert(*user_key);
+    }
+
+    /// Stops tracking a User. No scope changes are produced, as the User is
+    /// expected to be disconnecting
+    pub fn remove_user(&mut self, user_key: &UserKey) {
+        if let Some(view) = self.views.remove(user_key) {
+            for cell in view.cells.iter() {
+                remove_from_set(&mut self.view_cells, cell, user_key);
+            }
+        }
+        if let Some(entities) = self.entities_in_view.remove(user_key) {
+            for entity in entities {
+                if let Some(viewers) = self.viewers_of_entity.get_mut(&entity) {
+                    viewers.remove(user_key);
+                }
+            }
+        }
+        self.dirty_users.remove(user_key);
+    }
+
+    // Scope
+
+    /// Re-evaluates the Users & Entities which have moved since the last
+    /// update, queueing any resulting scope changes
+    pub fn update(&mut self) {
+        let dirty_users: Vec<UserKey> = self.dirty_users.drain().collect();
+        for user_key in dirty_users {
+            let mut candidates: HashSet<E> = HashSet::new();
+            if let Some(view) = self.views.get(&user_key) {
+                for cell in view.cells.iter() {
+                    if let Some(entities) = self.entity_cells.get(cell) {
+                        candidates.extend(entities.iter().copied());
+                    }
+                }
+            }
+            if let Some(entities) = self.entities_in_view.get(&user_key) {
+                candidates.extend(entities.iter().copied());
+            }
+            for entity in candidates {
+                self.evaluate(&user_key, &entity);
+            }
+        }
+
+        let dirty_entities: Vec<E> = self.dirty_entities.drain().collect();
+        for entity in dirty_entities {
+            let mut candidates: HashSet<UserKey> = HashSet::new();
+            if let Some((x, y)) = self.entity_positions.get(&entity) {
+                if let Some(users) = self.view_cells.get(&self.cell_of(*x, *y)) {
+                    candidates.extend(users.iter().copied());
+                }
+            }
+            if let Some(users) = self.viewers_of_entity.get(&entity) {
+                candidates.extend(users.iter().copied());
+            }
+            for user_key in candidates {
+                self.evaluate(&user_key, &entity);
+            }
+        }
+    }
+
+    /// Returns whether an Entity is within view of a User, as of the last
+    /// update
+    pub fn is_in_view(&self, user_key: &UserKey, entity: &E) -> bool {
+        return match self.entities_in_view.get(user_key) {
+            Some(entities) => entities.contains(entity),
+            None => false,
+        };
+    }
+
+    /// Pops the next change of scope, as a User, an Entity, and whether the
+    /// Entity is now in scope for the User
+    pub fn pop_scope_change(&mut self) -> Option<(UserKey, E, bool)> {
+        return self.scope_changes.pop_front();
+    }
+
+    // Private methods
+
+    fn evaluate(&mut self, user_key: &UserKey, entity: &E) {
+        let currently_in_view = self.is_in_view(user_key, entity);
+
+        let should_be_in_view = match (self.views.get(user_key), self.entity_positions.get(entity))
+        {
+            (Some(view), Some((x, y))) => {
+                let distance_squared = (x - view.x).powi(2) + (y - view.y).powi(2);
+                let radius = if currently_in_view {
+                    view.radius + self.config.hysteresis
+                } else {
+                    view.radius
+                };
+                distance_squared <= radius * radius
+            }
+            _ => false,
+        };
+
+        if currently_in_view == should_be_in_view {
+            return;
+        }
+
+        if should_be_in_view {
+            self.entities_in_view
+                .entry(*user_key)
+                .or_insert_with(HashSet::new)
+                .insert(*entity);
+            self.viewers_of_entity
+                .entry(*entity)
+                .or_insert_with(HashSet::new)
+                .insert(*user_key);
+        } else {
+            remove_from_set(&mut self.entities_in_view, user_key, entity);
+            remove_from_set(&mut self.viewers_of_entity, entity, user_key);
+        }
+        self.scope_changes
+            .push_back((*user_key, *entity, should_be_in_view));
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> Cell {
+        return (
+            (x / self.config.cell_size).floor() as i32,
+            (y / self.config.cell_size).floor() as i32,
+        );
+    }
+
+    fn cells_within(&self, x: f32, y: f32, radius: f32) -> Vec<Cell> {
+        let (min_x, min_y) = self.cell_of(x - radius, y - radius);
+        let (max_x, max_y) = self.cell_of(x + radius, y + radius);
+        let mut cells = Vec::new();
+        for cell_x in min_x..=max_x {
+            for cell_y in min_y..=max_y {
+                cells.push((cell_x, cell_y));
+            }
+        }
+        return cells;
+    }
+}
+
+// Removes a value from the set at the given key, removing the set once empty
+fn remove_from_set<K: Eq + Hash, V: Eq + Hash>(
+    map: &mut HashMap<K, HashSet<V>>,
+    key: &K,
+    value: &V,
+) {
+    if let Some(set) = map.get_mut(key) {
+        set.remove(value);
+        if set.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod interest_manager_tests {
+    use slotmap::DenseSlotMap;
+
+    use super::{InterestConfig, InterestManager, MAX_VIEW_RADIUS_CELLS};
+    use crate::user::user_key::UserKey;
+
+    fn user_key() -> UserKey {
+        let mut keys: DenseSlotMap<UserKey, ()> = DenseSlotMap::with_key();
+        return keys.insert(());
+    }
+
+    fn scope_changes(manager: &mut InterestManager<u32>) -> Vec<(UserKey, u32, bool)> {
+        manager.update();
+        let mut changes = Vec::new();
+        while let Some(change) = manager.pop_scope_change() {
+            changes.push(change);
+        }
+        return changes;
+    }
+
+    #[test]
+    fn enter_and_leave() {
+        let mut manager = InterestManager::new(&InterestConfig::default());
+        let user = user_key();
+        manager.set_user_view(&user, 0.0, 0.0, 10.0);
+        manager.set_entity_position(&1, 5.0, 0.0);
+        manager.set_entity_position(&2, 50.0, 0.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 1, true)]);
+        assert!(manager.is_in_view(&user, &1));
+
+        // the User's view moves away
+        manager.set_user_view(&user, 100.0, 0.0, 10.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 1, false)]);
+
+        // an Entity moves into view, from another cell
+        manager.set_entity_position(&2, 95.0, 0.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 2, true)]);
+
+        manager.remove_entity(&2);
+        assert!(scope_changes(&mut manager).is_empty());
+        assert!(!manager.is_in_view(&user, &2));
+    }
+
+    #[test]
+    fn hysteresis() {
+        let config = InterestConfig {
+            cell_size: 64.0,
+            hysteresis: 8.0,
+        };
+        let mut manager = InterestManager::new(&config);
+        let user = user_key();
+        manager.set_user_view(&user, 0.0, 0.0, 10.0);
+        manager.set_entity_position(&1, 12.0, 0.0);
+        assert!(scope_changes(&mut manager).is_empty());
+
+        manager.set_entity_position(&1, 9.0, 0.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 1, true)]);
+
+        // stays in scope until beyond the radius plus the hysteresis
+        manager.set_entity_position(&1, 17.0, 0.0);
+        assert!(scope_changes(&mut manager).is_empty());
+        manager.set_entity_position(&1, 19.0, 0.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 1, false)]);
+
+        // and must come back within the radius to re-enter
+        manager.set_entity_position(&1, 12.0, 0.0);
+        assert!(scope_changes(&mut manager).is_empty());
+    }
+
+    #[test]
+    fn radius_clamped() {
+        let config = InterestConfig::default();
+        let mut manager = InterestManager::new(&config);
+        let user = user_key();
+        manager.set_user_view(&user, 0.0, 0.0, f32::MAX);
+        let cells_across = (MAX_VIEW_RADIUS_CELLS as usize) * 2 + 2;
+        assert!(manager.views[&user].cells.len() <= cells_across * cells_across);
+
+        manager.set_entity_position(&1, 1_000_000.0, 0.0);
+        manager.set_entity_position(&2, 100.0, 100.0);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 2, true)]);
+
+        manager.set_user_view(&user, 0.0, 0.0, f32::NAN);
+        assert_eq!(scope_changes(&mut manager), vec![(user, 2, false)]);
+    }
+}
@@ -1,6 +1,6 @@
 use std::panic;
 
-use naia_server_socket::{NaiaServerSocketError, Packet, PacketReceiver, PacketSender};
+use naia_server_socket::{NaiaServerSocketError, Packet};
 
 pub use naia_shared::{
     wrapping_diff, BaseConnection, ConnectionConfig, Instant, KeyGenerator, LocalComponentKey,
@@ -9,38 +9,35 @@ pub use naia_shared::{
     Timestamp, WorldMutType, WorldRefType,
 };
 
+use super::transport::ServerTransport;
+
 pub struct Io {
-    packet_sender: Option<PacketSender>,
-    packet_receiver: Option<PacketReceiver>,
+    transport: Option<Box<dyn ServerTransport>>,
 }
 
 impl Io {
     pub fn new() -> Self {
-        Io {
-            packet_sender: None,
-            packet_receiver: None,
-        }
+        Io { transport: None }
     }
 
-    pub fn load(&mut self, packet_sender: PacketSender, packet_receiver: PacketReceiver) {
-        if self.packet_sender.is_some() {
+    pub fn load(&mut self, transport: Box<dyn ServerTransport>) {
+        if self.transport.is_some() {
             panic!("Packet sender/receiver already loaded! Cannot do this twice!");
         }
 
-        self.packet_sender = Some(packet_sender);
-        self.packet_receiver = Some(packet_receiver);
+        self.transport = Some(transport);
     }
 
-    pub fn send_packet(&self, packet: Packet) {
-        self.packet_sender
-            .as_ref()
+    pub fn send_packet(&mut self, packet: Packet) {
+        self.transport
+            .as_mut()
             .expect("Cannot call Server.send_packet() until you call Server.listen()!")
             .send(packet);
     }
 
     pub fn receive_packet(&mut self) -> Result<Option<Packet>, NaiaServerSocketError> {
         return self
-            .packet_receiver
+            .transport
             .as_mut()
             .expect("Cannot call Server.receive_packet() until you call Server.listen()!")
             .receive();
@@ -24,13 +24,17 @@ compile_error!("Naia Server can only use UDP or WebRTC, you must pick one");
 #[cfg(all(not(feature = "use-udp"), not(feature = "use-webrtc")))]
 compile_error!("Naia Server requires either the 'use-udp' or 'use-webrtc' feature to be enabled, you must pick one.");
 
-pub use naia_server_socket::ServerAddrs;
+pub use naia_server_socket::{NaiaServerSocketError, Packet, ServerAddrs};
 
 pub use naia_shared::{
-    LinkConditionerConfig, ProtocolType, Random, ReplicaMutWrapper, Replicate, SharedConfig,
-    SocketConfig, WorldMutType, WorldRefType,
+    blob_hash, BlobHash, ChannelConfig, ChannelIndex, ChannelMode, Channels, CongestionConfig,
+    DefaultChannels, LinkConditionerConfig, MemoryNetwork, ProtocolType, Random, ReplicaMutWrapper,
+    Replicate, SharedConfig, SocketConfig, WorldMutType, WorldRefType,
 };
 
+mod client_entity_action;
+mod client_entity_receiver;
+mod client_entity_record;
 mod command_receiver;
 mod connection;
 mod entity_action;
@@ -42,6 +46,7 @@ mod event;
 mod global_diff_handler;
 mod global_entity_record;
 mod handshake_manager;
+mod interest_manager;
 mod io;
 mod keys;
 mod local_component_record;
@@ -51,20 +56,31 @@ mod mut_channel;
 mod packet_writer;
 mod ping_manager;
 mod room;
+mod scope_check_cache;
 mod server;
 mod server_config;
 mod tick_manager;
+mod transport;
 mod user;
 mod user_diff_handler;
 mod user_scope;
 mod world_record;
 
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+
+#[cfg(test)]
+mod test_protocol;
+
 pub use entity_ref::{EntityMut, EntityRef};
 pub use error::NaiaServerError;
 pub use event::Event;
+pub use interest_manager::InterestConfig;
 pub use keys::ComponentKey;
 pub use room::{room_key::RoomKey, RoomMut, RoomRef};
 pub use server::Server;
 pub use server_config::ServerConfig;
+pub use transport::{MemoryServerTransport, ServerTransport};
 pub use user::{user_key::UserKey, User, UserMut, UserRef};
 pub use user_scope::UserScopeMut;
@@ -47,6 +47,22 @@ impl MutChannel {
         }
         return false;
     }
+
+    pub fn send_nested(&self, diff: u8, path: &[u16]) -> bool {
+        if let Ok(data) = self.data.as_ref().read() {
+            data.send_nested(diff, path);
+            return true;
+        }
+        return false;
+    }
+
+    pub fn send_all(&self) -> bool {
+        if let Ok(data) = self.data.as_ref().read() {
+            data.send_all();
+            return true;
+        }
+        return false;
+    }
 }
 
 struct MutChannelData {
@@ -78,6 +94,18 @@ impl MutChannelData {
             receiver.mutate(diff);
         }
     }
+
+    pub fn send_nested(&self, diff: u8, path: &[u16]) {
+        for (_, receiver) in self.recv_map.iter() {
+            receiver.mutate_nested(diff, path);
+        }
+    }
+
+    pub fn send_all(&self) {
+        for (_, receiver) in self.recv_map.iter() {
+            receiver.mutate_all();
+        }
+    }
 }
 
 // MutReceiver
@@ -103,6 +131,18 @@ impl MutReceiver {
         }
     }
 
+    pub fn mutate_nested(&self, diff: u8, path: &[u16]) {
+        if let Ok(mut mask) = self.mask.as_ref().write() {
+            mask.set_nested_bit(diff, path);
+        }
+    }
+
+    pub fn mutate_all(&self) {
+        if let Ok(mut mask) = self.mask.as_ref().write() {
+            mask.set_all();
+        }
+    }
+
     pub fn or_mask(&self, other_mask: &DiffMask) {
         if let Ok(mut mask) = self.mask.as_ref().write() {
             mask.or(other_mask);
@@ -140,6 +180,10 @@ impl PropertyMutate for MutSender {
     fn mutate(&mut self, property_index: u8) {
         self.channel.send(property_index);
     }
+
+    fn mutate_nested(&mut self, property_index: u8, path: &[u16]) {
+        self.channel.send_nested(property_index, path);
+    }
 }
 
 // MutReceiverBuilder
@@ -157,4 +201,8 @@ impl MutReceiverBuilder {
     pub fn build(&self, addr: &SocketAddr) -> Option<MutReceiver> {
         return self.channel.new_receiver(addr);
     }
+
+    pub fn send_all(&self) -> bool {
+        return self.channel.send_all();
+    }
 }
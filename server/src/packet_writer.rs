@@ -1,14 +1,16 @@
-use byteorder::WriteBytesExt;
+use naia_shared::{
+    BitWriter, BlobPacketWriter, BlobRecord, ManagerType, MessagePacketWriter, OutgoingMessage,
+    ProtocolType, SECTION_OVERHEAD_BITS,
+};
 
-use naia_shared::{ManagerType, MessagePacketWriter, ProtocolType};
-
-/// Handles writing of Message/Component data into an outgoing packet
+/// Handles writing of Message/Component/Blob data into an outgoing packet
 pub struct PacketWriter {
     message_writer: MessagePacketWriter,
-    /// bytes representing outgoing Message/Component messages / updates
-    pub entity_working_bytes: Vec<u8>,
+    blob_writer: BlobPacketWriter,
+    /// bits representing outgoing Message/Component messages / updates
+    pub entity_working_bits: BitWriter,
     /// number of Message/Component messages to be written
-    pub entity_action_count: u8,
+    pub entity_action_count: u16,
 }
 
 impl PacketWriter {
@@ -17,45 +19,64 @@ impl PacketWriter {
     pub fn new() -> PacketWriter {
         PacketWriter {
             message_writer: MessagePacketWriter::new(),
-            entity_working_bytes: Vec::<u8>::new(),
+            blob_writer: BlobPacketWriter::new(),
+            entity_working_bits: BitWriter::new(),
             entity_action_count: 0,
         }
     }
 
     /// Returns whether the writer has bytes to write into the outgoing packet
     pub fn has_bytes(&self) -> bool {
-        return self.message_writer.has_bytes() || self.entity_action_count != 0;
+        return self.message_writer.has_bytes()
+            || self.entity_action_count != 0
+            || self.blob_writer.has_bytes();
     }
 
     /// Gets the bytes to write into an outgoing packet
     pub fn get_bytes(&mut self) -> Box<[u8]> {
-        let mut out_bytes = Vec::<u8>::new();
+        let mut writer = BitWriter::new();
 
-        self.message_writer.get_bytes(&mut out_bytes);
+        self.message_writer.get_bytes(&mut writer);
 
-        //Write manager "header" (manager type & entity action count)
+        //Write manager "header" (manager type)
         if self.entity_action_count != 0 {
-            out_bytes.write_u8(ManagerType::Entity as u8).unwrap(); // write
-                                                                    // manager
-                                                                    // type
-            out_bytes.write_u8(self.entity_action_count).unwrap(); // write number of actions
-            out_bytes.append(&mut self.entity_working_bytes); // write entity payload
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::Entity.write(&mut writer); // write manager type
+            writer.append(&self.entity_working_bits); // write entity payload
+            writer.write_bit(false); // write that there are no more actions
 
+            self.entity_working_bits = BitWriter::new();
             self.entity_action_count = 0;
         }
 
-        out_bytes.into_boxed_slice()
+        self.blob_writer.get_bytes(&mut writer);
+
+        writer.write_bit(false); // write that there are no more manager sections
+
+        writer.to_bytes()
     }
 
-    /// Get the number of bytes which is ready to be written into an outgoing
+    /// Get the number of bits which is ready to be written into an outgoing
     /// packet
-    pub fn bytes_number(&self) -> usize {
-        return self.message_writer.bytes_number() + self.entity_working_bytes.len();
+    pub fn bits_number(&self) -> usize {
+        let mut output = self.message_writer.bits_number();
+        if self.entity_action_count != 0 {
+            output += self.entity_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
+        }
+        output += self.blob_writer.bits_number();
+        return output;
     }
 
     /// Writes an Message into the Writer's internal buffer, which will
     /// eventually be put into the outgoing packet
-    pub fn write_message<P: ProtocolType>(&mut self, message: &P) -> bool {
+    pub fn write_message<P: ProtocolType>(&mut self, message: &OutgoingMessage<P>) -> bool {
         return self.message_writer.write_message(message);
     }
+
+    /// Writes a Blob record into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet
+    pub fn write_blob_record(&mut self, record: &BlobRecord) -> bool {
+        let packet_bits_number = self.bits_number() - self.blob_writer.bits_number();
+        return self.blob_writer.write_record(record, packet_bits_number);
+    }
 }
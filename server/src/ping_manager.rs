@@ -1,24 +1,107 @@
+use std::{sync::Arc, time::Duration};
+
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
-use naia_shared::PacketReader;
+use naia_shared::{Clock, ClockTimer, DecodeError, PacketReader, SequenceBuffer, SequenceNumber};
 
-#[derive(Debug)]
-pub struct PingManager {}
+#[derive(Clone)]
+struct SentPing {
+    time_sent: Duration,
+}
+
+pub struct PingManager {
+    clock: Arc<dyn Clock>,
+    ping_timer: ClockTimer,
+    sent_pings: SequenceBuffer<SentPing>,
+    ping_index: SequenceNumber,
+    samples: f32,
+    max_samples: f32,
+    rtt_average: f32,
+}
 
 impl PingManager {
-    pub fn new() -> Self {
-        PingManager {}
+    pub fn new(ping_interval: Duration, rtt_sample_size: u16, clock: &Arc<dyn Clock>) -> Self {
+        PingManager {
+            clock: clock.clone(),
+            ping_timer: ClockTimer::new(clock, ping_interval),
+            sent_pings: SequenceBuffer::with_capacity(rtt_sample_size),
+            ping_index: 0,
+            samples: 0.0,
+            max_samples: f32::from(rtt_sample_size),
+            rtt_average: 0.0,
+        }
     }
 
     /// Process an incoming ping payload
-    pub fn process_ping(&self, ping_payload: &[u8]) -> Box<[u8]> {
+    pub fn process_ping(&self, ping_payload: &[u8]) -> Result<Box<[u8]>, DecodeError> {
         // read incoming ping index
         let mut reader = PacketReader::new(&ping_payload);
-        let ping_index = reader.get_cursor().read_u16::<BigEndian>().unwrap();
+        let ping_index = reader
+            .get_cursor()
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
 
         // write pong payload
         let mut out_bytes = Vec::<u8>::new();
         out_bytes.write_u16::<BigEndian>(ping_index).unwrap(); // write index
+        Ok(out_bytes.into_boxed_slice())
+    }
+
+    /// Returns whether a ping message should be sent
+    pub fn should_send_ping(&self) -> bool {
+        self.ping_timer.ringing()
+    }
+
+    /// Get an outgoing ping payload
+    pub fn get_ping_payload(&mut self) -> Box<[u8]> {
+        self.ping_timer.reset();
+
+        self.sent_pings.insert(
+            self.ping_index,
+            SentPing {
+                time_sent: self.clock.now(),
+            },
+        );
+
+        let mut out_bytes = Vec::<u8>::new();
+        out_bytes.write_u16::<BigEndian>(self.ping_index).unwrap(); // write index
+
+        // increment ping index
+        self.ping_index = self.ping_index.wrapping_add(1);
+
         out_bytes.into_boxed_slice()
     }
+
+    /// Process an incoming pong payload
+    pub fn process_pong(&mut self, pong_payload: &[u8]) -> Result<(), DecodeError> {
+        let mut reader = PacketReader::new(&pong_payload);
+        let ping_index = reader
+            .get_cursor()
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+
+        if let Some(ping) = self.sent_pings.remove(ping_index) {
+            let rtt_millis = self
+                .clock
+                .now()
+                .saturating_sub(ping.time_sent)
+                .as_secs_f32()
+                * 1000.0;
+            self.process_new_rtt(rtt_millis);
+        }
+        Ok(())
+    }
+
+    /// Gets the current calculated average Round Trip Time to the remote host,
+    /// in milliseconds. Returns 0 until the first pong has been received
+    pub fn get_rtt(&self) -> f32 {
+        return self.rtt_average;
+    }
+
+    fn process_new_rtt(&mut self, rtt_millis: f32) {
+        self.rtt_average = ((self.rtt_average * self.samples) + rtt_millis) / (self.samples + 1.0);
+        if self.samples < self.max_samples {
+            self.samples += 1.0;
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_set::Iter, HashSet, VecDeque},
+    collections::{hash_set::Iter, HashSet},
     hash::Hash,
 };
 
@@ -15,7 +15,8 @@ pub mod room_key {
 pub struct Room<E: Copy + Eq + Hash> {
     users: HashSet<UserKey>,
     entities: HashSet<E>,
-    entity_removal_queue: VecDeque<(UserKey, E)>,
+    parent: Option<RoomKey>,
+    children: HashSet<RoomKey>,
 }
 
 impl<E: Copy + Eq + Hash> Room<E> {
@@ -23,7 +24,8 @@ impl<E: Copy + Eq + Hash> Room<E> {
         Room {
             users: HashSet::new(),
             entities: HashSet::new(),
-            entity_removal_queue: VecDeque::new(),
+            parent: None,
+            children: HashSet::new(),
         }
     }
 
@@ -33,15 +35,12 @@ impl<E: Copy + Eq + Hash> Room<E> {
         return self.users.contains(user_key);
     }
 
-    pub(crate) fn subscribe_user(&mut self, user_key: &UserKey) {
-        self.users.insert(*user_key);
+    pub(crate) fn subscribe_user(&mut self, user_key: &UserKey) -> bool {
+        return self.users.insert(*user_key);
     }
 
-    pub(crate) fn unsubscribe_user(&mut self, user_key: &UserKey) {
-        self.users.remove(user_key);
-        for entity in self.entities.iter() {
-            self.entity_removal_queue.push_back((*user_key, *entity));
-        }
+    pub(crate) fn unsubscribe_user(&mut self, user_key: &UserKey) -> bool {
+        return self.users.remove(user_key);
     }
 
     pub(crate) fn user_keys(&self) -> Iter<UserKey> {
@@ -54,32 +53,43 @@ impl<E: Copy + Eq + Hash> Room<E> {
 
     // Entities
 
-    pub(crate) fn add_entity(&mut self, entity: &E) {
-        self.entities.insert(*entity);
+    pub(crate) fn add_entity(&mut self, entity: &E) -> bool {
+        return self.entities.insert(*entity);
     }
 
     pub(crate) fn remove_entity(&mut self, entity: &E) -> bool {
-        if self.entities.remove(entity) {
-            for user_key in self.users.iter() {
-                self.entity_removal_queue.push_back((*user_key, *entity));
-            }
-            return true;
-        } else {
-            panic!("Room does not contain Entity");
-        }
+        return self.entities.remove(entity);
     }
 
     pub(crate) fn entities(&self) -> Iter<E> {
         return self.entities.iter();
     }
 
-    pub(crate) fn pop_entity_removal_queue(&mut self) -> Option<(UserKey, E)> {
-        return self.entity_removal_queue.pop_front();
-    }
-
     pub(crate) fn entities_count(&self) -> usize {
         return self.entities.len();
     }
+
+    // Hierarchy
+
+    pub(crate) fn parent(&self) -> Option<RoomKey> {
+        return self.parent;
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<RoomKey>) {
+        self.parent = parent;
+    }
+
+    pub(crate) fn children(&self) -> Iter<RoomKey> {
+        return self.children.iter();
+    }
+
+    pub(crate) fn add_child(&mut self, child: &RoomKey) {
+        self.children.insert(*child);
+    }
+
+    pub(crate) fn remove_child(&mut self, child: &RoomKey) {
+        self.children.remove(child);
+    }
 }
 
 // room references
@@ -125,6 +135,16 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash> RoomRef<'s, P, E> {
     pub fn entities_count(&self) -> usize {
         return self.server.room_entities_count(&self.key);
     }
+
+    // Hierarchy
+
+    pub fn parent(&self) -> Option<RoomKey> {
+        return self.server.room_parent(&self.key);
+    }
+
+    pub fn children(&self) -> Vec<RoomKey> {
+        return self.server.room_children(&self.key);
+    }
 }
 
 // RoomMut
@@ -189,4 +209,30 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash> RoomMut<'s, P, E> {
     pub fn entities_count(&self) -> usize {
         return self.server.room_entities_count(&self.key);
     }
+
+    // Hierarchy
+
+    pub fn parent(&self) -> Option<RoomKey> {
+        return self.server.room_parent(&self.key);
+    }
+
+    pub fn children(&self) -> Vec<RoomKey> {
+        return self.server.room_children(&self.key);
+    }
+
+    /// Nests this Room inside another. Users in the parent Room, or any of
+    /// its ancestors, are also related to the Entities in this Room & all of
+    /// its descendants
+    pub fn set_parent(&mut self, parent_key: &RoomKey) -> &mut Self {
+        self.server.room_set_parent(&self.key, Some(parent_key));
+
+        self
+    }
+
+    /// Detaches this Room from its parent Room, if it has one
+    pub fn remove_parent(&mut self) -> &mut Self {
+        self.server.room_set_parent(&self.key, None);
+
+        self
+    }
 }
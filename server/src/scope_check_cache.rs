@@ -0,0 +1,217 @@
+use std::{
+    collections::{hash_map::Keys, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use super::{room::room_key::RoomKey, user::user_key::UserKey};
+
+/// Keeps every Entity Scope Set (a unique combination of a Room, a User in
+/// that Room or one of its ancestors & an Entity in that Room), updated as
+/// Users & Entities enter and leave Rooms, rather than being rebuilt every
+/// time it is needed.
+///
+/// A User can be related to an Entity through several Rooms, so each Scope
+/// Set & each User / Entity pair is reference counted, and a pair is only
+/// released once the last Room relating them is gone.
+///
+/// Pairs whose scope may have changed are tracked apart from the dirty Scope
+/// Sets returned to the application, so that the Server only evaluates those
+pub struct ScopeCheckCache<E: Copy + Eq + Hash> {
+    checks: HashMap<(RoomKey, UserKey, E), usize>,
+    pairs: HashMap<(UserKey, E), usize>,
+    dirty_checks: HashSet<(RoomKey, UserKey, E)>,
+    changed_pairs: HashSet<(UserKey, E)>,
+    released_pairs: VecDeque<(UserKey, E)>,
+}
+
+impl<E: Copy + Eq + Hash> ScopeCheckCache<E> {
+    pub fn new() -> Self {
+        ScopeCheckCache {
+            checks: HashMap::new(),
+            pairs: HashMap::new(),
+            dirty_checks: HashSet::new(),
+            changed_pairs: HashSet::new(),
+            released_pairs: VecDeque::new(),
+        }
+    }
+
+    /// Adds a reference to a Scope Set. New Scope Sets start out dirty so
+    /// that they are evaluated at least once
+    pub fn insert(&mut self, room_key: &RoomKey, user_key: &UserKey, entity: &E) {
+        let check = (*room_key, *user_key, *entity);
+        let count = self.checks.entry(check).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            self.dirty_checks.insert(check);
+        }
+        let pair = (*user_key, *entity);
+        let count = self.pairs.entry(pair).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            self.changed_pairs.insert(pair);
+        }
+    }
+
+    /// Removes a reference to a Scope Set
+    pub fn remove(&mut self, room_key: &RoomKey, user_key: &UserKey, entity: &E) {
+        let check = (*room_key, *user_key, *entity);
+        if !self.checks.contains_key(&check) {
+            return;
+        }
+        if release(&mut self.checks, &check) {
+            self.dirty_checks.remove(&check);
+        }
+
+        // every reference to a Scope Set is also a reference to its pair
+        let pair = (*user_key, *entity);
+        if release(&mut self.pairs, &pair) {
+            self.released_pairs.push_back(pair);
+        }
+    }
+
+    /// Marks an existing Scope Set as needing to be evaluated again
+    pub fn mark_dirty(&mut self, room_key: &RoomKey, user_key: &UserKey, entity: &E) {
+        let check = (*room_key, *user_key, *entity);
+        if self.checks.contains_key(&check) {
+            self.dirty_checks.insert(check);
+        }
+    }
+
+    pub fn iter(&self) -> Keys<(RoomKey, UserKey, E), usize> {
+        return self.checks.keys();
+    }
+
+    pub fn has_pair(&self, user_key: &UserKey, entity: &E) -> bool {
+        return self.pairs.contains_key(&(*user_key, *entity));
+    }
+
+    /// Returns all Scope Sets marked dirty since the last call
+    pub fn take_dirty(&mut self) -> Vec<(RoomKey, UserKey, E)> {
+        return self.dirty_checks.drain().collect();
+    }
+
+    /// Marks a User / Entity pair as needing its scope evaluated again by the
+    /// Server
+    pub fn mark_pair_changed(&mut self, user_key: &UserKey, entity: &E) {
+        self.changed_pairs.insert((*user_key, *entity));
+    }
+
+    /// Marks every pair containing the given User as needing its scope
+    /// evaluated again by the Server
+    pub fn mark_user_changed(&mut self, user_key: &UserKey) {
+        for (pair_user_key, entity) in self.pairs.keys() {
+            if pair_user_key == user_key {
+                self.changed_pairs.insert((*pair_user_key, *entity));
+            }
+        }
+    }
+
+    /// Returns all pairs which have been related, or marked as changed, since
+    /// the last call
+    pub fn take_changed_pairs(&mut self) -> Vec<(UserKey, E)> {
+        return self.changed_pairs.drain().collect();
+    }
+
+    /// Pops the next User / Entity pair which is no longer related through
+    /// any Room
+    pub fn pop_released_pair(&mut self) -> Option<(UserKey, E)> {
+        return self.released_pairs.pop_front();
+    }
+}
+
+// Decrements the count at the given key, removing it once it reaches zero.
+// Returns true if the key was removed
+fn release<K: Eq + Hash>(counts: &mut HashMap<K, usize>, key: &K) -> bool {
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+            return true;
+        }
+    }
+    return false;
+}
+
+#[cfg(test)]
+mod scope_check_cache_tests {
+    use slotmap::DenseSlotMap;
+
+    use super::ScopeCheckCache;
+    use crate::{room::room_key::RoomKey, user::user_key::UserKey};
+
+    fn keys<K: slotmap::Key>(count: usize) -> Vec<K> {
+        let mut map: DenseSlotMap<K, ()> = DenseSlotMap::with_key();
+        return (0..count).map(|_| map.insert(())).collect();
+    }
+
+    #[test]
+    fn changed_pairs() {
+        let rooms: Vec<RoomKey> = keys(2);
+        let users: Vec<UserKey> = keys(2);
+        let mut cache: ScopeCheckCache<u32> = ScopeCheckCache::new();
+
+        // a newly related pair is changed, but not once related again through
+        // another Room
+        cache.insert(&rooms[0], &users[0], &1);
+        assert_eq!(cache.take_changed_pairs(), vec![(users[0], 1)]);
+        cache.insert(&rooms[1], &users[0], &1);
+        assert!(cache.take_changed_pairs().is_empty());
+        assert_eq!(cache.take_dirty().len(), 2);
+
+        cache.mark_pair_changed(&users[0], &1);
+        assert_eq!(cache.take_changed_pairs(), vec![(users[0], 1)]);
+
+        cache.insert(&rooms[0], &users[1], &2);
+        cache.take_changed_pairs();
+        cache.mark_user_changed(&users[1]);
+        assert_eq!(cache.take_changed_pairs(), vec![(users[1], 2)]);
+        assert!(cache.take_changed_pairs().is_empty());
+    }
+
+    #[test]
+    fn nested_rooms() {
+        let rooms: Vec<RoomKey> = keys(1);
+        let users: Vec<UserKey> = keys(1);
+        let mut cache: ScopeCheckCache<u32> = ScopeCheckCache::new();
+
+        // the User is in a child Room & its parent, so relates to the child
+        // Room's Entity twice
+        cache.insert(&rooms[0], &users[0], &1);
+        cache.insert(&rooms[0], &users[0], &1);
+        assert_eq!(cache.iter().count(), 1);
+
+        cache.remove(&rooms[0], &users[0], &1);
+        assert!(cache.has_pair(&users[0], &1));
+        assert_eq!(cache.pop_released_pair(), None);
+
+        cache.remove(&rooms[0], &users[0], &1);
+        assert!(!cache.has_pair(&users[0], &1));
+        assert_eq!(cache.pop_released_pair(), Some((users[0], 1)));
+        assert_eq!(cache.iter().count(), 0);
+    }
+
+    #[test]
+    fn multiple_rooms() {
+        let rooms: Vec<RoomKey> = keys(2);
+        let users: Vec<UserKey> = keys(1);
+        let mut cache: ScopeCheckCache<u32> = ScopeCheckCache::new();
+
+        // the User & Entity share two Rooms
+        cache.insert(&rooms[0], &users[0], &1);
+        cache.insert(&rooms[1], &users[0], &1);
+        cache.insert(&rooms[0], &users[0], &1);
+
+        cache.remove(&rooms[1], &users[0], &1);
+        cache.remove(&rooms[0], &users[0], &1);
+        assert!(cache.has_pair(&users[0], &1));
+        assert_eq!(cache.pop_released_pair(), None);
+
+        // removing a Scope Set which does not exist has no effect
+        cache.remove(&rooms[1], &users[0], &1);
+        assert!(cache.has_pair(&users[0], &1));
+
+        cache.remove(&rooms[0], &users[0], &1);
+        assert_eq!(cache.pop_released_pair(), Some((users[0], 1)));
+        assert!(cache.take_dirty().is_empty());
+    }
+}
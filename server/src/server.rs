@@ -4,6 +4,7 @@ use std::{
     net::SocketAddr,
     panic,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use slotmap::DenseSlotMap;
@@ -11,13 +12,16 @@ use slotmap::DenseSlotMap;
 use naia_server_socket::{Packet, ServerAddrs, Socket};
 
 pub use naia_shared::{
-    wrapping_diff, BaseConnection, ConnectionConfig, Instant, KeyGenerator, LocalComponentKey,
+    wrapping_diff, BaseConnection, BlobEvent, ChannelConfig, ChannelIndex, Clock, ClockTimer,
+    CongestionConfig, ConnectionConfig, DecodeError, Instant, KeyGenerator, LocalComponentKey,
     ManagerType, Manifest, PacketReader, PacketType, PropertyMutate, PropertyMutator,
-    ProtocolKindType, ProtocolType, Replicate, ReplicateSafe, SharedConfig, StandardHeader, Timer,
-    Timestamp, WorldMutType, WorldRefType,
+    ProtocolInserter, ProtocolKindType, ProtocolType, Replicate, ReplicateSafe, SendMessageError,
+    SharedConfig, StandardHeader, Timer, Timestamp, WorldMutType, WorldRefType,
 };
 
 use super::{
+    client_entity_action::ClientEntityAction,
+    client_entity_record::ClientEntityRecord,
     connection::Connection,
     entity_ref::{EntityMut, EntityRef, WorldlessEntityMut},
     entity_scope_map::EntityScopeMap,
@@ -26,11 +30,14 @@ use super::{
     global_diff_handler::GlobalDiffHandler,
     global_entity_record::GlobalEntityRecord,
     handshake_manager::{HandshakeManager, HandshakeResult},
+    interest_manager::InterestManager,
     io::Io,
     keys::ComponentKey,
     room::{room_key::RoomKey, Room, RoomMut, RoomRef},
+    scope_check_cache::ScopeCheckCache,
     server_config::ServerConfig,
     tick_manager::TickManager,
+    transport::{ServerTransport, SocketTransport},
     user::{user_key::UserKey, User, UserMut, UserRef},
     user_scope::UserScopeMut,
     world_record::WorldRecord,
@@ -42,11 +49,16 @@ use super::{
 pub struct Server<P: ProtocolType, E: Copy + Eq + Hash> {
     // Config
     manifest: Manifest<P>,
+    delta_compression: bool,
+    channel_configs: Vec<ChannelConfig>,
+    congestion_config: CongestionConfig,
+    max_client_entities: usize,
+    clock: Arc<dyn Clock>,
     // Connection
     connection_config: ConnectionConfig,
     socket: Socket,
     io: Io,
-    heartbeat_timer: Timer,
+    heartbeat_timer: ClockTimer,
     handshake_manager: HandshakeManager<P>,
     // Users
     users: DenseSlotMap<UserKey, User>,
@@ -57,13 +69,19 @@ pub struct Server<P: ProtocolType, E: Copy + Eq + Hash> {
     world_record: WorldRecord<E, P::Kind>,
     entity_records: HashMap<E, GlobalEntityRecord>,
     entity_scope_map: EntityScopeMap<E>,
+    scope_check_cache: ScopeCheckCache<E>,
+    interest_manager: InterestManager<E>,
+    client_entity_records: HashMap<E, ClientEntityRecord>,
+    orphaned_client_entities: VecDeque<E>,
     // Components
     diff_handler: Arc<RwLock<GlobalDiffHandler>>,
+    component_authorities: HashMap<ComponentKey, UserKey>,
     // Events
     outstanding_connects: VecDeque<(SocketAddr, UserKey)>,
     outstanding_disconnects: VecDeque<UserKey>,
     outstanding_auths: VecDeque<(UserKey, P)>,
     outstanding_errors: VecDeque<NaiaServerError>,
+    outstanding_client_spawns: VecDeque<(UserKey, E)>,
     // Ticks
     tick_manager: Option<TickManager>,
 }
@@ -83,11 +101,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
 
         let socket = Socket::new(server_config.socket_config);
 
-        let heartbeat_timer = Timer::new(connection_config.heartbeat_interval);
+        let heartbeat_timer =
+            ClockTimer::new(&server_config.clock, connection_config.heartbeat_interval);
+
+        let handshake_manager =
+            HandshakeManager::new(server_config.require_auth, &shared_config.channels);
 
         let tick_manager = {
             if let Some(duration) = shared_config.tick_interval {
-                Some(TickManager::new(duration))
+                Some(TickManager::new(
+                    duration,
+                    &server_config.clock,
+                    server_config.manual_tick,
+                ))
             } else {
                 None
             }
@@ -96,12 +122,17 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         Server {
             // Config
             manifest: shared_config.manifest,
+            delta_compression: shared_config.delta_compression,
+            channel_configs: shared_config.channels,
+            congestion_config: server_config.congestion_config,
+            max_client_entities: server_config.max_client_entities,
+            clock: server_config.clock,
             // Connection
             connection_config,
             socket,
             io: Io::new(),
             heartbeat_timer,
-            handshake_manager: HandshakeManager::new(server_config.require_auth),
+            handshake_manager,
             // Users
             users: DenseSlotMap::with_key(),
             user_connections: HashMap::new(),
@@ -111,13 +142,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             world_record: WorldRecord::new(),
             entity_records: HashMap::new(),
             entity_scope_map: EntityScopeMap::new(),
+            scope_check_cache: ScopeCheckCache::new(),
+            interest_manager: InterestManager::new(&server_config.interest_config),
+            client_entity_records: HashMap::new(),
+            orphaned_client_entities: VecDeque::new(),
             // Components
             diff_handler: Arc::new(RwLock::new(GlobalDiffHandler::new())),
+            component_authorities: HashMap::new(),
             // Events
             outstanding_auths: VecDeque::new(),
             outstanding_connects: VecDeque::new(),
             outstanding_disconnects: VecDeque::new(),
             outstanding_errors: VecDeque::new(),
+            outstanding_client_spawns: VecDeque::new(),
             // Ticks
             tick_manager,
         }
@@ -126,10 +163,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// Listen at the given addresses
     pub fn listen(&mut self, server_addrs: ServerAddrs) {
         self.socket.listen(server_addrs);
-        self.io.load(
+        self.io.load(Box::new(SocketTransport::new(
             self.socket.get_packet_sender(),
             self.socket.get_packet_receiver(),
-        );
+        )));
+    }
+
+    /// Listen through the given transport rather than the Server's socket,
+    /// for example a MemoryServerTransport in tests
+    pub fn listen_with_transport<T: ServerTransport + 'static>(&mut self, transport: T) {
+        self.io.load(Box::new(transport));
     }
 
     /// Must be called regularly, maintains connection to and receives messages
@@ -153,10 +196,17 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                 user_address,
                 &user_key,
                 &self.diff_handler,
+                self.delta_compression,
+                &self.channel_configs,
+                &self.congestion_config,
+                self.max_client_entities,
+                &self.clock,
             );
             self.handshake_manager
                 .send_connect_accept_response(&mut self.io, &mut new_connection);
             self.user_connections.insert(user_address, new_connection);
+            // the User may have been added to Rooms before connecting
+            self.scope_check_cache.mark_user_changed(&user_key);
             events.push_back(Ok(Event::Connection(user_key)));
         }
 
@@ -167,6 +217,11 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             }
         }
 
+        // new client-spawned entities
+        while let Some((user_key, entity)) = self.outstanding_client_spawns.pop_front() {
+            events.push_back(Ok(Event::ClientSpawnedEntity(user_key, entity)));
+        }
+
         // TODO: have 1 single queue for commands/messages from all users, as it's
         // possible this current technique unfairly favors the 1st users in
         // self.user_connections
@@ -188,6 +243,12 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             while let Some(message) = connection.get_incoming_message() {
                 events.push_back(Ok(Event::Message(connection.user_key, message)));
             }
+            //receive blob deliveries
+            while let Some(blob_event) = connection.get_blob_event() {
+                if let BlobEvent::Delivered { name } = blob_event {
+                    events.push_back(Ok(Event::BlobDelivered(connection.user_key, name)));
+                }
+            }
         }
 
         // new errors
@@ -195,9 +256,9 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             events.push_back(Err(err));
         }
 
-        // tick event
+        // tick events
         if let Some(tick_manager) = &mut self.tick_manager {
-            if tick_manager.should_tick() {
+            while tick_manager.should_tick() {
                 events.push_back(Ok(Event::Tick));
             }
         }
@@ -219,22 +280,55 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// Rejects an incoming Client User, terminating their attempt to establish
     /// a connection with the Server
     pub fn reject_connection(&mut self, user_key: &UserKey) {
+        if let Some(user) = self.users.get(*user_key) {
+            self.handshake_manager
+                .send_reject_response(&mut self.io, &user.address);
+        }
         self.delete_user(user_key);
     }
 
     // Messages
 
     /// Queues up an Message to be sent to the Client associated with a given
-    /// UserKey
-    pub fn send_message<R: ReplicateSafe<P>>(
+    /// UserKey, through the given Channel
+    pub fn send_message<R: ReplicateSafe<P>, C: ChannelIndex>(
         &mut self,
         user_key: &UserKey,
         message: &R,
-        guaranteed_delivery: bool,
+        channel: C,
     ) {
         if let Some(user) = self.users.get(*user_key) {
             if let Some(connection) = self.user_connections.get_mut(&user.address) {
-                connection.send_message(message, guaranteed_delivery);
+                match connection.send_message(message, channel) {
+                    Ok(()) => {}
+                    Err(SendMessageError::QueueOverflow) => {
+                        self.outstanding_errors
+                            .push_back(NaiaServerError::QueueOverflow(*user_key));
+                    }
+                    Err(SendMessageError::ChannelNotFound(channel_index)) => {
+                        self.outstanding_errors
+                            .push_back(NaiaServerError::ChannelNotFound(channel_index));
+                    }
+                    Err(SendMessageError::MessageTooLarge) => {
+                        self.outstanding_errors
+                            .push_back(NaiaServerError::MessageTooLarge(*user_key));
+                    }
+                }
+            }
+        }
+    }
+
+    // Blobs
+
+    /// Queues up a named Blob of bytes (a map file, texture, script, etc.) to
+    /// be streamed in the background to the Client associated with a given
+    /// UserKey. Clients which already hold the Blob in their BlobStore will
+    /// skip the transfer. Panics if the Blob is larger than
+    /// MAX_BLOB_SIZE_BYTES
+    pub fn send_blob<B: Into<Arc<[u8]>>>(&mut self, user_key: &UserKey, name: &str, bytes: B) {
+        if let Some(user) = self.users.get(*user_key) {
+            if let Some(connection) = self.user_connections.get_mut(&user.address) {
+                connection.send_blob(name, bytes.into());
             }
         }
     }
@@ -251,37 +345,130 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// a related Room, User, and Entity, used to determine which Entities to
     /// replicate to which Users
     pub fn scope_checks(&self) -> Vec<(RoomKey, UserKey, E)> {
-        let mut list: Vec<(RoomKey, UserKey, E)> = Vec::new();
+        return self.scope_check_cache.iter().copied().collect();
+    }
 
-        // TODO: precache this, instead of generating a new list every call
-        // likely this is called A LOT
-        for (room_key, room) in self.rooms.iter() {
-            for user_key in room.user_keys() {
-                for entity in room.entities() {
-                    list.push((room_key, *user_key, *entity));
-                }
+    /// Like `scope_checks`, but only returns the Entity Scope Sets which have
+    /// been created, or marked dirty, since the last call. The remaining
+    /// Entity Scope Sets keep their last scope, so only changed pairs need to
+    /// be evaluated again
+    pub fn dirty_scope_checks(&mut self) -> Vec<(RoomKey, UserKey, E)> {
+        return self.scope_check_cache.take_dirty();
+    }
+
+    /// Marks every Entity Scope Set containing the given Entity as dirty, to
+    /// be returned by the next call to `dirty_scope_checks`
+    pub fn mark_entity_scope_dirty(&mut self, entity: &E) {
+        for room_key in self.entity_rooms(entity) {
+            let user_keys = self.users_of_rooms(&self.room_and_ancestors(&room_key));
+            for user_key in user_keys {
+                self.scope_check_cache
+                    .mark_dirty(&room_key, &user_key, entity);
             }
         }
+    }
 
-        return list;
+    /// Marks every Entity Scope Set containing the given User as dirty, to be
+    /// returned by the next call to `dirty_scope_checks`
+    pub fn mark_user_scope_dirty(&mut self, user_key: &UserKey) {
+        for room_key in self.user_rooms(user_key) {
+            let room_entities = self.entities_of_rooms(&self.room_and_descendants(&room_key));
+            for (entity_room_key, entity) in room_entities {
+                self.scope_check_cache
+                    .mark_dirty(&entity_room_key, user_key, &entity);
+            }
+        }
     }
 
     /// Sends all update messages to all Clients. If you don't call this
     /// method, the Server will never communicate with it's connected
     /// Clients
     pub fn send_all_updates<W: WorldRefType<P, E>>(&mut self, world: W) {
+        self.send_all_updates_internal(world, None::<&mut fn(&W, &UserKey, &E) -> f32>);
+    }
+
+    /// Sends all update messages to all Clients, like `send_all_updates`,
+    /// first evaluating the priority of each Entity in scope for each User
+    /// with the given function. The priority scales how quickly updates to
+    /// the Entity accumulate priority for that User, so that for example
+    /// Entities close to a User's avatar can be updated more often than
+    /// distant ones
+    pub fn send_all_updates_with_priority<
+        W: WorldRefType<P, E>,
+        F: FnMut(&W, &UserKey, &E) -> f32,
+    >(
+        &mut self,
+        world: W,
+        mut priority_evaluator: F,
+    ) {
+        self.send_all_updates_internal(world, Some(&mut priority_evaluator));
+    }
+
+    /// Sets the minimum duration between updates of every Component of the
+    /// given type, on every Entity. Changes made to a Component in between are
+    /// merged into its next update
+    pub fn set_component_update_interval<R: ReplicateSafe<P>>(&mut self, interval: Duration) {
+        self.world_record
+            .set_kind_update_interval(&P::kind_of::<R>(), interval);
+    }
+
+    /// Applies the updates received from Users to the Components they have
+    /// been given authority over, so that they are replicated to every other
+    /// User. Each update is first passed to the given validator, along with
+    /// the User & Entity it is from, and is only applied if it returns true.
+    /// Rejected updates are dropped, so to correct a User's copy of a
+    /// Component, revoke their authority over it
+    pub fn apply_authority_updates<W: WorldMutType<P, E>, F: FnMut(&UserKey, &E, &P) -> bool>(
+        &mut self,
+        mut world: W,
+        mut validator: F,
+    ) {
+        for (_, connection) in self.user_connections.iter_mut() {
+            let user_key = connection.user_key;
+            while let Some((component_key, component)) = connection.pop_authority_update() {
+                // authority may have been revoked since the update was sent
+                if self.component_authorities.get(&component_key) != Some(&user_key) {
+                    continue;
+                }
+                if let Some((entity, _)) = self.world_record.get_component_record(&component_key) {
+                    if !validator(&user_key, &entity, &component) {
+                        continue;
+                    }
+                    world.mirror_component(&entity, &component);
+                    self.diff_handler
+                        .as_ref()
+                        .read()
+                        .expect("Haven't initialized DiffHandler")
+                        .mutate_all(&component_key);
+                }
+            }
+        }
+    }
+
+    fn send_all_updates_internal<W: WorldRefType<P, E>, F: FnMut(&W, &UserKey, &E) -> f32>(
+        &mut self,
+        world: W,
+        priority_evaluator: Option<&mut F>,
+    ) {
         // update entity scopes
-        self.update_entity_scopes(&world);
+        self.update_entity_scopes(&world, priority_evaluator);
 
         // loop through all connections, send packet
         let server_tick_opt = self.server_tick();
         for (address, connection) in self.user_connections.iter_mut() {
             connection.collect_component_updates(&self.world_record);
-            while let Some(payload) =
-                connection.get_outgoing_packet(&world, &self.world_record, server_tick_opt)
-            {
-                self.io.send_packet(Packet::new_raw(*address, payload));
-                connection.mark_sent();
+            connection.update_congestion_control();
+            // anything left unsent once the bandwidth budget runs out stays queued
+            // until the next call
+            while connection.can_send_packet() {
+                if let Some(payload) =
+                    connection.get_outgoing_packet(&world, &self.world_record, server_tick_opt)
+                {
+                    self.io.send_packet(Packet::new_raw(*address, payload));
+                    connection.mark_sent();
+                } else {
+                    break;
+                }
             }
         }
     }
@@ -321,6 +508,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No Entity exists for given Key!");
     }
 
+    /// Retrieves an EntityRef that exposes read-only operations for the
+    /// Entity, or an error if the Entity does not exist
+    pub fn try_entity<'s, W: WorldRefType<P, E>>(
+        &'s self,
+        world: W,
+        entity: &E,
+    ) -> Result<EntityRef<'s, P, E, W>, NaiaServerError> {
+        if world.has_entity(entity) {
+            return Ok(EntityRef::new(self, world, &entity));
+        }
+        return Err(NaiaServerError::EntityNotFound);
+    }
+
     /// Retrieves an EntityMut that exposes read and write operations for the
     /// Entity.
     /// Panics if the Entity does not exist.
@@ -335,6 +535,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No Entity exists for given Key!");
     }
 
+    /// Retrieves an EntityMut that exposes read and write operations for the
+    /// Entity, or an error if the Entity does not exist
+    pub fn try_entity_mut<'s, 'w, W: WorldMutType<P, E>>(
+        &'s mut self,
+        world: W,
+        entity: &E,
+    ) -> Result<EntityMut<'s, P, E, W>, NaiaServerError> {
+        if world.has_entity(entity) {
+            return Ok(EntityMut::new(self, world, &entity));
+        }
+        return Err(NaiaServerError::EntityNotFound);
+    }
+
     /// Retrieves a WorldlessEntityMut that exposes read and write operations
     /// on the Entity, but with no references allowed to the World.
     /// This is a very niche use case.
@@ -343,6 +556,148 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         return WorldlessEntityMut::new(self, &entity);
     }
 
+    // Client Entities
+
+    /// Applies the actions Users have performed on the Entities they have
+    /// spawned to the given World. Newly spawned Entities are reported by the
+    /// next call to `receive`, as an `Event::ClientSpawnedEntity`.
+    /// Entities spawned by Users are not replicated to other Users unless
+    /// taken over by the Server. A User's spawns beyond
+    /// `ServerConfig::max_client_entities` are rejected
+    pub fn apply_client_entity_actions<W: WorldMutType<P, E>>(&mut self, mut world: W) {
+        // despawn the Entities of Users which have disconnected
+        while let Some(entity) = self.orphaned_client_entities.pop_front() {
+            if world.has_entity(&entity) {
+                world.despawn_entity(&entity);
+            }
+        }
+
+        for (_, connection) in self.user_connections.iter_mut() {
+            let user_key = connection.user_key;
+            while let Some(action) = connection.pop_client_entity_action() {
+                match action {
+                    ClientEntityAction::SpawnEntity(local_entity) => {
+                        let entity = world.spawn_entity();
+                        connection.insert_client_entity(&local_entity, &entity);
+                        self.client_entity_records
+                            .insert(entity, ClientEntityRecord::new(&user_key, &local_entity));
+                        self.outstanding_client_spawns.push_back((user_key, entity));
+                    }
+                    ClientEntityAction::DespawnEntity(local_entity) => {
+                        if let Some(entity) = connection.remove_client_entity(&local_entity) {
+                            self.client_entity_records.remove(&entity);
+                            if world.has_entity(&entity) {
+                                world.despawn_entity(&entity);
+                            }
+                        }
+                    }
+                    ClientEntityAction::InsertComponent(local_entity, component) => {
+                        if let Some(entity) = connection.client_entity(&local_entity) {
+                            if !world.has_entity(&entity) {
+                                continue;
+                            }
+                            // inserting a Component again updates it
+                            if world.has_component_of_kind(&entity, &component.dyn_ref().get_kind())
+                            {
+                                world.mirror_component(&entity, &component);
+                            } else {
+                                component.extract_and_insert(&entity, &mut world);
+                            }
+                        }
+                    }
+                    ClientEntityAction::RemoveComponent(local_entity, component_kind) => {
+                        if let Some(entity) = connection.client_entity(&local_entity) {
+                            if world.has_entity(&entity) {
+                                world.remove_component_of_kind(&entity, &component_kind);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Accepts an Entity spawned by a User. This is only recorded on the
+    /// Server, see `client_entity_is_accepted`: the User isn't notified, and
+    /// keeps control of the Entity, which may still be rejected or taken over
+    pub fn accept_client_entity(&mut self, entity: &E) {
+        if let Some(record) = self.client_entity_records.get_mut(entity) {
+            record.accepted = true;
+        }
+    }
+
+    /// Rejects an Entity spawned by a User, despawning it from the given World
+    /// and from the User's
+    pub fn reject_client_entity<W: WorldMutType<P, E>>(&mut self, mut world: W, entity: &E) {
+        if let Some(record) = self.client_entity_records.remove(entity) {
+            if let Some(user) = self.users.get(record.user_key) {
+                if let Some(connection) = self.user_connections.get_mut(&user.address) {
+                    connection.reject_client_entity(&record.local_entity);
+                }
+            }
+            if world.has_entity(entity) {
+                world.despawn_entity(entity);
+            }
+        }
+    }
+
+    /// Takes over an Entity spawned by a User, so that it becomes a regular
+    /// Entity of the Server, which can be replicated to every User in scope of
+    /// it. The User's copy of the Entity is despawned.
+    /// Panics if the Entity was not spawned by a User
+    pub fn take_over_client_entity<'s, W: WorldMutType<P, E>>(
+        &'s mut self,
+        mut world: W,
+        entity: &E,
+    ) -> EntityMut<'s, P, E, W> {
+        let record = self
+            .client_entity_records
+            .remove(entity)
+            .expect("No Client Entity exists for given Key!");
+        if let Some(user) = self.users.get(record.user_key) {
+            if let Some(connection) = self.user_connections.get_mut(&user.address) {
+                connection.take_over_client_entity(&record.local_entity);
+            }
+        }
+
+        // re-insert every Component, so that it is tracked like any other
+        let mut components = Vec::new();
+        for component_kind in world.get_component_kinds(entity) {
+            if let Some(component) = world.remove_component_of_kind(entity, &component_kind) {
+                components.push(component);
+            }
+        }
+        self.spawn_entity_init(entity);
+        {
+            let mut inserter = ComponentInserter {
+                server: self,
+                world: &mut world,
+            };
+            for component in components {
+                component.extract_and_insert(entity, &mut inserter);
+            }
+        }
+
+        return EntityMut::new(self, world, entity);
+    }
+
+    /// Returns the UserKey of the User which spawned the given Entity, if it
+    /// has not been rejected or taken over
+    pub fn client_entity_owner(&self, entity: &E) -> Option<UserKey> {
+        return self
+            .client_entity_records
+            .get(entity)
+            .map(|record| record.user_key);
+    }
+
+    /// Returns whether an Entity spawned by a User has been accepted
+    pub fn client_entity_is_accepted(&self, entity: &E) -> bool {
+        if let Some(record) = self.client_entity_records.get(entity) {
+            return record.accepted;
+        }
+        return false;
+    }
+
     /// Gets a Vec of all Entities in the given World
     pub fn entities<W: WorldRefType<P, E>>(&self, world: W) -> Vec<E> {
         return world.entities();
@@ -365,9 +720,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No User exists for given Key!");
     }
 
+    /// Retrieves an UserRef that exposes read-only operations for the User
+    /// associated with the given UserKey, or an error if the User does not
+    /// exist
+    pub fn try_user(&self, user_key: &UserKey) -> Result<UserRef<P, E>, NaiaServerError> {
+        if self.users.contains_key(*user_key) {
+            return Ok(UserRef::new(self, &user_key));
+        }
+        return Err(NaiaServerError::UserNotFound(*user_key));
+    }
+
     /// Retrieves an UserMut that exposes read and write operations for the User
     /// associated with the given UserKey.
-    /// Returns None if the user does not exist.
+    /// Panics if the user does not exist.
     pub fn user_mut(&mut self, user_key: &UserKey) -> UserMut<P, E> {
         if self.users.contains_key(*user_key) {
             return UserMut::new(self, &user_key);
@@ -375,6 +740,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No User exists for given Key!");
     }
 
+    /// Retrieves an UserMut that exposes read and write operations for the User
+    /// associated with the given UserKey, or an error if the User does not
+    /// exist
+    pub fn try_user_mut(&mut self, user_key: &UserKey) -> Result<UserMut<P, E>, NaiaServerError> {
+        if self.users.contains_key(*user_key) {
+            return Ok(UserMut::new(self, &user_key));
+        }
+        return Err(NaiaServerError::UserNotFound(*user_key));
+    }
+
     /// Return a list of all currently connected Users' keys
     pub fn user_keys(&self) -> Vec<UserKey> {
         let mut output = Vec::new();
@@ -400,6 +775,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No User exists for given Key!");
     }
 
+    /// Returns a UserScopeMut, which is used to include/exclude Entities for a
+    /// given User, or an error if the User does not exist
+    pub fn try_user_scope(
+        &mut self,
+        user_key: &UserKey,
+    ) -> Result<UserScopeMut<P, E>, NaiaServerError> {
+        if self.users.contains_key(*user_key) {
+            return Ok(UserScopeMut::new(self, &user_key));
+        }
+        return Err(NaiaServerError::UserNotFound(*user_key));
+    }
+
     /// Returns whether a given User has a particular Entity in-scope currently
     pub fn user_scope_has_entity(&self, user_key: &UserKey, entity: &E) -> bool {
         if let Some(user) = self.users.get(*user_key) {
@@ -437,6 +824,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No Room exists for given Key!");
     }
 
+    /// Retrieves an RoomRef that exposes read-only operations for the Room
+    /// associated with the given RoomKey, or an error if the Room does not
+    /// exist
+    pub fn try_room(&self, room_key: &RoomKey) -> Result<RoomRef<P, E>, NaiaServerError> {
+        if self.rooms.contains_key(*room_key) {
+            return Ok(RoomRef::new(self, room_key));
+        }
+        return Err(NaiaServerError::RoomNotFound(*room_key));
+    }
+
     /// Retrieves an RoomMut that exposes read and write operations for the
     /// Room associated with the given RoomKey.
     /// Panics if the room does not exist.
@@ -447,6 +844,16 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         panic!("No Room exists for given Key!");
     }
 
+    /// Retrieves an RoomMut that exposes read and write operations for the
+    /// Room associated with the given RoomKey, or an error if the Room does
+    /// not exist
+    pub fn try_room_mut(&mut self, room_key: &RoomKey) -> Result<RoomMut<P, E>, NaiaServerError> {
+        if self.rooms.contains_key(*room_key) {
+            return Ok(RoomMut::new(self, room_key));
+        }
+        return Err(NaiaServerError::RoomNotFound(*room_key));
+    }
+
     /// Return a list of all the Server's Rooms' keys
     pub fn room_keys(&self) -> Vec<RoomKey> {
         let mut output = Vec::new();
@@ -463,6 +870,27 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         self.rooms.len()
     }
 
+    /// Return a list of the keys of all the Rooms an Entity has been added to
+    pub fn entity_rooms(&self, entity: &E) -> Vec<RoomKey> {
+        if let Some(record) = self.entity_records.get(entity) {
+            return record.room_keys.iter().copied().collect();
+        }
+        return Vec::new();
+    }
+
+    /// Return a list of the keys of all the Rooms a User has been added to
+    pub fn user_rooms(&self, user_key: &UserKey) -> Vec<RoomKey> {
+        let mut output = Vec::new();
+
+        for (key, room) in self.rooms.iter() {
+            if room.has_user(user_key) {
+                output.push(key);
+            }
+        }
+
+        return output;
+    }
+
     // Ticks
 
     /// Gets the last received tick from the Client
@@ -484,6 +912,20 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         }
     }
 
+    /// Advances the Server's tick, when `ServerConfig::manual_tick` is set.
+    /// The resulting Tick event is returned by the next call to `receive()`.
+    /// Returns false, without ticking, if the Server ticks on its own, or
+    /// does not tick at all
+    pub fn tick(&mut self) -> bool {
+        match &mut self.tick_manager {
+            Some(tick_manager) if tick_manager.is_manual() => {
+                tick_manager.advance();
+                return true;
+            }
+            _ => return false,
+        }
+    }
+
     // Crate-Public methods
 
     //// Entities
@@ -520,6 +962,10 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         world.despawn_entity(entity);
 
         self.entity_scope_map.remove_entity(entity);
+        self.interest_manager.remove_entity(entity);
+        for room_key in self.entity_rooms(entity) {
+            self.room_remove_entity(&room_key, entity);
+        }
         self.entity_records.remove(entity);
     }
 
@@ -556,6 +1002,13 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                     if !user_connection.has_entity(entity) {
                         //add entity to user connection
                         user_connection.spawn_entity(&self.world_record, entity);
+                        Self::grant_entity_authority(
+                            &self.world_record,
+                            &self.component_authorities,
+                            user_connection,
+                            user_key,
+                            entity,
+                        );
                     }
 
                     // assign Entity to User as a Prediction
@@ -586,11 +1039,120 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                     entity_record.owner_key = None;
 
                     user_connection.disown_entity(entity);
+
+                    // the Entity was kept in scope while owned
+                    self.scope_check_cache
+                        .mark_pair_changed(&current_owner_key, entity);
                 }
             }
         }
     }
 
+    //// Entity Priority
+
+    /// Sets the position of an Entity used by spatial interest management
+    pub(crate) fn entity_set_interest_position(&mut self, entity: &E, x: f32, y: f32) {
+        self.interest_manager.set_entity_position(entity, x, y);
+    }
+
+    /// Sets the view of a User used by spatial interest management
+    pub(crate) fn user_set_view(&mut self, user_key: &UserKey, x: f32, y: f32, radius: f32) {
+        self.interest_manager.set_user_view(user_key, x, y, radius);
+    }
+
+    /// Sets the base update priority of all of an Entity's Components which
+    /// do not have a priority of their own
+    pub(crate) fn entity_set_priority(&mut self, entity: &E, priority: f32) {
+        self.world_record.set_entity_priority(entity, priority);
+    }
+
+    /// Sets the minimum duration between updates of each of an Entity's
+    /// Components
+    pub(crate) fn entity_set_update_interval(&mut self, entity: &E, interval: Duration) {
+        self.world_record
+            .set_entity_update_interval(entity, interval);
+    }
+
+    /// Sets the base update priority of one of an Entity's Components
+    pub(crate) fn component_set_priority(
+        &mut self,
+        entity: &E,
+        component_kind: &P::Kind,
+        priority: f32,
+    ) {
+        let component_key = self
+            .world_record
+            .get_key_from_type(entity, component_kind)
+            .expect("component does not exist!");
+        self.world_record
+            .set_component_priority(&component_key, priority);
+    }
+
+    //// Component Authority
+
+    /// Gives a User authority over one of an Entity's Components, taking it
+    /// away from the User currently holding it, if any. The User's Client may
+    /// then mutate the Component, and its updates are applied through
+    /// `apply_authority_updates`
+    pub(crate) fn component_give_authority(
+        &mut self,
+        entity: &E,
+        component_kind: &P::Kind,
+        user_key: &UserKey,
+    ) {
+        let component_key = self
+            .world_record
+            .get_key_from_type(entity, component_kind)
+            .expect("component does not exist!");
+
+        if let Some(old_user_key) = self.component_authorities.get(&component_key) {
+            if old_user_key == user_key {
+                return;
+            }
+        }
+        self.component_revoke_authority(entity, component_kind);
+
+        self.component_authorities.insert(component_key, *user_key);
+        if let Some(user) = self.users.get(*user_key) {
+            if let Some(user_connection) = self.user_connections.get_mut(&user.address) {
+                if user_connection.has_entity(entity) {
+                    user_connection.give_authority(&component_key);
+                }
+            }
+        }
+    }
+
+    /// Takes away authority over one of an Entity's Components from the User
+    /// holding it, if any. The User's Client is sent the Component's full
+    /// state from the Server
+    pub(crate) fn component_revoke_authority(&mut self, entity: &E, component_kind: &P::Kind) {
+        let component_key = self
+            .world_record
+            .get_key_from_type(entity, component_kind)
+            .expect("component does not exist!");
+
+        if let Some(user_key) = self.component_authorities.remove(&component_key) {
+            if let Some(user) = self.users.get(user_key) {
+                if let Some(user_connection) = self.user_connections.get_mut(&user.address) {
+                    user_connection.revoke_authority(&component_key);
+                }
+            }
+        }
+    }
+
+    /// Gets the UserKey of the User holding authority over one of an Entity's
+    /// Components, if any
+    pub(crate) fn component_authority(
+        &self,
+        entity: &E,
+        component_kind: &P::Kind,
+    ) -> Option<UserKey> {
+        let component_key = self
+            .world_record
+            .get_key_from_type(entity, component_kind)?;
+        return self.component_authorities.get(&component_key).copied();
+    }
+
     //// Entity Scopes
 
     pub(crate) fn user_scope_set_entity(
@@ -601,6 +1163,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     ) {
         self.entity_scope_map
             .insert(*user_key, *entity, is_contained);
+        self.scope_check_cache.mark_pair_changed(user_key, entity);
     }
 
     //// Components
@@ -678,16 +1241,30 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         return None;
     }
 
+    /// Get the average RTT to a User, given the associated UserKey
+    pub(crate) fn get_user_rtt(&self, user_key: &UserKey) -> Option<f32> {
+        let user = self.users.get(*user_key)?;
+        let connection = self.user_connections.get(&user.address)?;
+        return Some(connection.rtt());
+    }
+
+    /// Get the bandwidth budget of a User's connection, given the associated
+    /// UserKey
+    pub(crate) fn get_user_bandwidth(&self, user_key: &UserKey) -> Option<u32> {
+        let user = self.users.get(*user_key)?;
+        let connection = self.user_connections.get(&user.address)?;
+        return Some(connection.bandwidth());
+    }
+
     pub(crate) fn user_force_disconnect(&mut self, user_key: &UserKey) {
         self.outstanding_disconnects.push_back(*user_key);
     }
 
     /// All necessary cleanup, when they're actually gone...
     pub(crate) fn delete_user(&mut self, user_key: &UserKey) -> Option<User> {
-        // TODO: cache this?
         // Clean up all user data
-        for (_, room) in self.rooms.iter_mut() {
-            room.unsubscribe_user(&user_key);
+        for room_key in self.user_rooms(user_key) {
+            self.room_remove_user(&room_key, user_key);
         }
 
         if let Some(user) = self.users.remove(*user_key) {
@@ -699,7 +1276,18 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                 }
             }
 
+            self.component_authorities
+                .retain(|_, authority_key| authority_key != user_key);
+            let orphaned_client_entities = &mut self.orphaned_client_entities;
+            self.client_entity_records.retain(|entity, record| {
+                if record.user_key == *user_key {
+                    orphaned_client_entities.push_back(*entity);
+                    return false;
+                }
+                return true;
+            });
             self.entity_scope_map.remove_user(user_key);
+            self.interest_manager.remove_user(user_key);
             self.handshake_manager.delete_user(&user.address);
 
             return Some(user);
@@ -714,14 +1302,24 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// Returns true if the Room existed.
     pub(crate) fn room_destroy(&mut self, room_key: &RoomKey) -> bool {
         if self.rooms.contains_key(*room_key) {
-            // remove all entities from the entity_room_map
-            for entity in self.rooms.get(*room_key).unwrap().entities() {
-                if let Some(record) = self.entity_records.get_mut(entity) {
-                    record.room_key = None;
-                }
+            let room = self.rooms.get(*room_key).unwrap();
+            let user_keys: Vec<UserKey> = room.user_keys().copied().collect();
+            let entities: Vec<E> = room.entities().copied().collect();
+            let child_keys: Vec<RoomKey> = room.children().copied().collect();
+
+            // remove all users & entities, releasing their scope sets
+            for user_key in user_keys {
+                self.room_remove_user(room_key, &user_key);
+            }
+            for entity in entities {
+                self.room_remove_entity(room_key, &entity);
             }
 
-            // TODO: what else kind of cleanup do we need to do here? Scopes?
+            // detach from the hierarchy, child rooms become top-level rooms
+            for child_key in child_keys {
+                self.room_set_parent(&child_key, None);
+            }
+            self.room_set_parent(room_key, None);
 
             // actually remove the room from the collection
             self.rooms.remove(*room_key);
@@ -748,14 +1346,20 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// Room with them
     pub(crate) fn room_add_user(&mut self, room_key: &RoomKey, user_key: &UserKey) {
         if let Some(room) = self.rooms.get_mut(*room_key) {
-            room.subscribe_user(user_key);
+            if room.subscribe_user(user_key) {
+                let room_entities = self.entities_of_rooms(&self.room_and_descendants(room_key));
+                self.link_scope_checks(&[*user_key], &room_entities, true);
+            }
         }
     }
 
     /// Removes a User from a Room
     pub(crate) fn room_remove_user(&mut self, room_key: &RoomKey, user_key: &UserKey) {
         if let Some(room) = self.rooms.get_mut(*room_key) {
-            room.unsubscribe_user(user_key);
+            if room.unsubscribe_user(user_key) {
+                let room_entities = self.entities_of_rooms(&self.room_and_descendants(room_key));
+                self.link_scope_checks(&[*user_key], &room_entities, false);
+            }
         }
     }
 
@@ -773,36 +1377,37 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     /// their keys.
     pub(crate) fn room_has_entity(&self, room_key: &RoomKey, entity: &E) -> bool {
         if let Some(entity_record) = self.entity_records.get(entity) {
-            if let Some(actual_room_key) = entity_record.room_key {
-                return *room_key == actual_room_key;
-            }
+            return entity_record.room_keys.contains(room_key);
         }
         return false;
     }
 
     /// Add an Entity to a Room associated with the given RoomKey.
     /// Entities will only ever be in-scope for Users which are in a Room with
-    /// them.
+    /// them, or in one of that Room's ancestors. An Entity may belong to
+    /// several Rooms at once, and is in-scope for the Users of any of them
     pub(crate) fn room_add_entity(&mut self, room_key: &RoomKey, entity: &E) {
         if let Some(entity_record) = self.entity_records.get_mut(entity) {
-            if entity_record.room_key.is_some() {
-                panic!("Entity already belongs to a Room! Remove the Entity from the Room before adding it to a new Room.");
-            }
-
             if let Some(room) = self.rooms.get_mut(*room_key) {
-                room.add_entity(entity);
-                entity_record.room_key = Some(*room_key);
+                if room.add_entity(entity) {
+                    entity_record.room_keys.insert(*room_key);
+                    let user_keys = self.users_of_rooms(&self.room_and_ancestors(room_key));
+                    self.link_scope_checks(&user_keys, &[(*room_key, *entity)], true);
+                }
             }
         }
     }
 
-    /// Remove an Entity from a Room, associated with the given RoomKey
+    /// Remove an Entity from a Room, associated with the given RoomKey. Does
+    /// nothing if the Entity is not in the Room
     pub(crate) fn room_remove_entity(&mut self, room_key: &RoomKey, entity: &E) {
         if let Some(room) = self.rooms.get_mut(*room_key) {
             if room.remove_entity(entity) {
                 if let Some(entity_record) = self.entity_records.get_mut(entity) {
-                    entity_record.room_key = None;
+                    entity_record.room_keys.remove(room_key);
                 }
+                let user_keys = self.users_of_rooms(&self.room_and_ancestors(room_key));
+                self.link_scope_checks(&user_keys, &[(*room_key, *entity)], false);
             }
         }
     }
@@ -815,6 +1420,60 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
         return 0;
     }
 
+    //////// hierarchy
+
+    /// Gets the key of the Room a given Room is nested in, if any
+    pub(crate) fn room_parent(&self, room_key: &RoomKey) -> Option<RoomKey> {
+        if let Some(room) = self.rooms.get(*room_key) {
+            return room.parent();
+        }
+        return None;
+    }
+
+    /// Gets the keys of the Rooms nested directly in a given Room
+    pub(crate) fn room_children(&self, room_key: &RoomKey) -> Vec<RoomKey> {
+        if let Some(room) = self.rooms.get(*room_key) {
+            return room.children().copied().collect();
+        }
+        return Vec::new();
+    }
+
+    /// Nests a Room inside of another, or makes it a top-level Room if no
+    /// parent is given. Panics if the parent is the Room itself or one of its
+    /// descendants
+    pub(crate) fn room_set_parent(&mut self, room_key: &RoomKey, parent_key_opt: Option<&RoomKey>) {
+        if !self.rooms.contains_key(*room_key) {
+            return;
+        }
+        if let Some(parent_key) = parent_key_opt {
+            if !self.rooms.contains_key(*parent_key) {
+                return;
+            }
+            if self.room_and_ancestors(parent_key).contains(room_key) {
+                panic!("Room cannot be nested inside of itself or one of its descendants!");
+            }
+        }
+
+        // detach from the current parent
+        if let Some(old_parent_key) = self.rooms.get(*room_key).unwrap().parent() {
+            self.link_rooms(&old_parent_key, room_key, false);
+            if let Some(old_parent) = self.rooms.get_mut(old_parent_key) {
+                old_parent.remove_child(room_key);
+            }
+            self.rooms.get_mut(*room_key).unwrap().set_parent(None);
+        }
+
+        // attach to the new parent
+        if let Some(parent_key) = parent_key_opt {
+            self.rooms
+                .get_mut(*room_key)
+                .unwrap()
+                .set_parent(Some(*parent_key));
+            self.rooms.get_mut(*parent_key).unwrap().add_child(room_key);
+            self.link_rooms(parent_key, room_key, true);
+        }
+    }
+
     // Private methods
 
     fn maintain_socket(&mut self) {
@@ -827,6 +1486,8 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             for (user_address, connection) in self.user_connections.iter_mut() {
                 if connection.should_drop() {
                     self.outstanding_disconnects.push_back(connection.user_key);
+                    self.outstanding_errors
+                        .push_back(NaiaServerError::Timeout(connection.user_key));
                     continue;
                 }
 
@@ -845,19 +1506,41 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             }
         }
 
+        // pings
+        let server_tick_opt = self.server_tick();
+        for (user_address, connection) in self.user_connections.iter_mut() {
+            if connection.should_send_ping() {
+                let ping_payload = connection.get_ping_payload();
+                let payload = connection.process_outgoing_header(
+                    server_tick_opt,
+                    connection.get_last_received_tick(),
+                    PacketType::Ping,
+                    &ping_payload,
+                );
+                self.io.send_packet(Packet::new_raw(*user_address, payload));
+                connection.mark_sent();
+            }
+        }
+
         //receive socket events
         loop {
             match self.io.receive_packet() {
                 Ok(Some(packet)) => {
                     let address = packet.address();
 
+                    let (header, payload) = match StandardHeader::read(packet.payload()) {
+                        Ok(header_and_payload) => header_and_payload,
+                        Err(error) => {
+                            self.receive_malformed_packet(&address, error);
+                            continue;
+                        }
+                    };
+
                     if let Some(user_connection) = self.user_connections.get_mut(&address) {
                         user_connection.mark_heard();
                     }
 
-                    let (header, payload) = StandardHeader::read(packet.payload());
-
-                    match header.packet_type() {
+                    let result = match header.packet_type() {
                         PacketType::ClientChallengeRequest => {
                             let server_tick = self.server_tick().unwrap_or(0);
                             self.handshake_manager.receive_challenge_request(
@@ -869,16 +1552,19 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                         }
                         PacketType::ClientConnectRequest => {
                             if let Some(mut connection) = self.user_connections.get_mut(&address) {
-                                if let HandshakeResult::DisconnectUser =
-                                    self.handshake_manager.receive_old_connect_request(
-                                        &mut self.io,
-                                        &self.world_record,
-                                        &mut connection,
-                                        &header,
-                                        &payload,
-                                    )
-                                {
-                                    self.outstanding_disconnects.push_back(connection.user_key);
+                                match self.handshake_manager.receive_old_connect_request(
+                                    &mut self.io,
+                                    &self.world_record,
+                                    &mut connection,
+                                    &header,
+                                    &payload,
+                                ) {
+                                    Ok(HandshakeResult::DisconnectUser) => {
+                                        self.outstanding_disconnects.push_back(connection.user_key);
+                                        Ok(())
+                                    }
+                                    Ok(_) => Ok(()),
+                                    Err(error) => Err(error),
                                 }
                             } else {
                                 match self.handshake_manager.receive_new_connect_request(
@@ -886,17 +1572,27 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                                     &address,
                                     &payload,
                                 ) {
-                                    HandshakeResult::AuthUser(auth_message) => {
+                                    Ok(HandshakeResult::AuthUser(auth_message)) => {
                                         let user = User::new(address);
                                         let user_key = self.users.insert(user);
                                         self.outstanding_auths.push_back((user_key, auth_message));
+                                        Ok(())
                                     }
-                                    HandshakeResult::ConnectUser => {
+                                    Ok(HandshakeResult::ConnectUser) => {
                                         let user = User::new(address);
                                         let user_key = self.users.insert(user);
                                         self.accept_connection(&user_key);
+                                        Ok(())
+                                    }
+                                    Ok(HandshakeResult::Rejected) => {
+                                        self.handshake_manager
+                                            .send_reject_response(&mut self.io, &address);
+                                        self.outstanding_errors
+                                            .push_back(NaiaServerError::HandshakeRejected(address));
+                                        Ok(())
                                     }
-                                    _ => {}
+                                    Ok(_) => Ok(()),
+                                    Err(error) => Err(error),
                                 }
                             }
                         }
@@ -906,14 +1602,17 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                                 Some(connection) => {
                                     connection.process_incoming_header(&self.world_record, &header);
                                     connection.process_incoming_data(
+                                        &self.world_record,
                                         server_tick_opt,
                                         header.host_tick(),
+                                        header.local_packet_index(),
                                         &self.manifest,
                                         &payload,
-                                    );
+                                    )
                                 }
                                 None => {
                                     warn!("received data from unauthenticated client: {}", address);
+                                    Ok(())
                                 }
                             }
                         }
@@ -931,36 +1630,59 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                                     );
                                 }
                             }
+                            Ok(())
                         }
                         PacketType::Ping => {
                             let server_tick_opt = self.server_tick();
                             match self.user_connections.get_mut(&address) {
                                 Some(connection) => {
                                     connection.process_incoming_header(&self.world_record, &header);
-                                    let ping_payload = connection.process_ping(&payload);
-                                    let payload_with_header = connection.process_outgoing_header(
-                                        server_tick_opt,
-                                        connection.get_last_received_tick(),
-                                        PacketType::Pong,
-                                        &ping_payload,
-                                    );
-                                    self.io.send_packet(Packet::new_raw(
-                                        connection.address(),
-                                        payload_with_header,
-                                    ));
-                                    connection.mark_sent();
+                                    match connection.process_ping(&payload) {
+                                        Ok(ping_payload) => {
+                                            let payload_with_header = connection
+                                                .process_outgoing_header(
+                                                    server_tick_opt,
+                                                    connection.get_last_received_tick(),
+                                                    PacketType::Pong,
+                                                    &ping_payload,
+                                                );
+                                            self.io.send_packet(Packet::new_raw(
+                                                connection.address(),
+                                                payload_with_header,
+                                            ));
+                                            connection.mark_sent();
+                                            Ok(())
+                                        }
+                                        Err(error) => Err(error),
+                                    }
                                 }
                                 None => {
                                     warn!("received ping from unauthenticated client: {}", address);
+                                    Ok(())
                                 }
                             }
                         }
+                        PacketType::Pong => match self.user_connections.get_mut(&address) {
+                            Some(connection) => {
+                                connection.process_incoming_header(&self.world_record, &header);
+                                connection.process_pong(&payload)
+                            }
+                            None => {
+                                warn!("received pong from unauthenticated client: {}", address);
+                                Ok(())
+                            }
+                        },
                         PacketType::ServerChallengeResponse
                         | PacketType::ServerConnectResponse
-                        | PacketType::Pong
+                        | PacketType::ServerRejectResponse
                         | PacketType::Unknown => {
                             // do nothing
+                            Ok(())
                         }
+                    };
+
+                    if let Err(error) = result {
+                        self.receive_malformed_packet(&address, error);
                     }
                 }
                 Ok(None) => {
@@ -969,12 +1691,25 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
                 }
                 Err(error) => {
                     self.outstanding_errors
-                        .push_back(NaiaServerError::Wrapped(Box::new(error)));
+                        .push_back(NaiaServerError::Socket(Box::new(error)));
                 }
             }
         }
     }
 
+    /// Reports a malformed packet, disconnecting the Client which sent it,
+    /// as it is either misbehaving or using an incompatible Protocol
+    fn receive_malformed_packet(&mut self, address: &SocketAddr, error: DecodeError) {
+        if let Some(connection) = self.user_connections.get(address) {
+            self.outstanding_disconnects.push_back(connection.user_key);
+        }
+        let error = match error {
+            DecodeError::UnknownKind(kind) => NaiaServerError::ProtocolMismatch(*address, kind),
+            error => NaiaServerError::Decode(*address, error),
+        };
+        self.outstanding_errors.push_back(error);
+    }
+
     // Entity Helpers
 
     fn spawn_entity_init(&mut self, entity: &E) {
@@ -985,58 +1720,190 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
 
     // Entity Scopes
 
-    fn update_entity_scopes<W: WorldRefType<P, E>>(&mut self, world: &W) {
-        for (_, room) in self.rooms.iter_mut() {
-            while let Some((removed_user, removed_entity)) = room.pop_entity_removal_queue() {
-                if let Some(user) = self.users.get(removed_user) {
-                    if let Some(user_connection) = self.user_connections.get_mut(&user.address) {
-                        //remove entity from user connection
-                        user_connection.despawn_entity(&self.world_record, &removed_entity);
-                    }
+    fn update_entity_scopes<W: WorldRefType<P, E>, F: FnMut(&W, &UserKey, &E) -> f32>(
+        &mut self,
+        world: &W,
+        priority_evaluator: Option<&mut F>,
+    ) {
+        // spatial interest is kept apart from the scope set through `user_scope`,
+        // and changed pairs are evaluated against both below
+        self.interest_manager.update();
+        while let Some((user_key, entity, _)) = self.interest_manager.pop_scope_change() {
+            self.scope_check_cache.mark_pair_changed(&user_key, &entity);
+        }
+
+        while let Some((removed_user, removed_entity)) = self.scope_check_cache.pop_released_pair()
+        {
+            // pairs may have been related again through another room since, and entities
+            // despawned from the world have already been removed from every connection
+            if self
+                .scope_check_cache
+                .has_pair(&removed_user, &removed_entity)
+                || !world.has_entity(&removed_entity)
+            {
+                continue;
+            }
+            if let Some(user) = self.users.get(removed_user) {
+                if let Some(user_connection) = self.user_connections.get_mut(&user.address) {
+                    //remove entity from user connection
+                    user_connection.despawn_entity(&self.world_record, &removed_entity);
                 }
             }
+        }
 
-            // TODO: we should be able to cache these tuples of keys to avoid building a new
-            // list each time
-            for user_key in room.user_keys() {
-                for entity in room.entities() {
-                    if world.has_entity(entity) {
-                        if let Some(user) = self.users.get(*user_key) {
-                            if let Some(user_connection) =
-                                self.user_connections.get_mut(&user.address)
-                            {
-                                let currently_in_scope = user_connection.has_entity(entity);
-
-                                let should_be_in_scope: bool;
-                                if user_connection.has_prediction_entity(entity) {
-                                    should_be_in_scope = true;
-                                } else {
-                                    if let Some(in_scope) =
-                                        self.entity_scope_map.get(user_key, entity)
-                                    {
-                                        should_be_in_scope = *in_scope;
-                                    } else {
-                                        should_be_in_scope = false;
-                                    }
-                                }
+        // only pairs which have been related, or whose scope may have changed, since
+        // the last call are evaluated
+        for (user_key, entity) in self.scope_check_cache.take_changed_pairs().iter() {
+            if self.scope_check_cache.has_pair(user_key, entity) && world.has_entity(entity) {
+                if let Some(user) = self.users.get(*user_key) {
+                    if let Some(user_connection) = self.user_connections.get_mut(&user.address) {
+                        let currently_in_scope = user_connection.has_entity(entity);
+
+                        let should_be_in_scope: bool;
+                        if user_connection.has_prediction_entity(entity) {
+                            should_be_in_scope = true;
+                        } else {
+                            // an explicit include or exclude overrides spatial interest
+                            if let Some(in_scope) = self.entity_scope_map.get(user_key, entity) {
+                                should_be_in_scope = *in_scope;
+                            } else {
+                                should_be_in_scope =
+                                    self.interest_manager.is_in_view(user_key, entity);
+                            }
+                        }
 
-                                if should_be_in_scope {
-                                    if !currently_in_scope {
-                                        // add entity to the connections local scope
-                                        user_connection.spawn_entity(&self.world_record, entity);
-                                    }
-                                } else {
-                                    if currently_in_scope {
-                                        // remove entity from the connections local scope
-                                        user_connection.despawn_entity(&self.world_record, entity);
-                                    }
-                                }
+                        if should_be_in_scope {
+                            if !currently_in_scope {
+                                // add entity to the connections local scope
+                                user_connection.spawn_entity(&self.world_record, entity);
+                                Self::grant_entity_authority(
+                                    &self.world_record,
+                                    &self.component_authorities,
+                                    user_connection,
+                                    user_key,
+                                    entity,
+                                );
+                            }
+                        } else {
+                            if currently_in_scope {
+                                // remove entity from the connections local scope
+                                user_connection.despawn_entity(&self.world_record, entity);
                             }
                         }
                     }
                 }
             }
         }
+
+        if let Some(evaluator) = priority_evaluator {
+            for (_, user_connection) in self.user_connections.iter_mut() {
+                let user_key = user_connection.user_key;
+                for entity in user_connection.entities() {
+                    let priority = evaluator(world, &user_key, &entity);
+                    user_connection.set_entity_priority(&entity, priority);
+                }
+            }
+        }
+    }
+
+    // Gives a User's connection authority over each of an Entity's Components
+    // the User holds authority over, as the Entity comes into scope
+    fn grant_entity_authority(
+        world_record: &WorldRecord<E, P::Kind>,
+        component_authorities: &HashMap<ComponentKey, UserKey>,
+        user_connection: &mut Connection<P, E>,
+        user_key: &UserKey,
+        entity: &E,
+    ) {
+        for component_key in world_record.get_component_keys(entity) {
+            if component_authorities.get(&component_key) == Some(user_key) {
+                user_connection.give_authority(&component_key);
+            }
+        }
+    }
+
+    // Room Helpers
+
+    // Returns the given Room followed by all of its ancestors
+    fn room_and_ancestors(&self, room_key: &RoomKey) -> Vec<RoomKey> {
+        let mut output = Vec::new();
+        let mut current_key = Some(*room_key);
+        while let Some(key) = current_key {
+            match self.rooms.get(key) {
+                Some(room) => {
+                    output.push(key);
+                    current_key = room.parent();
+                }
+                None => break,
+            }
+        }
+        return output;
+    }
+
+    // Returns the given Room followed by all of its descendants
+    fn room_and_descendants(&self, room_key: &RoomKey) -> Vec<RoomKey> {
+        let mut output = Vec::new();
+        let mut stack = vec![*room_key];
+        while let Some(key) = stack.pop() {
+            if let Some(room) = self.rooms.get(key) {
+                output.push(key);
+                stack.extend(room.children().copied());
+            }
+        }
+        return output;
+    }
+
+    // Returns the Users of each of the given Rooms. A User in several of the
+    // Rooms is returned once for each
+    fn users_of_rooms(&self, room_keys: &[RoomKey]) -> Vec<UserKey> {
+        let mut output = Vec::new();
+        for room_key in room_keys {
+            if let Some(room) = self.rooms.get(*room_key) {
+                output.extend(room.user_keys().copied());
+            }
+        }
+        return output;
+    }
+
+    // Returns the Entities of each of the given Rooms, along with the Room
+    // they were found in
+    fn entities_of_rooms(&self, room_keys: &[RoomKey]) -> Vec<(RoomKey, E)> {
+        let mut output = Vec::new();
+        for room_key in room_keys {
+            if let Some(room) = self.rooms.get(*room_key) {
+                for entity in room.entities() {
+                    output.push((*room_key, *entity));
+                }
+            }
+        }
+        return output;
+    }
+
+    // Relates (or unrelates) the Users of a parent Room & its ancestors to the
+    // Entities of a child Room & its descendants
+    fn link_rooms(&mut self, parent_key: &RoomKey, child_key: &RoomKey, linked: bool) {
+        let user_keys = self.users_of_rooms(&self.room_and_ancestors(parent_key));
+        let room_entities = self.entities_of_rooms(&self.room_and_descendants(child_key));
+        self.link_scope_checks(&user_keys, &room_entities, linked);
+    }
+
+    // Adds (or removes) a reference to the Scope Set of each given User with
+    // each given Entity
+    fn link_scope_checks(
+        &mut self,
+        user_keys: &[UserKey],
+        room_entities: &[(RoomKey, E)],
+        linked: bool,
+    ) {
+        for user_key in user_keys {
+            for (room_key, entity) in room_entities {
+                if linked {
+                    self.scope_check_cache.insert(room_key, user_key, entity);
+                } else {
+                    self.scope_check_cache.remove(room_key, user_key, entity);
+                }
+            }
+        }
     }
 
     // Component Helpers
@@ -1067,6 +1934,7 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
     }
 
     fn component_cleanup(&mut self, component_key: &ComponentKey) {
+        self.component_authorities.remove(component_key);
         self.world_record.remove_component(component_key);
         self.diff_handler
             .as_ref()
@@ -1075,3 +1943,17 @@ impl<P: ProtocolType, E: Copy + Eq + Hash> Server<P, E> {
             .deregister_component(component_key);
     }
 }
+
+// Inserts the Components of an Entity taken over from a User into the Server
+struct ComponentInserter<'s, 'w, P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>> {
+    server: &'s mut Server<P, E>,
+    world: &'w mut W,
+}
+
+impl<'s, 'w, P: ProtocolType, E: Copy + Eq + Hash, W: WorldMutType<P, E>> ProtocolInserter<P, E>
+    for ComponentInserter<'s, 'w, P, E, W>
+{
+    fn insert<R: ReplicateSafe<P>>(&mut self, entity: &E, component: R) {
+        self.server.insert_component(self.world, entity, component);
+    }
+}
@@ -1,6 +1,8 @@
-use std::{default::Default, time::Duration};
+use std::{default::Default, sync::Arc, time::Duration};
 
-use naia_shared::SocketConfig;
+use naia_shared::{Clock, CongestionConfig, SocketConfig, SystemClock};
+
+use super::interest_manager::InterestConfig;
 
 /// Contains Config properties which will be used by the Server
 #[derive(Clone)]
@@ -28,6 +30,21 @@ pub struct ServerConfig {
     /// Determines whether to require that the Client send some auth message
     /// in order to connect.
     pub require_auth: bool,
+    /// Determines the bandwidth budget of each connection to a Client, which
+    /// adapts to the packet loss & RTT measured on the connection
+    pub congestion_config: CongestionConfig,
+    /// Determines how Entities given a position are automatically scoped to
+    /// Users given a view
+    pub interest_config: InterestConfig,
+    /// The source of time for the Server's ticks, timers & timeouts. Tests &
+    /// replays can use a ManualClock to drive time explicitly
+    pub clock: Arc<dyn Clock>,
+    /// Determines whether the Server's tick only advances when
+    /// `Server::tick()` is called, rather than every tick interval
+    pub manual_tick: bool,
+    /// The maximum number of Entities each User may have spawned at once.
+    /// Entities a User spawns beyond it are rejected
+    pub max_client_entities: usize,
 }
 
 impl Default for ServerConfig {
@@ -40,6 +57,11 @@ impl Default for ServerConfig {
             ping_interval: Duration::from_secs(1),
             rtt_sample_size: 20,
             require_auth: true,
+            congestion_config: CongestionConfig::default(),
+            interest_config: InterestConfig::default(),
+            clock: Arc::new(SystemClock::new()),
+            manual_tick: false,
+            max_client_entities: 256,
         }
     }
 }
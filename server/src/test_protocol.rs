@@ -0,0 +1,33 @@
+use naia_derive::ProtocolType;
+
+pub use health::Health;
+pub use position::Position;
+
+mod health {
+    use naia_derive::Replicate;
+    use naia_shared::Property;
+
+    #[derive(Replicate)]
+    #[protocol_path = "crate::test_protocol::Protocol"]
+    pub struct Health {
+        pub hp: Property<u8>,
+    }
+}
+
+mod position {
+    use naia_derive::Replicate;
+    use naia_shared::Property;
+
+    #[derive(Replicate)]
+    #[protocol_path = "crate::test_protocol::Protocol"]
+    pub struct Position {
+        pub x: Property<u8>,
+        pub y: Property<u8>,
+    }
+}
+
+#[derive(ProtocolType)]
+pub enum Protocol {
+    Position(Position),
+    Health(Health),
+}
@@ -1,30 +1,64 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use naia_shared::Timer;
+use naia_shared::{Clock, ClockTimer};
 
 /// Manages the current tick for the host
 pub struct TickManager {
     current_tick: u16,
-    timer: Timer,
+    // None if ticks are only advanced manually
+    timer: Option<ClockTimer>,
+    pending_ticks: u16,
 }
 
 impl TickManager {
-    /// Create a new TickManager with a given tick interval duration
-    pub fn new(tick_interval: Duration) -> Self {
+    /// Create a new TickManager with a given tick interval duration, or which
+    /// only ticks when advanced manually
+    pub fn new(tick_interval: Duration, clock: &Arc<dyn Clock>, manual: bool) -> Self {
         TickManager {
             current_tick: 0,
-            timer: Timer::new(tick_interval),
+            timer: if manual {
+                None
+            } else {
+                Some(ClockTimer::new(clock, tick_interval))
+            },
+            pending_ticks: 0,
         }
     }
 
+    /// Whether or not the host only ticks when advanced manually
+    pub fn is_manual(&self) -> bool {
+        return self.timer.is_none();
+    }
+
+    /// Queues up a tick, when ticks are advanced manually
+    pub fn advance(&mut self) {
+        self.pending_ticks = self.pending_ticks.saturating_add(1);
+    }
+
     /// Whether or not we should emit a tick event
     pub fn should_tick(&mut self) -> bool {
-        if self.timer.ringing() {
-            self.timer.reset();
+        let ticked = match &mut self.timer {
+            Some(timer) => {
+                if timer.ringing() {
+                    timer.reset();
+                    true
+                } else {
+                    false
+                }
+            }
+            None => {
+                if self.pending_ticks > 0 {
+                    self.pending_ticks -= 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if ticked {
             self.current_tick = self.current_tick.wrapping_add(1);
-            return true;
         }
-        return false;
+        return ticked;
     }
 
     /// Gets the current tick on the host
@@ -32,3 +66,50 @@ impl TickManager {
         self.current_tick
     }
 }
+
+#[cfg(test)]
+mod tick_manager_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use naia_shared::{Clock, ManualClock};
+
+    use super::TickManager;
+
+    fn manual_tick_manager() -> (TickManager, ManualClock) {
+        let clock = ManualClock::new();
+        let shared_clock: Arc<dyn Clock> = Arc::new(clock.clone());
+        let tick_manager = TickManager::new(Duration::from_millis(50), &shared_clock, true);
+        return (tick_manager, clock);
+    }
+
+    #[test]
+    fn ticks_only_when_advanced() {
+        let (mut tick_manager, clock) = manual_tick_manager();
+        assert!(tick_manager.is_manual());
+
+        // time passing doesn't tick
+        clock.advance(Duration::from_secs(1));
+        assert!(!tick_manager.should_tick());
+        assert_eq!(tick_manager.get_tick(), 0);
+
+        tick_manager.advance();
+        assert!(tick_manager.should_tick());
+        assert_eq!(tick_manager.get_tick(), 1);
+        assert!(!tick_manager.should_tick());
+    }
+
+    #[test]
+    fn pending_ticks_accumulate() {
+        let (mut tick_manager, _) = manual_tick_manager();
+        tick_manager.advance();
+        tick_manager.advance();
+        tick_manager.advance();
+
+        // each tick is emitted separately
+        for tick in 1..=3 {
+            assert!(tick_manager.should_tick());
+            assert_eq!(tick_manager.get_tick(), tick);
+        }
+        assert!(!tick_manager.should_tick());
+    }
+}
@@ -0,0 +1,71 @@
+use std::net::SocketAddr;
+
+use naia_server_socket::{NaiaServerSocketError, Packet, PacketReceiver, PacketSender};
+use naia_shared::MemoryNetwork;
+
+/// Sends & receives the packets of a Server. Implemented for the Server's
+/// own socket, & for a MemoryNetwork, so that tests can run a Server & its
+/// Clients in a single thread
+pub trait ServerTransport: Send + Sync {
+    /// Sends a packet to the Client at the packet's address
+    fn send(&mut self, packet: Packet);
+    /// Receives the next packet sent by any Client, if there is one
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaServerSocketError>;
+}
+
+/// A ServerTransport over the Server's socket
+pub struct SocketTransport {
+    packet_sender: PacketSender,
+    packet_receiver: PacketReceiver,
+}
+
+impl SocketTransport {
+    pub fn new(packet_sender: PacketSender, packet_receiver: PacketReceiver) -> Self {
+        SocketTransport {
+            packet_sender,
+            packet_receiver,
+        }
+    }
+}
+
+impl ServerTransport for SocketTransport {
+    fn send(&mut self, packet: Packet) {
+        self.packet_sender.send(packet);
+    }
+
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaServerSocketError> {
+        return self.packet_receiver.receive();
+    }
+}
+
+/// A ServerTransport over a MemoryNetwork, listening at the given address
+pub struct MemoryServerTransport {
+    network: MemoryNetwork,
+    address: SocketAddr,
+}
+
+impl MemoryServerTransport {
+    pub fn new(network: &MemoryNetwork, address: SocketAddr) -> Self {
+        MemoryServerTransport {
+            network: network.clone(),
+            address,
+        }
+    }
+}
+
+impl ServerTransport for MemoryServerTransport {
+    fn send(&mut self, packet: Packet) {
+        self.network.send(
+            &self.address,
+            &packet.address(),
+            Box::from(packet.payload()),
+        );
+    }
+
+    fn receive(&mut self) -> Result<Option<Packet>, NaiaServerSocketError> {
+        return Ok(self
+            .network
+            .receive(&self.address)
+            .map(|(address, payload)| Packet::new_raw(address, payload)));
+    }
+}
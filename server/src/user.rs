@@ -45,6 +45,17 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash> UserRef<'s, P, E> {
     pub fn address(&self) -> SocketAddr {
         return self.server.get_user_address(&self.key).unwrap();
     }
+
+    /// Gets the average Round Trip Time to the User, in milliseconds
+    pub fn rtt(&self) -> Option<f32> {
+        return self.server.get_user_rtt(&self.key);
+    }
+
+    /// Gets the current bandwidth budget of the connection to the User, in
+    /// bytes per second
+    pub fn bandwidth(&self) -> Option<u32> {
+        return self.server.get_user_bandwidth(&self.key);
+    }
 }
 
 // UserMut
@@ -70,6 +81,20 @@ impl<'s, P: ProtocolType, E: Copy + Eq + Hash> UserMut<'s, P, E> {
         self.server.user_force_disconnect(&self.key);
     }
 
+    // Interest
+
+    /// Sets the User's view for spatial interest management. Entities in the
+    /// User's Rooms which have been given a position come into scope once
+    /// within `radius` of the view, and leave scope once further than
+    /// `radius` plus the configured hysteresis. Including or excluding an
+    /// Entity through `user_scope` overrides this for that Entity. The view
+    /// reaches no further than 32 cells of the configured cell size
+    pub fn set_view(&mut self, x: f32, y: f32, radius: f32) -> &mut Self {
+        self.server.user_set_view(&self.key, x, y, radius);
+
+        self
+    }
+
     // Rooms
 
     pub fn enter_room(&mut self, room_key: &RoomKey) -> &mut Self {
@@ -44,10 +44,6 @@ impl UserDiffHandler {
         return None;
     }
 
-    pub fn has_diff_mask(&self, component_key: &ComponentKey) -> bool {
-        return self.receivers.contains_key(component_key);
-    }
-
     pub fn or_diff_mask(&mut self, component_key: &ComponentKey, other_mask: &DiffMask) {
         let current_diff_mask = self
             .receivers
@@ -56,6 +52,12 @@ impl UserDiffHandler {
         current_diff_mask.or_mask(other_mask);
     }
 
+    pub fn set_all_diff_mask(&mut self, component_key: &ComponentKey) {
+        if let Some(receiver) = self.receivers.get_mut(component_key) {
+            receiver.mutate_all();
+        }
+    }
+
     pub fn clear_diff_mask(&mut self, component_key: &ComponentKey) {
         if let Some(receiver) = self.receivers.get_mut(component_key) {
             receiver.clear_mask();
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{collections::HashMap, hash::Hash, time::Duration};
 
 use slotmap::DenseSlotMap;
 
@@ -6,9 +6,16 @@ use naia_shared::ProtocolKindType;
 
 use super::keys::ComponentKey;
 
+/// The base update priority of an Entity's Components, unless set otherwise
+pub const DEFAULT_PRIORITY: f32 = 1.0;
+
 pub struct WorldRecord<E: Copy + Eq + Hash, K: ProtocolKindType> {
     entities: HashMap<E, HashMap<K, ComponentKey>>,
     components: DenseSlotMap<ComponentKey, (E, K)>,
+    entity_priorities: HashMap<E, f32>,
+    component_priorities: HashMap<ComponentKey, f32>,
+    entity_update_intervals: HashMap<E, Duration>,
+    kind_update_intervals: HashMap<K, Duration>,
 }
 
 impl<E: Copy + Eq + Hash, K: ProtocolKindType> WorldRecord<E, K> {
@@ -16,6 +23,10 @@ impl<E: Copy + Eq + Hash, K: ProtocolKindType> WorldRecord<E, K> {
         WorldRecord {
             entities: HashMap::new(),
             components: DenseSlotMap::with_key(),
+            entity_priorities: HashMap::new(),
+            component_priorities: HashMap::new(),
+            entity_update_intervals: HashMap::new(),
+            kind_update_intervals: HashMap::new(),
         }
     }
 
@@ -36,9 +47,12 @@ impl<E: Copy + Eq + Hash, K: ProtocolKindType> WorldRecord<E, K> {
 
         for (_, component_key) in component_key_map {
             self.components.remove(*component_key);
+            self.component_priorities.remove(component_key);
         }
 
         self.entities.remove(entity);
+        self.entity_priorities.remove(entity);
+        self.entity_update_intervals.remove(entity);
     }
 
     pub fn add_component(&mut self, entity: &E, component_type: &K) -> ComponentKey {
@@ -57,6 +71,7 @@ impl<E: Copy + Eq + Hash, K: ProtocolKindType> WorldRecord<E, K> {
         }
 
         let (entity, component_kind) = self.components.remove(*component_key).unwrap();
+        self.component_priorities.remove(component_key);
         if let Some(component_map) = self.entities.get_mut(&entity) {
             component_map
                 .remove(&component_kind)
@@ -64,6 +79,64 @@ impl<E: Copy + Eq + Hash, K: ProtocolKindType> WorldRecord<E, K> {
         }
     }
 
+    // Priority
+
+    pub fn set_entity_priority(&mut self, entity: &E, priority: f32) {
+        if !self.entities.contains_key(entity) {
+            panic!("entity does not exist!");
+        }
+        self.entity_priorities.insert(*entity, priority);
+    }
+
+    pub fn set_component_priority(&mut self, component_key: &ComponentKey, priority: f32) {
+        if !self.components.contains_key(*component_key) {
+            panic!("component does not exist!");
+        }
+        self.component_priorities.insert(*component_key, priority);
+    }
+
+    /// Gets the base update priority of a Component, which is the priority set
+    /// on the Component itself if any, otherwise that set on its Entity
+    pub fn get_priority(&self, component_key: &ComponentKey) -> f32 {
+        if let Some(priority) = self.component_priorities.get(component_key) {
+            return *priority;
+        }
+        if let Some((entity, _)) = self.components.get(*component_key) {
+            if let Some(priority) = self.entity_priorities.get(entity) {
+                return *priority;
+            }
+        }
+        return DEFAULT_PRIORITY;
+    }
+
+    // Update Rate
+
+    pub fn set_entity_update_interval(&mut self, entity: &E, interval: Duration) {
+        if !self.entities.contains_key(entity) {
+            panic!("entity does not exist!");
+        }
+        self.entity_update_intervals.insert(*entity, interval);
+    }
+
+    pub fn set_kind_update_interval(&mut self, component_kind: &K, interval: Duration) {
+        self.kind_update_intervals.insert(*component_kind, interval);
+    }
+
+    /// Gets the minimum duration between updates of a Component, which is the
+    /// longer of the intervals set on its Entity & on its kind, if any
+    pub fn get_update_interval(&self, component_key: &ComponentKey) -> Option<Duration> {
+        let (entity, component_kind) = self.components.get(*component_key)?;
+        let entity_interval = self.entity_update_intervals.get(entity);
+        let kind_interval = self.kind_update_intervals.get(component_kind);
+        return match (entity_interval, kind_interval) {
+            (Some(entity_interval), Some(kind_interval)) => {
+                Some(*entity_interval.max(kind_interval))
+            }
+            (Some(interval), None) | (None, Some(interval)) => Some(*interval),
+            (None, None) => None,
+        };
+    }
+
     // Access
 
     pub fn has_entity(&self, entity: &E) -> bool {
@@ -0,0 +1,266 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use naia_client::{Client, ClientConfig, MemoryClientTransport, NaiaClientError};
+use naia_demo_world::{Entity, World};
+use naia_server::{
+    Event, MemoryServerTransport, NaiaServerError, RoomKey, Server, ServerConfig, UserKey,
+};
+use naia_shared::{Clock, DefaultChannels, ManualClock, MemoryNetwork, SharedConfig, WorldRefType};
+
+use protocol::{Position, Protocol};
+
+mod protocol {
+    use naia_derive::ProtocolType;
+
+    pub use position::Position;
+
+    mod position {
+        use naia_derive::Replicate;
+        use naia_shared::Property;
+
+        #[derive(Replicate)]
+        #[protocol_path = "crate::protocol::Protocol"]
+        pub struct Position {
+            pub x: Property<u8>,
+            pub y: Property<u8>,
+        }
+    }
+
+    #[derive(ProtocolType)]
+    pub enum Protocol {
+        Position(Position),
+    }
+}
+
+const STEP_DURATION: Duration = Duration::from_millis(50);
+
+fn address(port: u16) -> SocketAddr {
+    return SocketAddr::from(([127, 0, 0, 1], port));
+}
+
+fn shared_config() -> SharedConfig<Protocol> {
+    return SharedConfig::new(Protocol::load(), None, None);
+}
+
+// a Server & its Clients, exchanging packets over a MemoryNetwork, with time
+// driven by a single ManualClock
+struct Harness {
+    clock: ManualClock,
+    network: MemoryNetwork,
+    server: Server<Protocol, Entity>,
+    server_world: World<Protocol>,
+    room_key: RoomKey,
+    user_keys: Vec<UserKey>,
+    clients: Vec<(Client<Protocol, Entity>, World<Protocol>)>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        return Harness::with_auth(false);
+    }
+
+    fn with_auth(require_auth: bool) -> Self {
+        let clock = ManualClock::new();
+        let shared_clock: Arc<dyn Clock> = Arc::new(clock.clone());
+        let network = MemoryNetwork::new();
+
+        let mut server_config = ServerConfig::default();
+        server_config.require_auth = require_auth;
+        server_config.clock = shared_clock;
+        let mut server = Server::new(server_config, shared_config());
+        server.listen_with_transport(MemoryServerTransport::new(&network, address(14191)));
+        let room_key = server.make_room().key();
+
+        return Harness {
+            clock,
+            network,
+            server,
+            server_world: World::new(),
+            room_key,
+            user_keys: Vec::new(),
+            clients: Vec::new(),
+        };
+    }
+
+    fn connect_client(&mut self) {
+        let mut client_config = ClientConfig::default();
+        client_config.clock = Arc::new(self.clock.clone());
+        let mut client = Client::new(client_config, shared_config());
+        let client_address = address(14200 + self.clients.len() as u16);
+        client.connect_with_transport(
+            address(14191),
+            MemoryClientTransport::new(&self.network, client_address, address(14191)),
+        );
+        self.clients.push((client, World::new()));
+    }
+
+    // spawns an Entity with a Position, in the Room every User is added to
+    fn spawn(&mut self, x: u8, y: u8) -> Entity {
+        return self
+            .server
+            .spawn_entity(self.server_world.proxy_mut())
+            .insert_component(Position::new_complete(x, y))
+            .enter_room(&self.room_key)
+            .id();
+    }
+
+    // advances time, then lets the Server & every Client receive & send
+    fn step(&mut self) {
+        self.clock.advance(STEP_DURATION);
+
+        for event in self.server.receive() {
+            match event {
+                Ok(Event::Connection(user_key)) => {
+                    self.server.room_mut(&self.room_key).add_user(&user_key);
+                    self.user_keys.push(user_key);
+                }
+                Err(error) => panic!("server error: {}", error),
+                _ => {}
+            }
+        }
+        for (_, user_key, entity) in self.server.scope_checks() {
+            self.server.user_scope(&user_key).include(&entity);
+        }
+        self.server.send_all_updates(self.server_world.proxy());
+
+        for (client, world) in self.clients.iter_mut() {
+            for event in client.receive(world.proxy_mut()) {
+                if let Err(error) = event {
+                    panic!("client error: {}", error);
+                }
+            }
+        }
+    }
+
+    fn step_until<F: Fn(&Harness) -> bool>(&mut self, condition: F) {
+        for _ in 0..100 {
+            if condition(self) {
+                return;
+            }
+            self.step();
+        }
+        panic!("condition not met after 100 steps");
+    }
+
+    // gets the Positions replicated to the given Client
+    fn client_positions(&self, client_index: usize) -> Vec<(u8, u8)> {
+        let world = self.clients[client_index].1.proxy();
+        return world
+            .entities()
+            .iter()
+            .filter_map(|entity| world.get_component::<Position>(entity))
+            .map(|position| (*position.x.get(), *position.y.get()))
+            .collect();
+    }
+}
+
+#[test]
+fn replicates_to_every_client() {
+    let mut harness = Harness::new();
+    let entity = harness.spawn(1, 2);
+    harness.connect_client();
+    harness.connect_client();
+
+    harness.step_until(|harness| harness.clients.iter().all(|(client, _)| client.connected()));
+    assert_eq!(harness.server.users_count(), 2);
+
+    harness.step_until(|harness| {
+        harness.client_positions(0) == [(1, 2)] && harness.client_positions(1) == [(1, 2)]
+    });
+
+    // changes made on the Server are replicated
+    harness
+        .server
+        .entity_mut(harness.server_world.proxy_mut(), &entity)
+        .component::<Position>()
+        .unwrap()
+        .x
+        .set(7);
+    harness.step_until(|harness| {
+        harness.client_positions(0) == [(7, 2)] && harness.client_positions(1) == [(7, 2)]
+    });
+
+    // as is the Entity leaving the Users' scope
+    harness
+        .server
+        .entity_mut(harness.server_world.proxy_mut(), &entity)
+        .despawn();
+    harness.step_until(|harness| {
+        harness.client_positions(0).is_empty() && harness.client_positions(1).is_empty()
+    });
+}
+
+#[test]
+fn replicates_only_in_scope() {
+    let mut harness = Harness::new();
+    harness.connect_client();
+    harness.connect_client();
+    harness.step_until(|harness| harness.user_keys.len() == 2);
+
+    // only the first User is in the Room the Entity enters
+    let second_user_key = harness.user_keys[1];
+    harness
+        .server
+        .room_mut(&harness.room_key)
+        .remove_user(&second_user_key);
+    harness.spawn(3, 4);
+
+    harness.step_until(|harness| harness.client_positions(0) == [(3, 4)]);
+    for _ in 0..10 {
+        harness.step();
+    }
+    assert!(harness.client_positions(1).is_empty());
+}
+
+#[test]
+fn rejected_handshake() {
+    // the Client sends no auth message, which the Server requires
+    let mut harness = Harness::with_auth(true);
+    harness.connect_client();
+
+    let mut server_rejected = false;
+    let mut client_rejected = false;
+    for _ in 0..100 {
+        harness.clock.advance(STEP_DURATION);
+        for event in harness.server.receive() {
+            match event {
+                Err(NaiaServerError::HandshakeRejected(_)) => server_rejected = true,
+                Err(error) => panic!("server error: {}", error),
+                Ok(Event::Connection(_)) => panic!("rejected client connected"),
+                _ => {}
+            }
+        }
+        let (client, world) = &mut harness.clients[0];
+        for event in client.receive(world.proxy_mut()) {
+            match event {
+                Err(NaiaClientError::HandshakeRejected) => client_rejected = true,
+                Err(error) => panic!("client error: {}", error),
+                _ => {}
+            }
+        }
+        if client_rejected {
+            break;
+        }
+    }
+    assert!(server_rejected);
+    assert!(client_rejected);
+    assert!(!harness.clients[0].0.connected());
+}
+
+#[test]
+fn send_before_connected() {
+    let mut harness = Harness::new();
+    harness.connect_client();
+
+    let (client, world) = &mut harness.clients[0];
+    client.send_message(
+        &Position::new_complete(1, 2),
+        DefaultChannels::UnorderedUnreliable,
+    );
+    let errors: Vec<_> = client
+        .receive(world.proxy_mut())
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+    assert!(matches!(errors[..], [NaiaClientError::NotConnected]));
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use super::{
+    blob_manager::BlobManager,
     message_manager::MessageManager,
     packet_notifiable::PacketNotifiable,
     packet_type::PacketType,
@@ -27,6 +28,10 @@ pub struct AckManager {
     // However, we can only reasonably ack up to `REDUNDANT_PACKET_ACKS_SIZE + 1` packets on each
     // message we send so this should be that large.
     received_packets: SequenceBuffer<ReceivedPacket>,
+    // The number of sent data packets which have been acknowledged as delivered / dropped since
+    // these statistics were last taken.
+    delivered_packets: u32,
+    dropped_packets: u32,
 }
 
 impl AckManager {
@@ -37,6 +42,8 @@ impl AckManager {
             remote_ack_sequence_num: u16::max_value(),
             sent_packets: HashMap::with_capacity(DEFAULT_SEND_PACKETS_SIZE),
             received_packets: SequenceBuffer::with_capacity(REDUNDANT_PACKET_ACKS_SIZE + 1),
+            delivered_packets: 0,
+            dropped_packets: 0,
         }
     }
 
@@ -51,6 +58,7 @@ impl AckManager {
         &mut self,
         header: &StandardHeader,
         message_manager: &mut MessageManager<P>,
+        blob_manager: &mut BlobManager,
         packet_notifiable: &mut Option<&mut dyn PacketNotifiable>,
     ) {
         let remote_seq_num = header.local_packet_index();
@@ -69,7 +77,12 @@ impl AckManager {
         // the current `remote_ack_seq` was (clearly) received so we should remove it
         if let Some(sent_packet) = self.sent_packets.get(&remote_ack_seq) {
             if sent_packet.packet_type == PacketType::Data {
-                self.notify_packet_delivered(remote_ack_seq, message_manager, packet_notifiable);
+                self.notify_packet_delivered(
+                    remote_ack_seq,
+                    message_manager,
+                    blob_manager,
+                    packet_notifiable,
+                );
             }
 
             self.sent_packets.remove(&remote_ack_seq);
@@ -86,6 +99,7 @@ impl AckManager {
                         self.notify_packet_delivered(
                             ack_sequence,
                             message_manager,
+                            blob_manager,
                             packet_notifiable,
                         );
                     }
@@ -96,6 +110,7 @@ impl AckManager {
                         self.notify_packet_dropped(
                             ack_sequence,
                             message_manager,
+                            blob_manager,
                             packet_notifiable,
                         );
                     }
@@ -123,25 +138,40 @@ impl AckManager {
         self.sequence_number = self.sequence_number.wrapping_add(1);
     }
 
+    /// Returns the number of sent data packets which have been acknowledged
+    /// as delivered & dropped, in that order, since the last call
+    pub fn take_packet_loss(&mut self) -> (u32, u32) {
+        let packet_loss = (self.delivered_packets, self.dropped_packets);
+        self.delivered_packets = 0;
+        self.dropped_packets = 0;
+        return packet_loss;
+    }
+
     fn notify_packet_delivered<P: ProtocolType>(
-        &self,
+        &mut self,
         packet_sequence_number: u16,
         message_manager: &mut MessageManager<P>,
+        blob_manager: &mut BlobManager,
         packet_notifiable: &mut Option<&mut dyn PacketNotifiable>,
     ) {
+        self.delivered_packets = self.delivered_packets.saturating_add(1);
         message_manager.notify_packet_delivered(packet_sequence_number);
+        blob_manager.notify_packet_delivered(packet_sequence_number);
         if let Some(notifiable) = packet_notifiable {
             notifiable.notify_packet_delivered(packet_sequence_number);
         }
     }
 
     fn notify_packet_dropped<P: ProtocolType>(
-        &self,
+        &mut self,
         packet_sequence_number: u16,
         message_manager: &mut MessageManager<P>,
+        blob_manager: &mut BlobManager,
         packet_notifiable: &mut Option<&mut dyn PacketNotifiable>,
     ) {
+        self.dropped_packets = self.dropped_packets.saturating_add(1);
         message_manager.notify_packet_dropped(packet_sequence_number);
+        blob_manager.notify_packet_dropped(packet_sequence_number);
         if let Some(notifiable) = packet_notifiable {
             notifiable.notify_packet_dropped(packet_sequence_number);
         }
@@ -1,8 +1,18 @@
-use std::net::SocketAddr;
-
-use naia_socket_shared::{PacketReader, Timer};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
 
-use crate::{message_manager::MessageManager, wrapping_number::wrapping_diff};
+use crate::{
+    bit_reader::BitReader,
+    blob_manager::{BlobEvent, BlobManager, BlobRecord},
+    blob_store::BlobStore,
+    channel::{ChannelConfig, ChannelIndex},
+    clock::{Clock, ClockTimer},
+    decode_error::DecodeError,
+    message_manager::{MessageManager, OutgoingMessage, SendMessageError},
+    wrapping_number::wrapping_diff,
+};
 
 use super::{
     ack_manager::AckManager, connection_config::ConnectionConfig, manifest::Manifest,
@@ -14,22 +24,30 @@ use super::{
 /// manage the connection and the communications to it
 pub struct BaseConnection<P: ProtocolType> {
     address: SocketAddr,
-    heartbeat_timer: Timer,
-    timeout_timer: Timer,
+    heartbeat_timer: ClockTimer,
+    timeout_timer: ClockTimer,
     ack_manager: AckManager,
     message_manager: MessageManager<P>,
+    blob_manager: BlobManager,
     last_received_tick: u16,
 }
 
 impl<P: ProtocolType> BaseConnection<P> {
     /// Create a new BaseConnection, given the appropriate underlying managers
-    pub fn new(address: SocketAddr, config: &ConnectionConfig) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        config: &ConnectionConfig,
+        channel_configs: &[ChannelConfig],
+        blob_store: Option<Arc<RwLock<BlobStore>>>,
+        clock: &Arc<dyn Clock>,
+    ) -> Self {
         return BaseConnection {
             address,
-            heartbeat_timer: Timer::new(config.heartbeat_interval),
-            timeout_timer: Timer::new(config.disconnection_timeout_duration),
+            heartbeat_timer: ClockTimer::new(clock, config.heartbeat_interval),
+            timeout_timer: ClockTimer::new(clock, config.disconnection_timeout_duration),
             ack_manager: AckManager::new(),
-            message_manager: MessageManager::new(),
+            message_manager: MessageManager::new(channel_configs),
+            blob_manager: BlobManager::new(blob_store),
             last_received_tick: 0,
         };
     }
@@ -68,8 +86,12 @@ impl<P: ProtocolType> BaseConnection<P> {
         if wrapping_diff(self.last_received_tick, header.host_tick()) > 0 {
             self.last_received_tick = header.host_tick();
         }
-        self.ack_manager
-            .process_incoming(&header, &mut self.message_manager, packet_notifiable);
+        self.ack_manager.process_incoming(
+            &header,
+            &mut self.message_manager,
+            &mut self.blob_manager,
+            packet_notifiable,
+        );
     }
 
     /// Given a packet payload, start tracking the packet via it's index, attach
@@ -115,11 +137,17 @@ impl<P: ProtocolType> BaseConnection<P> {
         return self.ack_manager.get_local_packet_index();
     }
 
-    /// Queue up a message to be sent to the remote host
-    pub fn send_message<R: ReplicateSafe<P>>(&mut self, message: &R, guaranteed_delivery: bool) {
+    /// Queue up a message to be sent to the remote host through the given
+    /// Channel. Fails if the Channel doesn't exist, its queue is full, or the
+    /// Message is too large
+    pub fn send_message<R: ReplicateSafe<P>, C: ChannelIndex>(
+        &mut self,
+        message: &R,
+        channel: C,
+    ) -> Result<(), SendMessageError> {
         return self
             .message_manager
-            .queue_outgoing_message(message, guaranteed_delivery);
+            .queue_outgoing_message(message, channel);
     }
 
     /// Returns whether there are messages to be sent to the remote host
@@ -128,13 +156,13 @@ impl<P: ProtocolType> BaseConnection<P> {
     }
 
     /// Pop the next outgoing message from the queue
-    pub fn pop_outgoing_message(&mut self, next_packet_index: u16) -> Option<P> {
+    pub fn pop_outgoing_message(&mut self, next_packet_index: u16) -> Option<OutgoingMessage<P>> {
         return self.message_manager.pop_outgoing_message(next_packet_index);
     }
 
     /// If for some reason the next outgoing message could not be written into a
     /// message and sent, place it back into the front of the queue
-    pub fn unpop_outgoing_message(&mut self, next_packet_index: u16, message: P) {
+    pub fn unpop_outgoing_message(&mut self, next_packet_index: u16, message: OutgoingMessage<P>) {
         return self
             .message_manager
             .unpop_outgoing_message(next_packet_index, message);
@@ -144,10 +172,10 @@ impl<P: ProtocolType> BaseConnection<P> {
     /// the data to the MessageManager for processing
     pub fn process_message_data(
         &mut self,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         manifest: &Manifest<P>,
         packet_index: u16,
-    ) {
+    ) -> Result<(), DecodeError> {
         return self
             .message_manager
             .process_data(reader, manifest, packet_index);
@@ -158,6 +186,46 @@ impl<P: ProtocolType> BaseConnection<P> {
         return self.message_manager.pop_incoming_message();
     }
 
+    /// Queue up a named Blob to be streamed to the remote host
+    pub fn send_blob(&mut self, name: &str, bytes: Arc<[u8]>) {
+        return self.blob_manager.send_blob(name, bytes);
+    }
+
+    /// Returns whether there are Blob records to be sent to the remote host
+    pub fn has_outgoing_blob_records(&self) -> bool {
+        return self.blob_manager.has_outgoing_records();
+    }
+
+    /// Pop the next outgoing Blob record
+    pub fn pop_outgoing_blob_record(&mut self, next_packet_index: u16) -> Option<BlobRecord> {
+        return self.blob_manager.pop_outgoing_record(next_packet_index);
+    }
+
+    /// If for some reason the next outgoing Blob record could not be written
+    /// into a packet and sent, put it back to be sent next
+    pub fn unpop_outgoing_blob_record(&mut self, next_packet_index: u16, record: BlobRecord) {
+        return self
+            .blob_manager
+            .unpop_outgoing_record(next_packet_index, record);
+    }
+
+    /// Given an incoming packet which has been identified as containing Blob
+    /// records, send the data to the BlobManager for processing
+    pub fn process_blob_data(&mut self, reader: &mut BitReader) -> Result<(), DecodeError> {
+        return self.blob_manager.process_data(reader);
+    }
+
+    /// Get the next event resulting from the transfer of a Blob
+    pub fn get_blob_event(&mut self) -> Option<BlobEvent> {
+        return self.blob_manager.pop_event();
+    }
+
+    /// Returns the number of sent data packets which have been acknowledged
+    /// as delivered & dropped, in that order, since the last call
+    pub fn take_packet_loss(&mut self) -> (u32, u32) {
+        return self.ack_manager.take_packet_loss();
+    }
+
     /// Get the address of the remote host
     pub fn get_address(&self) -> SocketAddr {
         return self.address;
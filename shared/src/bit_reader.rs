@@ -0,0 +1,128 @@
+use crate::decode_error::DecodeError;
+
+/// Reads a stream of individual bits out of a byte buffer, written by a
+/// BitWriter. Reading past the end of the buffer doesn't panic, it yields
+/// zeroed bits & records an error, which can be checked once done reading
+pub struct BitReader<'b> {
+    buffer: &'b [u8],
+    bit_index: usize,
+    error: Option<DecodeError>,
+}
+
+impl<'b> BitReader<'b> {
+    /// Create a new BitReader, which reads from the start of the given buffer
+    pub fn new(buffer: &'b [u8]) -> Self {
+        BitReader {
+            buffer,
+            bit_index: 0,
+            error: None,
+        }
+    }
+
+    /// Reads a single bit from the stream
+    pub fn read_bit(&mut self) -> bool {
+        let byte = match self.buffer.get(self.bit_index / 8) {
+            Some(byte) => *byte,
+            None => {
+                self.set_error(DecodeError::UnexpectedEnd);
+                return false;
+            }
+        };
+        let bit = byte & (1 << (7 - (self.bit_index % 8))) != 0;
+        self.bit_index += 1;
+        return bit;
+    }
+
+    /// Reads a value of `bits` length from the stream, most significant bit
+    /// first
+    pub fn read_bits(&mut self, bits: u8) -> u64 {
+        if bits > 64 {
+            panic!("can't read more than 64 bits of a value at once");
+        }
+        let mut output: u64 = 0;
+        for _ in 0..bits {
+            output <<= 1;
+            if self.read_bit() {
+                output |= 1;
+            }
+        }
+        return output;
+    }
+
+    /// Reads a byte from the stream
+    pub fn read_u8(&mut self) -> u8 {
+        if self.bit_index % 8 == 0 {
+            if let Some(byte) = self.buffer.get(self.bit_index / 8) {
+                self.bit_index += 8;
+                return *byte;
+            }
+        }
+        return self.read_bits(8) as u8;
+    }
+
+    /// Reads a u16 from the stream
+    pub fn read_u16(&mut self) -> u16 {
+        return self.read_bits(16) as u16;
+    }
+
+    /// Reads a u32 from the stream
+    pub fn read_u32(&mut self) -> u32 {
+        return self.read_bits(32) as u32;
+    }
+
+    /// Reads a number of bytes from the stream
+    pub fn read_bytes(&mut self, length: usize) -> Vec<u8> {
+        if length > self.bits_remaining() / 8 {
+            self.set_error(DecodeError::UnexpectedEnd);
+            self.bit_index = self.buffer.len() * 8;
+            return Vec::new();
+        }
+        if self.bit_index % 8 == 0 {
+            let start = self.bit_index / 8;
+            let bytes = self.buffer[start..start + length].to_vec();
+            self.bit_index += length * 8;
+            return bytes;
+        }
+        let mut bytes = Vec::with_capacity(length);
+        for _ in 0..length {
+            bytes.push(self.read_u8());
+        }
+        return bytes;
+    }
+
+    /// Returns whether there are any unread bits left in the stream
+    pub fn has_more(&self) -> bool {
+        return self.bit_index < self.buffer.len() * 8;
+    }
+
+    /// Gets the number of unread bits left in the stream
+    pub fn bits_remaining(&self) -> usize {
+        return (self.buffer.len() * 8).saturating_sub(self.bit_index);
+    }
+
+    /// Records an error found while decoding the stream. Only the first
+    /// error is kept, as later ones are likely caused by it
+    pub fn set_error(&mut self, error: DecodeError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    /// Gets the first error found while reading the stream, if any
+    pub fn error(&self) -> Option<&DecodeError> {
+        return self.error.as_ref();
+    }
+
+    /// Returns whether an error has been found while reading the stream
+    pub fn has_error(&self) -> bool {
+        return self.error.is_some();
+    }
+
+    /// Returns the first error found while reading the stream, if any
+    pub fn check(&self) -> Result<(), DecodeError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+/// Writes a stream of individual bits into a byte buffer, so that values which
+/// don't require a full byte can be packed together in an outgoing packet
+#[derive(Clone)]
+pub struct BitWriter {
+    buffer: Vec<u8>,
+    scratch: u8,
+    scratch_bits: u8,
+}
+
+impl Default for BitWriter {
+    fn default() -> Self {
+        return BitWriter::new();
+    }
+}
+
+impl BitWriter {
+    /// Create a new, empty BitWriter
+    pub fn new() -> Self {
+        BitWriter {
+            buffer: Vec::new(),
+            scratch: 0,
+            scratch_bits: 0,
+        }
+    }
+
+    /// Writes a single bit into the stream
+    pub fn write_bit(&mut self, bit: bool) {
+        if bit {
+            self.scratch |= 1 << (7 - self.scratch_bits);
+        }
+        self.scratch_bits += 1;
+
+        if self.scratch_bits == 8 {
+            self.buffer.push(self.scratch);
+            self.scratch = 0;
+            self.scratch_bits = 0;
+        }
+    }
+
+    /// Writes the lowest `bits` bits of a value into the stream, most
+    /// significant bit first
+    pub fn write_bits(&mut self, value: u64, bits: u8) {
+        if bits > 64 {
+            panic!("can't write more than 64 bits of a value at once");
+        }
+        for index in (0..bits).rev() {
+            self.write_bit((value >> index) & 1 != 0);
+        }
+    }
+
+    /// Writes a byte into the stream
+    pub fn write_u8(&mut self, value: u8) {
+        if self.scratch_bits == 0 {
+            self.buffer.push(value);
+            return;
+        }
+        self.write_bits(u64::from(value), 8);
+    }
+
+    /// Writes a u16 into the stream
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bits(u64::from(value), 16);
+    }
+
+    /// Writes a u32 into the stream
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bits(u64::from(value), 32);
+    }
+
+    /// Writes a slice of bytes into the stream
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.scratch_bits == 0 {
+            self.buffer.extend_from_slice(bytes);
+            return;
+        }
+        for byte in bytes {
+            self.write_u8(*byte);
+        }
+    }
+
+    /// Appends all the bits written into another BitWriter onto the end of
+    /// this stream
+    pub fn append(&mut self, other: &BitWriter) {
+        self.write_bytes(&other.buffer);
+        for index in 0..other.scratch_bits {
+            self.write_bit(other.scratch & (1 << (7 - index)) != 0);
+        }
+    }
+
+    /// Gets the number of bits which have been written into the stream
+    pub fn bits_number(&self) -> usize {
+        return (self.buffer.len() * 8) + usize::from(self.scratch_bits);
+    }
+
+    /// Returns whether no bits have been written into the stream
+    pub fn is_empty(&self) -> bool {
+        return self.bits_number() == 0;
+    }
+
+    /// Consumes the writer, returning the written bytes. Any remaining bits in
+    /// the final byte are padded with zeros
+    pub fn to_bytes(mut self) -> Box<[u8]> {
+        if self.scratch_bits > 0 {
+            self.buffer.push(self.scratch);
+        }
+        return self.buffer.into_boxed_slice();
+    }
+}
+
+#[cfg(test)]
+mod bit_tests {
+    use crate::{BitReader, BitWriter, DecodeError};
+
+    #[test]
+    fn read_write_bits() {
+        let mut writer = BitWriter::new();
+
+        writer.write_bit(true);
+        writer.write_bits(5, 3);
+        writer.write_bit(false);
+        writer.write_u16(54321);
+        writer.write_bits(2, 2);
+
+        assert_eq!(writer.bits_number(), 23);
+
+        let bytes = writer.to_bytes();
+        assert_eq!(bytes.len(), 3);
+
+        let mut reader = BitReader::new(&bytes);
+
+        assert!(reader.read_bit());
+        assert_eq!(reader.read_bits(3), 5);
+        assert!(!reader.read_bit());
+        assert_eq!(reader.read_u16(), 54321);
+        assert_eq!(reader.read_bits(2), 2);
+    }
+
+    #[test]
+    fn read_past_end() {
+        let bytes = [0b1010_0000u8];
+        let mut reader = BitReader::new(&bytes);
+
+        assert_eq!(reader.read_bits(3), 5);
+        assert!(reader.check().is_ok());
+
+        assert_eq!(reader.read_u16(), 0);
+        assert_eq!(reader.error(), Some(&DecodeError::UnexpectedEnd));
+        assert!(reader.read_bytes(4).is_empty());
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn append() {
+        let mut writer_a = BitWriter::new();
+        writer_a.write_bits(3, 2);
+
+        let mut writer_b = BitWriter::new();
+        writer_b.write_u8(200);
+        writer_b.write_bit(true);
+
+        writer_a.append(&writer_b);
+        assert_eq!(writer_a.bits_number(), 11);
+
+        let bytes = writer_a.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+
+        assert_eq!(reader.read_bits(2), 3);
+        assert_eq!(reader.read_u8(), 200);
+        assert!(reader.read_bit());
+        assert_eq!(reader.read_bits(5), 0);
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn aligned_bytes() {
+        let mut writer = BitWriter::new();
+        writer.write_bytes(&[1, 2, 3]);
+        writer.write_bit(true);
+        writer.write_bytes(&[4, 5]);
+
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+
+        assert_eq!(reader.read_bytes(3), vec![1, 2, 3]);
+        assert!(reader.read_bit());
+        assert_eq!(reader.read_bytes(2), vec![4, 5]);
+    }
+}
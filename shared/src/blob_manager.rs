@@ -0,0 +1,814 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, RwLock},
+};
+
+use super::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    blob_store::{
+        blob_hash, BlobHash, BlobStore, ChunkReceipt, BLOB_CHUNK_SIZE_BYTES, MAX_BLOB_SIZE_BYTES,
+    },
+    decode_error::DecodeError,
+    packet_notifiable::PacketNotifiable,
+};
+
+// the maximum number of unacknowledged chunks of a single Blob, which limits
+// the rate at which a Blob is streamed to the round trip time of the connection
+const MAX_CHUNKS_IN_FLIGHT: u16 = 32;
+
+// the number of completed incoming transfers which are remembered, so that
+// duplicate records for them are ignored, before their ids may be reused
+const COMPLETED_TRANSFER_WINDOW: usize = 64;
+
+// Number of bits used to represent the type of a BlobRecord on the wire
+const RECORD_TYPE_BITS: u8 = 3;
+
+/// A unit of communication of the Blob streaming protocol, which is
+/// re-transmitted until delivered
+#[derive(Clone, Debug)]
+pub enum BlobRecord {
+    /// Announces a Blob which the sender wishes to transfer
+    Offer {
+        id: u16,
+        name: String,
+        hash: BlobHash,
+        size: u32,
+    },
+    /// Requests the content of an offered Blob, starting at the given offset
+    Accept { id: u16, offset: u32 },
+    /// Refuses an offered Blob, because the receiver already has it
+    Decline { id: u16 },
+    /// A slice of the content of an accepted Blob
+    Chunk {
+        id: u16,
+        offset: u32,
+        bytes: Box<[u8]>,
+    },
+    /// Requests an accepted Blob be sent again from the start, because its
+    /// content did not match its hash
+    Retry { id: u16 },
+    /// Confirms an accepted Blob has been received, and matches its hash
+    Confirm { id: u16 },
+}
+
+impl BlobRecord {
+    /// Writes the BlobRecord into an outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        match self {
+            BlobRecord::Offer {
+                id,
+                name,
+                hash,
+                size,
+            } => {
+                writer.write_bits(0, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+                writer.write_bytes(hash);
+                writer.write_u32(*size);
+                writer.write_u16(name.len() as u16);
+                writer.write_bytes(name.as_bytes());
+            }
+            BlobRecord::Accept { id, offset } => {
+                writer.write_bits(1, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+                writer.write_u32(*offset);
+            }
+            BlobRecord::Decline { id } => {
+                writer.write_bits(2, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+            }
+            BlobRecord::Chunk { id, offset, bytes } => {
+                writer.write_bits(3, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+                writer.write_u32(*offset);
+                writer.write_u16(bytes.len() as u16);
+                writer.write_bytes(bytes);
+            }
+            BlobRecord::Retry { id } => {
+                writer.write_bits(4, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+            }
+            BlobRecord::Confirm { id } => {
+                writer.write_bits(5, RECORD_TYPE_BITS);
+                writer.write_u16(*id);
+            }
+        }
+    }
+
+    /// Reads a BlobRecord from an incoming bit stream
+    pub fn read(reader: &mut BitReader) -> Self {
+        let record_type = reader.read_bits(RECORD_TYPE_BITS);
+        let id = reader.read_u16();
+        match record_type {
+            0 => {
+                let mut hash = [0; 32];
+                for byte in hash.iter_mut() {
+                    *byte = reader.read_u8();
+                }
+                let size = reader.read_u32();
+                if size as usize > MAX_BLOB_SIZE_BYTES {
+                    reader.set_error(DecodeError::InvalidValue);
+                }
+                let name_length = reader.read_u16();
+                let name_bytes = reader.read_bytes(usize::from(name_length));
+                return BlobRecord::Offer {
+                    id,
+                    name: String::from_utf8_lossy(&name_bytes).into_owned(),
+                    hash,
+                    size,
+                };
+            }
+            1 => {
+                let offset = reader.read_u32();
+                return BlobRecord::Accept { id, offset };
+            }
+            2 => {
+                return BlobRecord::Decline { id };
+            }
+            3 => {
+                let offset = reader.read_u32();
+                let length = reader.read_u16();
+                let bytes = reader.read_bytes(usize::from(length)).into_boxed_slice();
+                return BlobRecord::Chunk { id, offset, bytes };
+            }
+            4 => {
+                return BlobRecord::Retry { id };
+            }
+            5 => {
+                return BlobRecord::Confirm { id };
+            }
+            _ => {
+                reader.set_error(DecodeError::InvalidValue);
+                return BlobRecord::Confirm { id };
+            }
+        }
+    }
+}
+
+/// An event resulting from the transfer of a Blob
+#[derive(Debug)]
+pub enum BlobEvent {
+    /// Part of an incoming Blob has been received
+    Progress {
+        name: String,
+        received_bytes: u32,
+        total_bytes: u32,
+    },
+    /// An incoming Blob has been fully received, or was already held in the
+    /// BlobStore
+    Received { name: String, bytes: Box<[u8]> },
+    /// An outgoing Blob has been fully received by the remote host and matched
+    /// its hash, or the remote host already had it
+    Delivered { name: String },
+}
+
+/// Streams named Blobs of bytes to the remote host in the background, and
+/// receives Blobs streamed from the remote host into a BlobStore
+#[derive(Debug)]
+pub struct BlobManager {
+    blob_store: Option<Arc<RwLock<BlobStore>>>,
+    next_transfer_id: u16,
+    outgoing_transfers: BTreeMap<u16, OutgoingTransfer>,
+    incoming_transfers: HashMap<u16, IncomingTransfer>,
+    completed_incoming_ids: VecDeque<u16>,
+    queued_outgoing_records: VecDeque<BlobRecord>,
+    sent_records: HashMap<u16, Vec<BlobRecord>>,
+    events: VecDeque<BlobEvent>,
+}
+
+// kept until the remote host confirms it holds the Blob, so that a corrupt
+// transfer can be retried
+#[derive(Debug)]
+struct OutgoingTransfer {
+    name: String,
+    bytes: Arc<[u8]>,
+    // None until the remote host has accepted the Blob
+    next_offset: Option<u32>,
+    resend_offsets: VecDeque<u32>,
+    chunks_in_flight: u16,
+}
+
+#[derive(Debug)]
+struct IncomingTransfer {
+    name: String,
+    hash: BlobHash,
+    size: u32,
+    complete: bool,
+}
+
+impl BlobManager {
+    /// Creates a new BlobManager. Blobs offered by the remote host are
+    /// received into the given BlobStore, or declined if there is none
+    pub fn new(blob_store: Option<Arc<RwLock<BlobStore>>>) -> Self {
+        return BlobManager {
+            blob_store,
+            next_transfer_id: 0,
+            outgoing_transfers: BTreeMap::new(),
+            incoming_transfers: HashMap::new(),
+            completed_incoming_ids: VecDeque::new(),
+            queued_outgoing_records: VecDeque::new(),
+            sent_records: HashMap::new(),
+            events: VecDeque::new(),
+        };
+    }
+
+    /// Queues a named Blob to be streamed to the remote host
+    pub fn send_blob(&mut self, name: &str, bytes: Arc<[u8]>) {
+        if bytes.len() > MAX_BLOB_SIZE_BYTES {
+            panic!("Blob is too large to be sent");
+        }
+        if name.len() > usize::from(u16::MAX) {
+            panic!("Blob name is too long to be sent");
+        }
+
+        // skip ids still held by transfers awaiting confirmation
+        let mut id = self.next_transfer_id;
+        while self.outgoing_transfers.contains_key(&id) {
+            id = id.wrapping_add(1);
+        }
+        self.next_transfer_id = id.wrapping_add(1);
+
+        self.queued_outgoing_records.push_back(BlobRecord::Offer {
+            id,
+            name: name.to_string(),
+            hash: blob_hash(&bytes),
+            size: bytes.len() as u32,
+        });
+        self.outgoing_transfers.insert(
+            id,
+            OutgoingTransfer {
+                name: name.to_string(),
+                bytes,
+                next_offset: None,
+                resend_offsets: VecDeque::new(),
+                chunks_in_flight: 0,
+            },
+        );
+    }
+
+    /// Returns whether the Manager has records that can be transmitted to the
+    /// remote host
+    pub fn has_outgoing_records(&self) -> bool {
+        return !self.queued_outgoing_records.is_empty()
+            || self
+                .outgoing_transfers
+                .values()
+                .any(|transfer| transfer.can_send_chunk());
+    }
+
+    /// Gets the next record to be transmitted
+    pub fn pop_outgoing_record(&mut self, packet_index: u16) -> Option<BlobRecord> {
+        let record = match self.queued_outgoing_records.pop_front() {
+            Some(record) => record,
+            None => {
+                let (id, transfer) = self
+                    .outgoing_transfers
+                    .iter_mut()
+                    .find(|(_, transfer)| transfer.can_send_chunk())?;
+                transfer.pop_chunk(*id)
+            }
+        };
+
+        self.sent_records
+            .entry(packet_index)
+            .or_insert_with(Vec::new)
+            .push(record.clone());
+
+        return Some(record);
+    }
+
+    /// If the last popped record somehow wasn't able to be written into a
+    /// packet, put the record back to be transmitted next
+    pub fn unpop_outgoing_record(&mut self, packet_index: u16, record: BlobRecord) {
+        if let Some(sent_records_list) = self.sent_records.get_mut(&packet_index) {
+            sent_records_list.pop();
+            if sent_records_list.is_empty() {
+                self.sent_records.remove(&packet_index);
+            }
+        }
+
+        match record {
+            BlobRecord::Chunk { id, offset, .. } => {
+                if let Some(transfer) = self.outgoing_transfers.get_mut(&id) {
+                    transfer.chunks_in_flight = transfer.chunks_in_flight.saturating_sub(1);
+                    transfer.resend_offsets.push_front(offset);
+                }
+            }
+            _ => {
+                self.queued_outgoing_records.push_front(record);
+            }
+        }
+    }
+
+    /// Given incoming packet data, read transmitted records, and update the
+    /// state of the corresponding transfers
+    pub fn process_data(&mut self, reader: &mut BitReader) -> Result<(), DecodeError> {
+        let mut progressed_transfers: BTreeMap<u16, u32> = BTreeMap::new();
+
+        // each record is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let record = BlobRecord::read(reader);
+            reader.check()?;
+            match record {
+                BlobRecord::Offer {
+                    id,
+                    name,
+                    hash,
+                    size,
+                } => {
+                    self.receive_offer(id, name, hash, size);
+                }
+                BlobRecord::Accept { id, offset } => {
+                    self.receive_accept(id, offset);
+                }
+                BlobRecord::Decline { id } | BlobRecord::Confirm { id } => {
+                    self.complete_outgoing(id);
+                }
+                BlobRecord::Retry { id } => {
+                    if let Some(transfer) = self.outgoing_transfers.get_mut(&id) {
+                        if transfer.next_offset.is_some() {
+                            transfer.next_offset = Some(0);
+                            transfer.resend_offsets.clear();
+                        }
+                    }
+                }
+                BlobRecord::Chunk { id, offset, bytes } => {
+                    if let Some(received_bytes) = self.receive_chunk(id, offset, &bytes) {
+                        progressed_transfers.insert(id, received_bytes);
+                    } else {
+                        progressed_transfers.remove(&id);
+                    }
+                }
+            }
+        }
+
+        for (id, received_bytes) in progressed_transfers {
+            if let Some(transfer) = self.incoming_transfers.get(&id) {
+                self.events.push_back(BlobEvent::Progress {
+                    name: transfer.name.clone(),
+                    received_bytes,
+                    total_bytes: transfer.size,
+                });
+            }
+        }
+        return reader.check();
+    }
+
+    /// Get the next event resulting from the transfer of a Blob
+    pub fn pop_event(&mut self) -> Option<BlobEvent> {
+        return self.events.pop_front();
+    }
+
+    fn receive_offer(&mut self, id: u16, name: String, hash: BlobHash, size: u32) {
+        if let Some(transfer) = self.incoming_transfers.get(&id) {
+            if transfer.hash == hash && transfer.name == name && transfer.size == size {
+                // duplicate offer
+                return;
+            }
+            // the id has been reused for a new Blob
+            self.incoming_transfers.remove(&id);
+            self.completed_incoming_ids
+                .retain(|completed_id| *completed_id != id);
+        }
+
+        let blob_store = match &self.blob_store {
+            Some(blob_store) => blob_store,
+            None => {
+                self.queued_outgoing_records
+                    .push_back(BlobRecord::Decline { id });
+                return;
+            }
+        };
+        let mut blob_store = blob_store.write().unwrap();
+
+        if size == 0 {
+            blob_store.insert(Box::new([]));
+        }
+
+        if let Some(bytes) = blob_store.get(&hash) {
+            self.queued_outgoing_records
+                .push_back(BlobRecord::Decline { id });
+            self.events.push_back(BlobEvent::Received {
+                name: name.clone(),
+                bytes: bytes.into(),
+            });
+            self.incoming_transfers.insert(
+                id,
+                IncomingTransfer {
+                    name,
+                    hash,
+                    size,
+                    complete: true,
+                },
+            );
+            drop(blob_store);
+            self.complete_incoming(id);
+            return;
+        }
+
+        let offset = blob_store.resume_offset(&hash, size);
+        self.queued_outgoing_records
+            .push_back(BlobRecord::Accept { id, offset });
+        self.incoming_transfers.insert(
+            id,
+            IncomingTransfer {
+                name,
+                hash,
+                size,
+                complete: false,
+            },
+        );
+    }
+
+    fn receive_accept(&mut self, id: u16, offset: u32) {
+        if let Some(transfer) = self.outgoing_transfers.get_mut(&id) {
+            if transfer.next_offset.is_some() {
+                // duplicate accept, the transfer is already in progress
+                return;
+            }
+            let size = transfer.bytes.len();
+            let offset = (offset as usize).min(size);
+            let offset = offset - (offset % BLOB_CHUNK_SIZE_BYTES);
+            transfer.next_offset = Some(offset as u32);
+        }
+    }
+
+    // returns the number of bytes of the Blob received, if the chunk
+    // progressed an incomplete Blob
+    fn receive_chunk(&mut self, id: u16, offset: u32, bytes: &[u8]) -> Option<u32> {
+        let transfer = self.incoming_transfers.get_mut(&id)?;
+        if transfer.complete {
+            return None;
+        }
+        let mut blob_store = self.blob_store.as_ref()?.write().unwrap();
+
+        match blob_store.write_chunk(&transfer.hash, transfer.size, offset, bytes) {
+            ChunkReceipt::Discarded => {
+                return None;
+            }
+            ChunkReceipt::Progress(received_bytes) => {
+                return Some(received_bytes);
+            }
+            ChunkReceipt::Complete => {
+                transfer.complete = true;
+                self.events.push_back(BlobEvent::Received {
+                    name: transfer.name.clone(),
+                    bytes: blob_store.get(&transfer.hash).unwrap().into(),
+                });
+                drop(blob_store);
+                self.queued_outgoing_records
+                    .push_back(BlobRecord::Confirm { id });
+                self.complete_incoming(id);
+                return None;
+            }
+            ChunkReceipt::Corrupt => {
+                // start the transfer over
+                self.queued_outgoing_records
+                    .push_back(BlobRecord::Retry { id });
+                return None;
+            }
+        }
+    }
+
+    fn complete_incoming(&mut self, id: u16) {
+        self.completed_incoming_ids.push_back(id);
+        if self.completed_incoming_ids.len() > COMPLETED_TRANSFER_WINDOW {
+            if let Some(oldest_id) = self.completed_incoming_ids.pop_front() {
+                self.incoming_transfers.remove(&oldest_id);
+            }
+        }
+    }
+
+    fn complete_outgoing(&mut self, id: u16) {
+        if let Some(transfer) = self.outgoing_transfers.remove(&id) {
+            self.events.push_back(BlobEvent::Delivered {
+                name: transfer.name,
+            });
+        }
+    }
+}
+
+impl OutgoingTransfer {
+    fn can_send_chunk(&self) -> bool {
+        if let Some(next_offset) = self.next_offset {
+            return self.chunks_in_flight < MAX_CHUNKS_IN_FLIGHT
+                && (!self.resend_offsets.is_empty() || (next_offset as usize) < self.bytes.len());
+        }
+        return false;
+    }
+
+    fn pop_chunk(&mut self, id: u16) -> BlobRecord {
+        let offset = match self.resend_offsets.pop_front() {
+            Some(offset) => offset,
+            None => {
+                let offset = self.next_offset.unwrap();
+                let chunk_length = BLOB_CHUNK_SIZE_BYTES.min(self.bytes.len() - offset as usize);
+                self.next_offset = Some(offset + chunk_length as u32);
+                offset
+            }
+        };
+        self.chunks_in_flight += 1;
+
+        let start = offset as usize;
+        let end = (start + BLOB_CHUNK_SIZE_BYTES).min(self.bytes.len());
+        return BlobRecord::Chunk {
+            id,
+            offset,
+            bytes: self.bytes[start..end].into(),
+        };
+    }
+}
+
+impl PacketNotifiable for BlobManager {
+    /// Occurs when a packet has been notified as delivered. Stops tracking the
+    /// records in that packet. Transfers are completed once the remote host
+    /// confirms the Blob it received
+    fn notify_packet_delivered(&mut self, packet_index: u16) {
+        if let Some(delivered_records) = self.sent_records.remove(&packet_index) {
+            for record in delivered_records {
+                if let BlobRecord::Chunk { id, .. } = record {
+                    if let Some(transfer) = self.outgoing_transfers.get_mut(&id) {
+                        transfer.chunks_in_flight = transfer.chunks_in_flight.saturating_sub(1);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Occurs when a packet has been notified as having been dropped. Queues up
+    /// the records in that packet for retransmission
+    fn notify_packet_dropped(&mut self, packet_index: u16) {
+        if let Some(dropped_records) = self.sent_records.remove(&packet_index) {
+            for record in dropped_records {
+                match record {
+                    BlobRecord::Chunk { id, offset, .. } => {
+                        if let Some(transfer) = self.outgoing_transfers.get_mut(&id) {
+                            transfer.chunks_in_flight = transfer.chunks_in_flight.saturating_sub(1);
+                            transfer.resend_offsets.push_back(offset);
+                        }
+                    }
+                    _ => {
+                        self.queued_outgoing_records.push_back(record);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod blob_manager_tests {
+    use std::sync::{Arc, RwLock};
+
+    use super::{BlobEvent, BlobManager, BlobRecord, COMPLETED_TRANSFER_WINDOW};
+    use crate::{
+        blob_hash, BitReader, BitWriter, BlobStore, DecodeError, PacketNotifiable,
+        MAX_BLOB_SIZE_BYTES,
+    };
+
+    // sends every outgoing record of one manager to another, in a single
+    // packet which is dropped or delivered
+    fn transmit(
+        sender: &mut BlobManager,
+        receiver: &mut BlobManager,
+        packet_index: u16,
+        deliver: bool,
+    ) {
+        let mut writer = BitWriter::new();
+        while let Some(record) = sender.pop_outgoing_record(packet_index) {
+            writer.write_bit(true);
+            record.write(&mut writer);
+        }
+        writer.write_bit(false);
+
+        if deliver {
+            let bytes = writer.to_bytes();
+            let mut reader = BitReader::new(&bytes);
+            assert!(receiver.process_data(&mut reader).is_ok());
+            sender.notify_packet_delivered(packet_index);
+        } else {
+            sender.notify_packet_dropped(packet_index);
+        }
+    }
+
+    // processes the given records as an incoming packet
+    fn receive(receiver: &mut BlobManager, records: &[BlobRecord]) {
+        let mut writer = BitWriter::new();
+        for record in records {
+            writer.write_bit(true);
+            record.write(&mut writer);
+        }
+        writer.write_bit(false);
+
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        assert!(receiver.process_data(&mut reader).is_ok());
+    }
+
+    fn blob(size: usize) -> Arc<[u8]> {
+        return (0..size)
+            .map(|index| (index % 249) as u8)
+            .collect::<Vec<u8>>()
+            .into();
+    }
+
+    #[test]
+    fn transfer_with_loss() {
+        let bytes = blob(5000);
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+        let mut server = BlobManager::new(None);
+        let mut client = BlobManager::new(Some(blob_store.clone()));
+
+        server.send_blob("level", bytes.clone());
+        let mut packet_index = 0;
+        while server.has_outgoing_records() || client.has_outgoing_records() {
+            let deliver = packet_index % 3 != 1;
+            transmit(&mut server, &mut client, packet_index, deliver);
+            transmit(&mut client, &mut server, packet_index, deliver);
+            packet_index += 1;
+        }
+
+        let mut received = None;
+        while let Some(event) = client.pop_event() {
+            if let BlobEvent::Received { name, bytes } = event {
+                assert_eq!(name, "level");
+                received = Some(bytes);
+            }
+        }
+        assert_eq!(received.unwrap().as_ref(), bytes.as_ref());
+        assert!(matches!(
+            server.pop_event(),
+            Some(BlobEvent::Delivered { .. })
+        ));
+    }
+
+    #[test]
+    fn cached_blob_is_declined() {
+        let bytes = blob(3000);
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+        blob_store.write().unwrap().insert(bytes.as_ref().into());
+
+        let mut server = BlobManager::new(None);
+        let mut client = BlobManager::new(Some(blob_store));
+        server.send_blob("texture", bytes);
+
+        transmit(&mut server, &mut client, 0, true);
+        transmit(&mut client, &mut server, 0, true);
+
+        assert!(matches!(
+            client.pop_event(),
+            Some(BlobEvent::Received { .. })
+        ));
+        assert!(matches!(
+            server.pop_event(),
+            Some(BlobEvent::Delivered { .. })
+        ));
+        assert!(!server.has_outgoing_records());
+    }
+
+    #[test]
+    fn resume_transfer() {
+        let bytes = blob(10000);
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+
+        // the first connection is lost part way through the transfer
+        let mut server = BlobManager::new(None);
+        let mut client = BlobManager::new(Some(blob_store.clone()));
+        server.send_blob("map", bytes.clone());
+        for packet_index in 0..2 {
+            transmit(&mut server, &mut client, packet_index, true);
+            transmit(&mut client, &mut server, packet_index, true);
+        }
+        assert!(server.has_outgoing_records());
+
+        // only the remainder of the Blob is sent over the next connection
+        let mut server = BlobManager::new(None);
+        let mut client = BlobManager::new(Some(blob_store));
+        server.send_blob("map", bytes.clone());
+        transmit(&mut server, &mut client, 0, true);
+        transmit(&mut client, &mut server, 0, true);
+
+        let mut chunk_count = 0;
+        while let Some(super::BlobRecord::Chunk { .. }) = server.pop_outgoing_record(1) {
+            chunk_count += 1;
+        }
+        assert!(chunk_count < (10000 / super::BLOB_CHUNK_SIZE_BYTES) - 20);
+    }
+
+    #[test]
+    fn duplicate_accept_is_ignored() {
+        let mut server = BlobManager::new(None);
+        server.send_blob("map", blob(1000));
+        server.pop_outgoing_record(0);
+        server.notify_packet_delivered(0);
+
+        let accept = BlobRecord::Accept { id: 0, offset: 0 };
+        receive(&mut server, std::slice::from_ref(&accept));
+        assert!(matches!(
+            server.pop_outgoing_record(1),
+            Some(BlobRecord::Chunk { offset: 0, .. })
+        ));
+        receive(&mut server, &[accept]);
+        assert!(matches!(
+            server.pop_outgoing_record(1),
+            Some(BlobRecord::Chunk { offset: 256, .. })
+        ));
+    }
+
+    #[test]
+    fn corrupt_blob_is_retried() {
+        let bytes = blob(600);
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+        let mut server = BlobManager::new(None);
+        let mut client = BlobManager::new(Some(blob_store));
+        server.send_blob("save", bytes.clone());
+        transmit(&mut server, &mut client, 0, true);
+        transmit(&mut client, &mut server, 0, true);
+
+        // every chunk of the first attempt is corrupted in transit
+        let mut records = Vec::new();
+        while let Some(mut record) = server.pop_outgoing_record(1) {
+            if let BlobRecord::Chunk { bytes, .. } = &mut record {
+                bytes[0] ^= 1;
+            }
+            records.push(record);
+        }
+        receive(&mut client, &records);
+        server.notify_packet_delivered(1);
+        assert!(server.pop_event().is_none());
+
+        let mut packet_index = 2;
+        while server.has_outgoing_records() || client.has_outgoing_records() {
+            transmit(&mut client, &mut server, packet_index, true);
+            transmit(&mut server, &mut client, packet_index, true);
+            packet_index += 1;
+        }
+
+        let mut received = None;
+        while let Some(event) = client.pop_event() {
+            if let BlobEvent::Received { bytes, .. } = event {
+                received = Some(bytes);
+            }
+        }
+        assert_eq!(received.unwrap().as_ref(), bytes.as_ref());
+        assert!(matches!(
+            server.pop_event(),
+            Some(BlobEvent::Delivered { .. })
+        ));
+    }
+
+    #[test]
+    fn reused_id_is_accepted() {
+        let cached = blob(100);
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+        blob_store.write().unwrap().insert(cached.as_ref().into());
+        let mut client = BlobManager::new(Some(blob_store));
+
+        let offer = |id: u16, bytes: &[u8]| BlobRecord::Offer {
+            id,
+            name: "level".to_string(),
+            hash: blob_hash(bytes),
+            size: bytes.len() as u32,
+        };
+        receive(&mut client, &[offer(0, &cached), offer(0, &blob(200))]);
+        assert!(matches!(
+            client.pop_outgoing_record(0),
+            Some(BlobRecord::Decline { id: 0 })
+        ));
+        assert!(matches!(
+            client.pop_outgoing_record(0),
+            Some(BlobRecord::Accept { id: 0, offset: 0 })
+        ));
+
+        // completed transfers are forgotten once outside the window
+        for id in 1..200 {
+            receive(&mut client, &[offer(id, &cached)]);
+        }
+        assert!(client.incoming_transfers.len() <= COMPLETED_TRANSFER_WINDOW + 1);
+    }
+
+    #[test]
+    fn oversized_offer() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        BlobRecord::Offer {
+            id: 0,
+            name: "huge".to_string(),
+            hash: blob_hash(&[]),
+            size: (MAX_BLOB_SIZE_BYTES + 1) as u32,
+        }
+        .write(&mut writer);
+        writer.write_bit(false);
+
+        let blob_store = Arc::new(RwLock::new(BlobStore::new()));
+        let mut client = BlobManager::new(Some(blob_store));
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(
+            client.process_data(&mut reader),
+            Err(DecodeError::InvalidValue)
+        );
+        assert!(!client.has_outgoing_records());
+    }
+}
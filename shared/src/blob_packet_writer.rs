@@ -0,0 +1,73 @@
+use super::{
+    bit_writer::BitWriter,
+    blob_manager::BlobRecord,
+    manager_type::ManagerType,
+    message_packet_writer::{MTU_SIZE_BITS, SECTION_OVERHEAD_BITS},
+};
+
+/// Handles writing of Blob records into an outgoing packet
+pub struct BlobPacketWriter {
+    record_working_bits: BitWriter,
+    record_count: u16,
+}
+
+impl BlobPacketWriter {
+    /// Construct a new instance of `BlobPacketWriter`
+    pub fn new() -> BlobPacketWriter {
+        BlobPacketWriter {
+            record_working_bits: BitWriter::new(),
+            record_count: 0,
+        }
+    }
+
+    /// Returns whether the writer has bytes to write into the outgoing packet
+    pub fn has_bytes(&self) -> bool {
+        return self.record_count != 0;
+    }
+
+    /// Writes the Blob record data into an outgoing packet's bit stream
+    pub fn get_bytes(&mut self, writer: &mut BitWriter) {
+        //Write manager "header" (manager type)
+        if self.record_count != 0 {
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::Blob.write(writer); // write manager type
+            writer.append(&self.record_working_bits); // write record payload
+            writer.write_bit(false); // write that there are no more records
+
+            self.record_working_bits = BitWriter::new();
+            self.record_count = 0;
+        }
+    }
+
+    /// Get the number of bits which is ready to be written into an outgoing
+    /// packet, including the manager section header
+    pub fn bits_number(&self) -> usize {
+        if self.record_count == 0 {
+            return 0;
+        }
+        return self.record_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
+    }
+
+    /// Writes a Blob record into the Writer's internal buffer, which will
+    /// eventually be put into the outgoing packet. `packet_bits_number` is the
+    /// number of bits used by the other sections of the packet
+    pub fn write_record(&mut self, record: &BlobRecord, packet_bits_number: usize) -> bool {
+        let mut record_total_bits = BitWriter::new();
+
+        record_total_bits.write_bit(true); // write that there is another record
+        record.write(&mut record_total_bits); // write record
+
+        let mut hypothetical_next_payload_size =
+            packet_bits_number + self.bits_number() + record_total_bits.bits_number();
+        if self.record_count == 0 {
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
+        }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
+            self.record_count = self.record_count.wrapping_add(1);
+            self.record_working_bits.append(&record_total_bits);
+            return true;
+        } else {
+            return false;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, HashMap};
+
+use sha2::{Digest, Sha256};
+
+/// The number of bytes of a Blob which are sent in a single chunk
+pub const BLOB_CHUNK_SIZE_BYTES: usize = 256;
+
+/// The maximum size of a Blob which can be sent or received
+pub const MAX_BLOB_SIZE_BYTES: usize = 64 * 1024 * 1024;
+
+/// The SHA-256 content hash of a Blob
+pub type BlobHash = [u8; 32];
+
+/// Gets the content hash of a Blob, used to identify Blobs which the remote
+/// host has already received
+pub fn blob_hash(bytes: &[u8]) -> BlobHash {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&Sha256::digest(bytes));
+    return hash;
+}
+
+/// A cache of Blobs, keyed by their content hash. Also holds the received
+/// parts of incomplete Blobs, so that their transfers can be resumed
+#[derive(Debug, Default)]
+pub struct BlobStore {
+    blobs: HashMap<BlobHash, Box<[u8]>>,
+    partial_blobs: HashMap<BlobHash, PartialBlob>,
+}
+
+// the received chunks of an incomplete Blob, keyed by chunk index, so that
+// memory is only allocated as chunks arrive
+#[derive(Debug)]
+struct PartialBlob {
+    size: u32,
+    chunks: BTreeMap<usize, Box<[u8]>>,
+    received_bytes: u32,
+}
+
+/// The result of writing a received chunk into the BlobStore
+#[derive(Debug, PartialEq)]
+pub(crate) enum ChunkReceipt {
+    /// The chunk had already been received, or was not part of the Blob
+    Discarded,
+    /// The chunk was written, with the given number of bytes of the Blob now
+    /// received
+    Progress(u32),
+    /// The chunk completed the Blob, which is now stored
+    Complete,
+    /// The chunk completed the Blob, but its content hash did not match, so
+    /// all received parts of the Blob were discarded
+    Corrupt,
+}
+
+impl BlobStore {
+    /// Creates a new, empty BlobStore
+    pub fn new() -> Self {
+        return BlobStore::default();
+    }
+
+    /// Inserts a Blob into the store, returning its content hash
+    pub fn insert(&mut self, bytes: Box<[u8]>) -> BlobHash {
+        let hash = blob_hash(&bytes);
+        self.partial_blobs.remove(&hash);
+        self.blobs.insert(hash, bytes);
+        return hash;
+    }
+
+    /// Gets the Blob with the given content hash, if it is stored
+    pub fn get(&self, hash: &BlobHash) -> Option<&[u8]> {
+        return self.blobs.get(hash).map(|bytes| bytes.as_ref());
+    }
+
+    /// Returns whether the Blob with the given content hash is stored
+    pub fn contains(&self, hash: &BlobHash) -> bool {
+        return self.blobs.contains_key(hash);
+    }
+
+    /// Removes the Blob with the given content hash from the store, along
+    /// with any received parts of it
+    pub fn remove(&mut self, hash: &BlobHash) -> Option<Box<[u8]>> {
+        self.partial_blobs.remove(hash);
+        return self.blobs.remove(hash);
+    }
+
+    // Gets the offset from which the transfer of a Blob should resume, which is
+    // the number of bytes of the Blob received without a gap
+    pub(crate) fn resume_offset(&mut self, hash: &BlobHash, size: u32) -> u32 {
+        let partial_blob = self.partial_blob(hash, size);
+        let received_chunks = partial_blob
+            .chunks
+            .keys()
+            .enumerate()
+            .take_while(|(index, chunk_index)| index == *chunk_index)
+            .count();
+        return (received_chunks * BLOB_CHUNK_SIZE_BYTES).min(size as usize) as u32;
+    }
+
+    // Writes a received chunk of a Blob into the store
+    pub(crate) fn write_chunk(
+        &mut self,
+        hash: &BlobHash,
+        size: u32,
+        offset: u32,
+        bytes: &[u8],
+    ) -> ChunkReceipt {
+        let offset = offset as usize;
+        if offset % BLOB_CHUNK_SIZE_BYTES != 0
+            || size as usize > MAX_BLOB_SIZE_BYTES
+            || offset >= size as usize
+            || bytes.len() != BLOB_CHUNK_SIZE_BYTES.min(size as usize - offset)
+        {
+            return ChunkReceipt::Discarded;
+        }
+
+        let partial_blob = self.partial_blob(hash, size);
+        let chunk_index = offset / BLOB_CHUNK_SIZE_BYTES;
+        if partial_blob.chunks.contains_key(&chunk_index) {
+            return ChunkReceipt::Discarded;
+        }
+        partial_blob.chunks.insert(chunk_index, bytes.into());
+        partial_blob.received_bytes += bytes.len() as u32;
+
+        if partial_blob.received_bytes < size {
+            return ChunkReceipt::Progress(partial_blob.received_bytes);
+        }
+
+        let partial_blob = self.partial_blobs.remove(hash).unwrap();
+        let bytes: Vec<u8> = partial_blob
+            .chunks
+            .values()
+            .flat_map(|chunk| chunk.iter().copied())
+            .collect();
+        if blob_hash(&bytes) != *hash {
+            return ChunkReceipt::Corrupt;
+        }
+        self.blobs.insert(*hash, bytes.into_boxed_slice());
+        return ChunkReceipt::Complete;
+    }
+
+    fn partial_blob(&mut self, hash: &BlobHash, size: u32) -> &mut PartialBlob {
+        let partial_blob = self
+            .partial_blobs
+            .entry(*hash)
+            .or_insert_with(|| PartialBlob::new(size));
+        if partial_blob.size != size {
+            *partial_blob = PartialBlob::new(size);
+        }
+        return partial_blob;
+    }
+}
+
+impl PartialBlob {
+    fn new(size: u32) -> Self {
+        return PartialBlob {
+            size,
+            chunks: BTreeMap::new(),
+            received_bytes: 0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod blob_store_tests {
+    use super::{blob_hash, BlobStore, ChunkReceipt, BLOB_CHUNK_SIZE_BYTES, MAX_BLOB_SIZE_BYTES};
+
+    fn blob(size: usize) -> Vec<u8> {
+        return (0..size).map(|index| (index % 253) as u8).collect();
+    }
+
+    #[test]
+    fn resume_and_complete() {
+        let bytes = blob(BLOB_CHUNK_SIZE_BYTES * 2 + 10);
+        let hash = blob_hash(&bytes);
+        let size = bytes.len() as u32;
+        let chunk = BLOB_CHUNK_SIZE_BYTES;
+
+        let mut store = BlobStore::new();
+        assert_eq!(store.resume_offset(&hash, size), 0);
+
+        // chunks received with a gap
+        assert_eq!(
+            store.write_chunk(&hash, size, 0, &bytes[..chunk]),
+            ChunkReceipt::Progress(chunk as u32)
+        );
+        assert_eq!(
+            store.write_chunk(&hash, size, (chunk * 2) as u32, &bytes[chunk * 2..]),
+            ChunkReceipt::Progress((chunk + 10) as u32)
+        );
+        assert_eq!(
+            store.write_chunk(&hash, size, 0, &bytes[..chunk]),
+            ChunkReceipt::Discarded
+        );
+        assert_eq!(store.resume_offset(&hash, size), chunk as u32);
+
+        assert_eq!(
+            store.write_chunk(&hash, size, chunk as u32, &bytes[chunk..chunk * 2]),
+            ChunkReceipt::Complete
+        );
+        assert_eq!(store.get(&hash), Some(bytes.as_slice()));
+    }
+
+    #[test]
+    fn corrupt() {
+        let bytes = blob(10);
+        let hash = blob_hash(&bytes);
+
+        let mut store = BlobStore::new();
+        assert_eq!(
+            store.write_chunk(&hash, 10, 0, &[0; 10]),
+            ChunkReceipt::Corrupt
+        );
+        assert!(!store.contains(&hash));
+        assert_eq!(store.resume_offset(&hash, 10), 0);
+    }
+
+    #[test]
+    fn sha256_hash() {
+        let hash = blob_hash(b"abc");
+        assert_eq!(&hash[..4], &[0xba, 0x78, 0x16, 0xbf]);
+        assert_eq!(&hash[28..], &[0xf2, 0x00, 0x15, 0xad]);
+    }
+
+    #[test]
+    fn reject_oversized() {
+        let hash = blob_hash(&[]);
+        let size = (MAX_BLOB_SIZE_BYTES + 1) as u32;
+
+        let mut store = BlobStore::new();
+        assert_eq!(
+            store.write_chunk(&hash, size, 0, &[0; BLOB_CHUNK_SIZE_BYTES]),
+            ChunkReceipt::Discarded
+        );
+        assert_eq!(store.resume_offset(&hash, size), 0);
+    }
+}
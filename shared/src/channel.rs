@@ -0,0 +1,149 @@
+use super::{bit_reader::BitReader, bit_writer::BitWriter, serde::Serde};
+
+/// The delivery guarantees of a Channel through which Messages are sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Messages may be dropped, and may arrive in any order
+    UnorderedUnreliable,
+    /// Messages may be dropped, and any Message arriving after a more recently
+    /// sent Message is discarded
+    SequencedUnreliable,
+    /// Messages are resent until delivered, and may arrive in any order
+    UnorderedReliable,
+    /// Messages are resent until delivered, and are handed to the application
+    /// in the order they were sent
+    OrderedReliable,
+}
+
+impl ChannelMode {
+    /// Returns whether Messages sent through the Channel are resent until
+    /// delivered
+    pub fn is_reliable(&self) -> bool {
+        match self {
+            ChannelMode::UnorderedUnreliable | ChannelMode::SequencedUnreliable => false,
+            ChannelMode::UnorderedReliable | ChannelMode::OrderedReliable => true,
+        }
+    }
+
+    /// Returns whether Messages sent through the Channel are tagged with an
+    /// index, used by the remote host to discard or reorder Messages
+    pub fn is_indexed(&self) -> bool {
+        return *self != ChannelMode::UnorderedUnreliable;
+    }
+}
+
+// Written during the handshake, so that the Server can check that the Client
+// was configured with the same Channels
+impl Serde for ChannelMode {
+    fn ser(&self, writer: &mut BitWriter) {
+        let index: u64 = match self {
+            ChannelMode::UnorderedUnreliable => 0,
+            ChannelMode::SequencedUnreliable => 1,
+            ChannelMode::UnorderedReliable => 2,
+            ChannelMode::OrderedReliable => 3,
+        };
+        writer.write_bits(index, 2);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        match reader.read_bits(2) {
+            0 => ChannelMode::UnorderedUnreliable,
+            1 => ChannelMode::SequencedUnreliable,
+            2 => ChannelMode::UnorderedReliable,
+            _ => ChannelMode::OrderedReliable,
+        }
+    }
+}
+
+/// Contains the configuration of a Channel through which Messages are sent
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelConfig {
+    /// The delivery guarantees of the Channel
+    pub mode: ChannelMode,
+    /// Messages of Channels with a higher priority are written into outgoing
+    /// packets before those of Channels with a lower priority
+    pub priority: u8,
+    /// The maximum number of Messages of the Channel which can be written into
+    /// a single outgoing packet, or None if unlimited
+    pub max_messages_per_packet: Option<u16>,
+    /// The maximum number of Messages of the Channel which can be queued up,
+    /// waiting to be sent, or None if unlimited. Messages sent while the
+    /// queue is full are dropped, & reported as an error
+    pub max_queued_messages: Option<usize>,
+}
+
+impl ChannelConfig {
+    /// Creates a new ChannelConfig with the lowest priority, no budget and an
+    /// unlimited queue
+    pub fn new(mode: ChannelMode) -> Self {
+        return ChannelConfig {
+            mode,
+            priority: 0,
+            max_messages_per_packet: None,
+            max_queued_messages: None,
+        };
+    }
+}
+
+/// Identifies a Channel through which Messages are sent
+pub trait ChannelIndex: Copy {
+    /// Gets the index of the Channel
+    fn index(&self) -> u8;
+}
+
+/// A set of Channels, declared by the Protocol, which both the Server & Client
+/// use to send Messages
+pub trait Channels: ChannelIndex {
+    /// Gets the configuration of each Channel, in order of index
+    fn configs() -> Vec<ChannelConfig>;
+}
+
+/// The Channels through which Messages can be sent, one for each ChannelMode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DefaultChannels {
+    /// Messages may be dropped, and may arrive in any order
+    UnorderedUnreliable = 0,
+    /// Messages may be dropped, and any Message arriving after a more recently
+    /// sent Message is discarded
+    SequencedUnreliable = 1,
+    /// Messages are resent until delivered, and may arrive in any order
+    UnorderedReliable = 2,
+    /// Messages are resent until delivered, and are handed to the application
+    /// in the order they were sent
+    OrderedReliable = 3,
+}
+
+impl ChannelIndex for DefaultChannels {
+    fn index(&self) -> u8 {
+        return *self as u8;
+    }
+}
+
+impl Channels for DefaultChannels {
+    fn configs() -> Vec<ChannelConfig> {
+        return vec![
+            ChannelConfig::new(ChannelMode::UnorderedUnreliable),
+            ChannelConfig::new(ChannelMode::SequencedUnreliable),
+            ChannelConfig::new(ChannelMode::UnorderedReliable),
+            ChannelConfig::new(ChannelMode::OrderedReliable),
+        ];
+    }
+}
+
+#[cfg(test)]
+mod channel_tests {
+    use crate::{serde::round_trip, ChannelMode};
+
+    #[test]
+    fn channel_modes() {
+        let modes = vec![
+            ChannelMode::UnorderedUnreliable,
+            ChannelMode::SequencedUnreliable,
+            ChannelMode::UnorderedReliable,
+            ChannelMode::OrderedReliable,
+        ];
+        // a 6 bit length, then 2 bits for each mode
+        assert_eq!(round_trip(modes), 6 + 4 * 2);
+    }
+}
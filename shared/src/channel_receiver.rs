@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{
+    channel::ChannelMode,
+    wrapping_number::{sequence_greater_than, sequence_less_than},
+};
+
+// the furthest a reliable Message may be ahead of the oldest Message not yet
+// received. The sender never sends past this, so any Message further ahead
+// is ignored rather than buffered
+pub(crate) const MAX_MESSAGES_AHEAD: u16 = 1024;
+
+/// Receives the Messages of a single Channel, handing them to the application
+/// according to the Channel's ChannelMode
+#[derive(Debug)]
+pub struct ChannelReceiver<P> {
+    mode: ChannelMode,
+    // the index of the oldest Message not yet received, where applicable
+    next_index: u16,
+    // whether any indexed Message has been received
+    has_received: bool,
+    // reliable Messages which were received ahead of `next_index`
+    received_ahead: HashSet<u16>,
+    // ordered Messages waiting on the delivery of an earlier Message
+    buffered_messages: HashMap<u16, P>,
+}
+
+impl<P> ChannelReceiver<P> {
+    pub fn new(mode: ChannelMode) -> Self {
+        return Self {
+            mode,
+            next_index: 0,
+            has_received: false,
+            received_ahead: HashSet::new(),
+            buffered_messages: HashMap::new(),
+        };
+    }
+
+    /// Receives a Message, and pushes any Messages that are ready to be handed
+    /// to the application into the given queue
+    pub fn receive(&mut self, message_index: u16, message: P, incoming: &mut VecDeque<P>) {
+        match self.mode {
+            ChannelMode::UnorderedUnreliable => {
+                incoming.push_back(message);
+            }
+            ChannelMode::SequencedUnreliable => {
+                if !self.has_received || !sequence_greater_than(self.next_index, message_index) {
+                    self.has_received = true;
+                    self.next_index = message_index.wrapping_add(1);
+                    incoming.push_back(message);
+                }
+            }
+            ChannelMode::UnorderedReliable => {
+                if !self.is_expected(message_index) {
+                    return;
+                }
+                self.received_ahead.insert(message_index);
+                incoming.push_back(message);
+                self.advance(incoming);
+            }
+            ChannelMode::OrderedReliable => {
+                if !self.is_expected(message_index) {
+                    return;
+                }
+                self.received_ahead.insert(message_index);
+                self.buffered_messages.insert(message_index, message);
+                self.advance(incoming);
+            }
+        }
+    }
+
+    // whether a reliable Message is neither a duplicate, nor too far ahead
+    fn is_expected(&self, message_index: u16) -> bool {
+        return !sequence_less_than(message_index, self.next_index)
+            && message_index.wrapping_sub(self.next_index) < MAX_MESSAGES_AHEAD
+            && !self.received_ahead.contains(&message_index);
+    }
+
+    // moves `next_index` past every Message which has been received, handing
+    // any buffered Messages to the application in order
+    fn advance(&mut self, incoming: &mut VecDeque<P>) {
+        while self.received_ahead.remove(&self.next_index) {
+            if let Some(message) = self.buffered_messages.remove(&self.next_index) {
+                incoming.push_back(message);
+            }
+            self.next_index = self.next_index.wrapping_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod channel_receiver_tests {
+    use std::collections::VecDeque;
+
+    use super::{ChannelReceiver, MAX_MESSAGES_AHEAD};
+    use crate::ChannelMode;
+
+    fn receive_all(mode: ChannelMode, indices: &[u16]) -> Vec<u16> {
+        let mut receiver = ChannelReceiver::new(mode);
+        let mut incoming = VecDeque::new();
+        for index in indices {
+            receiver.receive(*index, *index, &mut incoming);
+        }
+        return incoming.into_iter().collect();
+    }
+
+    #[test]
+    fn sequenced_unreliable() {
+        let output = receive_all(ChannelMode::SequencedUnreliable, &[0, 2, 1, 3, 3, 5]);
+        assert_eq!(output, vec![0, 2, 3, 5]);
+    }
+
+    #[test]
+    fn unordered_reliable() {
+        let output = receive_all(ChannelMode::UnorderedReliable, &[1, 0, 1, 3, 2, 0, 3]);
+        assert_eq!(output, vec![1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn ordered_reliable() {
+        let output = receive_all(ChannelMode::OrderedReliable, &[2, 0, 3, 0, 1, 2, 4]);
+        assert_eq!(output, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn ignore_too_far_ahead() {
+        let mut receiver = ChannelReceiver::new(ChannelMode::OrderedReliable);
+        let mut incoming = VecDeque::new();
+        receiver.receive(MAX_MESSAGES_AHEAD, 0, &mut incoming);
+        for index in 1..MAX_MESSAGES_AHEAD {
+            receiver.receive(index, index, &mut incoming);
+        }
+        assert_eq!(
+            receiver.buffered_messages.len(),
+            usize::from(MAX_MESSAGES_AHEAD - 1)
+        );
+
+        receiver.receive(0, 0, &mut incoming);
+        assert_eq!(incoming.len(), usize::from(MAX_MESSAGES_AHEAD));
+        assert!(receiver.received_ahead.is_empty());
+        assert!(receiver.buffered_messages.is_empty());
+    }
+
+    #[test]
+    fn ordered_reliable_wrapping() {
+        let mut receiver = ChannelReceiver::new(ChannelMode::OrderedReliable);
+        let mut incoming = VecDeque::new();
+        for index in 0..u16::MAX {
+            receiver.receive(index, index, &mut incoming);
+        }
+        incoming.clear();
+
+        receiver.receive(0, 0, &mut incoming);
+        receiver.receive(u16::MAX, u16::MAX, &mut incoming);
+        assert_eq!(
+            incoming.into_iter().collect::<Vec<u16>>(),
+            vec![u16::MAX, 0]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use naia_socket_shared::Instant;
+
+/// A source of time for the timers, ticks & timeouts of a Server or Client.
+/// Times are measured from an arbitrary, fixed point
+pub trait Clock: Send + Sync {
+    /// Gets the time elapsed since the Clock's fixed point
+    fn now(&self) -> Duration;
+}
+
+/// A Clock which follows the system's time
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        return self.start.elapsed();
+    }
+}
+
+/// A Clock which only moves forward when it is advanced, so that tests &
+/// replays can drive time explicitly. Clones share the same time
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        return ManualClock::default();
+    }
+
+    /// Moves the Clock forward by the given duration
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        return *self.now.lock().unwrap();
+    }
+}
+
+/// Rings once a given duration has passed on a Clock, since the Timer was
+/// created or last reset
+pub struct ClockTimer {
+    clock: Arc<dyn Clock>,
+    duration: Duration,
+    last: Duration,
+    rung_manually: bool,
+}
+
+impl ClockTimer {
+    pub fn new(clock: &Arc<dyn Clock>, duration: Duration) -> Self {
+        ClockTimer {
+            clock: clock.clone(),
+            duration,
+            last: clock.now(),
+            rung_manually: false,
+        }
+    }
+
+    /// Starts waiting for the duration again, from the current time
+    pub fn reset(&mut self) {
+        self.last = self.clock.now();
+        self.rung_manually = false;
+    }
+
+    /// Makes the Timer ring until it is next reset, as if the duration had
+    /// passed
+    pub fn ring_manual(&mut self) {
+        self.rung_manually = true;
+    }
+
+    /// Returns whether the duration has passed
+    pub fn ringing(&self) -> bool {
+        return self.rung_manually || self.clock.now().saturating_sub(self.last) >= self.duration;
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{Clock, ClockTimer, ManualClock};
+
+    #[test]
+    fn manual_clock_shared_between_clones() {
+        let clock = ManualClock::new();
+        let other = clock.clone();
+        clock.advance(Duration::from_millis(30));
+        other.advance(Duration::from_millis(20));
+        assert_eq!(clock.now(), Duration::from_millis(50));
+        assert_eq!(other.now(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn timer_rings_after_duration() {
+        let manual_clock = ManualClock::new();
+        let clock: Arc<dyn Clock> = Arc::new(manual_clock.clone());
+        let mut timer = ClockTimer::new(&clock, Duration::from_secs(1));
+        assert!(!timer.ringing());
+
+        manual_clock.advance(Duration::from_millis(999));
+        assert!(!timer.ringing());
+        manual_clock.advance(Duration::from_millis(1));
+        assert!(timer.ringing());
+
+        timer.reset();
+        assert!(!timer.ringing());
+        manual_clock.advance(Duration::from_secs(1));
+        assert!(timer.ringing());
+
+        timer.reset();
+        timer.ring_manual();
+        assert!(timer.ringing());
+        timer.reset();
+        assert!(!timer.ringing());
+    }
+}
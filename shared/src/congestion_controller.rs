@@ -0,0 +1,267 @@
+use std::{sync::Arc, time::Duration};
+
+use super::{clock::Clock, message_packet_writer::MTU_SIZE};
+
+// the shortest interval over which the bandwidth budget is re-evaluated. The
+// interval is otherwise the current RTT to the remote host
+const MIN_EVALUATION_INTERVAL_SECONDS: f32 = 0.1;
+// the longest time for which unused budget is allowed to accumulate, which
+// bounds the size of a burst of packets after an idle period
+const MAX_BURST_SECONDS: f32 = 0.25;
+// the fraction of packets dropped within an interval above which the link is
+// considered congested
+const LOSS_THRESHOLD: f32 = 0.05;
+// the increase in RTT over the lowest RTT measured, in milliseconds, above
+// which the link is considered congested (packets are queueing up)
+const QUEUEING_DELAY_THRESHOLD_MILLIS: f32 = 100.0;
+// the factor by which the budget is multiplied when congestion is detected
+const DECREASE_FACTOR: f32 = 0.75;
+// the fraction of the budget for an interval which must be used for the budget
+// to grow, so that an idle connection does not build up a budget it has never
+// tested
+const UTILIZATION_THRESHOLD: f32 = 0.5;
+
+/// Contains Config properties which determine the bandwidth budget of a
+/// Connection, in bytes per second
+#[derive(Clone, Debug)]
+pub struct CongestionConfig {
+    /// The budget a Connection starts out with
+    pub initial_bandwidth: u32,
+    /// The budget will never be lowered below this, however congested the
+    /// link appears
+    pub min_bandwidth: u32,
+    /// The budget will never be raised above this
+    pub max_bandwidth: u32,
+    /// The amount the budget is raised by for every interval in which the
+    /// budget was used without signs of congestion
+    pub additive_increase: u32,
+}
+
+impl Default for CongestionConfig {
+    fn default() -> Self {
+        Self {
+            initial_bandwidth: 64 * 1024,
+            min_bandwidth: 8 * 1024,
+            max_bandwidth: 1024 * 1024,
+            additive_increase: 4 * 1024,
+        }
+    }
+}
+
+/// Limits the rate at which packets are sent to a remote host to a bandwidth
+/// budget, which is adapted (additive increase, multiplicative decrease) to
+/// the packet loss & RTT measured on the Connection
+pub struct CongestionController {
+    config: CongestionConfig,
+    bandwidth: f32,
+    available_bytes: f32,
+    clock: Arc<dyn Clock>,
+    last_refill: Duration,
+    last_evaluation: Duration,
+    interval_bytes_sent: f32,
+    interval_delivered: u32,
+    interval_dropped: u32,
+    rtt_millis: f32,
+    min_rtt_millis: Option<f32>,
+}
+
+impl CongestionController {
+    /// Creates a new CongestionController
+    pub fn new(config: &CongestionConfig, clock: &Arc<dyn Clock>) -> Self {
+        let bandwidth = config.initial_bandwidth as f32;
+        let now = clock.now();
+        return CongestionController {
+            config: config.clone(),
+            bandwidth,
+            available_bytes: bandwidth * MIN_EVALUATION_INTERVAL_SECONDS,
+            clock: clock.clone(),
+            last_refill: now,
+            last_evaluation: now,
+            interval_bytes_sent: 0.0,
+            interval_delivered: 0,
+            interval_dropped: 0,
+            rtt_millis: 0.0,
+            min_rtt_millis: None,
+        };
+    }
+
+    /// Gets the current bandwidth budget, in bytes per second
+    pub fn bandwidth(&self) -> u32 {
+        return self.bandwidth as u32;
+    }
+
+    /// Records the latest RTT to the remote host, in milliseconds
+    pub fn record_rtt(&mut self, rtt_millis: f32) {
+        if rtt_millis <= 0.0 {
+            return;
+        }
+        self.rtt_millis = rtt_millis;
+        self.min_rtt_millis = Some(match self.min_rtt_millis {
+            Some(min_rtt_millis) => min_rtt_millis.min(rtt_millis),
+            None => rtt_millis,
+        });
+    }
+
+    /// Records the number of packets which have been acknowledged as
+    /// delivered & dropped since the last call
+    pub fn record_packet_loss(&mut self, delivered: u32, dropped: u32) {
+        self.interval_delivered += delivered;
+        self.interval_dropped += dropped;
+    }
+
+    /// Adds the budget accumulated since the last call, and adapts the
+    /// bandwidth budget if an evaluation interval has passed
+    pub fn update(&mut self) {
+        let now = self.clock.now();
+        let elapsed_seconds = now.saturating_sub(self.last_refill).as_secs_f32();
+        self.last_refill = now;
+        self.refill(elapsed_seconds);
+
+        let interval_seconds = (self.rtt_millis / 1000.0).max(MIN_EVALUATION_INTERVAL_SECONDS);
+        let evaluation_elapsed_seconds = now.saturating_sub(self.last_evaluation).as_secs_f32();
+        if evaluation_elapsed_seconds >= interval_seconds {
+            self.last_evaluation = now;
+            self.evaluate(evaluation_elapsed_seconds);
+        }
+    }
+
+    /// Returns whether there is budget left to send another packet
+    pub fn can_send(&self) -> bool {
+        return self.available_bytes > 0.0;
+    }
+
+    /// Records that a packet of the given size has been sent. The budget may
+    /// go into debt, which is paid back before the next packet can be sent
+    pub fn mark_sent(&mut self, bytes: usize) {
+        self.available_bytes -= bytes as f32;
+        self.interval_bytes_sent += bytes as f32;
+    }
+
+    fn refill(&mut self, elapsed_seconds: f32) {
+        let max_available_bytes = (self.bandwidth * MAX_BURST_SECONDS).max(MTU_SIZE as f32);
+        self.available_bytes =
+            (self.available_bytes + self.bandwidth * elapsed_seconds).min(max_available_bytes);
+    }
+
+    fn evaluate(&mut self, interval_seconds: f32) {
+        let packets = self.interval_delivered + self.interval_dropped;
+        let lossy = packets > 0 && (self.interval_dropped as f32 / packets as f32) > LOSS_THRESHOLD;
+        let queueing = match self.min_rtt_millis {
+            Some(min_rtt_millis) => {
+                self.rtt_millis - min_rtt_millis > QUEUEING_DELAY_THRESHOLD_MILLIS
+            }
+            None => false,
+        };
+        let utilized =
+            self.interval_bytes_sent >= self.bandwidth * interval_seconds * UTILIZATION_THRESHOLD;
+
+        if lossy || queueing {
+            self.bandwidth *= DECREASE_FACTOR;
+        } else if utilized {
+            self.bandwidth += self.config.additive_increase as f32;
+        }
+        self.bandwidth = self
+            .bandwidth
+            .max(self.config.min_bandwidth as f32)
+            .min(self.config.max_bandwidth as f32);
+
+        self.interval_bytes_sent = 0.0;
+        self.interval_delivered = 0;
+        self.interval_dropped = 0;
+    }
+}
+
+#[cfg(test)]
+mod congestion_controller_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{CongestionConfig, CongestionController, MAX_BURST_SECONDS};
+    use crate::clock::{Clock, ManualClock};
+
+    fn controller_with_clock(clock: &ManualClock) -> CongestionController {
+        let clock: Arc<dyn Clock> = Arc::new(clock.clone());
+        return CongestionController::new(
+            &CongestionConfig {
+                initial_bandwidth: 10_000,
+                min_bandwidth: 2_000,
+                max_bandwidth: 20_000,
+                additive_increase: 1_000,
+            },
+            &clock,
+        );
+    }
+
+    fn controller() -> CongestionController {
+        return controller_with_clock(&ManualClock::new());
+    }
+
+    #[test]
+    fn budget_limits_sending() {
+        let mut controller = controller();
+        controller.available_bytes = 0.0;
+        assert!(!controller.can_send());
+
+        controller.refill(0.05);
+        assert!(controller.can_send());
+        controller.mark_sent(600);
+        assert!(!controller.can_send());
+
+        // unused budget only accumulates up to a burst
+        controller.refill(10.0);
+        assert_eq!(controller.available_bytes, 10_000.0 * MAX_BURST_SECONDS);
+    }
+
+    #[test]
+    fn increase_when_utilized() {
+        let mut controller = controller();
+
+        // idle connection does not grow its budget
+        controller.record_packet_loss(10, 0);
+        controller.evaluate(1.0);
+        assert_eq!(controller.bandwidth(), 10_000);
+
+        controller.mark_sent(8_000);
+        controller.record_packet_loss(10, 0);
+        controller.evaluate(1.0);
+        assert_eq!(controller.bandwidth(), 11_000);
+    }
+
+    #[test]
+    fn decrease_on_loss() {
+        let mut controller = controller();
+        controller.mark_sent(10_000);
+        controller.record_packet_loss(8, 2);
+        controller.evaluate(1.0);
+        assert_eq!(controller.bandwidth(), 7_500);
+
+        for _ in 0..10 {
+            controller.record_packet_loss(0, 10);
+            controller.evaluate(1.0);
+        }
+        assert_eq!(controller.bandwidth(), 2_000);
+    }
+
+    #[test]
+    fn decrease_on_queueing_delay() {
+        let mut controller = controller();
+        controller.record_rtt(50.0);
+        controller.record_rtt(200.0);
+        controller.mark_sent(10_000);
+        controller.evaluate(1.0);
+        assert_eq!(controller.bandwidth(), 7_500);
+    }
+
+    #[test]
+    fn update_follows_clock() {
+        let clock = ManualClock::new();
+        let mut controller = controller_with_clock(&clock);
+        controller.available_bytes = 0.0;
+
+        controller.update();
+        assert!(!controller.can_send());
+
+        clock.advance(Duration::from_millis(50));
+        controller.update();
+        assert_eq!(controller.available_bytes, 500.0);
+    }
+}
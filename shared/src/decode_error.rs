@@ -0,0 +1,42 @@
+use std::{error::Error, fmt};
+
+/// An error encountered while decoding an incoming packet, which means the
+/// packet is malformed, or was sent by a peer with an incompatible Protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The packet ended before all of its contents could be read
+    UnexpectedEnd,
+    /// The packet has an unknown type
+    UnknownPacketType(u8),
+    /// A section of the packet is addressed to an unknown manager
+    UnknownManagerType,
+    /// An Entity action of an unknown type was read
+    UnknownEntityAction,
+    /// A Message or Component of an unknown kind was read
+    UnknownKind(u16),
+    /// A value was read which isn't valid for its type
+    InvalidValue,
+    /// The packet refers to an Entity, Component or other state which does
+    /// not exist
+    InvalidReference,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "packet ended unexpectedly"),
+            DecodeError::UnknownPacketType(packet_type) => {
+                write!(f, "unknown packet type: {}", packet_type)
+            }
+            DecodeError::UnknownManagerType => write!(f, "unknown manager type"),
+            DecodeError::UnknownEntityAction => write!(f, "unknown entity action"),
+            DecodeError::UnknownKind(kind) => write!(f, "unknown protocol kind: {}", kind),
+            DecodeError::InvalidValue => write!(f, "invalid value"),
+            DecodeError::InvalidReference => {
+                write!(f, "reference to a nonexistent entity or component")
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
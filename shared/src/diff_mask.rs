@@ -1,16 +1,16 @@
-use byteorder::WriteBytesExt;
-use std::fmt;
-
-use naia_socket_shared::PacketReader;
+use std::{collections::BTreeMap, fmt};
 
 /// The DiffMask is a variable-length byte array, where each bit represents
 /// the current state of a Property owned by a Replica.
 /// The Property tracks whether it has been updated and needs to be synced
-/// with the remote Client
+/// with the remote Client.
+/// A Property with nested state (see `NestedProperty`) may instead have only
+/// some of its elements marked as updated, tracked by a SubDiffMask
 #[derive(Debug, Clone)]
 pub struct DiffMask {
     mask: Vec<u8>,
     bytes: u8,
+    nested: BTreeMap<u8, SubDiffMask>,
 }
 
 impl DiffMask {
@@ -19,6 +19,7 @@ impl DiffMask {
         DiffMask {
             bytes,
             mask: vec![0; bytes as usize],
+            nested: BTreeMap::new(),
         }
     }
 
@@ -32,8 +33,37 @@ impl DiffMask {
         return None;
     }
 
-    /// Sets the bit at the specified position within the DiffMask
+    /// Sets the bit at the specified position within the DiffMask. A set bit
+    /// marks the whole Property as updated
     pub fn set_bit(&mut self, index: u8, value: bool) {
+        self.nested.remove(&index);
+        self.set_bit_only(index, value);
+    }
+
+    /// Marks an element nested within the Property at the specified position
+    /// as updated. The path locates the element within the Property
+    pub fn set_nested_bit(&mut self, index: u8, path: &[u16]) {
+        if path.is_empty() {
+            self.set_bit(index, true);
+            return;
+        }
+        if let Some(true) = self.get_bit(index) {
+            if !self.nested.contains_key(&index) {
+                // the whole Property is already marked as updated
+                return;
+            }
+        }
+        self.set_bit_only(index, true);
+        self.nested.entry(index).or_default().set(path);
+    }
+
+    /// Gets the SubDiffMask of the Property at the specified position, if only
+    /// some of its nested elements have been updated
+    pub fn get_nested(&self, index: u8) -> Option<&SubDiffMask> {
+        return self.nested.get(&index);
+    }
+
+    fn set_bit_only(&mut self, index: u8, value: bool) {
         if let Some(byte) = self.mask.get_mut((index / 8) as usize) {
             let adjusted_index = index % 8;
             let bit_mask = 1 << adjusted_index;
@@ -45,9 +75,16 @@ impl DiffMask {
         }
     }
 
+    /// Marks every Property as wholly updated
+    pub fn set_all(&mut self) {
+        self.mask = vec![u8::MAX; self.bytes as usize];
+        self.nested.clear();
+    }
+
     /// Clears the whole DiffMask
     pub fn clear(&mut self) {
         self.mask = vec![0; self.bytes as usize];
+        self.nested.clear();
     }
 
     /// Returns whether any bit has been set in the DiffMask
@@ -79,8 +116,43 @@ impl DiffMask {
 
         for n in 0..self.bytes {
             if let Some(my_byte) = self.mask.get_mut(n as usize) {
-                let other_byte = !other.get_byte(n as usize);
-                *my_byte &= other_byte;
+                let mut other_byte = other.get_byte(n as usize);
+                // bits of Properties which have been partially updated in both
+                // masks must be handled below
+                for bit in 0..8 {
+                    let index = n * 8 + bit;
+                    if self.nested.contains_key(&index) || other.nested.contains_key(&index) {
+                        other_byte &= !(1 << bit);
+                    }
+                }
+                *my_byte &= !other_byte;
+            }
+        }
+
+        let indices: Vec<u8> = self
+            .nested
+            .keys()
+            .chain(other.nested.keys())
+            .copied()
+            .collect();
+        for index in indices {
+            if let (Some(true), Some(true)) = (self.get_bit(index), other.get_bit(index)) {
+                match (self.nested.get_mut(&index), other.nested.get(&index)) {
+                    (Some(my_nested), Some(other_nested)) => {
+                        my_nested.nand(other_nested);
+                        if my_nested.is_clear() {
+                            self.set_bit(index, false);
+                        }
+                    }
+                    (Some(_), None) => {
+                        // the other mask covers the whole Property
+                        self.set_bit(index, false);
+                    }
+                    _ => {
+                        // the whole Property is updated, which the other mask
+                        // only partially covers
+                    }
+                }
             }
         }
     }
@@ -92,6 +164,34 @@ impl DiffMask {
             return;
         }
 
+        let indices: Vec<u8> = self
+            .nested
+            .keys()
+            .chain(other.nested.keys())
+            .copied()
+            .collect();
+        for index in indices {
+            let my_bit = self.get_bit(index) == Some(true);
+            let other_bit = other.get_bit(index) == Some(true);
+            match (self.nested.get_mut(&index), other.nested.get(&index)) {
+                (Some(my_nested), Some(other_nested)) => {
+                    my_nested.or(other_nested);
+                }
+                (Some(_), None) => {
+                    if other_bit {
+                        // the other mask covers the whole Property
+                        self.nested.remove(&index);
+                    }
+                }
+                (None, Some(other_nested)) => {
+                    if !my_bit {
+                        self.nested.insert(index, other_nested.clone());
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
         for n in 0..self.bytes {
             if let Some(my_byte) = self.mask.get_mut(n as usize) {
                 let other_byte = other.get_byte(n as usize);
@@ -100,24 +200,6 @@ impl DiffMask {
         }
     }
 
-    /// Writes the DiffMask into an outgoing byte stream
-    pub fn write(&self, out_bytes: &mut Vec<u8>) {
-        out_bytes.write_u8(self.bytes).unwrap();
-        for x in 0..self.bytes {
-            out_bytes.write_u8(self.mask[x as usize]).unwrap();
-        }
-    }
-
-    /// Reads the DiffMask from an incoming packet
-    pub fn read(reader: &mut PacketReader) -> DiffMask {
-        let bytes: u8 = reader.read_u8();
-        let mut mask: Vec<u8> = Vec::new();
-        for _ in 0..bytes {
-            mask.push(reader.read_u8());
-        }
-        DiffMask { bytes, mask }
-    }
-
     /// Copies the DiffMask into another DiffMask
     pub fn copy_contents(&mut self, other: &DiffMask) {
         //if other diff mask has different capacity, do nothing
@@ -131,6 +213,107 @@ impl DiffMask {
                 *my_byte = other_byte;
             }
         }
+        self.nested = other.nested.clone();
+    }
+}
+
+/// Tracks which elements nested within a Property have been updated. Each
+/// element is either updated as a whole, or has only some of its own nested
+/// elements updated
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubDiffMask {
+    entries: BTreeMap<u16, Option<SubDiffMask>>,
+}
+
+impl SubDiffMask {
+    /// Create a new, clear SubDiffMask
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Marks the element at the given path as updated
+    pub fn set(&mut self, path: &[u16]) {
+        if let Some((index, rest)) = path.split_first() {
+            if rest.is_empty() {
+                self.entries.insert(*index, None);
+                return;
+            }
+            match self.entries.get_mut(index) {
+                // the whole element is already marked as updated
+                Some(None) => {}
+                Some(Some(nested)) => nested.set(rest),
+                None => {
+                    let mut nested = SubDiffMask::new();
+                    nested.set(rest);
+                    self.entries.insert(*index, Some(nested));
+                }
+            }
+        }
+    }
+
+    /// Returns whether the element at the given index has been updated
+    pub fn contains(&self, index: u16) -> bool {
+        return self.entries.contains_key(&index);
+    }
+
+    /// Gets the SubDiffMask of the element at the given index, if only some of
+    /// its nested elements have been updated
+    pub fn get(&self, index: u16) -> Option<&SubDiffMask> {
+        if let Some(Some(nested)) = self.entries.get(&index) {
+            return Some(nested);
+        }
+        return None;
+    }
+
+    /// Gets the indices of all updated elements, in ascending order
+    pub fn indices(&self) -> impl Iterator<Item = u16> + '_ {
+        return self.entries.keys().copied();
+    }
+
+    /// Returns whether no element has been updated
+    pub fn is_clear(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Marks every element updated in another SubDiffMask as updated
+    pub fn or(&mut self, other: &SubDiffMask) {
+        for (index, other_entry) in other.entries.iter() {
+            match (self.entries.get_mut(index), other_entry) {
+                (Some(None), _) => {}
+                (Some(my_entry), None) => {
+                    *my_entry = None;
+                }
+                (Some(Some(my_nested)), Some(other_nested)) => {
+                    my_nested.or(other_nested);
+                }
+                (None, _) => {
+                    self.entries.insert(*index, other_entry.clone());
+                }
+            }
+        }
+    }
+
+    /// Clears every element which has been updated as a whole in another
+    /// SubDiffMask
+    pub fn nand(&mut self, other: &SubDiffMask) {
+        for (index, other_entry) in other.entries.iter() {
+            let mut remove = false;
+            match (self.entries.get_mut(index), other_entry) {
+                (Some(_), None) => {
+                    remove = true;
+                }
+                (Some(Some(my_nested)), Some(other_nested)) => {
+                    my_nested.nand(other_nested);
+                    remove = my_nested.is_clear();
+                }
+                _ => {}
+            }
+            if remove {
+                self.entries.remove(index);
+            }
+        }
     }
 }
 
@@ -265,6 +448,105 @@ mod single_byte_tests {
     }
 }
 
+#[cfg(test)]
+mod nested_tests {
+    use crate::{DiffMask, SubDiffMask};
+
+    #[test]
+    fn set_nested() {
+        let mut mask = DiffMask::new(1);
+        mask.set_nested_bit(1, &[3]);
+        mask.set_nested_bit(1, &[5, 2]);
+
+        assert!(mask.get_bit(1).unwrap() == true);
+        let nested = mask.get_nested(1).unwrap();
+        assert!(nested.contains(3));
+        assert!(nested.get(3).is_none());
+        assert!(nested.get(5).unwrap().contains(2));
+        assert!(nested.contains(4) == false);
+
+        mask.set_bit(1, true);
+        assert!(mask.get_nested(1).is_none());
+
+        mask.set_nested_bit(1, &[3]);
+        assert!(mask.get_nested(1).is_none());
+    }
+
+    #[test]
+    fn set_all() {
+        let mut mask = DiffMask::new(1);
+        mask.set_nested_bit(1, &[3]);
+
+        mask.set_all();
+        assert!(mask.get_bit(0).unwrap() == true);
+        assert!(mask.get_bit(7).unwrap() == true);
+        assert!(mask.get_nested(1).is_none());
+    }
+
+    #[test]
+    fn or() {
+        let mut mask_a = DiffMask::new(1);
+        mask_a.set_nested_bit(0, &[1]);
+        mask_a.set_nested_bit(2, &[1]);
+
+        let mut mask_b = DiffMask::new(1);
+        mask_b.set_nested_bit(0, &[2]);
+        mask_b.set_bit(2, true);
+        mask_b.set_nested_bit(3, &[4]);
+
+        mask_a.or(&mask_b);
+
+        let nested = mask_a.get_nested(0).unwrap();
+        assert!(nested.contains(1));
+        assert!(nested.contains(2));
+        assert!(mask_a.get_bit(2).unwrap() == true);
+        assert!(mask_a.get_nested(2).is_none());
+        assert!(mask_a.get_nested(3).unwrap().contains(4));
+    }
+
+    #[test]
+    fn nand() {
+        let mut mask_a = DiffMask::new(1);
+        mask_a.set_nested_bit(0, &[1]);
+        mask_a.set_nested_bit(0, &[2]);
+        mask_a.set_nested_bit(1, &[1]);
+        mask_a.set_bit(2, true);
+
+        let mut mask_b = DiffMask::new(1);
+        mask_b.set_nested_bit(0, &[1]);
+        mask_b.set_bit(1, true);
+        mask_b.set_nested_bit(2, &[1]);
+
+        mask_a.nand(&mask_b);
+
+        let nested = mask_a.get_nested(0).unwrap();
+        assert!(nested.contains(1) == false);
+        assert!(nested.contains(2));
+        assert!(mask_a.get_bit(1).unwrap() == false);
+        assert!(mask_a.get_bit(2).unwrap() == true);
+        assert!(mask_a.get_nested(2).is_none());
+
+        let mut mask_c = DiffMask::new(1);
+        mask_c.set_nested_bit(0, &[2]);
+        mask_a.nand(&mask_c);
+        assert!(mask_a.get_bit(0).unwrap() == false);
+    }
+
+    #[test]
+    fn sub_nand() {
+        let mut mask_a = SubDiffMask::new();
+        mask_a.set(&[1, 2]);
+        mask_a.set(&[1, 3]);
+
+        let mut mask_b = SubDiffMask::new();
+        mask_b.set(&[1, 2]);
+
+        mask_a.nand(&mask_b);
+        assert!(mask_a.get(1).unwrap().contains(2) == false);
+        assert!(mask_a.get(1).unwrap().contains(3));
+    }
+}
+
 #[cfg(test)]
 mod double_byte_tests {
     use crate::DiffMask;
@@ -1,3 +1,5 @@
+use super::{bit_reader::BitReader, bit_writer::BitWriter};
+
 /// Enum used as a shared network protocol, representing various message types
 /// related to Entities/Components
 #[derive(Copy, Clone)]
@@ -17,10 +19,27 @@ pub enum EntityActionType {
     DisownEntity,
     /// Action indicating a Component to be added to an Entity
     InsertComponent,
+    /// Action indicating the Client has been given authority over a Component
+    GrantAuthority,
+    /// Action indicating the Client's authority over a Component has been
+    /// revoked
+    RevokeAuthority,
+    /// Action indicating an Entity spawned by the Client has been rejected by
+    /// the Server
+    RejectEntity,
+    /// Action indicating an Entity spawned by the Client has been taken over
+    /// by the Server
+    TakeOverEntity,
+    /// Action indicating the Client is missing the state a delta-compressed
+    /// Component update was encoded relative to, and needs the whole Component
+    RequestComponentState,
     /// Unknown / Undefined message, should always be last variant in this list
     Unknown,
 }
 
+// Number of bits used to represent an EntityActionType on the wire
+const ENTITY_ACTION_TYPE_BITS: u8 = 4;
+
 impl EntityActionType {
     /// Converts the action type to u8
     pub fn to_u8(&self) -> u8 {
@@ -36,4 +55,14 @@ impl EntityActionType {
         let z: EntityActionType = unsafe { ::std::mem::transmute(v) };
         z
     }
+
+    /// Writes the action type into an outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_bits(u64::from(self.to_u8()), ENTITY_ACTION_TYPE_BITS);
+    }
+
+    /// Reads an EntityActionType from an incoming bit stream
+    pub fn read(reader: &mut BitReader) -> Self {
+        return Self::from_u8(reader.read_bits(ENTITY_ACTION_TYPE_BITS) as u8);
+    }
 }
@@ -0,0 +1,249 @@
+use super::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    decode_error::DecodeError,
+    property::PropertyEncoding,
+    serde::{de_difference, ser_difference, Serde},
+};
+
+/// An unsigned integer which is written using exactly `BITS` bits
+pub type UnsignedInteger<const BITS: u8> = SerdeInteger<false, false, BITS>;
+/// A signed integer which is written using a sign bit, followed by exactly
+/// `BITS` bits of magnitude
+pub type SignedInteger<const BITS: u8> = SerdeInteger<true, false, BITS>;
+/// An unsigned integer which is written in chunks of `BITS` bits, using only
+/// as many chunks as are needed to represent its value
+pub type UnsignedVariableInteger<const BITS: u8> = SerdeInteger<false, true, BITS>;
+/// A signed integer which is written using a sign bit, followed by chunks of
+/// `BITS` bits of magnitude, using only as many chunks as are needed
+pub type SignedVariableInteger<const BITS: u8> = SerdeInteger<true, true, BITS>;
+
+/// An integer which is written using fewer bits than a primitive integer
+/// would. Use through one of the `UnsignedInteger`, `SignedInteger`,
+/// `UnsignedVariableInteger` or `SignedVariableInteger` aliases
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SerdeInteger<const SIGNED: bool, const VARIABLE: bool, const BITS: u8> {
+    inner: i64,
+}
+
+impl<const SIGNED: bool, const VARIABLE: bool, const BITS: u8>
+    SerdeInteger<SIGNED, VARIABLE, BITS>
+{
+    /// Create a new integer. Panics if the value can't be represented
+    pub fn new(value: i64) -> Self {
+        let mut output = Self { inner: 0 };
+        output.set(value);
+        return output;
+    }
+
+    /// Gets the value of the integer
+    pub fn get(&self) -> i64 {
+        return self.inner;
+    }
+
+    /// Sets the value of the integer. Panics if the value can't be represented
+    pub fn set(&mut self, value: i64) {
+        if BITS == 0 || BITS > 63 {
+            panic!("integer must be written with between 1 and 63 bits");
+        }
+        if !SIGNED && value < 0 {
+            panic!("can't store a negative value in an unsigned integer");
+        }
+        if !VARIABLE && value.unsigned_abs() >= (1 << BITS) {
+            panic!("value {} can't be represented with {} bits", value, BITS);
+        }
+        self.inner = value;
+    }
+}
+
+impl<const SIGNED: bool, const VARIABLE: bool, const BITS: u8> Serde
+    for SerdeInteger<SIGNED, VARIABLE, BITS>
+{
+    fn ser(&self, writer: &mut BitWriter) {
+        if SIGNED {
+            writer.write_bit(self.inner < 0);
+        }
+
+        let mut magnitude = self.inner.unsigned_abs();
+
+        if !VARIABLE {
+            writer.write_bits(magnitude, BITS);
+            return;
+        }
+
+        loop {
+            let chunk = magnitude & ((1 << BITS) - 1);
+            magnitude >>= BITS;
+            // write whether there is another chunk after this one
+            writer.write_bit(magnitude != 0);
+            writer.write_bits(chunk, BITS);
+            if magnitude == 0 {
+                return;
+            }
+        }
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let negative = SIGNED && reader.read_bit();
+
+        let magnitude = if VARIABLE {
+            let mut output: u64 = 0;
+            let mut shift: u32 = 0;
+            loop {
+                if shift >= 64 {
+                    reader.set_error(DecodeError::InvalidValue);
+                    break;
+                }
+                let proceed = reader.read_bit();
+                output |= reader.read_bits(BITS) << shift;
+                shift += u32::from(BITS);
+                if !proceed {
+                    break;
+                }
+            }
+            output
+        } else {
+            reader.read_bits(BITS)
+        };
+
+        let mut inner = magnitude as i64;
+        if negative {
+            inner = inner.wrapping_neg();
+        }
+
+        return Self { inner };
+    }
+
+    fn ser_delta(&self, baseline: &Self, writer: &mut BitWriter) {
+        ser_difference(self.inner.wrapping_sub(baseline.inner), writer);
+    }
+
+    fn de_delta(baseline: &Self, reader: &mut BitReader) -> Self {
+        return Self {
+            inner: baseline.inner.wrapping_add(de_difference(reader)),
+        };
+    }
+}
+
+/// An integer which is known to lie within the range `MIN..=MAX`, and is
+/// written using only as many bits as are needed to cover that range
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RangedInteger<const MIN: i64, const MAX: i64> {
+    inner: i64,
+}
+
+impl<const MIN: i64, const MAX: i64> RangedInteger<MIN, MAX> {
+    /// Create a new integer. Panics if the value lies outside of the range
+    pub fn new(value: i64) -> Self {
+        let mut output = Self { inner: MIN };
+        output.set(value);
+        return output;
+    }
+
+    /// Gets the value of the integer
+    pub fn get(&self) -> i64 {
+        return self.inner;
+    }
+
+    /// Sets the value of the integer. Panics if the value lies outside of the
+    /// range
+    pub fn set(&mut self, value: i64) {
+        if value < MIN || value > MAX {
+            panic!("value {} lies outside of range {}..={}", value, MIN, MAX);
+        }
+        self.inner = value;
+    }
+
+    // the number of bits needed to represent any value in the range
+    fn bits() -> u8 {
+        let span = (MAX as i128 - MIN as i128) as u128;
+        return (128 - span.leading_zeros()) as u8;
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Serde for RangedInteger<MIN, MAX> {
+    fn ser(&self, writer: &mut BitWriter) {
+        let offset = (self.inner as i128 - MIN as i128) as u64;
+        writer.write_bits(offset, Self::bits());
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let offset = reader.read_bits(Self::bits());
+        let inner = MIN as i128 + offset as i128;
+        if inner > MAX as i128 {
+            reader.set_error(DecodeError::InvalidValue);
+            return Self { inner: MAX };
+        }
+        return Self {
+            inner: inner as i64,
+        };
+    }
+}
+
+// Integers are encoded from, & decoded into, any primitive integer type. A
+// received value which doesn't fit the primitive type is truncated
+macro_rules! impl_property_encoding {
+    ($($type:ty),*) => {$(
+        impl<const SIGNED: bool, const VARIABLE: bool, const BITS: u8> PropertyEncoding<$type>
+            for SerdeInteger<SIGNED, VARIABLE, BITS>
+        {
+            fn encode(value: &$type) -> Self {
+                return Self::new(i64::from(*value));
+            }
+
+            #[allow(trivial_numeric_casts)]
+            fn decode(&self) -> $type {
+                return self.get() as $type;
+            }
+        }
+
+        impl<const MIN: i64, const MAX: i64> PropertyEncoding<$type> for RangedInteger<MIN, MAX> {
+            fn encode(value: &$type) -> Self {
+                return Self::new(i64::from(*value));
+            }
+
+            #[allow(trivial_numeric_casts)]
+            fn decode(&self) -> $type {
+                return self.get() as $type;
+            }
+        }
+    )*};
+}
+
+impl_property_encoding!(u8, u16, u32, i8, i16, i32, i64);
+
+#[cfg(test)]
+mod integer_tests {
+    use crate::{
+        serde::round_trip, RangedInteger, SignedInteger, SignedVariableInteger, UnsignedInteger,
+        UnsignedVariableInteger,
+    };
+
+    #[test]
+    fn fixed_integers() {
+        assert_eq!(round_trip(UnsignedInteger::<7>::new(100)), 7);
+        assert_eq!(round_trip(SignedInteger::<5>::new(-31)), 6);
+        assert_eq!(round_trip(SignedInteger::<5>::new(12)), 6);
+    }
+
+    #[test]
+    fn variable_integers() {
+        assert_eq!(round_trip(UnsignedVariableInteger::<4>::new(9)), 5);
+        assert_eq!(round_trip(UnsignedVariableInteger::<4>::new(300)), 15);
+        assert_eq!(round_trip(SignedVariableInteger::<4>::new(-300)), 16);
+    }
+
+    #[test]
+    fn ranged_integers() {
+        assert_eq!(round_trip(RangedInteger::<-10, 10>::new(-10)), 5);
+        assert_eq!(round_trip(RangedInteger::<-10, 10>::new(10)), 5);
+        assert_eq!(round_trip(RangedInteger::<1000, 1003>::new(1002)), 2);
+        assert_eq!(round_trip(RangedInteger::<5, 5>::new(5)), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        UnsignedInteger::<3>::new(8);
+    }
+}
@@ -0,0 +1,7 @@
+/// A Component which can be smoothly interpolated between two of its states,
+/// as received from the Server
+pub trait Interpolate {
+    /// Returns the state in between this state & the next one, where a
+    /// fraction of 0.0 is this state and 1.0 is the next
+    fn interpolate(&self, next: &Self, fraction: f32) -> Self;
+}
@@ -10,60 +10,110 @@
 
 mod ack_manager;
 mod base_connection;
+mod bit_reader;
+mod bit_writer;
+mod blob_manager;
+mod blob_packet_writer;
+mod blob_store;
+mod channel;
+mod channel_receiver;
+mod clock;
+mod congestion_controller;
 mod connection_config;
+mod decode_error;
 mod diff_mask;
 mod entity_action_type;
+mod integer;
+mod interpolate;
 mod key_store;
 mod keys;
 mod manager_type;
 mod manifest;
+mod memory_network;
+mod message_fragment;
 mod message_manager;
 mod message_packet_writer;
+mod nested_property;
 mod packet_notifiable;
 mod packet_type;
 mod property;
 mod property_mutate;
 mod protocol_type;
+mod quantized_float;
 mod replica_builder;
 mod replica_ref;
 mod replicate;
+mod replicated_map;
+mod replicated_vec;
 mod sequence_buffer;
+mod serde;
 mod shared_config;
 mod standard_header;
+mod unit_quaternion;
 mod world_type;
 mod wrapping_number;
 
 /// Commonly used utility methods to be used by naia-server & naia-client
 pub mod utils;
 
+// lets the derived Protocol used in tests refer to this crate by name
+#[cfg(test)]
+extern crate self as naia_shared;
+#[cfg(test)]
+mod test_protocol;
+
 pub use naia_socket_shared::{
     Instant, LinkConditionerConfig, PacketReader, Random, SocketConfig, Timer, Timestamp,
 };
 
 pub use ack_manager::AckManager;
 pub use base_connection::BaseConnection;
+pub use bit_reader::BitReader;
+pub use bit_writer::BitWriter;
+pub use blob_manager::{BlobEvent, BlobManager, BlobRecord};
+pub use blob_packet_writer::BlobPacketWriter;
+pub use blob_store::{blob_hash, BlobHash, BlobStore, BLOB_CHUNK_SIZE_BYTES, MAX_BLOB_SIZE_BYTES};
+pub use channel::{ChannelConfig, ChannelIndex, ChannelMode, Channels, DefaultChannels};
+pub use clock::{Clock, ClockTimer, ManualClock, SystemClock};
+pub use congestion_controller::{CongestionConfig, CongestionController};
 pub use connection_config::ConnectionConfig;
-pub use diff_mask::DiffMask;
+pub use decode_error::DecodeError;
+pub use diff_mask::{DiffMask, SubDiffMask};
 pub use entity_action_type::EntityActionType;
+pub use integer::{
+    RangedInteger, SerdeInteger, SignedInteger, SignedVariableInteger, UnsignedInteger,
+    UnsignedVariableInteger,
+};
+pub use interpolate::Interpolate;
 pub use key_store::KeyGenerator;
 pub use keys::{LocalComponentKey, LocalEntity, NaiaKey};
 pub use manager_type::ManagerType;
 pub use manifest::Manifest;
-pub use message_manager::MessageManager;
-pub use message_packet_writer::{MessagePacketWriter, MTU_SIZE};
+pub use memory_network::MemoryNetwork;
+pub use message_fragment::{FRAGMENT_SIZE_BYTES, MAX_MESSAGE_SIZE_BYTES};
+pub use message_manager::{MessageManager, OutgoingMessage, SendMessageError};
+pub use message_packet_writer::{
+    MessagePacketWriter, MTU_SIZE, MTU_SIZE_BITS, SECTION_OVERHEAD_BITS,
+};
+pub use nested_property::NestedProperty;
 pub use packet_notifiable::PacketNotifiable;
 pub use packet_type::PacketType;
-pub use property::Property;
-pub use property_mutate::{PropertyMutate, PropertyMutator};
+pub use property::{Property, PropertyEncoding};
+pub use property_mutate::{NestedMutator, PropertyMutate, PropertyMutator};
 pub use protocol_type::{ProtocolInserter, ProtocolKindType, ProtocolType};
+pub use quantized_float::QuantizedFloat;
 pub use replica_builder::ReplicaBuilder;
 pub use replica_ref::{
     ReplicaDynMut, ReplicaDynMutTrait, ReplicaDynMutWrapper, ReplicaDynRef, ReplicaDynRefTrait,
     ReplicaDynRefWrapper, ReplicaMutTrait, ReplicaMutWrapper, ReplicaRefTrait, ReplicaRefWrapper,
 };
 pub use replicate::{Replicate, ReplicateSafe};
+pub use replicated_map::ReplicatedMap;
+pub use replicated_vec::ReplicatedVec;
 pub use sequence_buffer::{SequenceBuffer, SequenceIterator, SequenceNumber};
-pub use shared_config::SharedConfig;
+pub use serde::{de_difference, de_length, ser_difference, ser_length, Serde};
+pub use shared_config::{SharedConfig, CLIENT_ENTITY_ACTION_WINDOW, DELTA_BASELINE_WINDOW};
 pub use standard_header::StandardHeader;
+pub use unit_quaternion::UnitQuaternion;
 pub use world_type::{WorldMutType, WorldRefType};
 pub use wrapping_number::{sequence_greater_than, sequence_less_than, wrapping_diff};
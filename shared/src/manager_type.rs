@@ -1,5 +1,7 @@
+use super::{bit_reader::BitReader, bit_writer::BitWriter};
+
 /// Every data packet transmitted has data specific to either the Message,
-/// Entity, Command managers. This value is written to differentiate those parts
+/// Entity, Command, Blob, ClientEntity managers. This value is written to differentiate those parts
 /// of the payload.
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u8)]
@@ -10,16 +12,37 @@ pub enum ManagerType {
     Entity = 2,
     /// A CommandManager
     Command = 3,
+    /// A BlobManager
+    Blob = 4,
+    /// A ClientEntityManager
+    ClientEntity = 5,
     /// Unknown Manager
     Unknown = 255,
 }
 
+// Number of bits used to represent a ManagerType on the wire
+const MANAGER_TYPE_BITS: u8 = 3;
+
+impl ManagerType {
+    /// Writes the ManagerType into an outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_bits(u64::from(*self as u8), MANAGER_TYPE_BITS);
+    }
+
+    /// Reads a ManagerType from an incoming bit stream
+    pub fn read(reader: &mut BitReader) -> Self {
+        return (reader.read_bits(MANAGER_TYPE_BITS) as u8).into();
+    }
+}
+
 impl From<u8> for ManagerType {
     fn from(orig: u8) -> Self {
         match orig {
             1 => return ManagerType::Message,
             2 => return ManagerType::Entity,
             3 => return ManagerType::Command,
+            4 => return ManagerType::Blob,
+            5 => return ManagerType::ClientEntity,
             _ => return ManagerType::Unknown,
         };
     }
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use naia_socket_shared::PacketReader;
-
 use super::{
+    bit_reader::BitReader,
+    decode_error::DecodeError,
     protocol_type::{ProtocolKindType, ProtocolType},
     replica_builder::ReplicaBuilder,
 };
@@ -31,21 +31,21 @@ impl<P: ProtocolType> Manifest<P> {
     }
 
     /// Creates a Message/Component instance, given a NaiaId and a
-    /// payload, typically from an incoming packet
+    /// payload, typically from an incoming packet. Returns an error if no
+    /// ReplicaBuilder is registered for the NaiaId, or the payload is invalid
     pub fn create_replica(
         &self,
         component_kind: P::Kind,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         packet_index: u16,
-    ) -> P {
-        if let Some(replica_builder) = self.builder_map.get(&component_kind) {
-            return replica_builder.as_ref().build(reader, packet_index);
-        }
-
-        // TODO: this shouldn't panic .. could crash the server
-        panic!(
-            "No ReplicaBuilder registered for NaiaId: {}",
-            component_kind.to_u16()
-        );
+    ) -> Result<P, DecodeError> {
+        reader.check()?;
+        let replica_builder = self
+            .builder_map
+            .get(&component_kind)
+            .ok_or_else(|| DecodeError::UnknownKind(component_kind.to_u16()))?;
+        let replica = replica_builder.as_ref().build(reader, packet_index);
+        reader.check()?;
+        return Ok(replica);
     }
 }
@@ -0,0 +1,76 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// An in-process network, through which a Server & its Clients can exchange
+/// packets without a socket, so that they can be run & stepped from a single
+/// thread. Packets are delivered in order, & are never dropped. Clones refer
+/// to the same network
+#[derive(Clone, Default)]
+pub struct MemoryNetwork {
+    inboxes: Arc<Mutex<HashMap<SocketAddr, VecDeque<(SocketAddr, Box<[u8]>)>>>>,
+}
+
+impl MemoryNetwork {
+    pub fn new() -> Self {
+        return MemoryNetwork::default();
+    }
+
+    /// Sends a packet from one address to another. Packets are kept until
+    /// received from the destination address
+    pub fn send(&self, from_address: &SocketAddr, to_address: &SocketAddr, payload: Box<[u8]>) {
+        self.inboxes
+            .lock()
+            .unwrap()
+            .entry(*to_address)
+            .or_insert_with(VecDeque::new)
+            .push_back((*from_address, payload));
+    }
+
+    /// Receives the oldest packet sent to the given address, along with the
+    /// address it was sent from
+    pub fn receive(&self, address: &SocketAddr) -> Option<(SocketAddr, Box<[u8]>)> {
+        return self.inboxes.lock().unwrap().get_mut(address)?.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod memory_network_tests {
+    use std::net::SocketAddr;
+
+    use super::MemoryNetwork;
+
+    fn address(port: u16) -> SocketAddr {
+        return SocketAddr::from(([127, 0, 0, 1], port));
+    }
+
+    #[test]
+    fn delivers_in_order() {
+        let network = MemoryNetwork::new();
+        network.send(&address(1), &address(2), Box::new([1]));
+        network.send(&address(3), &address(2), Box::new([2]));
+
+        let receiver = network.clone();
+        assert_eq!(
+            receiver.receive(&address(2)),
+            Some((address(1), Box::from([1u8])))
+        );
+        assert_eq!(
+            receiver.receive(&address(2)),
+            Some((address(3), Box::from([2u8])))
+        );
+        assert_eq!(receiver.receive(&address(2)), None);
+    }
+
+    #[test]
+    fn separates_addresses() {
+        let network = MemoryNetwork::new();
+        network.send(&address(1), &address(2), Box::new([1]));
+
+        assert_eq!(network.receive(&address(1)), None);
+        assert_eq!(network.receive(&address(3)), None);
+        assert!(network.receive(&address(2)).is_some());
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{bit_reader::BitReader, bit_writer::BitWriter, wrapping_number::sequence_less_than};
+
+/// The maximum number of bytes of a Message which are sent in a single
+/// fragment. Messages larger than this are split into multiple fragments
+pub const FRAGMENT_SIZE_BYTES: usize = 400;
+
+/// The maximum number of bytes of a serialized Message. Larger payloads
+/// should be sent as Blobs
+pub const MAX_MESSAGE_SIZE_BYTES: usize = 256 * 1024;
+
+// the maximum number of fragments of a single Message
+const MAX_FRAGMENT_COUNT: usize =
+    (MAX_MESSAGE_SIZE_BYTES + FRAGMENT_SIZE_BYTES - 1) / FRAGMENT_SIZE_BYTES;
+
+// the number of completed fragmented Messages to remember, so that duplicate
+// fragments arriving late are not mistaken for the start of a new Message
+const COMPLETED_ID_WINDOW: usize = 256;
+
+// the distance, in fragment ids, behind the newest fragment received at which
+// an incomplete Message is given up on, as its id is about to be reused
+const STALE_ID_WINDOW: u16 = 1024;
+
+// the maximum number of Messages which can be reassembled at once. Senders
+// never have more than this many fragmented Messages undelivered
+pub(crate) const MAX_IN_FLIGHT_MESSAGES: usize = 16;
+
+/// A numbered slice of a Message too large to fit into a single packet
+#[derive(Clone, Debug)]
+pub struct MessageFragment {
+    id: u16,
+    index: u16,
+    count: u16,
+    bytes: Box<[u8]>,
+}
+
+impl MessageFragment {
+    /// Splits the serialized bytes of a Message into fragments, all sharing
+    /// the given id. Panics if there are more than `MAX_MESSAGE_SIZE_BYTES`
+    pub fn split(id: u16, bytes: &[u8]) -> Vec<MessageFragment> {
+        if bytes.len() > MAX_MESSAGE_SIZE_BYTES {
+            panic!("Message is too large to be fragmented");
+        }
+        let chunks: Vec<&[u8]> = bytes.chunks(FRAGMENT_SIZE_BYTES).collect();
+        let count = chunks.len() as u16;
+
+        return chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| MessageFragment {
+                id,
+                index: index as u16,
+                count,
+                bytes: chunk.into(),
+            })
+            .collect();
+    }
+
+    /// Gets the id shared by all the fragments of the Message
+    pub fn id(&self) -> u16 {
+        return self.id;
+    }
+
+    /// Writes the fragment into an outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_u16(self.id);
+        writer.write_u16(self.index);
+        writer.write_u16(self.count);
+        writer.write_u16(self.bytes.len() as u16);
+        writer.write_bytes(&self.bytes);
+    }
+
+    /// Reads a fragment from an incoming bit stream
+    pub fn read(reader: &mut BitReader) -> MessageFragment {
+        let id = reader.read_u16();
+        let index = reader.read_u16();
+        let count = reader.read_u16();
+        let length = reader.read_u16();
+        let bytes = reader.read_bytes(usize::from(length)).into_boxed_slice();
+        return MessageFragment {
+            id,
+            index,
+            count,
+            bytes,
+        };
+    }
+}
+
+/// Collects the fragments of incoming Messages, returning the bytes of each
+/// Message once all of its fragments have been received
+#[derive(Debug)]
+pub struct FragmentReassembler {
+    buffers: HashMap<u16, FragmentBuffer>,
+    completed_ids: VecDeque<u16>,
+    completed_id_set: HashSet<u16>,
+    newest_id: Option<u16>,
+}
+
+#[derive(Debug)]
+struct FragmentBuffer {
+    fragments: Vec<Option<Box<[u8]>>>,
+    received_count: u16,
+}
+
+impl FragmentReassembler {
+    pub fn new() -> Self {
+        return FragmentReassembler {
+            buffers: HashMap::new(),
+            completed_ids: VecDeque::new(),
+            completed_id_set: HashSet::new(),
+            newest_id: None,
+        };
+    }
+
+    /// Receives a fragment, returning the reassembled bytes of its Message if
+    /// this was the last fragment outstanding. Fragments which are malformed,
+    /// belong to a stale Message, or would start a Message while too many are
+    /// being reassembled already, are dropped. A sender which limits its
+    /// undelivered fragmented Messages to `MAX_IN_FLIGHT_MESSAGES` never has
+    /// its fragments dropped
+    pub fn receive(&mut self, fragment: MessageFragment) -> Option<Box<[u8]>> {
+        if fragment.count == 0
+            || usize::from(fragment.count) > MAX_FRAGMENT_COUNT
+            || fragment.index >= fragment.count
+            || fragment.bytes.len() > FRAGMENT_SIZE_BYTES
+            || self.completed_id_set.contains(&fragment.id)
+        {
+            return None;
+        }
+
+        match self.newest_id {
+            Some(newest_id) if !sequence_less_than(newest_id, fragment.id) => {
+                if sequence_less_than(fragment.id, newest_id.wrapping_sub(STALE_ID_WINDOW)) {
+                    return None;
+                }
+            }
+            _ => {
+                self.newest_id = Some(fragment.id);
+                let oldest_id = fragment.id.wrapping_sub(STALE_ID_WINDOW);
+                self.buffers
+                    .retain(|id, _| !sequence_less_than(*id, oldest_id));
+            }
+        }
+
+        if !self.buffers.contains_key(&fragment.id) && self.buffers.len() >= MAX_IN_FLIGHT_MESSAGES
+        {
+            return None;
+        }
+
+        let buffer = self
+            .buffers
+            .entry(fragment.id)
+            .or_insert_with(|| FragmentBuffer {
+                fragments: vec![None; usize::from(fragment.count)],
+                received_count: 0,
+            });
+
+        if buffer.fragments.len() != usize::from(fragment.count) {
+            return None;
+        }
+        let slot = &mut buffer.fragments[usize::from(fragment.index)];
+        if slot.is_some() {
+            return None;
+        }
+        *slot = Some(fragment.bytes);
+        buffer.received_count += 1;
+
+        if buffer.received_count < fragment.count {
+            return None;
+        }
+
+        let buffer = self.buffers.remove(&fragment.id)?;
+        self.complete(fragment.id);
+
+        let mut bytes = Vec::new();
+        for fragment_bytes in buffer.fragments.into_iter().flatten() {
+            bytes.extend_from_slice(&fragment_bytes);
+        }
+        return Some(bytes.into_boxed_slice());
+    }
+
+    fn complete(&mut self, id: u16) {
+        self.completed_ids.push_back(id);
+        self.completed_id_set.insert(id);
+        if self.completed_ids.len() > COMPLETED_ID_WINDOW {
+            if let Some(old_id) = self.completed_ids.pop_front() {
+                self.completed_id_set.remove(&old_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod message_fragment_tests {
+    use super::{
+        FragmentReassembler, MessageFragment, FRAGMENT_SIZE_BYTES, MAX_FRAGMENT_COUNT,
+        MAX_IN_FLIGHT_MESSAGES, STALE_ID_WINDOW,
+    };
+    use crate::{BitReader, BitWriter};
+
+    fn message_bytes(length: usize) -> Vec<u8> {
+        return (0..length).map(|index| (index % 251) as u8).collect();
+    }
+
+    #[test]
+    fn split_sizes() {
+        let fragments = MessageFragment::split(0, &message_bytes(FRAGMENT_SIZE_BYTES * 2 + 1));
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[2].bytes.len(), 1);
+    }
+
+    #[test]
+    fn reassemble_out_of_order() {
+        let bytes = message_bytes(FRAGMENT_SIZE_BYTES * 3 + 17);
+        let mut fragments = MessageFragment::split(7, &bytes);
+        fragments.reverse();
+
+        let mut reassembler = FragmentReassembler::new();
+        let mut output = None;
+        for fragment in fragments {
+            // each fragment goes through a round trip on the wire
+            let mut writer = BitWriter::new();
+            writer.write_bit(true);
+            fragment.write(&mut writer);
+            let written = writer.to_bytes();
+            let mut reader = BitReader::new(&written);
+            reader.read_bit();
+            let fragment = MessageFragment::read(&mut reader);
+
+            assert!(output.is_none());
+            output = reassembler.receive(fragment);
+        }
+        assert_eq!(output.unwrap().to_vec(), bytes);
+    }
+
+    #[test]
+    fn ignore_duplicates() {
+        let bytes = message_bytes(FRAGMENT_SIZE_BYTES + 1);
+        let fragments = MessageFragment::split(3, &bytes);
+
+        let mut reassembler = FragmentReassembler::new();
+        assert!(reassembler.receive(fragments[0].clone()).is_none());
+        assert!(reassembler.receive(fragments[0].clone()).is_none());
+        assert!(reassembler.receive(fragments[1].clone()).is_some());
+
+        // a late duplicate of a completed Message is discarded
+        assert!(reassembler.receive(fragments[1].clone()).is_none());
+        assert!(reassembler.receive(fragments[0].clone()).is_none());
+        assert!(reassembler.buffers.is_empty());
+    }
+
+    #[test]
+    fn reject_oversized() {
+        let mut reassembler = FragmentReassembler::new();
+        let fragment = MessageFragment {
+            id: 0,
+            index: 0,
+            count: (MAX_FRAGMENT_COUNT + 1) as u16,
+            bytes: message_bytes(1).into_boxed_slice(),
+        };
+        assert!(reassembler.receive(fragment).is_none());
+        let fragment = MessageFragment {
+            id: 0,
+            index: 0,
+            count: 2,
+            bytes: message_bytes(FRAGMENT_SIZE_BYTES + 1).into_boxed_slice(),
+        };
+        assert!(reassembler.receive(fragment).is_none());
+        assert!(reassembler.buffers.is_empty());
+    }
+
+    #[test]
+    fn limit_in_flight() {
+        let bytes = message_bytes(FRAGMENT_SIZE_BYTES + 1);
+        let mut reassembler = FragmentReassembler::new();
+        for id in 0..MAX_IN_FLIGHT_MESSAGES as u16 {
+            reassembler.receive(MessageFragment::split(id, &bytes)[0].clone());
+        }
+        assert_eq!(reassembler.buffers.len(), MAX_IN_FLIGHT_MESSAGES);
+
+        // no Message can be started until another one completes
+        let extra_id = MAX_IN_FLIGHT_MESSAGES as u16;
+        let extra = MessageFragment::split(extra_id, &bytes);
+        assert!(reassembler.receive(extra[0].clone()).is_none());
+        assert!(reassembler.receive(extra[1].clone()).is_none());
+        assert_eq!(reassembler.buffers.len(), MAX_IN_FLIGHT_MESSAGES);
+
+        assert!(reassembler
+            .receive(MessageFragment::split(0, &bytes)[1].clone())
+            .is_some());
+        assert!(reassembler.receive(extra[0].clone()).is_none());
+        assert!(reassembler.receive(extra[1].clone()).is_some());
+    }
+
+    #[test]
+    fn evict_stale() {
+        let bytes = message_bytes(FRAGMENT_SIZE_BYTES + 1);
+        let mut reassembler = FragmentReassembler::new();
+        let stale = MessageFragment::split(0, &bytes);
+        reassembler.receive(stale[0].clone());
+        reassembler.receive(MessageFragment::split(STALE_ID_WINDOW, &bytes)[0].clone());
+        assert_eq!(reassembler.buffers.len(), 2);
+
+        // once the id is about to be reused, the incomplete Message is dropped
+        reassembler.receive(MessageFragment::split(STALE_ID_WINDOW + 1, &bytes)[0].clone());
+        assert_eq!(reassembler.buffers.len(), 2);
+        assert!(!reassembler.buffers.contains_key(&0));
+        assert!(reassembler.receive(stale[1].clone()).is_none());
+    }
+}
@@ -3,93 +3,306 @@ use std::{
     vec::Vec,
 };
 
-use naia_socket_shared::PacketReader;
-
 use super::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    channel::{ChannelConfig, ChannelIndex},
+    channel_receiver::{ChannelReceiver, MAX_MESSAGES_AHEAD},
+    decode_error::DecodeError,
     manifest::Manifest,
+    message_fragment::{
+        FragmentReassembler, MessageFragment, FRAGMENT_SIZE_BYTES, MAX_IN_FLIGHT_MESSAGES,
+        MAX_MESSAGE_SIZE_BYTES,
+    },
     packet_notifiable::PacketNotifiable,
     protocol_type::{ProtocolKindType, ProtocolType},
     replicate::ReplicateSafe,
 };
 
+/// The reason a Message couldn't be queued to be sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendMessageError {
+    /// No Channel was configured with the given index
+    ChannelNotFound(u8),
+    /// The Channel's queue is full, so the Message was dropped
+    QueueOverflow,
+    /// The Message is larger than `MAX_MESSAGE_SIZE_BYTES`, so it was dropped
+    MessageTooLarge,
+}
+
+/// A Message, or a fragment of a Message, which has been queued for
+/// transmission through a Channel
+#[derive(Debug)]
+pub struct OutgoingMessage<P: ProtocolType> {
+    channel_index: u8,
+    channel_index_bits: u8,
+    message_index: Option<u16>,
+    payload: OutgoingPayload<P>,
+}
+
+#[derive(Debug)]
+enum OutgoingPayload<P: ProtocolType> {
+    Message(P),
+    // the serialized bytes of a Message too large to fit into a single
+    // packet, split into fragments once the remote host has room for it
+    Fragmented(Box<[u8]>),
+    Fragment(MessageFragment),
+}
+
+impl<P: ProtocolType> Clone for OutgoingMessage<P> {
+    fn clone(&self) -> Self {
+        let payload = match &self.payload {
+            OutgoingPayload::Message(message) => OutgoingPayload::Message(message.clone()),
+            OutgoingPayload::Fragmented(bytes) => OutgoingPayload::Fragmented(bytes.clone()),
+            OutgoingPayload::Fragment(fragment) => OutgoingPayload::Fragment(fragment.clone()),
+        };
+        return OutgoingMessage {
+            channel_index: self.channel_index,
+            channel_index_bits: self.channel_index_bits,
+            message_index: self.message_index,
+            payload,
+        };
+    }
+}
+
+impl<P: ProtocolType> OutgoingMessage<P> {
+    /// Writes the Channel of the Message, its index within that Channel if
+    /// the remote host requires it, and then either the Message itself or one
+    /// of its fragments, into an outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        writer.write_bits(u64::from(self.channel_index), self.channel_index_bits);
+        if let Some(message_index) = self.message_index {
+            writer.write_u16(message_index);
+        }
+
+        match &self.payload {
+            OutgoingPayload::Message(message) => {
+                writer.write_bit(false);
+                write_message(message, writer);
+            }
+            OutgoingPayload::Fragment(fragment) => {
+                writer.write_bit(true);
+                fragment.write(writer);
+            }
+            OutgoingPayload::Fragmented(_) => {
+                panic!("Message must be split into fragments before being written");
+            }
+        }
+    }
+
+    fn is_fragment(&self) -> bool {
+        return !matches!(self.payload, OutgoingPayload::Message(_));
+    }
+}
+
+// writes the kind of a Message, followed by its payload
+fn write_message<P: ProtocolType>(message: &P, writer: &mut BitWriter) {
+    let message_ref = message.dyn_ref();
+    message_ref.get_kind().write(writer);
+    message_ref.write(writer);
+}
+
 /// Handles incoming/outgoing messages, tracks the delivery status of Messages
-/// so that guaranteed Messages can be re-transmitted to the remote host
+/// so that reliable Messages can be re-transmitted to the remote host, and
+/// delivers received Messages according to the mode of their Channel
 #[derive(Debug)]
 pub struct MessageManager<P: ProtocolType> {
-    queued_outgoing_messages: VecDeque<(bool, P)>,
+    channel_configs: Vec<ChannelConfig>,
+    channel_index_bits: u8,
+    // indices of the Channels, in the order their Messages are written
+    channel_order: Vec<usize>,
+    next_message_indices: Vec<u16>,
+    // for each reliable Channel, the number of parts of each Message which
+    // have not been delivered yet, and the index of the oldest such Message
+    undelivered_messages: Vec<HashMap<u16, u16>>,
+    oldest_undelivered_indices: Vec<u16>,
+    channel_receivers: Vec<ChannelReceiver<P>>,
+    queued_outgoing_messages: Vec<VecDeque<OutgoingMessage<P>>>,
+    // the number of Messages of each Channel written into the current packet
+    packet_message_counts: Vec<u16>,
+    current_packet_index: Option<u16>,
+    next_fragment_id: u16,
+    // the number of fragments of each fragmented Message being sent which
+    // have not been delivered yet
+    undelivered_fragments: HashMap<u16, u16>,
+    fragment_reassembler: FragmentReassembler,
     queued_incoming_messages: VecDeque<P>,
-    sent_guaranteed_messages: HashMap<u16, Vec<P>>,
-    last_popped_message_guarantee: bool,
+    sent_reliable_messages: HashMap<u16, Vec<OutgoingMessage<P>>>,
 }
 
 impl<P: ProtocolType> MessageManager<P> {
-    /// Creates a new MessageManager
-    pub fn new() -> Self {
+    /// Creates a new MessageManager, given the configuration of each Channel
+    /// in order of index
+    pub fn new(channel_configs: &[ChannelConfig]) -> Self {
+        let channel_count = channel_configs.len();
+
+        // higher priority Channels first, otherwise in order of index
+        let mut channel_order: Vec<usize> = (0..channel_count).collect();
+        channel_order.sort_by_key(|index| std::cmp::Reverse(channel_configs[*index].priority));
+
         MessageManager {
-            queued_outgoing_messages: VecDeque::new(),
+            channel_configs: channel_configs.to_vec(),
+            channel_index_bits: channel_index_bits(channel_count),
+            channel_order,
+            next_message_indices: vec![0; channel_count],
+            undelivered_messages: (0..channel_count).map(|_| HashMap::new()).collect(),
+            oldest_undelivered_indices: vec![0; channel_count],
+            channel_receivers: channel_configs
+                .iter()
+                .map(|config| ChannelReceiver::new(config.mode))
+                .collect(),
+            queued_outgoing_messages: (0..channel_count).map(|_| VecDeque::new()).collect(),
+            packet_message_counts: vec![0; channel_count],
+            current_packet_index: None,
+            next_fragment_id: 0,
+            undelivered_fragments: HashMap::new(),
+            fragment_reassembler: FragmentReassembler::new(),
             queued_incoming_messages: VecDeque::new(),
-            sent_guaranteed_messages: HashMap::new(),
-            last_popped_message_guarantee: false,
+            sent_reliable_messages: HashMap::new(),
         }
     }
 
     /// Returns whether the Manager has queued Messages that can be transmitted
     /// to the remote host
     pub fn has_outgoing_messages(&self) -> bool {
-        return self.queued_outgoing_messages.len() != 0;
-    }
-
-    /// Gets the next queued Message to be transmitted
-    pub fn pop_outgoing_message(&mut self, packet_index: u16) -> Option<P> {
-        match self.queued_outgoing_messages.pop_front() {
-            Some((guaranteed, message)) => {
-                //place in transmission record if this is a guaranteed message
-                if guaranteed {
-                    if !self.sent_guaranteed_messages.contains_key(&packet_index) {
-                        let sent_messages_list: Vec<P> = Vec::new();
-                        self.sent_guaranteed_messages
-                            .insert(packet_index, sent_messages_list);
-                    }
+        return self
+            .queued_outgoing_messages
+            .iter()
+            .any(|queue| queue.len() != 0);
+    }
 
-                    if let Some(sent_messages_list) =
-                        self.sent_guaranteed_messages.get_mut(&packet_index)
-                    {
-                        sent_messages_list.push(message.clone());
-                    }
+    /// Gets the next queued Message to be transmitted, taken from the Channel
+    /// with the highest priority which has not exhausted its budget for the
+    /// given packet
+    pub fn pop_outgoing_message(&mut self, packet_index: u16) -> Option<OutgoingMessage<P>> {
+        if self.current_packet_index != Some(packet_index) {
+            self.current_packet_index = Some(packet_index);
+            for count in self.packet_message_counts.iter_mut() {
+                *count = 0;
+            }
+        }
+
+        for order_index in 0..self.channel_order.len() {
+            let channel_index = self.channel_order[order_index];
+            if let Some(max_messages) = self.channel_configs[channel_index].max_messages_per_packet
+            {
+                if self.packet_message_counts[channel_index] >= max_messages {
+                    continue;
                 }
+            }
+
+            if !self.is_in_window(channel_index) {
+                // the remote host would ignore the Message
+                continue;
+            }
+
+            let is_fragmented = matches!(
+                self.queued_outgoing_messages[channel_index].front(),
+                Some(OutgoingMessage {
+                    payload: OutgoingPayload::Fragmented(_),
+                    ..
+                })
+            );
+            if is_fragmented && !self.split_fragmented_message(channel_index) {
+                // the remote host has no room for another fragmented Message
+                continue;
+            }
+
+            if let Some(message) = self.queued_outgoing_messages[channel_index].pop_front() {
+                self.packet_message_counts[channel_index] += 1;
 
-                self.last_popped_message_guarantee = guaranteed;
+                //place in transmission record if this is a reliable message
+                if self.is_reliable(&message) {
+                    self.sent_reliable_messages
+                        .entry(packet_index)
+                        .or_insert_with(Vec::new)
+                        .push(message.clone());
+                }
 
-                Some(message)
+                return Some(message);
             }
-            None => None,
         }
+
+        return None;
     }
 
     /// If  the last popped Message from the queue somehow wasn't able to be
     /// written into a packet, put the Message back into the front of the queue
-    pub fn unpop_outgoing_message(&mut self, packet_index: u16, message: P) {
-        if self.last_popped_message_guarantee {
-            if let Some(sent_messages_list) = self.sent_guaranteed_messages.get_mut(&packet_index) {
+    pub fn unpop_outgoing_message(&mut self, packet_index: u16, message: OutgoingMessage<P>) {
+        if self.is_reliable(&message) {
+            if let Some(sent_messages_list) = self.sent_reliable_messages.get_mut(&packet_index) {
                 sent_messages_list.pop();
                 if sent_messages_list.len() == 0 {
-                    self.sent_guaranteed_messages.remove(&packet_index);
+                    self.sent_reliable_messages.remove(&packet_index);
                 }
             }
         }
 
-        self.queued_outgoing_messages
-            .push_front((self.last_popped_message_guarantee, message));
+        let channel_index = usize::from(message.channel_index);
+        if self.current_packet_index == Some(packet_index) {
+            let count = &mut self.packet_message_counts[channel_index];
+            *count = count.saturating_sub(1);
+        }
+
+        self.queued_outgoing_messages[channel_index].push_front(message);
     }
 
-    /// Queues an Message to be transmitted to the remote host
-    pub fn queue_outgoing_message<R: ReplicateSafe<P>>(
+    /// Queues an Message to be transmitted to the remote host through the
+    /// given Channel. Messages too large to fit into a single packet are split
+    /// into fragments, which are always sent reliably. Fails if the Channel
+    /// doesn't exist, its queue is full, or the Message is too large, in which
+    /// case the Message is dropped
+    pub fn queue_outgoing_message<R: ReplicateSafe<P>, C: ChannelIndex>(
         &mut self,
         message: &R,
-        guaranteed_delivery: bool,
-    ) {
-        self.queued_outgoing_messages
-            .push_back((guaranteed_delivery, message.protocol_copy()));
+        channel: C,
+    ) -> Result<(), SendMessageError> {
+        let channel_index = channel.index();
+        let config = self
+            .channel_configs
+            .get(usize::from(channel_index))
+            .ok_or(SendMessageError::ChannelNotFound(channel_index))?;
+
+        let message = message.protocol_copy();
+        let mut message_bits = BitWriter::new();
+        write_message(&message, &mut message_bits);
+
+        let payload = if message_bits.bits_number() <= FRAGMENT_SIZE_BYTES * 8 {
+            OutgoingPayload::Message(message)
+        } else if message_bits.bits_number() > MAX_MESSAGE_SIZE_BYTES * 8 {
+            return Err(SendMessageError::MessageTooLarge);
+        } else {
+            OutgoingPayload::Fragmented(message_bits.to_bytes())
+        };
+
+        // checked before an index is taken, so that no gap is left in the
+        // Channel's indices
+        let queue = &mut self.queued_outgoing_messages[usize::from(channel_index)];
+        if let Some(max_queued_messages) = config.max_queued_messages {
+            if queue.len() >= max_queued_messages {
+                return Err(SendMessageError::QueueOverflow);
+            }
+        }
+
+        let message_index = if config.mode.is_indexed() {
+            let next_message_index = &mut self.next_message_indices[usize::from(channel_index)];
+            let message_index = *next_message_index;
+            *next_message_index = next_message_index.wrapping_add(1);
+            Some(message_index)
+        } else {
+            None
+        };
+        if config.mode.is_reliable() {
+            self.undelivered_messages[usize::from(channel_index)].insert(message_index.unwrap(), 1);
+        }
+
+        queue.push_back(OutgoingMessage {
+            channel_index,
+            channel_index_bits: self.channel_index_bits,
+            message_index,
+            payload,
+        });
+        return Ok(());
     }
 
     /// Returns whether any Messages have been received that must be handed to
@@ -107,37 +320,344 @@ impl<P: ProtocolType> MessageManager<P> {
     /// be returned to the application
     pub fn process_data(
         &mut self,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         manifest: &Manifest<P>,
         packet_index: u16,
-    ) {
-        let message_count = reader.read_u8();
-        for _x in 0..message_count {
-            let component_kind: P::Kind = P::Kind::from_u16(reader.read_u16());
+    ) -> Result<(), DecodeError> {
+        // each Message is preceded by a bit indicating whether there is another
+        while reader.read_bit() {
+            let channel_index = reader.read_bits(self.channel_index_bits) as usize;
+            let mode = match self.channel_configs.get(channel_index) {
+                Some(config) => config.mode,
+                None => {
+                    // unable to read the remainder of the packet
+                    return Err(DecodeError::InvalidValue);
+                }
+            };
+            let message_index = if mode.is_indexed() {
+                reader.read_u16()
+            } else {
+                0
+            };
+
+            let new_message = if reader.read_bit() {
+                let fragment = MessageFragment::read(reader);
+                reader.check()?;
+                match self.fragment_reassembler.receive(fragment) {
+                    Some(message_bytes) => {
+                        let mut message_reader = BitReader::new(&message_bytes);
+                        read_message(&mut message_reader, manifest, packet_index)?
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            } else {
+                read_message(reader, manifest, packet_index)?
+            };
+
+            self.channel_receivers[channel_index].receive(
+                message_index,
+                new_message,
+                &mut self.queued_incoming_messages,
+            );
+        }
+        return reader.check();
+    }
+
+    // Splits the fragmented Message at the front of the given Channel's queue
+    // into fragments, if the remote host has room to reassemble it. Fragment
+    // ids are taken in the order Messages are split, and no more than
+    // `MAX_IN_FLIGHT_MESSAGES` consecutive ids are undelivered at once, so
+    // that the remote host never has to drop a fragment
+    fn split_fragmented_message(&mut self, channel_index: usize) -> bool {
+        let next_fragment_id = self.next_fragment_id;
+        if self.undelivered_fragments.keys().any(|fragment_id| {
+            usize::from(next_fragment_id.wrapping_sub(*fragment_id)) >= MAX_IN_FLIGHT_MESSAGES
+        }) {
+            return false;
+        }
+
+        let queue = &mut self.queued_outgoing_messages[channel_index];
+        let message = match queue.pop_front() {
+            Some(message) => message,
+            None => return false,
+        };
+        let bytes = match message.payload {
+            OutgoingPayload::Fragmented(bytes) => bytes,
+            _ => panic!("expected a fragmented Message"),
+        };
+        self.next_fragment_id = next_fragment_id.wrapping_add(1);
+        let fragments = MessageFragment::split(next_fragment_id, &bytes);
+        self.undelivered_fragments
+            .insert(next_fragment_id, fragments.len() as u16);
+        if let Some(message_index) = message.message_index {
+            if let Some(count) = self.undelivered_messages[channel_index].get_mut(&message_index) {
+                *count = fragments.len() as u16;
+            }
+        }
+        for fragment in fragments.into_iter().rev() {
+            queue.push_front(OutgoingMessage {
+                channel_index: message.channel_index,
+                channel_index_bits: message.channel_index_bits,
+                message_index: message.message_index,
+                payload: OutgoingPayload::Fragment(fragment),
+            });
+        }
+        return true;
+    }
+
+    // whether the Message at the front of the given Channel's queue is within
+    // `MAX_MESSAGES_AHEAD` of the oldest undelivered Message of the Channel
+    fn is_in_window(&self, channel_index: usize) -> bool {
+        if !self.channel_configs[channel_index].mode.is_reliable() {
+            return true;
+        }
+        return match self.queued_outgoing_messages[channel_index].front() {
+            Some(OutgoingMessage {
+                message_index: Some(message_index),
+                ..
+            }) => {
+                message_index.wrapping_sub(self.oldest_undelivered_indices[channel_index])
+                    < MAX_MESSAGES_AHEAD
+            }
+            _ => true,
+        };
+    }
 
-            let new_message = manifest.create_replica(component_kind, reader, packet_index);
-            self.queued_incoming_messages.push_back(new_message);
+    // records the delivery of a Message, or one of its fragments, on a
+    // reliable Channel
+    fn message_delivered(&mut self, channel_index: usize, message_index: u16) {
+        let undelivered_messages = &mut self.undelivered_messages[channel_index];
+        if let Some(count) = undelivered_messages.get_mut(&message_index) {
+            *count -= 1;
+            if *count == 0 {
+                undelivered_messages.remove(&message_index);
+            }
+        }
+
+        let oldest_index = &mut self.oldest_undelivered_indices[channel_index];
+        while *oldest_index != self.next_message_indices[channel_index]
+            && !undelivered_messages.contains_key(oldest_index)
+        {
+            *oldest_index = oldest_index.wrapping_add(1);
         }
     }
+
+    fn is_reliable(&self, message: &OutgoingMessage<P>) -> bool {
+        return message.is_fragment()
+            || self.channel_configs[usize::from(message.channel_index)]
+                .mode
+                .is_reliable();
+    }
+}
+
+// reads the kind of a Message, followed by its payload
+fn read_message<P: ProtocolType>(
+    reader: &mut BitReader,
+    manifest: &Manifest<P>,
+    packet_index: u16,
+) -> Result<P, DecodeError> {
+    let component_kind: P::Kind = P::Kind::read(reader);
+    return manifest.create_replica(component_kind, reader, packet_index);
+}
+
+// Gets the number of bits needed to write the index of any of the Channels
+fn channel_index_bits(channel_count: usize) -> u8 {
+    let mut bits = 0;
+    while (1 << bits) < channel_count {
+        bits += 1;
+    }
+    return bits;
 }
 
 impl<P: ProtocolType> PacketNotifiable for MessageManager<P> {
     /// Occurs when a packet has been notified as delivered. Stops tracking the
     /// status of Messages in that packet.
     fn notify_packet_delivered(&mut self, packet_index: u16) {
-        self.sent_guaranteed_messages.remove(&packet_index);
+        if let Some(delivered_messages_list) = self.sent_reliable_messages.remove(&packet_index) {
+            for delivered_message in delivered_messages_list.iter() {
+                let channel_index = usize::from(delivered_message.channel_index);
+                if self.channel_configs[channel_index].mode.is_reliable() {
+                    if let Some(message_index) = delivered_message.message_index {
+                        self.message_delivered(channel_index, message_index);
+                    }
+                }
+
+                if let OutgoingPayload::Fragment(fragment) = &delivered_message.payload {
+                    let fragment_id = fragment.id();
+                    if let Some(count) = self.undelivered_fragments.get_mut(&fragment_id) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.undelivered_fragments.remove(&fragment_id);
+                        }
+                    }
+                }
+            }
+        }
     }
 
     /// Occurs when a packet has been notified as having been dropped. Queues up
-    /// any guaranteed Messages that were lost in the packet for retransmission.
+    /// any reliable Messages that were lost in the packet for retransmission.
+    /// Lost Messages go to the front of the queue, in their original order, so
+    /// that they are never held behind a Message waiting for them to be
+    /// delivered
     fn notify_packet_dropped(&mut self, packet_index: u16) {
-        if let Some(dropped_messages_list) = self.sent_guaranteed_messages.get(&packet_index) {
-            for dropped_message in dropped_messages_list.into_iter() {
-                self.queued_outgoing_messages
-                    .push_back((true, dropped_message.clone()));
+        if let Some(dropped_messages_list) = self.sent_reliable_messages.remove(&packet_index) {
+            for dropped_message in dropped_messages_list.into_iter().rev() {
+                self.queued_outgoing_messages[usize::from(dropped_message.channel_index)]
+                    .push_front(dropped_message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod message_manager_tests {
+    use super::MessageManager;
+    use crate::{
+        channel_receiver::MAX_MESSAGES_AHEAD,
+        message_fragment::MAX_IN_FLIGHT_MESSAGES,
+        test_protocol::{Payload, Protocol},
+        BitReader, BitWriter, ChannelConfig, ChannelIndex, ChannelMode, PacketNotifiable,
+        ProtocolType, FRAGMENT_SIZE_BYTES,
+    };
+
+    #[derive(Clone, Copy)]
+    struct Channel;
+
+    impl ChannelIndex for Channel {
+        fn index(&self) -> u8 {
+            return 0;
+        }
+    }
+
+    // a reliable Channel sending one Message or fragment per packet
+    fn manager() -> MessageManager<Protocol> {
+        let mut config = ChannelConfig::new(ChannelMode::OrderedReliable);
+        config.max_messages_per_packet = Some(1);
+        return MessageManager::new(&[config]);
+    }
+
+    // a Message split into 2 fragments, tagged with the given byte
+    fn large_message(tag: u8) -> Payload {
+        return Payload::new_complete(vec![tag; FRAGMENT_SIZE_BYTES + 1]);
+    }
+
+    fn tag_of(message: &Protocol) -> u8 {
+        return message.cast_ref::<Payload>().unwrap().bytes.get()[0];
+    }
+
+    // writes the next Message or fragment into a packet, if there is one
+    fn send(sender: &mut MessageManager<Protocol>, packet_index: u16) -> Option<Box<[u8]>> {
+        let message = sender.pop_outgoing_message(packet_index)?;
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        message.write(&mut writer);
+        writer.write_bit(false);
+        return Some(writer.to_bytes());
+    }
+
+    fn receive(receiver: &mut MessageManager<Protocol>, packet_index: u16, packet: &[u8]) {
+        let mut reader = BitReader::new(packet);
+        receiver
+            .process_data(&mut reader, &Protocol::load(), packet_index)
+            .unwrap();
+    }
+
+    #[test]
+    fn redeliver_lost_fragment() {
+        let mut sender = manager();
+        let mut receiver = manager();
+        sender
+            .queue_outgoing_message(&large_message(7), Channel)
+            .unwrap();
+
+        // the first fragment is lost
+        send(&mut sender, 0).unwrap();
+        sender.notify_packet_dropped(0);
+
+        let packet = send(&mut sender, 1).unwrap();
+        receive(&mut receiver, 1, &packet);
+        sender.notify_packet_delivered(1);
+        assert!(receiver.pop_incoming_message().is_none());
+
+        let packet = send(&mut sender, 2).unwrap();
+        receive(&mut receiver, 2, &packet);
+        sender.notify_packet_delivered(2);
+        assert_eq!(tag_of(&receiver.pop_incoming_message().unwrap()), 7);
+        assert!(send(&mut sender, 3).is_none());
+    }
+
+    #[test]
+    fn limit_fragmented_in_flight() {
+        let mut sender = manager();
+        let mut receiver = manager();
+        let message_count = MAX_IN_FLIGHT_MESSAGES + 4;
+        for tag in 0..message_count {
+            sender
+                .queue_outgoing_message(&large_message(tag as u8), Channel)
+                .unwrap();
+        }
+
+        // without any packet being delivered, no more fragmented Messages
+        // are sent than the receiver can reassemble
+        let mut packet_index = 0;
+        while let Some(packet) = send(&mut sender, packet_index) {
+            receive(&mut receiver, packet_index, &packet);
+            packet_index += 1;
+        }
+        assert_eq!(usize::from(packet_index), MAX_IN_FLIGHT_MESSAGES * 2);
+
+        // each delivery makes room for the next one
+        let mut delivered_index = 0;
+        while delivered_index < packet_index {
+            sender.notify_packet_delivered(delivered_index);
+            delivered_index += 1;
+            while let Some(packet) = send(&mut sender, packet_index) {
+                receive(&mut receiver, packet_index, &packet);
+                packet_index += 1;
             }
+        }
+        for tag in 0..message_count {
+            let message = receiver.pop_incoming_message().unwrap();
+            assert_eq!(usize::from(tag_of(&message)), tag);
+        }
+        assert!(receiver.pop_incoming_message().is_none());
+    }
+
+    #[test]
+    fn limit_reliable_in_flight() {
+        let mut sender = manager();
+        let mut receiver = manager();
+        for index in 0..=MAX_MESSAGES_AHEAD {
+            sender
+                .queue_outgoing_message(&Payload::new_complete(vec![index as u8]), Channel)
+                .unwrap();
+        }
 
-            self.sent_guaranteed_messages.remove(&packet_index);
+        // nothing is sent past the window of the receiver while the first
+        // Message is in flight
+        send(&mut sender, 0).unwrap();
+        let mut packet_index = 1;
+        while let Some(packet) = send(&mut sender, packet_index) {
+            receive(&mut receiver, packet_index, &packet);
+            sender.notify_packet_delivered(packet_index);
+            packet_index += 1;
+        }
+        assert_eq!(packet_index, MAX_MESSAGES_AHEAD);
+
+        // the first Message is lost, and resent ahead of the rest
+        sender.notify_packet_dropped(0);
+        while let Some(packet) = send(&mut sender, packet_index) {
+            receive(&mut receiver, packet_index, &packet);
+            sender.notify_packet_delivered(packet_index);
+            packet_index += 1;
+        }
+        for index in 0..=MAX_MESSAGES_AHEAD {
+            let message = receiver.pop_incoming_message().unwrap();
+            assert_eq!(tag_of(&message), index as u8);
         }
+        assert!(receiver.pop_incoming_message().is_none());
     }
 }
@@ -1,18 +1,22 @@
-use byteorder::{BigEndian, WriteBytesExt};
-
 use super::{
-    manager_type::ManagerType,
-    protocol_type::{ProtocolKindType, ProtocolType},
-    standard_header::StandardHeader,
+    bit_writer::BitWriter, manager_type::ManagerType, message_manager::OutgoingMessage,
+    protocol_type::ProtocolType, standard_header::StandardHeader,
 };
 
 /// The maximum of bytes that can be used for the payload of a given packet. (See #38 of http://ithare.com/64-network-dos-and-donts-for-game-engines-part-v-udp/)
 pub const MTU_SIZE: usize = 508 - StandardHeader::bytes_number();
 
+/// The maximum of bits that can be used for the payload of a given packet
+pub const MTU_SIZE_BITS: usize = MTU_SIZE * 8;
+
+/// The number of bits used by a manager section of a packet, aside from its
+/// contents: a continuation bit, the ManagerType, and a list terminator bit
+pub const SECTION_OVERHEAD_BITS: usize = 5;
+
 /// Handles writing of Message data into an outgoing packet
 pub struct MessagePacketWriter {
-    message_working_bytes: Vec<u8>,
-    message_count: u8,
+    message_working_bits: BitWriter,
+    message_count: u16,
 }
 
 impl MessagePacketWriter {
@@ -20,7 +24,7 @@ impl MessagePacketWriter {
     /// will be used to read information from.
     pub fn new() -> MessagePacketWriter {
         MessagePacketWriter {
-            message_working_bytes: Vec::<u8>::new(),
+            message_working_bits: BitWriter::new(),
             message_count: 0,
         }
     }
@@ -30,51 +34,45 @@ impl MessagePacketWriter {
         return self.message_count != 0;
     }
 
-    /// Gets the bytes to write into an outgoing packet
-    pub fn get_bytes(&mut self, out_bytes: &mut Vec<u8>) {
-        //Write manager "header" (manager type & message count)
+    /// Writes the Message data into an outgoing packet's bit stream
+    pub fn get_bytes(&mut self, writer: &mut BitWriter) {
+        //Write manager "header" (manager type)
         if self.message_count != 0 {
-            out_bytes.write_u8(ManagerType::Message as u8).unwrap(); // write manager type
-            out_bytes.write_u8(self.message_count).unwrap(); // write number of messages in the following message
-            out_bytes.append(&mut self.message_working_bytes); // write message payload
+            writer.write_bit(true); // write that there is another manager section
+            ManagerType::Message.write(writer); // write manager type
+            writer.append(&self.message_working_bits); // write message payload
+            writer.write_bit(false); // write that there are no more messages
+
+            self.message_working_bits = BitWriter::new();
             self.message_count = 0;
         }
     }
 
-    /// Get the number of bytes which is ready to be written into an outgoing
-    /// packet
-    pub fn bytes_number(&self) -> usize {
-        return self.message_working_bytes.len();
+    /// Get the number of bits which is ready to be written into an outgoing
+    /// packet, including the manager section header
+    pub fn bits_number(&self) -> usize {
+        if self.message_count == 0 {
+            return 0;
+        }
+        return self.message_working_bits.bits_number() + SECTION_OVERHEAD_BITS;
     }
 
     /// Writes an Message into the Writer's internal buffer, which will
     /// eventually be put into the outgoing packet
-    pub fn write_message<P: ProtocolType>(&mut self, message: &P) -> bool {
-        let message_ref = message.dyn_ref();
-
-        //Write message payload
-        let mut message_payload_bytes = Vec::<u8>::new();
-        message_ref.write(&mut message_payload_bytes);
-
-        //Write message "header"
-        let mut message_total_bytes = Vec::<u8>::new();
+    pub fn write_message<P: ProtocolType>(&mut self, message: &OutgoingMessage<P>) -> bool {
+        let mut message_total_bits = BitWriter::new();
 
-        let message_kind = message_ref.get_kind();
-        message_total_bytes
-            .write_u16::<BigEndian>(message_kind.to_u16())
-            .unwrap(); // write naia id
-        message_total_bytes.append(&mut message_payload_bytes); // write payload
+        message_total_bits.write_bit(true); // write that there is another message
+        message.write(&mut message_total_bits); // write message channel & payload
 
-        let mut hypothetical_next_payload_size = self.bytes_number() + message_total_bytes.len();
+        let mut hypothetical_next_payload_size =
+            self.bits_number() + message_total_bits.bits_number();
         if self.message_count == 0 {
-            hypothetical_next_payload_size += 2;
+            hypothetical_next_payload_size += SECTION_OVERHEAD_BITS;
         }
-        if hypothetical_next_payload_size < MTU_SIZE {
-            if self.message_count == 255 {
-                return false;
-            }
+        if hypothetical_next_payload_size < MTU_SIZE_BITS {
             self.message_count = self.message_count.wrapping_add(1);
-            self.message_working_bytes.append(&mut message_total_bytes);
+            self.message_working_bits.append(&message_total_bits);
             return true;
         } else {
             return false;
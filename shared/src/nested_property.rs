@@ -0,0 +1,78 @@
+use crate::{
+    bit_reader::BitReader, bit_writer::BitWriter, diff_mask::SubDiffMask,
+    property_mutate::PropertyMutator, wrapping_number::sequence_greater_than,
+};
+
+/// A Property of a Component whose nested elements are tracked & synced
+/// individually, so that changing one element does not resend the whole
+/// Property. Implemented by `ReplicatedVec`, `ReplicatedMap`, and structs
+/// deriving `NestedProperty`
+pub trait NestedProperty: Clone + Send + Sync + 'static {
+    /// Given a cursor into incoming packet data, initializes the Property with
+    /// the synced value
+    fn new_read(reader: &mut BitReader, packet_index: u16) -> Self;
+
+    /// Given a cursor into incoming packet data, updates the whole Property
+    /// with the synced value, but only if data is newer than the last data
+    /// received
+    fn read(&mut self, reader: &mut BitReader, packet_index: u16);
+
+    /// Given a cursor into incoming packet data, updates the nested elements
+    /// of the Property which have been synced
+    fn read_partial(&mut self, reader: &mut BitReader, packet_index: u16);
+
+    /// Given a cursor into incoming packet data, updates the Property with a
+    /// synced value which was encoded relative to the Property's current value
+    fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16);
+
+    /// Writes the whole Property into an outgoing bit stream
+    fn write(&self, writer: &mut BitWriter);
+
+    /// Writes the nested elements of the Property which are marked in the
+    /// SubDiffMask into an outgoing bit stream
+    fn write_partial(&self, diff_mask: &SubDiffMask, writer: &mut BitWriter);
+
+    /// Writes the Property into an outgoing bit stream, encoded relative to
+    /// the value of a baseline Property
+    fn write_delta(&self, baseline: &Self, writer: &mut BitWriter);
+
+    /// Compare to another Property
+    fn equals(&self, other: &Self) -> bool;
+
+    /// Set value to the value of another Property
+    fn mirror(&mut self, other: &Self);
+
+    /// Set a PropertyMutator to track changes to the Property, which is found
+    /// at the given index of its parent
+    fn set_mutator(&mut self, mutator: &PropertyMutator, property_index: u8);
+
+    /// Writes either the whole Property, or only its nested elements which are
+    /// marked in the SubDiffMask, preceded by a bit indicating which
+    fn write_masked(&self, diff_mask: Option<&SubDiffMask>, writer: &mut BitWriter) {
+        if let Some(diff_mask) = diff_mask {
+            writer.write_bit(true);
+            self.write_partial(diff_mask, writer);
+        } else {
+            writer.write_bit(false);
+            self.write(writer);
+        }
+    }
+
+    /// Reads data written by `write_masked`
+    fn read_masked(&mut self, reader: &mut BitReader, packet_index: u16) {
+        if reader.read_bit() {
+            self.read_partial(reader, packet_index);
+        } else {
+            self.read(reader, packet_index);
+        }
+    }
+}
+
+// Returns whether data from the given packet is newer than the data last
+// received, if any
+pub(crate) fn is_newer(packet_index: u16, last_recv_index: Option<u16>) -> bool {
+    if let Some(last_recv_index) = last_recv_index {
+        return sequence_greater_than(packet_index, last_recv_index);
+    }
+    return true;
+}
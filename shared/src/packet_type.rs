@@ -22,6 +22,8 @@ pub enum PacketType {
     /// A Pong message, used to calculate RTT. Must be the response to all Ping
     /// messages
     Pong = 8,
+    /// The Server's response to a connect request which it has rejected
+    ServerRejectResponse = 9,
     /// An unknown packet type
     Unknown = 255,
 }
@@ -37,6 +39,7 @@ impl From<u8> for PacketType {
             6 => return PacketType::ServerConnectResponse,
             7 => return PacketType::Ping,
             8 => return PacketType::Pong,
+            9 => return PacketType::ServerRejectResponse,
             _ => return PacketType::Unknown,
         };
     }
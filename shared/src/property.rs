@@ -1,13 +1,22 @@
-use nanoserde::{DeBin, SerBin};
-
-use naia_socket_shared::PacketReader;
-
-use crate::{property_mutate::PropertyMutator, wrapping_number::sequence_greater_than};
+use crate::{
+    bit_reader::BitReader, bit_writer::BitWriter, property_mutate::PropertyMutator, serde::Serde,
+    wrapping_number::sequence_greater_than,
+};
+
+/// A compact encoding of a Property's value, such as a QuantizedFloat for an
+/// `f32`. Set on a field of a Replica with `#[encoding = "Type"]`, so that the
+/// value is written through the encoding, & decoded when it is read
+pub trait PropertyEncoding<T>: Serde {
+    /// Encodes a value, as it will be written
+    fn encode(value: &T) -> Self;
+    /// Decodes a value, as it was received
+    fn decode(&self) -> T;
+}
 
 /// A Property of an Component/Message, that contains data
 /// which must be tracked for updates
 #[derive(Clone)]
-pub struct Property<T: Clone + DeBin + SerBin + PartialEq> {
+pub struct Property<T: Clone + Serde + PartialEq> {
     inner: T,
     mutator: Option<PropertyMutator>,
     mutator_index: u8,
@@ -15,7 +24,7 @@ pub struct Property<T: Clone + DeBin + SerBin + PartialEq> {
 }
 
 // should be shared
-impl<T: Clone + DeBin + SerBin + PartialEq> Property<T> {
+impl<T: Clone + Serde + PartialEq> Property<T> {
     /// Create a new Property
     pub fn new(value: T, mutator_index: u8, packet_index: u16) -> Property<T> {
         return Property::<T> {
@@ -28,7 +37,7 @@ impl<T: Clone + DeBin + SerBin + PartialEq> Property<T> {
 
     /// Given a cursor into incoming packet data, initializes the Property with
     /// the synced value
-    pub fn new_read(reader: &mut PacketReader, mutator_index: u8, packet_index: u16) -> Self {
+    pub fn new_read(reader: &mut BitReader, mutator_index: u8, packet_index: u16) -> Self {
         let inner = Self::read_inner(reader);
 
         return Property::<T> {
@@ -41,7 +50,7 @@ impl<T: Clone + DeBin + SerBin + PartialEq> Property<T> {
 
     /// Given a cursor into incoming packet data, updates the Property with the
     /// synced value, but only if data is newer than the last data received
-    pub fn read(&mut self, reader: &mut PacketReader, packet_index: u16) {
+    pub fn read(&mut self, reader: &mut BitReader, packet_index: u16) {
         let inner = Self::read_inner(reader);
 
         if sequence_greater_than(packet_index, self.last_recv_index) {
@@ -50,21 +59,61 @@ impl<T: Clone + DeBin + SerBin + PartialEq> Property<T> {
         }
     }
 
-    fn read_inner(reader: &mut PacketReader) -> T {
-        let length = reader.read_u8();
+    /// Given a cursor into incoming packet data, updates the Property with a
+    /// synced value which was encoded relative to the Property's current value
+    pub fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16) {
+        self.inner = T::de_delta(&self.inner, reader);
+        self.last_recv_index = packet_index;
+    }
 
-        let buffer = reader.get_buffer();
-        let cursor = reader.get_cursor();
+    /// Given a cursor into incoming packet data, initializes the Property with
+    /// the synced value, written through the encoding `S`
+    pub fn new_read_encoded<S: PropertyEncoding<T>>(
+        reader: &mut BitReader,
+        mutator_index: u8,
+        packet_index: u16,
+    ) -> Self {
+        let inner = S::de(reader).decode();
+
+        return Property::<T> {
+            inner,
+            mutator: None,
+            mutator_index,
+            last_recv_index: packet_index,
+        };
+    }
 
-        let start: usize = cursor.position() as usize;
-        let end: usize = start + (length as usize);
+    /// Given a cursor into incoming packet data, updates the Property with the
+    /// synced value written through the encoding `S`, but only if data is
+    /// newer than the last data received
+    pub fn read_encoded<S: PropertyEncoding<T>>(
+        &mut self,
+        reader: &mut BitReader,
+        packet_index: u16,
+    ) {
+        let inner = S::de(reader).decode();
 
-        let inner =
-            DeBin::deserialize_bin(&buffer[start..end]).expect("error deserializing property");
+        if sequence_greater_than(packet_index, self.last_recv_index) {
+            self.last_recv_index = packet_index;
+            self.inner = inner;
+        }
+    }
 
-        cursor.set_position(end as u64);
+    /// Given a cursor into incoming packet data, updates the Property with a
+    /// synced value which was written through the encoding `S`, relative to
+    /// the Property's current value
+    pub fn read_delta_encoded<S: PropertyEncoding<T>>(
+        &mut self,
+        reader: &mut BitReader,
+        packet_index: u16,
+    ) {
+        let baseline = S::encode(&self.inner);
+        self.inner = S::de_delta(&baseline, reader).decode();
+        self.last_recv_index = packet_index;
+    }
 
-        return inner;
+    fn read_inner(reader: &mut BitReader) -> T {
+        return T::de(reader);
     }
 
     /// Gets a mutable reference to the value contained by the Property, queue
@@ -99,11 +148,34 @@ impl<T: Clone + DeBin + SerBin + PartialEq> Property<T> {
         self.inner = other.inner.clone();
     }
 
-    /// Writes contained value into outgoing byte stream
-    pub fn write(&self, buffer: &mut Vec<u8>) {
-        let encoded = &mut SerBin::serialize_bin(&self.inner);
-        buffer.push(encoded.len() as u8);
-        buffer.append(encoded);
+    /// Writes contained value into outgoing bit stream
+    pub fn write(&self, writer: &mut BitWriter) {
+        self.inner.ser(writer);
+    }
+
+    /// Writes contained value into outgoing bit stream, encoded relative to
+    /// the value of a baseline Property
+    pub fn write_delta(&self, baseline: &Property<T>, writer: &mut BitWriter) {
+        self.inner.ser_delta(&baseline.inner, writer);
+    }
+
+    /// Writes contained value into outgoing bit stream, through the encoding
+    /// `S`
+    pub fn write_encoded<S: PropertyEncoding<T>>(&self, writer: &mut BitWriter) {
+        S::encode(&self.inner).ser(writer);
+    }
+
+    /// Writes contained value into outgoing bit stream, through the encoding
+    /// `S`, relative to the value of a baseline Property
+    pub fn write_delta_encoded<S: PropertyEncoding<T>>(
+        &self,
+        baseline: &Property<T>,
+        writer: &mut BitWriter,
+    ) {
+        // the remote host holds the baseline as it was decoded, so the delta
+        // must be relative to the encoding of that value
+        let baseline = S::encode(&S::encode(&baseline.inner).decode());
+        S::encode(&self.inner).ser_delta(&baseline, writer);
     }
 
     /// Set an PropertyMutator to track changes to the Property
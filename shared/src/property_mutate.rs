@@ -6,6 +6,10 @@ pub trait PropertyMutate: PropertyMutateClone + Send + Sync + 'static {
     /// Given the index of the Property whose value has changed, queue that
     /// Property for transmission to the Client
     fn mutate(&mut self, property_index: u8);
+    /// Given the index of a Property, and the path of an element nested within
+    /// it whose value has changed, queue that element for transmission to the
+    /// Client
+    fn mutate_nested(&mut self, property_index: u8, path: &[u16]);
 }
 
 pub trait PropertyMutateClone {
@@ -58,3 +62,35 @@ impl DerefMut for PropertyMutator {
         self.inner.deref_mut()
     }
 }
+
+/// Tracks changes to the Properties of a NestedProperty, reporting them as
+/// changes to nested elements of the Property which contains it
+#[derive(Clone)]
+pub struct NestedMutator {
+    parent: PropertyMutator,
+    property_index: u8,
+}
+
+impl NestedMutator {
+    pub fn new(parent: &PropertyMutator, property_index: u8) -> Self {
+        return Self {
+            parent: parent.clone_new(),
+            property_index,
+        };
+    }
+}
+
+impl PropertyMutate for NestedMutator {
+    fn mutate(&mut self, property_index: u8) {
+        self.parent
+            .mutate_nested(self.property_index, &[u16::from(property_index)]);
+    }
+
+    fn mutate_nested(&mut self, property_index: u8, path: &[u16]) {
+        let mut nested_path = Vec::with_capacity(path.len() + 1);
+        nested_path.push(u16::from(property_index));
+        nested_path.extend_from_slice(path);
+        self.parent
+            .mutate_nested(self.property_index, &nested_path);
+    }
+}
@@ -1,6 +1,8 @@
 use std::{any::TypeId, hash::Hash};
 
 use super::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
     replica_ref::{ReplicaDynMut, ReplicaDynRef},
     replicate::{Replicate, ReplicateSafe},
 };
@@ -37,6 +39,11 @@ pub trait ProtocolKindType: Eq + Hash + Copy + Send + Sync {
     fn to_u16(&self) -> u16;
     fn from_u16(val: u16) -> Self;
     fn to_type_id(&self) -> TypeId;
+    /// Writes the kind into an outgoing bit stream, using the minimum number
+    /// of bits needed to represent every kind in the Protocol
+    fn write(&self, writer: &mut BitWriter);
+    /// Reads a kind from an incoming bit stream
+    fn read(reader: &mut BitReader) -> Self;
 }
 
 pub trait ProtocolInserter<P: ProtocolType, N> {
@@ -0,0 +1,125 @@
+use super::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    decode_error::DecodeError,
+    property::PropertyEncoding,
+    serde::{de_difference, ser_difference, Serde},
+};
+
+/// A float which is clamped to the range `MIN..=MAX`, and written as one of
+/// `2^BITS` evenly spaced steps within that range
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QuantizedFloat<const MIN: i32, const MAX: i32, const BITS: u8> {
+    step: u64,
+}
+
+impl<const MIN: i32, const MAX: i32, const BITS: u8> QuantizedFloat<MIN, MAX, BITS> {
+    /// Create a new float, which will be clamped to the range and rounded to
+    /// the nearest step
+    pub fn new(value: f32) -> Self {
+        let mut output = Self { step: 0 };
+        output.set(value);
+        return output;
+    }
+
+    /// Gets the value of the float, as it will be received by the remote host
+    pub fn get(&self) -> f32 {
+        let ratio = self.step as f64 / Self::max_step() as f64;
+        return (f64::from(MIN) + ratio * Self::span()) as f32;
+    }
+
+    /// Sets the value of the float, which will be clamped to the range and
+    /// rounded to the nearest step
+    pub fn set(&mut self, value: f32) {
+        if MIN >= MAX {
+            panic!("QuantizedFloat range must have MIN less than MAX");
+        }
+        if BITS == 0 || BITS > 32 {
+            panic!("QuantizedFloat must be written with between 1 and 32 bits");
+        }
+        let ratio = (f64::from(value) - f64::from(MIN)) / Self::span();
+        let ratio = ratio.clamp(0.0, 1.0);
+        self.step = (ratio * Self::max_step() as f64).round() as u64;
+    }
+
+    fn span() -> f64 {
+        return f64::from(MAX) - f64::from(MIN);
+    }
+
+    fn max_step() -> u64 {
+        return (1 << BITS) - 1;
+    }
+}
+
+impl<const MIN: i32, const MAX: i32, const BITS: u8> Serde for QuantizedFloat<MIN, MAX, BITS> {
+    fn ser(&self, writer: &mut BitWriter) {
+        writer.write_bits(self.step, BITS);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return Self {
+            step: reader.read_bits(BITS),
+        };
+    }
+
+    fn ser_delta(&self, baseline: &Self, writer: &mut BitWriter) {
+        ser_difference(self.step as i64 - baseline.step as i64, writer);
+    }
+
+    fn de_delta(baseline: &Self, reader: &mut BitReader) -> Self {
+        let step = (baseline.step as i64).wrapping_add(de_difference(reader)) as u64;
+        if step > Self::max_step() {
+            reader.set_error(DecodeError::InvalidValue);
+            return *baseline;
+        }
+        return Self { step };
+    }
+}
+
+impl<const MIN: i32, const MAX: i32, const BITS: u8> PropertyEncoding<f32>
+    for QuantizedFloat<MIN, MAX, BITS>
+{
+    fn encode(value: &f32) -> Self {
+        return Self::new(*value);
+    }
+
+    fn decode(&self) -> f32 {
+        return self.get();
+    }
+}
+
+#[cfg(test)]
+mod quantized_float_tests {
+    use crate::{serde::round_trip, BitReader, BitWriter, Property, QuantizedFloat};
+
+    #[test]
+    fn quantized_float() {
+        let value = QuantizedFloat::<-100, 100, 12>::new(33.3);
+        assert_eq!(round_trip(value), 12);
+        assert!((value.get() - 33.3).abs() < 0.05);
+
+        assert_eq!(QuantizedFloat::<0, 10, 8>::new(-5.0).get(), 0.0);
+        assert_eq!(QuantizedFloat::<0, 10, 8>::new(25.0).get(), 10.0);
+    }
+
+    #[test]
+    fn property_encoding() {
+        type Encoding = QuantizedFloat<-100, 100, 12>;
+        let baseline = Property::new(10.0f32, 0, 0);
+        let property = Property::new(33.3f32, 0, 0);
+
+        let mut writer = BitWriter::new();
+        property.write_encoded::<Encoding>(&mut writer);
+        assert_eq!(writer.bits_number(), 12);
+        property.write_delta_encoded::<Encoding>(&baseline, &mut writer);
+        let bytes = writer.to_bytes();
+
+        let mut reader = BitReader::new(&bytes);
+        let mut received = Property::<f32>::new_read_encoded::<Encoding>(&mut reader, 0, 1);
+        assert_eq!(*received.get(), Encoding::new(33.3).get());
+
+        received.set(Encoding::new(10.0).get());
+        received.read_delta_encoded::<Encoding>(&mut reader, 2);
+        assert_eq!(*received.get(), Encoding::new(33.3).get());
+    }
+}
@@ -1,6 +1,6 @@
-use naia_socket_shared::PacketReader;
-
 use crate::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
     diff_mask::DiffMask,
     property_mutate::PropertyMutator,
     protocol_type::ProtocolType,
@@ -41,11 +41,19 @@ pub trait ReplicateSafe<P: ProtocolType>: Sync + Send + 'static {
     fn set_mutator(&mut self, mutator: &PropertyMutator);
     /// Reads data from an incoming packet, sufficient to sync the in-memory
     /// Component with it's replica on the Server
-    fn read_partial(&mut self, diff_mask: &DiffMask, reader: &mut PacketReader, packet_index: u16);
-    /// Writes data into an outgoing byte stream, sufficient to completely
+    fn read_partial(&mut self, reader: &mut BitReader, packet_index: u16);
+    /// Reads data from an incoming packet which was written relative to a
+    /// baseline, where the baseline is the current state of the Replica
+    fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16);
+    /// Writes data into an outgoing bit stream, sufficient to completely
     /// recreate the Message/Component on the client
-    fn write(&self, out_bytes: &mut Vec<u8>);
-    /// Write data into an outgoing byte stream, sufficient only to update the
-    /// mutated Properties of the Message/Component on the client
-    fn write_partial(&self, diff_mask: &DiffMask, out_bytes: &mut Vec<u8>);
+    fn write(&self, writer: &mut BitWriter);
+    /// Write data into an outgoing bit stream, sufficient only to update the
+    /// mutated Properties of the Message/Component on the client. Each
+    /// Property is preceded by a single bit indicating whether it has changed
+    fn write_partial(&self, diff_mask: &DiffMask, writer: &mut BitWriter);
+    /// Write data into an outgoing bit stream, sufficient to turn a baseline
+    /// Replica of the same type into this Replica. Each Property is preceded
+    /// by a single bit indicating whether it differs from the baseline
+    fn write_delta(&self, baseline: &P, writer: &mut BitWriter);
 }
@@ -0,0 +1,462 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    decode_error::DecodeError,
+    diff_mask::SubDiffMask,
+    nested_property::{is_newer, NestedProperty},
+    property_mutate::PropertyMutator,
+    serde::{de_length, ser_length, Serde},
+};
+
+/// A map Property of a Component, of which only the entries that have changed
+/// are synced. Each entry is stored in a numbered slot, which is used to
+/// identify the entry when syncing. The map holds at most `MAX_LENGTH` slots,
+/// which can't be more than 65536, so that a slot received from a remote host
+/// can't force a huge allocation
+pub struct ReplicatedMap<
+    K: Serde + Eq + Hash + Clone,
+    V: Serde + Clone + PartialEq,
+    const MAX_LENGTH: usize = 4096,
+> {
+    slots: Vec<Option<(K, V)>>,
+    slot_map: HashMap<K, u16>,
+    free_slots: Vec<u16>,
+    mutator: Option<(PropertyMutator, u8)>,
+    recv_indices: Vec<Option<u16>>,
+    full_recv_index: Option<u16>,
+}
+
+impl<K: Serde + Eq + Hash + Clone, V: Serde + Clone + PartialEq, const MAX_LENGTH: usize>
+    ReplicatedMap<K, V, MAX_LENGTH>
+{
+    /// Create a new, empty ReplicatedMap
+    pub fn new() -> Self {
+        return Self {
+            slots: Vec::new(),
+            slot_map: HashMap::new(),
+            free_slots: Vec::new(),
+            mutator: None,
+            recv_indices: Vec::new(),
+            full_recv_index: None,
+        };
+    }
+
+    /// Gets the number of entries in the map
+    pub fn len(&self) -> usize {
+        return self.slot_map.len();
+    }
+
+    /// Returns whether the map contains no entries
+    pub fn is_empty(&self) -> bool {
+        return self.slot_map.is_empty();
+    }
+
+    /// Returns whether the map contains an entry for the given key
+    pub fn contains_key(&self, key: &K) -> bool {
+        return self.slot_map.contains_key(key);
+    }
+
+    /// Gets a reference to the value of the entry for the given key
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let slot = self.slot_map.get(key)?;
+        return self.slots[usize::from(*slot)]
+            .as_ref()
+            .map(|(_, value)| value);
+    }
+
+    /// Gets a mutable reference to the value of the entry for the given key,
+    /// queue to update
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let slot = *self.slot_map.get(key)?;
+        self.mutate_slot(slot);
+        return self.slots[usize::from(slot)]
+            .as_mut()
+            .map(|(_, value)| value);
+    }
+
+    /// Inserts an entry into the map, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let slot = if let Some(slot) = self.slot_map.get(&key) {
+            *slot
+        } else if let Some(slot) = self.free_slots.pop() {
+            slot
+        } else {
+            if self.slots.len() >= Self::max_slots() {
+                panic!(
+                    "ReplicatedMap cannot hold more than {} entries",
+                    Self::max_slots()
+                );
+            }
+            let slot = self.slots.len() as u16;
+            self.slots.push(None);
+            self.recv_indices.push(None);
+            slot
+        };
+
+        self.slot_map.insert(key.clone(), slot);
+        let previous = self.slots[usize::from(slot)].replace((key, value));
+        self.mutate_slot(slot);
+        return previous.map(|(_, value)| value);
+    }
+
+    /// Removes the entry for the given key from the map, returning its value
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.slot_map.remove(key)?;
+        let previous = self.slots[usize::from(slot)].take();
+        self.free_slots.push(slot);
+        self.mutate_slot(slot);
+        return previous.map(|(_, value)| value);
+    }
+
+    /// Removes all entries from the map
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.slot_map.clear();
+        self.free_slots.clear();
+        self.recv_indices.clear();
+        if let Some((mutator, property_index)) = &mut self.mutator {
+            mutator.mutate(*property_index);
+        }
+    }
+
+    /// Gets an iterator over the entries of the map
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        return self
+            .slots
+            .iter()
+            .filter_map(|slot| slot.as_ref().map(|(key, value)| (key, value)));
+    }
+
+    fn max_slots() -> usize {
+        return MAX_LENGTH.min(usize::from(u16::MAX) + 1);
+    }
+
+    fn mutate_slot(&mut self, slot: u16) {
+        if let Some((mutator, property_index)) = &mut self.mutator {
+            mutator.mutate_nested(*property_index, &[slot]);
+        }
+    }
+
+    fn read_slot(reader: &mut BitReader) -> Option<(K, V)> {
+        if reader.read_bit() {
+            let key = K::de(reader);
+            let value = V::de(reader);
+            return Some((key, value));
+        }
+        return None;
+    }
+
+    fn write_slot(&self, slot: usize, writer: &mut BitWriter) {
+        if let Some((key, value)) = &self.slots[slot] {
+            writer.write_bit(true);
+            key.ser(writer);
+            value.ser(writer);
+        } else {
+            writer.write_bit(false);
+        }
+    }
+
+    // Sets the entry of a slot with data received from the given packet, if it
+    // is newer than the data last received for that slot
+    fn receive_slot(&mut self, slot: usize, entry: Option<(K, V)>, packet_index: u16) {
+        let last_recv_index = self
+            .recv_indices
+            .get(slot)
+            .copied()
+            .flatten()
+            .or(self.full_recv_index);
+        if !is_newer(packet_index, last_recv_index) {
+            return;
+        }
+
+        if slot >= self.slots.len() {
+            self.slots.resize(slot + 1, None);
+            self.recv_indices.resize(slot + 1, None);
+        }
+        self.recv_indices[slot] = Some(packet_index);
+
+        if let Some((old_key, _)) = self.slots[slot].take() {
+            if self.slot_map.get(&old_key) == Some(&(slot as u16)) {
+                self.slot_map.remove(&old_key);
+            }
+        }
+
+        if let Some((key, value)) = entry {
+            // the key may still be held by a slot whose removal has not been
+            // received yet
+            if let Some(other_slot) = self.slot_map.insert(key.clone(), slot as u16) {
+                self.slots[usize::from(other_slot)] = None;
+            }
+            self.slots[slot] = Some((key, value));
+        }
+    }
+
+    fn set_slots(&mut self, slots: Vec<Option<(K, V)>>, packet_index: u16) {
+        self.slot_map.clear();
+        self.free_slots.clear();
+        for (slot, entry) in slots.iter().enumerate() {
+            if let Some((key, _)) = entry {
+                self.slot_map.insert(key.clone(), slot as u16);
+            } else {
+                self.free_slots.push(slot as u16);
+            }
+        }
+        self.recv_indices = vec![Some(packet_index); slots.len()];
+        self.full_recv_index = Some(packet_index);
+        self.slots = slots;
+    }
+
+    fn read_slots(reader: &mut BitReader) -> Vec<Option<(K, V)>> {
+        let length = de_length(reader);
+        if length > Self::max_slots() {
+            reader.set_error(DecodeError::InvalidValue);
+            return Vec::new();
+        }
+        let mut slots = Vec::with_capacity(length.min(reader.bits_remaining()));
+        for _ in 0..length {
+            let slot = Self::read_slot(reader);
+            if reader.has_error() {
+                break;
+            }
+            slots.push(slot);
+        }
+        return slots;
+    }
+}
+
+impl<K: Serde + Eq + Hash + Clone, V: Serde + Clone + PartialEq, const MAX_LENGTH: usize> Default
+    for ReplicatedMap<K, V, MAX_LENGTH>
+{
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<K: Serde + Eq + Hash + Clone, V: Serde + Clone + PartialEq, const MAX_LENGTH: usize> Clone
+    for ReplicatedMap<K, V, MAX_LENGTH>
+{
+    fn clone(&self) -> Self {
+        return Self {
+            slots: self.slots.clone(),
+            slot_map: self.slot_map.clone(),
+            free_slots: self.free_slots.clone(),
+            mutator: None,
+            recv_indices: self.recv_indices.clone(),
+            full_recv_index: self.full_recv_index,
+        };
+    }
+}
+
+impl<
+        K: Serde + Eq + Hash + Clone + Send + Sync + 'static,
+        V: Serde + Clone + PartialEq + Send + Sync + 'static,
+        const MAX_LENGTH: usize,
+    > NestedProperty for ReplicatedMap<K, V, MAX_LENGTH>
+{
+    fn new_read(reader: &mut BitReader, packet_index: u16) -> Self {
+        let mut output = Self::new();
+        output.set_slots(Self::read_slots(reader), packet_index);
+        return output;
+    }
+
+    fn read(&mut self, reader: &mut BitReader, packet_index: u16) {
+        let slots = Self::read_slots(reader);
+        let length = slots.len().max(self.slots.len());
+        let mut slots = slots.into_iter();
+        for slot in 0..length {
+            // slots beyond those received are empty
+            let entry = slots.next().flatten();
+            self.receive_slot(slot, entry, packet_index);
+        }
+        if is_newer(packet_index, self.full_recv_index) {
+            self.full_recv_index = Some(packet_index);
+        }
+    }
+
+    fn read_partial(&mut self, reader: &mut BitReader, packet_index: u16) {
+        while reader.read_bit() {
+            let slot = de_length(reader);
+            let entry = Self::read_slot(reader);
+            if slot >= Self::max_slots() {
+                reader.set_error(DecodeError::InvalidValue);
+            }
+            if reader.has_error() {
+                return;
+            }
+            self.receive_slot(slot, entry, packet_index);
+        }
+    }
+
+    fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16) {
+        self.set_slots(Self::read_slots(reader), packet_index);
+    }
+
+    fn write(&self, writer: &mut BitWriter) {
+        ser_length(self.slots.len(), writer);
+        for slot in 0..self.slots.len() {
+            self.write_slot(slot, writer);
+        }
+    }
+
+    fn write_partial(&self, diff_mask: &SubDiffMask, writer: &mut BitWriter) {
+        for slot in diff_mask.indices() {
+            let slot = usize::from(slot);
+            if slot < self.slots.len() {
+                writer.write_bit(true);
+                ser_length(slot, writer);
+                self.write_slot(slot, writer);
+            }
+        }
+        writer.write_bit(false);
+    }
+
+    fn write_delta(&self, _baseline: &Self, writer: &mut BitWriter) {
+        self.write(writer);
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        for (key, value) in self.iter() {
+            if other.get(key) != Some(value) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn mirror(&mut self, other: &Self) {
+        self.slots = other.slots.clone();
+        self.slot_map = other.slot_map.clone();
+        self.free_slots = other.free_slots.clone();
+        self.recv_indices = other.recv_indices.clone();
+        self.full_recv_index = other.full_recv_index;
+    }
+
+    fn set_mutator(&mut self, mutator: &PropertyMutator, property_index: u8) {
+        self.mutator = Some((mutator.clone_new(), property_index));
+    }
+}
+
+#[cfg(test)]
+mod replicated_map_tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        ser_length, BitReader, BitWriter, DecodeError, DiffMask, NestedProperty, PropertyMutate,
+        PropertyMutator, ReplicatedMap,
+    };
+
+    #[derive(Clone)]
+    struct TestMutator {
+        mask: Arc<Mutex<DiffMask>>,
+    }
+
+    impl PropertyMutate for TestMutator {
+        fn mutate(&mut self, property_index: u8) {
+            self.mask.lock().unwrap().set_bit(property_index, true);
+        }
+
+        fn mutate_nested(&mut self, property_index: u8, path: &[u16]) {
+            self.mask
+                .lock()
+                .unwrap()
+                .set_nested_bit(property_index, path);
+        }
+    }
+
+    fn sync(
+        server: &ReplicatedMap<u8, String>,
+        mask: &Arc<Mutex<DiffMask>>,
+        client: &mut ReplicatedMap<u8, String>,
+        packet_index: u16,
+    ) -> usize {
+        let mut writer = BitWriter::new();
+        server.write_masked(mask.lock().unwrap().get_nested(0), &mut writer);
+        mask.lock().unwrap().clear();
+        let bits = writer.bits_number();
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        client.read_masked(&mut reader, packet_index);
+        return bits;
+    }
+
+    #[test]
+    fn partial_sync() {
+        let mask = Arc::new(Mutex::new(DiffMask::new(1)));
+        let mut server = ReplicatedMap::new();
+        server.set_mutator(&PropertyMutator::new(TestMutator { mask: mask.clone() }), 0);
+        let mut client = server.clone();
+
+        for key in 0..10 {
+            server.insert(key, key.to_string());
+        }
+        sync(&server, &mask, &mut client, 1);
+        assert!(client.equals(&server));
+
+        server.remove(&3);
+        *server.get_mut(&4).unwrap() = "four".to_string();
+        let partial_bits = sync(&server, &mask, &mut client, 2);
+        assert!(client.equals(&server));
+        assert!(client.contains_key(&3) == false);
+        assert_eq!(client.get(&4).unwrap(), "four");
+
+        let mut writer = BitWriter::new();
+        server.write(&mut writer);
+        assert!(partial_bits < writer.bits_number());
+
+        // a slot freed by a removal is reused
+        server.insert(20, "twenty".to_string());
+        sync(&server, &mask, &mut client, 3);
+        assert!(client.equals(&server));
+
+        server.clear();
+        sync(&server, &mask, &mut client, 4);
+        assert!(client.is_empty());
+    }
+
+    #[test]
+    fn slot_over_max() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        ser_length(4, &mut writer);
+        writer.write_bit(false);
+        writer.write_bit(false);
+        let bytes = writer.to_bytes();
+
+        let mut reader = BitReader::new(&bytes);
+        let mut client = ReplicatedMap::<u8, String, 4>::new();
+        client.read_partial(&mut reader, 1);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+        assert!(client.is_empty());
+
+        let mut reader = BitReader::new(&bytes);
+        let mut client = ReplicatedMap::<u8, String, 5>::new();
+        client.read_partial(&mut reader, 1);
+        assert_eq!(reader.error(), None);
+    }
+
+    #[test]
+    fn late_packet() {
+        let mask = Arc::new(Mutex::new(DiffMask::new(1)));
+        let mut server = ReplicatedMap::new();
+        server.set_mutator(&PropertyMutator::new(TestMutator { mask: mask.clone() }), 0);
+        let mut client = server.clone();
+
+        server.insert(1, "one".to_string());
+        let mut writer = BitWriter::new();
+        server.write_masked(mask.lock().unwrap().get_nested(0), &mut writer);
+        mask.lock().unwrap().clear();
+        let late_bytes = writer.to_bytes();
+
+        server.remove(&1);
+        sync(&server, &mask, &mut client, 2);
+
+        let mut reader = BitReader::new(&late_bytes);
+        client.read_masked(&mut reader, 1);
+        assert!(client.is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+use std::convert::TryFrom;
+
+use crate::{
+    bit_reader::BitReader,
+    bit_writer::BitWriter,
+    decode_error::DecodeError,
+    diff_mask::SubDiffMask,
+    nested_property::{is_newer, NestedProperty},
+    property_mutate::PropertyMutator,
+    serde::{de_length, ser_length, Serde},
+};
+
+/// A list Property of a Component, of which only the elements that have
+/// changed are synced. The list holds at most `MAX_LENGTH` elements, so that a
+/// length received from a remote host can't force a huge allocation
+pub struct ReplicatedVec<T: Serde + Clone + PartialEq, const MAX_LENGTH: usize = 4096> {
+    inner: Vec<T>,
+    mutator: Option<(PropertyMutator, u8)>,
+    recv_indices: Vec<Option<u16>>,
+    length_recv_index: Option<u16>,
+}
+
+impl<T: Serde + Clone + PartialEq, const MAX_LENGTH: usize> ReplicatedVec<T, MAX_LENGTH> {
+    /// Create a new ReplicatedVec. Panics if there are more than `MAX_LENGTH`
+    /// values
+    pub fn new(values: Vec<T>) -> Self {
+        Self::check_length(values.len());
+        let recv_indices = vec![None; values.len()];
+        return Self {
+            inner: values,
+            mutator: None,
+            recv_indices,
+            length_recv_index: None,
+        };
+    }
+
+    /// Gets the number of elements in the list
+    pub fn len(&self) -> usize {
+        return self.inner.len();
+    }
+
+    /// Returns whether the list contains no elements
+    pub fn is_empty(&self) -> bool {
+        return self.inner.is_empty();
+    }
+
+    /// Gets a reference to the element at the given index
+    pub fn get(&self, index: usize) -> Option<&T> {
+        return self.inner.get(index);
+    }
+
+    /// Gets a mutable reference to the element at the given index, queue to
+    /// update
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.inner.len() {
+            self.mutate_element(index);
+        }
+        return self.inner.get_mut(index);
+    }
+
+    /// Set the element at the given index
+    pub fn set(&mut self, index: usize, value: T) {
+        self.inner[index] = value;
+        self.mutate_element(index);
+    }
+
+    /// Appends an element to the end of the list
+    pub fn push(&mut self, value: T) {
+        Self::check_length(self.inner.len() + 1);
+        self.inner.push(value);
+        self.recv_indices.push(None);
+        self.mutate_element(self.inner.len() - 1);
+    }
+
+    /// Removes the last element of the list and returns it
+    pub fn pop(&mut self) -> Option<T> {
+        let output = self.inner.pop();
+        if output.is_some() {
+            self.recv_indices.pop();
+            // marking the removed index syncs the new length of the list
+            self.mutate_element(self.inner.len());
+        }
+        return output;
+    }
+
+    /// Inserts an element at the given index, shifting all elements after it
+    pub fn insert(&mut self, index: usize, value: T) {
+        Self::check_length(self.inner.len() + 1);
+        self.inner.insert(index, value);
+        self.recv_indices.insert(index, None);
+        self.mutate_range(index, self.inner.len());
+    }
+
+    /// Removes the element at the given index and returns it, shifting all
+    /// elements after it
+    pub fn remove(&mut self, index: usize) -> T {
+        let output = self.inner.remove(index);
+        self.recv_indices.remove(index);
+        self.mutate_range(index, self.inner.len() + 1);
+        return output;
+    }
+
+    /// Removes all elements from the list
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.recv_indices.clear();
+        if let Some((mutator, property_index)) = &mut self.mutator {
+            mutator.mutate(*property_index);
+        }
+    }
+
+    /// Gets an iterator over the elements of the list
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        return self.inner.iter();
+    }
+
+    /// Gets the elements of the list as a slice
+    pub fn as_slice(&self) -> &[T] {
+        return self.inner.as_slice();
+    }
+
+    fn check_length(length: usize) {
+        if length > MAX_LENGTH {
+            panic!(
+                "ReplicatedVec cannot hold more than {} elements",
+                MAX_LENGTH
+            );
+        }
+    }
+
+    fn mutate_range(&mut self, start: usize, end: usize) {
+        for index in start..end {
+            self.mutate_element(index);
+        }
+    }
+
+    fn mutate_element(&mut self, index: usize) {
+        if let Some((mutator, property_index)) = &mut self.mutator {
+            if let Ok(index) = u16::try_from(index) {
+                mutator.mutate_nested(*property_index, &[index]);
+            } else {
+                // element can't be addressed individually, so sync the whole list
+                mutator.mutate(*property_index);
+            }
+        }
+    }
+
+    fn read_inner(reader: &mut BitReader) -> Vec<T> {
+        let length = de_length(reader);
+        if length > MAX_LENGTH {
+            reader.set_error(DecodeError::InvalidValue);
+            return Vec::new();
+        }
+        let mut output = Vec::with_capacity(length.min(reader.bits_remaining()));
+        for _ in 0..length {
+            let value = T::de(reader);
+            if reader.has_error() {
+                break;
+            }
+            output.push(value);
+        }
+        return output;
+    }
+
+    fn set_inner(&mut self, inner: Vec<T>, packet_index: u16) {
+        self.recv_indices = vec![Some(packet_index); inner.len()];
+        self.length_recv_index = Some(packet_index);
+        self.inner = inner;
+    }
+}
+
+impl<T: Serde + Clone + PartialEq + Default, const MAX_LENGTH: usize> ReplicatedVec<T, MAX_LENGTH> {
+    fn read_length(&mut self, length: usize, packet_index: u16) {
+        if is_newer(packet_index, self.length_recv_index) {
+            self.length_recv_index = Some(packet_index);
+            // elements added here are received within this or another packet
+            self.inner.resize(length, T::default());
+            self.recv_indices.resize(length, None);
+        }
+    }
+
+    fn read_element(&mut self, index: usize, value: T, packet_index: u16) {
+        if index < self.inner.len() && is_newer(packet_index, self.recv_indices[index]) {
+            self.inner[index] = value;
+            self.recv_indices[index] = Some(packet_index);
+        }
+    }
+}
+
+impl<T: Serde + Clone + PartialEq, const MAX_LENGTH: usize> Clone for ReplicatedVec<T, MAX_LENGTH> {
+    fn clone(&self) -> Self {
+        return Self {
+            inner: self.inner.clone(),
+            mutator: None,
+            recv_indices: self.recv_indices.clone(),
+            length_recv_index: self.length_recv_index,
+        };
+    }
+}
+
+impl<T: Serde + Clone + PartialEq + Default + Send + Sync + 'static, const MAX_LENGTH: usize>
+    NestedProperty for ReplicatedVec<T, MAX_LENGTH>
+{
+    fn new_read(reader: &mut BitReader, packet_index: u16) -> Self {
+        let mut output = Self::new(Vec::new());
+        output.set_inner(Self::read_inner(reader), packet_index);
+        return output;
+    }
+
+    fn read(&mut self, reader: &mut BitReader, packet_index: u16) {
+        let inner = Self::read_inner(reader);
+        self.read_length(inner.len(), packet_index);
+        for (index, value) in inner.into_iter().enumerate() {
+            self.read_element(index, value, packet_index);
+        }
+    }
+
+    fn read_partial(&mut self, reader: &mut BitReader, packet_index: u16) {
+        let length = de_length(reader);
+        if length > MAX_LENGTH {
+            reader.set_error(DecodeError::InvalidValue);
+        }
+        if reader.has_error() {
+            return;
+        }
+        self.read_length(length, packet_index);
+
+        while reader.read_bit() {
+            let index = de_length(reader);
+            let value = T::de(reader);
+            if reader.has_error() {
+                return;
+            }
+            self.read_element(index, value, packet_index);
+        }
+    }
+
+    fn read_delta(&mut self, reader: &mut BitReader, packet_index: u16) {
+        self.set_inner(Self::read_inner(reader), packet_index);
+    }
+
+    fn write(&self, writer: &mut BitWriter) {
+        ser_length(self.inner.len(), writer);
+        for value in self.inner.iter() {
+            value.ser(writer);
+        }
+    }
+
+    fn write_partial(&self, diff_mask: &SubDiffMask, writer: &mut BitWriter) {
+        ser_length(self.inner.len(), writer);
+        for index in diff_mask.indices() {
+            let index = usize::from(index);
+            if let Some(value) = self.inner.get(index) {
+                writer.write_bit(true);
+                ser_length(index, writer);
+                value.ser(writer);
+            }
+        }
+        writer.write_bit(false);
+    }
+
+    fn write_delta(&self, _baseline: &Self, writer: &mut BitWriter) {
+        self.write(writer);
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        return self.inner == other.inner;
+    }
+
+    fn mirror(&mut self, other: &Self) {
+        self.inner = other.inner.clone();
+        self.recv_indices = other.recv_indices.clone();
+        self.length_recv_index = other.length_recv_index;
+    }
+
+    fn set_mutator(&mut self, mutator: &PropertyMutator, property_index: u8) {
+        self.mutator = Some((mutator.clone_new(), property_index));
+    }
+}
+
+#[cfg(test)]
+mod replicated_vec_tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        BitReader, BitWriter, DecodeError, DiffMask, NestedProperty, PropertyMutate,
+        PropertyMutator, ReplicatedVec,
+    };
+
+    #[derive(Clone)]
+    struct TestMutator {
+        mask: Arc<Mutex<DiffMask>>,
+    }
+
+    impl PropertyMutate for TestMutator {
+        fn mutate(&mut self, property_index: u8) {
+            self.mask.lock().unwrap().set_bit(property_index, true);
+        }
+
+        fn mutate_nested(&mut self, property_index: u8, path: &[u16]) {
+            self.mask
+                .lock()
+                .unwrap()
+                .set_nested_bit(property_index, path);
+        }
+    }
+
+    fn sync(
+        server: &ReplicatedVec<u16>,
+        mask: &DiffMask,
+        client: &mut ReplicatedVec<u16>,
+        packet_index: u16,
+    ) {
+        let mut writer = BitWriter::new();
+        server.write_masked(mask.get_nested(0), &mut writer);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        client.read_masked(&mut reader, packet_index);
+    }
+
+    #[test]
+    fn partial_sync() {
+        let mask = Arc::new(Mutex::new(DiffMask::new(1)));
+        let mut server = ReplicatedVec::new(vec![1, 2, 3, 4]);
+        server.set_mutator(&PropertyMutator::new(TestMutator { mask: mask.clone() }), 0);
+        let mut client = server.clone();
+
+        server.set(1, 20);
+        server.push(5);
+        {
+            let mask = mask.lock().unwrap();
+            let nested = mask.get_nested(0).unwrap();
+            assert!(nested.contains(1));
+            assert!(nested.contains(4));
+            assert!(nested.contains(0) == false);
+        }
+
+        sync(&server, &mask.lock().unwrap(), &mut client, 1);
+        assert_eq!(client.as_slice(), &[1, 20, 3, 4, 5]);
+
+        mask.lock().unwrap().clear();
+        server.remove(0);
+        server.pop();
+        sync(&server, &mask.lock().unwrap(), &mut client, 2);
+        assert_eq!(client.as_slice(), &[20, 3, 4]);
+
+        mask.lock().unwrap().clear();
+        server.clear();
+        assert!(mask.lock().unwrap().get_nested(0).is_none());
+        sync(&server, &mask.lock().unwrap(), &mut client, 3);
+        assert!(client.is_empty());
+    }
+
+    #[test]
+    fn length_over_max() {
+        let mut writer = BitWriter::new();
+        ReplicatedVec::<u16>::new(vec![7; 5]).write(&mut writer);
+        let bytes = writer.to_bytes();
+
+        let mut reader = BitReader::new(&bytes);
+        let mut client = ReplicatedVec::<u16, 4>::new(Vec::new());
+        client.read_partial(&mut reader, 1);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+        assert!(client.is_empty());
+
+        let mut reader = BitReader::new(&bytes);
+        ReplicatedVec::<u16, 4>::new_read(&mut reader, 1);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_over_max() {
+        let mut list = ReplicatedVec::<u16, 4>::new(vec![1, 2, 3, 4]);
+        list.push(5);
+    }
+}
@@ -0,0 +1,481 @@
+use std::convert::TryInto;
+
+use super::{
+    bit_reader::BitReader, bit_writer::BitWriter, decode_error::DecodeError,
+    message_fragment::MAX_MESSAGE_SIZE_BYTES,
+};
+
+/// A type which is able to write itself into, and read itself from, a bit
+/// stream. Every value contained by a Property must implement Serde
+pub trait Serde: Sized {
+    /// Writes the value into an outgoing bit stream
+    fn ser(&self, writer: &mut BitWriter);
+    /// Reads a value from an incoming bit stream. Invalid data doesn't
+    /// panic, it is recorded as an error on the reader
+    fn de(reader: &mut BitReader) -> Self;
+    /// Writes the value into an outgoing bit stream, encoded relative to a
+    /// baseline value which the remote host already has. By default, the
+    /// value is written in full
+    fn ser_delta(&self, _baseline: &Self, writer: &mut BitWriter) {
+        self.ser(writer);
+    }
+    /// Reads a value which was written relative to a baseline value from an
+    /// incoming bit stream
+    fn de_delta(_baseline: &Self, reader: &mut BitReader) -> Self {
+        return Self::de(reader);
+    }
+}
+
+// Number of bits in each chunk of an encoded length
+const LENGTH_CHUNK_BITS: u8 = 5;
+
+/// Writes a length (of a String, Vec, etc.) into an outgoing bit stream, using
+/// as few chunks of bits as are needed to represent it
+pub fn ser_length(length: usize, writer: &mut BitWriter) {
+    ser_varint(length as u64, writer);
+}
+
+/// Reads a length which was written with `ser_length` from an incoming bit
+/// stream
+pub fn de_length(reader: &mut BitReader) -> usize {
+    return de_varint(reader) as usize;
+}
+
+/// Writes the signed difference between two values into an outgoing bit
+/// stream, using as few chunks of bits as are needed to represent it
+pub fn ser_difference(difference: i64, writer: &mut BitWriter) {
+    writer.write_bit(difference < 0);
+    ser_varint(difference.unsigned_abs(), writer);
+}
+
+/// Reads a difference which was written with `ser_difference` from an
+/// incoming bit stream
+pub fn de_difference(reader: &mut BitReader) -> i64 {
+    let negative = reader.read_bit();
+    let magnitude = de_varint(reader) as i64;
+    if negative {
+        return magnitude.wrapping_neg();
+    }
+    return magnitude;
+}
+
+fn ser_varint(value: u64, writer: &mut BitWriter) {
+    let mut remaining = value;
+    loop {
+        let chunk = remaining & ((1 << LENGTH_CHUNK_BITS) - 1);
+        remaining >>= LENGTH_CHUNK_BITS;
+        // write whether there is another chunk after this one
+        writer.write_bit(remaining != 0);
+        writer.write_bits(chunk, LENGTH_CHUNK_BITS);
+        if remaining == 0 {
+            return;
+        }
+    }
+}
+
+fn de_varint(reader: &mut BitReader) -> u64 {
+    let mut output: u64 = 0;
+    let mut shift: u8 = 0;
+    loop {
+        if shift >= 64 {
+            reader.set_error(DecodeError::InvalidValue);
+            return output;
+        }
+        let proceed = reader.read_bit();
+        output |= reader.read_bits(LENGTH_CHUNK_BITS) << shift;
+        shift += LENGTH_CHUNK_BITS;
+        if !proceed {
+            return output;
+        }
+    }
+}
+
+// Primitives
+
+impl Serde for bool {
+    fn ser(&self, writer: &mut BitWriter) {
+        writer.write_bit(*self);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return reader.read_bit();
+    }
+}
+
+macro_rules! impl_serde_for_unsigned {
+    ($type:ty, $signed:ty, $bits:expr) => {
+        #[allow(trivial_numeric_casts)]
+        impl Serde for $type {
+            fn ser(&self, writer: &mut BitWriter) {
+                writer.write_bits(u64::from(*self), $bits);
+            }
+
+            fn de(reader: &mut BitReader) -> Self {
+                return reader.read_bits($bits) as $type;
+            }
+
+            fn ser_delta(&self, baseline: &Self, writer: &mut BitWriter) {
+                ser_difference(i64::from(self.wrapping_sub(*baseline) as $signed), writer);
+            }
+
+            fn de_delta(baseline: &Self, reader: &mut BitReader) -> Self {
+                return baseline.wrapping_add(de_difference(reader) as $type);
+            }
+        }
+    };
+}
+
+impl_serde_for_unsigned!(u8, i8, 8);
+impl_serde_for_unsigned!(u16, i16, 16);
+impl_serde_for_unsigned!(u32, i32, 32);
+impl_serde_for_unsigned!(u64, i64, 64);
+
+macro_rules! impl_serde_for_signed {
+    ($type:ty, $unsigned:ty, $bits:expr) => {
+        #[allow(trivial_numeric_casts)]
+        impl Serde for $type {
+            fn ser(&self, writer: &mut BitWriter) {
+                writer.write_bits(u64::from(*self as $unsigned), $bits);
+            }
+
+            fn de(reader: &mut BitReader) -> Self {
+                return reader.read_bits($bits) as $unsigned as $type;
+            }
+
+            fn ser_delta(&self, baseline: &Self, writer: &mut BitWriter) {
+                ser_difference(i64::from(self.wrapping_sub(*baseline)), writer);
+            }
+
+            fn de_delta(baseline: &Self, reader: &mut BitReader) -> Self {
+                return baseline.wrapping_add(de_difference(reader) as $type);
+            }
+        }
+    };
+}
+
+impl_serde_for_signed!(i8, u8, 8);
+impl_serde_for_signed!(i16, u16, 16);
+impl_serde_for_signed!(i32, u32, 32);
+impl_serde_for_signed!(i64, u64, 64);
+
+impl Serde for usize {
+    fn ser(&self, writer: &mut BitWriter) {
+        ser_length(*self, writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return de_length(reader);
+    }
+}
+
+impl Serde for isize {
+    fn ser(&self, writer: &mut BitWriter) {
+        (*self as i64).ser(writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return i64::de(reader) as isize;
+    }
+}
+
+impl Serde for f32 {
+    fn ser(&self, writer: &mut BitWriter) {
+        self.to_bits().ser(writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return f32::from_bits(u32::de(reader));
+    }
+}
+
+impl Serde for f64 {
+    fn ser(&self, writer: &mut BitWriter) {
+        self.to_bits().ser(writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return f64::from_bits(u64::de(reader));
+    }
+}
+
+impl Serde for char {
+    fn ser(&self, writer: &mut BitWriter) {
+        u32::from(*self).ser(writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return match std::char::from_u32(u32::de(reader)) {
+            Some(value) => value,
+            None => {
+                reader.set_error(DecodeError::InvalidValue);
+                std::char::REPLACEMENT_CHARACTER
+            }
+        };
+    }
+}
+
+impl Serde for () {
+    fn ser(&self, _: &mut BitWriter) {}
+
+    fn de(_: &mut BitReader) -> Self {}
+}
+
+// Containers
+
+impl Serde for String {
+    fn ser(&self, writer: &mut BitWriter) {
+        ser_length(self.len(), writer);
+        writer.write_bytes(self.as_bytes());
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let length = de_length(reader);
+        let bytes = reader.read_bytes(length);
+        return match String::from_utf8(bytes) {
+            Ok(value) => value,
+            Err(_) => {
+                reader.set_error(DecodeError::InvalidValue);
+                String::new()
+            }
+        };
+    }
+}
+
+impl<T: Serde> Serde for Option<T> {
+    fn ser(&self, writer: &mut BitWriter) {
+        if let Some(value) = self {
+            writer.write_bit(true);
+            value.ser(writer);
+        } else {
+            writer.write_bit(false);
+        }
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        if reader.read_bit() {
+            return Some(T::de(reader));
+        }
+        return None;
+    }
+}
+
+impl<T: Serde> Serde for Box<T> {
+    fn ser(&self, writer: &mut BitWriter) {
+        self.as_ref().ser(writer);
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        return Box::new(T::de(reader));
+    }
+}
+
+// Most items a Vec is read with. Any item with a size is written with at least
+// one bit, so a Message can't hold more of them than it has bits
+const MAX_VEC_LENGTH: usize = MAX_MESSAGE_SIZE_BYTES * 8;
+
+impl<T: Serde> Serde for Vec<T> {
+    fn ser(&self, writer: &mut BitWriter) {
+        ser_length(self.len(), writer);
+        for item in self {
+            item.ser(writer);
+        }
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let length = de_length(reader);
+        if length > MAX_VEC_LENGTH {
+            reader.set_error(DecodeError::InvalidValue);
+            return Vec::new();
+        }
+        // the length can't be trusted until the items have been read
+        let mut output = Vec::with_capacity(length.min(reader.bits_remaining()));
+        let zero_sized = std::mem::size_of::<T>() == 0;
+        for _ in 0..length {
+            if reader.has_error() {
+                break;
+            }
+            // zero-sized items are read without any bits
+            if !zero_sized && reader.bits_remaining() == 0 {
+                reader.set_error(DecodeError::UnexpectedEnd);
+                break;
+            }
+            let item = T::de(reader);
+            if reader.has_error() {
+                break;
+            }
+            output.push(item);
+        }
+        return output;
+    }
+}
+
+// arrays have a known length, so no length is written
+impl<T: Serde, const N: usize> Serde for [T; N] {
+    fn ser(&self, writer: &mut BitWriter) {
+        for item in self {
+            item.ser(writer);
+        }
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let mut output = Vec::with_capacity(N);
+        for _ in 0..N {
+            output.push(T::de(reader));
+        }
+        if let Ok(array) = output.try_into() {
+            return array;
+        }
+        unreachable!("array was built with the correct length");
+    }
+}
+
+macro_rules! impl_serde_for_tuple {
+    ($($name:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($name: Serde),+> Serde for ($($name,)+) {
+            fn ser(&self, writer: &mut BitWriter) {
+                let ($($name,)+) = self;
+                $($name.ser(writer);)+
+            }
+
+            fn de(reader: &mut BitReader) -> Self {
+                return ($($name::de(reader),)+);
+            }
+        }
+    };
+}
+
+impl_serde_for_tuple!(A);
+impl_serde_for_tuple!(A, B);
+impl_serde_for_tuple!(A, B, C);
+impl_serde_for_tuple!(A, B, C, D);
+
+/// Writes a value, then reads it back & checks it is unchanged. Returns the
+/// number of bits the value was written with
+#[cfg(test)]
+pub(crate) fn round_trip<T: Serde + PartialEq + std::fmt::Debug>(value: T) -> usize {
+    let mut writer = BitWriter::new();
+    value.ser(&mut writer);
+    let bits = writer.bits_number();
+
+    let bytes = writer.to_bytes();
+    let mut reader = BitReader::new(&bytes);
+    assert_eq!(T::de(&mut reader), value);
+
+    return bits;
+}
+
+#[cfg(test)]
+mod serde_tests {
+    use super::{de_length, round_trip, ser_length, MAX_VEC_LENGTH};
+    use crate::{BitReader, BitWriter, DecodeError, Serde};
+
+    #[test]
+    fn primitives() {
+        assert_eq!(round_trip(true), 1);
+        assert_eq!(round_trip(200u8), 8);
+        assert_eq!(round_trip(-12345i16), 16);
+        assert_eq!(round_trip(-1i64), 64);
+        round_trip(3.5f32);
+        round_trip(-0.25f64);
+        round_trip('x');
+    }
+
+    #[test]
+    fn deltas() {
+        let mut writer = BitWriter::new();
+        300u16.ser_delta(&298, &mut writer);
+        5u8.ser_delta(&250, &mut writer);
+        (-70000i32).ser_delta(&-70005, &mut writer);
+        3.5f32.ser_delta(&1.0, &mut writer);
+
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(u16::de_delta(&298, &mut reader), 300);
+        assert_eq!(u8::de_delta(&250, &mut reader), 5);
+        assert_eq!(i32::de_delta(&-70005, &mut reader), -70000);
+        assert_eq!(f32::de_delta(&1.0, &mut reader), 3.5);
+    }
+
+    #[test]
+    fn small_delta() {
+        let mut writer = BitWriter::new();
+        1_000_001u32.ser_delta(&1_000_000, &mut writer);
+        assert_eq!(writer.bits_number(), 7);
+    }
+
+    #[test]
+    fn containers() {
+        round_trip(String::from("hello naia"));
+        round_trip(vec![1u16, 2, 3]);
+        round_trip(Some(7u32));
+        round_trip(None::<u32>);
+        round_trip([true, false, true]);
+        round_trip((5u8, String::from("a"), false));
+    }
+
+    #[test]
+    fn long_string() {
+        // longer than could previously be represented with a single byte
+        // length prefix
+        let long = "x".repeat(1000);
+        round_trip(long);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut writer = BitWriter::new();
+        String::from("hello naia").ser(&mut writer);
+        let bytes = writer.to_bytes();
+
+        let mut reader = BitReader::new(&bytes[..4]);
+        String::de(&mut reader);
+        assert_eq!(reader.error(), Some(&DecodeError::UnexpectedEnd));
+
+        // a huge length, followed by nothing
+        let mut writer = BitWriter::new();
+        ser_length(MAX_VEC_LENGTH, &mut writer);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        assert!(Vec::<u32>::de(&mut reader).is_empty());
+        assert_eq!(reader.error(), Some(&DecodeError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn invalid_values() {
+        let mut writer = BitWriter::new();
+        0xD800u32.ser(&mut writer);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        char::de(&mut reader);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+
+        let mut writer = BitWriter::new();
+        vec![0xFFu8, 0xFE].ser(&mut writer);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        String::de(&mut reader);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+
+        // a length which never ends
+        let bytes = [0xFFu8; 16];
+        let mut reader = BitReader::new(&bytes);
+        de_length(&mut reader);
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+
+        // more items than any Message could hold, even zero-sized ones
+        let mut writer = BitWriter::new();
+        ser_length(MAX_VEC_LENGTH + 1, &mut writer);
+        let bytes = writer.to_bytes();
+        let mut reader = BitReader::new(&bytes);
+        assert!(Vec::<()>::de(&mut reader).is_empty());
+        assert_eq!(reader.error(), Some(&DecodeError::InvalidValue));
+    }
+
+    #[test]
+    fn small_lengths() {
+        assert_eq!(round_trip(Vec::<bool>::new()), 6);
+        assert_eq!(round_trip(vec![true; 31]), 6 + 31);
+        assert_eq!(round_trip(vec![true; 32]), 12 + 32);
+    }
+}
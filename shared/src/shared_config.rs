@@ -2,7 +2,16 @@ use std::time::Duration;
 
 use naia_socket_shared::LinkConditionerConfig;
 
-use crate::{Manifest, ProtocolType};
+use crate::{ChannelConfig, Channels, DefaultChannels, Manifest, ProtocolType};
+
+/// The maximum age, in packets, of an acknowledged Component state which a
+/// delta-compressed update may be encoded relative to
+pub const DELTA_BASELINE_WINDOW: u16 = 32;
+
+/// The furthest an action on an Entity spawned by the Client may be ahead of
+/// the oldest action the Server has not received. The Client never sends
+/// past this, and the Server ignores any action further ahead
+pub const CLIENT_ENTITY_ACTION_WINDOW: u16 = 1024;
 
 /// Contains Config properties which will be shared by Server and Client
 pub struct SharedConfig<P: ProtocolType> {
@@ -12,6 +21,12 @@ pub struct SharedConfig<P: ProtocolType> {
     pub tick_interval: Option<Duration>,
     /// Configuration used to simulate network conditions
     pub link_condition_config: Option<LinkConditionerConfig>,
+    /// Whether Component updates are encoded relative to the last state of
+    /// the Component acknowledged by the Client, rather than in full
+    pub delta_compression: bool,
+    /// The configuration of each Channel through which Messages are sent, in
+    /// order of index. Defaults to those of `DefaultChannels`
+    pub channels: Vec<ChannelConfig>,
 }
 
 impl<P: ProtocolType> SharedConfig<P> {
@@ -25,6 +40,8 @@ impl<P: ProtocolType> SharedConfig<P> {
             manifest,
             tick_interval,
             link_condition_config,
+            delta_compression: false,
+            channels: DefaultChannels::configs(),
         }
     }
 }
@@ -1,8 +1,6 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
-use std::io::Read;
-
-use crate::packet_type::PacketType;
+use crate::{decode_error::DecodeError, packet_type::PacketType};
 
 #[derive(Copy, Clone, Debug)]
 /// This header provides reliability information.
@@ -97,19 +95,36 @@ impl StandardHeader {
             .unwrap();
     }
 
-    /// Reads the header from an incoming byte slice
-    pub fn read(mut msg: &[u8]) -> (Self, Box<[u8]>) {
-        let p_type: PacketType = msg.read_u8().unwrap().into();
-        let seq = msg.read_u16::<BigEndian>().unwrap();
-        let ack_seq = msg.read_u16::<BigEndian>().unwrap();
-        let ack_field = msg.read_u32::<BigEndian>().unwrap();
-        let host_tick = msg.read_u16::<BigEndian>().unwrap();
-        let last_received_tick = msg.read_u16::<BigEndian>().unwrap();
+    /// Reads the header from an incoming byte slice, returning an error if
+    /// the slice is too short or the packet type is unknown
+    pub fn read(mut msg: &[u8]) -> Result<(Self, Box<[u8]>), DecodeError> {
+        if msg.len() < Self::bytes_number() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let p_type_u8 = msg.read_u8().map_err(|_| DecodeError::UnexpectedEnd)?;
+        let p_type: PacketType = p_type_u8.into();
+        if p_type == PacketType::Unknown {
+            return Err(DecodeError::UnknownPacketType(p_type_u8));
+        }
+        let seq = msg
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+        let ack_seq = msg
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+        let ack_field = msg
+            .read_u32::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+        let host_tick = msg
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
+        let last_received_tick = msg
+            .read_u16::<BigEndian>()
+            .map_err(|_| DecodeError::UnexpectedEnd)?;
 
-        let mut buffer = Vec::new();
-        msg.read_to_end(&mut buffer).unwrap();
+        let buffer = msg.to_vec();
 
-        (
+        Ok((
             StandardHeader {
                 p_type,
                 local_packet_index: seq,
@@ -119,6 +134,6 @@ impl StandardHeader {
                 last_received_tick,
             },
             buffer.into_boxed_slice(),
-        )
+        ))
     }
 }
@@ -0,0 +1,20 @@
+use naia_derive::ProtocolType;
+
+pub use payload::Payload;
+
+mod payload {
+    use naia_derive::Replicate;
+
+    use crate::Property;
+
+    #[derive(Replicate)]
+    #[protocol_path = "crate::test_protocol::Protocol"]
+    pub struct Payload {
+        pub bytes: Property<Vec<u8>>,
+    }
+}
+
+#[derive(ProtocolType)]
+pub enum Protocol {
+    Payload(Payload),
+}
@@ -0,0 +1,147 @@
+use super::{
+    bit_reader::BitReader, bit_writer::BitWriter, property::PropertyEncoding, serde::Serde,
+};
+
+// The largest magnitude that any component other than the largest component
+// of a unit quaternion can have (1 / sqrt(2))
+const COMPONENT_LIMIT: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// A rotation, stored as a unit quaternion, which is written using the
+/// "smallest three" encoding: the index of the largest component in 2 bits,
+/// followed by each of the other three components quantized to `BITS` bits.
+/// The largest component is then recomputed by the remote host
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnitQuaternion<const BITS: u8> {
+    largest_index: u8,
+    steps: [u64; 3],
+}
+
+impl<const BITS: u8> UnitQuaternion<BITS> {
+    /// Create a new quaternion from its x, y, z & w components. The quaternion
+    /// will be normalized
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        let mut output = Self {
+            largest_index: 3,
+            steps: [0; 3],
+        };
+        output.set(x, y, z, w);
+        return output;
+    }
+
+    /// Gets the x, y, z & w components of the quaternion, as they will be
+    /// received by the remote host
+    pub fn get(&self) -> (f32, f32, f32, f32) {
+        let mut components = [0.0; 4];
+        let mut sum_squares = 0.0;
+        let mut step_index = 0;
+        for (index, component) in components.iter_mut().enumerate() {
+            if index == usize::from(self.largest_index) {
+                continue;
+            }
+            let ratio = self.steps[step_index] as f32 / Self::max_step() as f32;
+            *component = (ratio * 2.0 - 1.0) * COMPONENT_LIMIT;
+            sum_squares += *component * *component;
+            step_index += 1;
+        }
+        components[usize::from(self.largest_index)] = (1.0 - sum_squares).max(0.0).sqrt();
+        return (components[0], components[1], components[2], components[3]);
+    }
+
+    /// Sets the x, y, z & w components of the quaternion. The quaternion will
+    /// be normalized
+    pub fn set(&mut self, x: f32, y: f32, z: f32, w: f32) {
+        if BITS == 0 || BITS > 32 {
+            panic!("UnitQuaternion must be written with between 1 and 32 bits");
+        }
+
+        let mut components = [x, y, z, w];
+        let length = components.iter().map(|c| c * c).sum::<f32>().sqrt();
+        if length == 0.0 {
+            panic!("can't create a UnitQuaternion from a zero-length quaternion");
+        }
+
+        let mut largest_index = 0;
+        for index in 1..4 {
+            if components[index].abs() > components[largest_index].abs() {
+                largest_index = index;
+            }
+        }
+
+        // q and -q represent the same rotation, so flip the quaternion to make
+        // the largest component positive, which means its sign needn't be sent
+        let sign = if components[largest_index] < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        for component in components.iter_mut() {
+            *component *= sign / length;
+        }
+
+        let mut step_index = 0;
+        for (index, component) in components.iter().enumerate() {
+            if index == largest_index {
+                continue;
+            }
+            let ratio = (component / COMPONENT_LIMIT + 1.0) / 2.0;
+            let ratio = ratio.clamp(0.0, 1.0);
+            self.steps[step_index] = (ratio * Self::max_step() as f32).round() as u64;
+            step_index += 1;
+        }
+        self.largest_index = largest_index as u8;
+    }
+
+    fn max_step() -> u64 {
+        return (1 << BITS) - 1;
+    }
+}
+
+impl<const BITS: u8> Serde for UnitQuaternion<BITS> {
+    fn ser(&self, writer: &mut BitWriter) {
+        writer.write_bits(u64::from(self.largest_index), 2);
+        for step in &self.steps {
+            writer.write_bits(*step, BITS);
+        }
+    }
+
+    fn de(reader: &mut BitReader) -> Self {
+        let largest_index = reader.read_bits(2) as u8;
+        let mut steps = [0; 3];
+        for step in steps.iter_mut() {
+            *step = reader.read_bits(BITS);
+        }
+        return Self {
+            largest_index,
+            steps,
+        };
+    }
+}
+
+impl<const BITS: u8> PropertyEncoding<(f32, f32, f32, f32)> for UnitQuaternion<BITS> {
+    fn encode(value: &(f32, f32, f32, f32)) -> Self {
+        return Self::new(value.0, value.1, value.2, value.3);
+    }
+
+    fn decode(&self) -> (f32, f32, f32, f32) {
+        return self.get();
+    }
+}
+
+#[cfg(test)]
+mod unit_quaternion_tests {
+    use crate::{serde::round_trip, UnitQuaternion};
+
+    #[test]
+    fn unit_quaternion() {
+        // a 90 degree rotation about the y axis, negated
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let value = UnitQuaternion::<10>::new(0.0, -half * 2.0, 0.0, -half * 2.0);
+        assert_eq!(round_trip(value), 32);
+
+        let (x, y, z, w) = value.get();
+        assert!(x.abs() < 0.002);
+        assert!((y - half).abs() < 0.002);
+        assert!(z.abs() < 0.002);
+        assert!((w - half).abs() < 0.002);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{packet_type::PacketType, standard_header::StandardHeader};
+use crate::{decode_error::DecodeError, packet_type::PacketType, standard_header::StandardHeader};
 
 /// Write a connectionless packet, that is, one that does not rely on
 /// information normally retrieved from the connection
@@ -16,7 +16,7 @@ pub fn write_connectionless_payload(packet_type: PacketType, payload: &[u8]) ->
 
 /// Strip the standard header off of a packet's payload and retrieve the payload
 /// bytes
-pub fn read_headerless_payload(payload: &[u8]) -> Box<[u8]> {
-    let (_, stripped_message) = StandardHeader::read(payload);
-    stripped_message
+pub fn read_headerless_payload(payload: &[u8]) -> Result<Box<[u8]>, DecodeError> {
+    let (_, stripped_message) = StandardHeader::read(payload)?;
+    Ok(stripped_message)
 }
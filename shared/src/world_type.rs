@@ -1,7 +1,5 @@
-use naia_socket_shared::PacketReader;
-
 use super::{
-    diff_mask::DiffMask,
+    bit_reader::BitReader,
     protocol_type::{ProtocolInserter, ProtocolType},
     replica_ref::{ReplicaDynRefWrapper, ReplicaMutWrapper, ReplicaRefWrapper},
     replicate::{Replicate, ReplicateSafe},
@@ -58,10 +56,12 @@ pub trait WorldMutType<P: ProtocolType, E>: WorldRefType<P, E> + ProtocolInserte
         &mut self,
         entity: &E,
         component_kind: &P::Kind,
-        diff_mask: &DiffMask,
-        reader: &mut PacketReader,
+        reader: &mut BitReader,
         packet_index: u16,
     );
+    /// sets an entity's component to the state of another component of the
+    /// same kind
+    fn mirror_component(&mut self, entity: &E, component: &P);
     /// mirrors the state of the same component owned by two different entities
     /// (setting 1st entity's component to 2nd entity's component's state)
     fn mirror_components(